@@ -15,15 +15,29 @@ async function refreshDashboard() {
       throw new Error(await readError(response));
     }
 
-    const dropdown = document.querySelector("details.repo-dropdown");
-    const wasOpen = dropdown && dropdown.hasAttribute("open");
+    const openDropdowns = Array.from(
+      document.querySelectorAll("details.repo-dropdown[open]"),
+      (dropdown) => dropdown.dataset.dropdown,
+    );
+    const search = document.querySelector("input[data-search-input]");
+    const searchState = search && {
+      value: search.value,
+      focused: document.activeElement === search,
+    };
     dashboardRoot.innerHTML = await response.text();
-    if (wasOpen) {
-      const restored = document.querySelector("details.repo-dropdown");
+    for (const name of openDropdowns) {
+      const restored = document.querySelector(`details.repo-dropdown[data-dropdown="${name}"]`);
       if (restored) {
         restored.setAttribute("open", "");
       }
     }
+    const restoredSearch = document.querySelector("input[data-search-input]");
+    if (searchState && restoredSearch) {
+      restoredSearch.value = searchState.value;
+      if (searchState.focused) {
+        restoredSearch.focus();
+      }
+    }
   })();
 
   try {
@@ -200,7 +214,7 @@ let repoFilterTimer = null;
 
 document.addEventListener("change", async (event) => {
   const target = event.target;
-  if (!(target instanceof HTMLInputElement)) {
+  if (!(target instanceof HTMLInputElement || target instanceof HTMLSelectElement)) {
     return;
   }
 
@@ -223,10 +237,11 @@ document.addEventListener("change", async (event) => {
 });
 
 document.addEventListener("click", (event) => {
-  // Close repo dropdown when clicking outside.
-  const openDropdown = document.querySelector("details.repo-dropdown[open]");
-  if (openDropdown && !openDropdown.contains(event.target)) {
-    openDropdown.removeAttribute("open");
+  // Close the repository and jobs dropdowns when clicking outside.
+  for (const openDropdown of document.querySelectorAll("details.repo-dropdown[open]")) {
+    if (!openDropdown.contains(event.target)) {
+      openDropdown.removeAttribute("open");
+    }
   }
 
   const threadLink = event.target.closest(".thread-open-link");
@@ -275,9 +290,66 @@ document.addEventListener("click", (event) => {
     }
   }
 
+  const guidelines = document.getElementById("review-guidelines");
+  if (guidelines instanceof HTMLElement) {
+    const source = reviewButton.dataset.reviewGuidelines;
+    guidelines.textContent = source ? `Guidelines: ${source}` : "";
+    guidelines.hidden = !source;
+  }
+
+  fillPublishForm(reviewButton);
+
   modal.showModal();
 });
 
+// Lists the findings of the opened review as checkboxes, all selected.
+function fillPublishForm(reviewButton) {
+  const publishForm = document.getElementById("publish-form");
+  const list = document.getElementById("publish-findings");
+  if (!(publishForm instanceof HTMLFormElement) || !(list instanceof HTMLElement)) {
+    return;
+  }
+
+  const publishAction = reviewButton.dataset.publishAction;
+  const reviewId = reviewButton.dataset.reviewId;
+  if (!publishAction || !reviewId) {
+    publishForm.style.display = "none";
+    return;
+  }
+
+  publishForm.action = publishAction;
+  publishForm.elements.namedItem("review_id").value = reviewId;
+  publishForm.elements.namedItem("submit").checked = false;
+  list.replaceChildren();
+
+  let findings = [];
+  try {
+    findings = JSON.parse(reviewButton.dataset.reviewFindings || "[]");
+  } catch {
+    findings = [];
+  }
+  for (const finding of findings) {
+    const label = document.createElement("label");
+    label.className = "publish-finding";
+    const input = document.createElement("input");
+    input.type = "checkbox";
+    input.name = `finding:${finding.id}`;
+    input.checked = true;
+    const badge = document.createElement("span");
+    badge.className = `finding-badge ${finding.severity}`;
+    badge.textContent = finding.severity;
+    const text = document.createElement("span");
+    text.textContent = finding.label;
+    label.append(input, badge, text);
+    list.append(label);
+  }
+  if (findings.length === 0) {
+    list.textContent = "No findings: only the summary will be published.";
+  }
+
+  publishForm.style.display = "";
+}
+
 const events = new EventSource("/dashboard/events");
 events.addEventListener("update", async () => {
   try {
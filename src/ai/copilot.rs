@@ -0,0 +1,42 @@
+use super::{AiAgent, AiFuture, AiRequest, AiTask, CliInvocation, run_cli_agent};
+
+#[derive(Debug)]
+pub(super) struct Copilot;
+
+impl AiAgent for Copilot {
+    fn id(&self) -> &'static str {
+        "copilot"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "GitHub Copilot"
+    }
+
+    fn default_model(&self, task: AiTask) -> &'static str {
+        match task {
+            AiTask::Review | AiTask::Fix => "gpt-5.3-codex",
+            AiTask::CommitMessage => "gpt-5-mini",
+        }
+    }
+
+    fn run<'a>(&'a self, request: AiRequest<'a>) -> AiFuture<'a> {
+        let model = self.resolve_model(&request);
+        let prompt_flag = if request.interactive {
+            "--interactive"
+        } else {
+            "--prompt"
+        };
+        let invocation = CliInvocation {
+            program: "copilot",
+            args: vec![
+                "--silent".to_string(),
+                "--model".to_string(),
+                model.to_string(),
+                prompt_flag.to_string(),
+                request.prompt.to_string(),
+            ],
+            display_args: format!("--silent --model {model} {prompt_flag}"),
+        };
+        Box::pin(run_cli_agent(self, invocation, request))
+    }
+}
@@ -0,0 +1,259 @@
+use serde_json::Value;
+
+use crate::{
+    cmd::{Cmd, ensure_command_available},
+    config::{CustomAiConfig, CustomOutputFormat, PromptInput},
+    terminal::strip_control_sequences,
+};
+
+use super::{AiAgent, AiFuture, AiOutput, AiRequest, AiTask};
+
+const MODEL_PLACEHOLDER: &str = "{model}";
+const PROMPT_PLACEHOLDER: &str = "{prompt}";
+
+#[derive(Debug)]
+pub(super) struct Custom {
+    config: CustomAiConfig,
+}
+
+impl Custom {
+    pub(super) fn new(config: CustomAiConfig) -> Self {
+        Self { config }
+    }
+
+    async fn run_command(&self, request: AiRequest<'_>) -> anyhow::Result<AiOutput> {
+        let program = self.config.command.trim();
+        anyhow::ensure!(
+            !program.is_empty(),
+            "❌ `ai.custom.command` must be set when provider = \"custom\""
+        );
+        ensure_command_available(program).await?;
+
+        let model = self.resolve_model(&request);
+        anyhow::ensure!(
+            !model.is_empty() || !self.uses_placeholder(MODEL_PLACEHOLDER),
+            "❌ Custom AI command expects {MODEL_PLACEHOLDER} but no model is configured: \
+             set `ai.model`, `ai.custom.default_model` or pass `--model`"
+        );
+        let stdin_prompt = self.config.prompt_input == PromptInput::Stdin;
+        anyhow::ensure!(
+            !(request.interactive && stdin_prompt),
+            "❌ Custom AI command cannot run interactively with prompt_input = \"stdin\""
+        );
+
+        let args = render_args(
+            &self.config.args,
+            model,
+            (!stdin_prompt).then_some(request.prompt),
+        );
+        let display_args = render_args(&self.config.args, model, (!stdin_prompt).then_some("..."));
+
+        let mut cmd = Cmd::new(program, args);
+        cmd.with_title(format!("🚀 {program} {} ...", display_args.join(" ")))
+            .with_current_dir(request.repo_root);
+        if stdin_prompt {
+            cmd.with_stdin(request.prompt);
+        }
+
+        let output = if request.interactive {
+            cmd.run_interactive().await?
+        } else {
+            cmd.hide_stdout().run().await?
+        };
+        output.ensure_success(format!("❌ Failed to generate output with {program}"))?;
+
+        let text = if request.interactive {
+            String::new()
+        } else {
+            let text = extract_output(
+                output.stdout(),
+                self.config.output,
+                self.config.output_json_pointer.as_deref(),
+            )?;
+            anyhow::ensure!(
+                !text.trim().is_empty(),
+                "❌ {program} returned empty output"
+            );
+            text
+        };
+
+        Ok(AiOutput {
+            provider: self.id().to_string(),
+            model: (!model.is_empty()).then(|| model.to_string()),
+            text,
+        })
+    }
+
+    fn uses_placeholder(&self, placeholder: &str) -> bool {
+        self.config.args.iter().any(|arg| arg.contains(placeholder))
+    }
+}
+
+impl AiAgent for Custom {
+    fn id(&self) -> &'static str {
+        "custom"
+    }
+
+    fn display_name(&self) -> &str {
+        if self.config.command.trim().is_empty() {
+            "custom command"
+        } else {
+            self.config.command.trim()
+        }
+    }
+
+    fn default_model(&self, _task: AiTask) -> &str {
+        self.config.default_model.as_deref().unwrap_or_default()
+    }
+
+    fn run<'a>(&'a self, request: AiRequest<'a>) -> AiFuture<'a> {
+        Box::pin(self.run_command(request))
+    }
+}
+
+/// Substitute placeholders in the configured arguments. When `prompt` is set
+/// and no argument references it, it is appended as last argument.
+fn render_args(template: &[String], model: &str, prompt: Option<&str>) -> Vec<String> {
+    let mut prompt_used = false;
+    let mut args: Vec<String> = template
+        .iter()
+        .map(|arg| {
+            let arg = arg.replace(MODEL_PLACEHOLDER, model);
+            match prompt {
+                Some(prompt) if arg.contains(PROMPT_PLACEHOLDER) => {
+                    prompt_used = true;
+                    arg.replace(PROMPT_PLACEHOLDER, prompt)
+                }
+                _ => arg,
+            }
+        })
+        .collect();
+
+    if let Some(prompt) = prompt
+        && !prompt_used
+    {
+        args.push(prompt.to_string());
+    }
+    args
+}
+
+fn extract_output(
+    stdout: &str,
+    format: CustomOutputFormat,
+    json_pointer: Option<&str>,
+) -> anyhow::Result<String> {
+    let stdout = strip_control_sequences(stdout);
+    match format {
+        CustomOutputFormat::Text => Ok(stdout),
+        CustomOutputFormat::Json => {
+            let pointer = json_pointer.unwrap_or_default();
+            let value: Value = serde_json::from_str(stdout.trim())
+                .map_err(|e| anyhow::anyhow!("❌ Custom AI command did not return JSON: {e}"))?;
+            match value.pointer(pointer) {
+                Some(Value::String(text)) => Ok(text.clone()),
+                Some(_) => {
+                    anyhow::bail!("❌ Custom AI command output at `{pointer}` is not a string")
+                }
+                None => anyhow::bail!("❌ Custom AI command output has no value at `{pointer}`"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use camino::Utf8Path;
+
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn render_args_replaces_placeholders() {
+        let args = render_args(
+            &strings(&["--model={model}", "-p", "{prompt}"]),
+            "m1",
+            Some("hello"),
+        );
+        assert_eq!(args, strings(&["--model=m1", "-p", "hello"]));
+    }
+
+    #[test]
+    fn render_args_appends_prompt_without_placeholder() {
+        let args = render_args(&strings(&["run"]), "m1", Some("hello"));
+        assert_eq!(args, strings(&["run", "hello"]));
+    }
+
+    #[test]
+    fn render_args_leaves_prompt_out_for_stdin() {
+        let args = render_args(&strings(&["run", "{model}"]), "m1", None);
+        assert_eq!(args, strings(&["run", "m1"]));
+    }
+
+    #[test]
+    fn extract_output_strips_control_sequences() {
+        let text =
+            extract_output("\u{1b}[1mhello\u{1b}[0m", CustomOutputFormat::Text, None).unwrap();
+        assert_eq!(text, "hello");
+    }
+
+    #[test]
+    fn extract_output_reads_json_pointer() {
+        let text = extract_output(
+            r#"{"result": {"text": "answer"}}"#,
+            CustomOutputFormat::Json,
+            Some("/result/text"),
+        )
+        .unwrap();
+        assert_eq!(text, "answer");
+
+        assert!(extract_output("{}", CustomOutputFormat::Json, Some("/missing")).is_err());
+        assert!(extract_output("not json", CustomOutputFormat::Json, Some("")).is_err());
+    }
+
+    #[tokio::test]
+    async fn runs_command_with_prompt_on_stdin() {
+        let agent = Custom::new(CustomAiConfig {
+            command: "cat".to_string(),
+            prompt_input: PromptInput::Stdin,
+            ..CustomAiConfig::default()
+        });
+        let output = agent
+            .run(AiRequest {
+                repo_root: Utf8Path::new("."),
+                prompt: "review this",
+                model: None,
+                task: AiTask::Review,
+                interactive: false,
+            })
+            .await
+            .unwrap();
+        assert_eq!(output.provider, "custom");
+        assert_eq!(output.model, None);
+        assert_eq!(output.text, "review this");
+    }
+
+    #[tokio::test]
+    async fn runs_command_with_prompt_in_argv() {
+        let agent = Custom::new(CustomAiConfig {
+            command: "echo".to_string(),
+            args: strings(&["{model}:", "{prompt}"]),
+            default_model: Some("local".to_string()),
+            ..CustomAiConfig::default()
+        });
+        let output = agent
+            .run(AiRequest {
+                repo_root: Utf8Path::new("."),
+                prompt: "hi",
+                model: None,
+                task: AiTask::CommitMessage,
+                interactive: false,
+            })
+            .await
+            .unwrap();
+        assert_eq!(output.model.as_deref(), Some("local"));
+        assert_eq!(output.text, "local: hi");
+    }
+}
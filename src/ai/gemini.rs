@@ -0,0 +1,44 @@
+use super::{AiAgent, AiFuture, AiRequest, AiTask, CliInvocation, run_cli_agent};
+
+#[derive(Debug)]
+pub(super) struct Gemini;
+
+impl AiAgent for Gemini {
+    fn id(&self) -> &'static str {
+        "gemini"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Gemini"
+    }
+
+    fn default_model(&self, task: AiTask) -> &'static str {
+        match task {
+            AiTask::Review | AiTask::Fix => "gemini-3-pro-preview",
+            AiTask::CommitMessage => "gemini-3-flash-preview",
+        }
+    }
+
+    fn run<'a>(&'a self, request: AiRequest<'a>) -> AiFuture<'a> {
+        let model = self.resolve_model(&request);
+        let prompt_flag = if request.interactive {
+            "--prompt-interactive"
+        } else {
+            "--prompt"
+        };
+        let invocation = CliInvocation {
+            program: "gemini",
+            args: vec![
+                "--model".to_string(),
+                model.to_string(),
+                "--sandbox".to_string(),
+                "--output-format".to_string(),
+                "text".to_string(),
+                prompt_flag.to_string(),
+                request.prompt.to_string(),
+            ],
+            display_args: format!("--model {model} --sandbox --output-format text {prompt_flag}"),
+        };
+        Box::pin(run_cli_agent(self, invocation, request))
+    }
+}
@@ -0,0 +1,44 @@
+use super::{AiAgent, AiFuture, AiRequest, AiTask, CliInvocation, run_cli_agent};
+
+const DEFAULT_KIRO_MODEL: &str = "claude-opus-4.7";
+
+#[derive(Debug)]
+pub(super) struct Kiro;
+
+impl AiAgent for Kiro {
+    fn id(&self) -> &'static str {
+        "kiro"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Kiro"
+    }
+
+    fn default_model(&self, _task: AiTask) -> &str {
+        DEFAULT_KIRO_MODEL
+    }
+
+    fn run<'a>(&'a self, request: AiRequest<'a>) -> AiFuture<'a> {
+        let model = self.resolve_model(&request);
+        let mut args = vec!["chat".to_string()];
+        if !request.interactive {
+            args.push("--no-interactive".to_string());
+        }
+        args.extend(["--model".to_string(), model.to_string()]);
+        args.push(request.prompt.to_string());
+
+        let invocation = CliInvocation {
+            program: "kiro-cli",
+            args,
+            display_args: format!(
+                "chat {}--model {model}",
+                if request.interactive {
+                    ""
+                } else {
+                    "--no-interactive "
+                }
+            ),
+        };
+        Box::pin(run_cli_agent(self, invocation, request))
+    }
+}
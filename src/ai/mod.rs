@@ -0,0 +1,172 @@
+mod copilot;
+mod custom;
+mod gemini;
+mod kiro;
+mod openai;
+
+use std::pin::Pin;
+
+use camino::Utf8Path;
+
+use crate::{
+    cmd::{Cmd, ensure_command_available},
+    config::{AiConfig, AiProvider},
+};
+
+/// What the agent is being asked to do. Agents may pick a different default
+/// model per task (e.g. a cheaper model for commit messages).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiTask {
+    Review,
+    Fix,
+    CommitMessage,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AiRequest<'a> {
+    pub repo_root: &'a Utf8Path,
+    pub prompt: &'a str,
+    /// Model requested by the user. `None` means the agent default for `task`.
+    pub model: Option<&'a str>,
+    pub task: AiTask,
+    /// Hand the terminal over to the agent instead of capturing its output.
+    pub interactive: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct AiOutput {
+    pub provider: String,
+    pub model: Option<String>,
+    /// Raw text produced by the agent. Empty for interactive runs.
+    pub text: String,
+}
+
+pub type AiFuture<'a> = Pin<Box<dyn Future<Output = anyhow::Result<AiOutput>> + Send + 'a>>;
+
+/// An AI backend able to answer the prompts built by `review` and `commit`.
+///
+/// Adding a provider means implementing this trait and registering it in
+/// [`agent_for`].
+pub trait AiAgent: std::fmt::Debug + Send + Sync {
+    /// Identifier stored alongside reviews and fix runs (e.g. `copilot`).
+    fn id(&self) -> &str;
+
+    /// Human readable name used in progress and error messages.
+    fn display_name(&self) -> &str;
+
+    fn default_model(&self, task: AiTask) -> &str;
+
+    fn run<'a>(&'a self, request: AiRequest<'a>) -> AiFuture<'a>;
+
+    fn resolve_model<'a>(&'a self, request: &AiRequest<'a>) -> &'a str {
+        request
+            .model
+            .unwrap_or_else(|| self.default_model(request.task))
+    }
+}
+
+/// `config` provides the settings of the configurable providers; `provider`
+/// may differ from `config.provider` when overridden on the command line.
+pub fn agent_for(provider: AiProvider, config: &AiConfig) -> Box<dyn AiAgent> {
+    match provider {
+        AiProvider::Copilot => Box::new(copilot::Copilot),
+        AiProvider::Gemini => Box::new(gemini::Gemini),
+        AiProvider::Kiro => Box::new(kiro::Kiro),
+        AiProvider::Custom => Box::new(custom::Custom::new(config.custom.clone())),
+        AiProvider::OpenAi => Box::new(openai::OpenAi::new(config.openai.clone())),
+    }
+}
+
+/// Command line of a CLI-backed agent, split so the prompt never shows up in
+/// verbose logs.
+struct CliInvocation {
+    program: &'static str,
+    args: Vec<String>,
+    /// Arguments printed in the command title instead of `args`.
+    display_args: String,
+}
+
+async fn run_cli_agent(
+    agent: &dyn AiAgent,
+    invocation: CliInvocation,
+    request: AiRequest<'_>,
+) -> anyhow::Result<AiOutput> {
+    ensure_command_available(invocation.program).await?;
+
+    let mut cmd = Cmd::new(invocation.program, invocation.args);
+    cmd.with_title(format!(
+        "🚀 {} {} ...",
+        invocation.program, invocation.display_args
+    ))
+    .with_current_dir(request.repo_root);
+
+    let output = if request.interactive {
+        cmd.run_interactive().await?
+    } else {
+        cmd.hide_stdout().run().await?
+    };
+
+    output.ensure_success(format!(
+        "❌ Failed to generate output with {}",
+        agent.display_name()
+    ))?;
+    if !request.interactive {
+        anyhow::ensure!(
+            !output.stdout().trim().is_empty(),
+            "❌ {} returned empty output",
+            agent.display_name()
+        );
+    }
+
+    Ok(AiOutput {
+        provider: agent.id().to_string(),
+        model: Some(agent.resolve_model(&request).to_string()),
+        text: output.stdout().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_maps_every_provider_to_its_agent() {
+        for (provider, id) in [
+            (AiProvider::Copilot, "copilot"),
+            (AiProvider::Gemini, "gemini"),
+            (AiProvider::Kiro, "kiro"),
+            (AiProvider::Custom, "custom"),
+            (AiProvider::OpenAi, "openai"),
+        ] {
+            assert_eq!(agent_for(provider, &AiConfig::default()).id(), id);
+        }
+    }
+
+    #[test]
+    fn resolve_model_prefers_requested_model() {
+        let agent = agent_for(AiProvider::Copilot, &AiConfig::default());
+        let request = AiRequest {
+            repo_root: Utf8Path::new("."),
+            prompt: "p",
+            model: Some("custom-model"),
+            task: AiTask::Review,
+            interactive: false,
+        };
+        assert_eq!(agent.resolve_model(&request), "custom-model");
+    }
+
+    #[test]
+    fn resolve_model_uses_task_specific_default() {
+        let agent = agent_for(AiProvider::Gemini, &AiConfig::default());
+        let mut request = AiRequest {
+            repo_root: Utf8Path::new("."),
+            prompt: "p",
+            model: None,
+            task: AiTask::Review,
+            interactive: false,
+        };
+        assert_eq!(agent.resolve_model(&request), "gemini-3-pro-preview");
+        request.task = AiTask::CommitMessage;
+        assert_eq!(agent.resolve_model(&request), "gemini-3-flash-preview");
+    }
+}
@@ -0,0 +1,400 @@
+use std::{io::Write as _, time::Duration};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::OpenAiConfig, terminal::strip_control_sequences};
+
+use super::{AiAgent, AiFuture, AiOutput, AiRequest, AiTask};
+
+#[derive(Debug)]
+pub(super) struct OpenAi {
+    config: OpenAiConfig,
+    /// Reads the env var of the API key, replaced in tests.
+    read_env: fn(&str) -> Result<String, std::env::VarError>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: [ChatMessage<'a>; 1],
+    stream: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    /// Set on regular responses.
+    message: Option<ChatContent>,
+    /// Set on streamed chunks.
+    delta: Option<ChatContent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatContent {
+    content: Option<String>,
+}
+
+impl OpenAi {
+    pub(super) fn new(config: OpenAiConfig) -> Self {
+        Self {
+            config,
+            read_env: |var| std::env::var(var),
+        }
+    }
+
+    fn endpoint(&self) -> String {
+        format!(
+            "{}/v1/chat/completions",
+            self.config.base_url.trim_end_matches('/')
+        )
+    }
+
+    fn api_key(&self) -> anyhow::Result<Option<String>> {
+        let Some(var) = self.config.api_key_env.as_deref() else {
+            return Ok(None);
+        };
+        let key = (self.read_env)(var)
+            .with_context(|| format!("❌ API key env var `{var}` is not set"))?;
+        Ok(Some(key))
+    }
+
+    async fn complete(&self, request: AiRequest<'_>) -> anyhow::Result<AiOutput> {
+        let model = self.resolve_model(&request);
+        anyhow::ensure!(
+            !model.is_empty(),
+            "❌ No model configured for the OpenAI-compatible provider: \
+             set `ai.model`, `ai.openai.default_model` or pass `--model`"
+        );
+
+        let endpoint = self.endpoint();
+        if request.interactive {
+            println!("🚀 POST {endpoint} (model {model}) ...");
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(self.config.timeout_seconds))
+            .build()?;
+        let mut http_request = client.post(&endpoint).json(&ChatRequest {
+            model,
+            messages: [ChatMessage {
+                role: "user",
+                content: request.prompt,
+            }],
+            stream: self.config.stream,
+        });
+        if let Some(key) = self.api_key()? {
+            http_request = http_request.bearer_auth(key);
+        }
+
+        let mut response = http_request
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("❌ Failed to reach {endpoint}: {e}"))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("❌ {endpoint} returned {status}: {}", body.trim());
+        }
+
+        let text = if self.config.stream {
+            read_stream(&mut response, &endpoint, request.interactive).await?
+        } else {
+            let body: ChatResponse = response
+                .json()
+                .await
+                .with_context(|| format!("❌ Invalid response from {endpoint}"))?;
+            let text = first_content(body, |choice| choice.message);
+            if request.interactive {
+                print!("{text}");
+            }
+            text
+        };
+        if request.interactive {
+            println!();
+        }
+
+        let text = strip_control_sequences(&text);
+        anyhow::ensure!(
+            !text.trim().is_empty(),
+            "❌ {endpoint} returned empty output"
+        );
+
+        Ok(AiOutput {
+            provider: self.id().to_string(),
+            model: Some(model.to_string()),
+            text,
+        })
+    }
+}
+
+impl AiAgent for OpenAi {
+    fn id(&self) -> &'static str {
+        "openai"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "OpenAI-compatible server"
+    }
+
+    fn default_model(&self, _task: AiTask) -> &str {
+        self.config.default_model.as_deref().unwrap_or_default()
+    }
+
+    fn run<'a>(&'a self, request: AiRequest<'a>) -> AiFuture<'a> {
+        Box::pin(self.complete(request))
+    }
+}
+
+/// Read a streamed completion, echoing the text as it arrives when
+/// `interactive` is set.
+async fn read_stream(
+    response: &mut reqwest::Response,
+    endpoint: &str,
+    interactive: bool,
+) -> anyhow::Result<String> {
+    let mut parser = StreamParser::default();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .with_context(|| format!("❌ Failed to read response from {endpoint}"))?
+    {
+        let delta = parser.push(&chunk)?;
+        if interactive && !delta.is_empty() {
+            print!("{delta}");
+            std::io::stdout().flush()?;
+        }
+    }
+    parser.finish()
+}
+
+fn first_content(
+    response: ChatResponse,
+    content: impl Fn(ChatChoice) -> Option<ChatContent>,
+) -> String {
+    response
+        .choices
+        .into_iter()
+        .next()
+        .and_then(content)
+        .and_then(|content| content.content)
+        .unwrap_or_default()
+}
+
+/// Accumulates a `text/event-stream` body. Events may be split across chunks,
+/// so only complete lines are parsed.
+#[derive(Debug, Default)]
+struct StreamParser {
+    pending: Vec<u8>,
+    text: String,
+    done: bool,
+}
+
+impl StreamParser {
+    /// Feed a chunk of the body and return the text it added.
+    fn push(&mut self, chunk: &[u8]) -> anyhow::Result<String> {
+        self.pending.extend_from_slice(chunk);
+        let mut added = String::new();
+        while let Some(newline) = self.pending.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=newline).collect();
+            added.push_str(&self.parse_line(String::from_utf8_lossy(&line).trim())?);
+        }
+        self.text.push_str(&added);
+        Ok(added)
+    }
+
+    fn parse_line(&mut self, line: &str) -> anyhow::Result<String> {
+        let Some(data) = line.strip_prefix("data:") else {
+            return Ok(String::new());
+        };
+        let data = data.trim();
+        if data == "[DONE]" {
+            self.done = true;
+            return Ok(String::new());
+        }
+        if self.done {
+            return Ok(String::new());
+        }
+        let chunk: ChatResponse = serde_json::from_str(data)
+            .with_context(|| format!("❌ Invalid stream event `{data}`"))?;
+        Ok(first_content(chunk, |choice| choice.delta))
+    }
+
+    fn finish(mut self) -> anyhow::Result<String> {
+        let rest = std::mem::take(&mut self.pending);
+        let added = self.parse_line(String::from_utf8_lossy(&rest).trim())?;
+        self.text.push_str(&added);
+        Ok(self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{
+        Json, Router,
+        http::{HeaderMap, StatusCode},
+        response::IntoResponse,
+        routing::post,
+    };
+    use camino::Utf8Path;
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::test_util::spawn_stub;
+
+    #[derive(Debug, Clone, Default)]
+    struct Captured {
+        body: Arc<Mutex<Option<Value>>>,
+        authorization: Arc<Mutex<Option<String>>>,
+    }
+
+    async fn completions(
+        captured: axum::extract::State<Captured>,
+        headers: HeaderMap,
+        Json(body): Json<Value>,
+    ) -> impl IntoResponse {
+        *captured.authorization.lock().unwrap() = headers
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string);
+        let stream = body["stream"].as_bool().unwrap_or(false);
+        *captured.body.lock().unwrap() = Some(body);
+
+        if stream {
+            let events = [
+                json!({"choices": [{"delta": {"role": "assistant"}}]}),
+                json!({"choices": [{"delta": {"content": "REQUIRES_CODE_CHANGES: NO\n"}}]}),
+                json!({"choices": [{"delta": {"content": "Looks good"}}]}),
+            ]
+            .iter()
+            .map(|event| format!("data: {event}\n\n"))
+            .collect::<String>();
+            (
+                [("content-type", "text/event-stream")],
+                format!("{events}data: [DONE]\n\n"),
+            )
+                .into_response()
+        } else {
+            Json(json!({
+                "choices": [{"message": {"role": "assistant", "content": "Fix typo"}}]
+            }))
+            .into_response()
+        }
+    }
+
+    fn request(prompt: &str, task: AiTask) -> AiRequest<'_> {
+        AiRequest {
+            repo_root: Utf8Path::new("."),
+            prompt,
+            model: Some("local-model"),
+            task,
+            interactive: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn posts_prompt_and_reads_message() {
+        let captured = Captured::default();
+        let base_url = spawn_stub(
+            Router::new()
+                .route("/v1/chat/completions", post(completions))
+                .with_state(captured.clone()),
+        )
+        .await;
+        let agent = OpenAi::new(OpenAiConfig {
+            base_url: format!("{base_url}/"),
+            stream: false,
+            ..OpenAiConfig::default()
+        });
+
+        let output = agent
+            .run(request("write a commit message", AiTask::CommitMessage))
+            .await
+            .unwrap();
+
+        assert_eq!(output.provider, "openai");
+        assert_eq!(output.model.as_deref(), Some("local-model"));
+        assert_eq!(output.text, "Fix typo");
+        let body = captured.body.lock().unwrap().clone().unwrap();
+        assert_eq!(body["model"], "local-model");
+        assert_eq!(body["messages"][0]["content"], "write a commit message");
+        assert_eq!(body["stream"], false);
+        assert_eq!(*captured.authorization.lock().unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn streams_chunks_and_sends_api_key() {
+        let captured = Captured::default();
+        let base_url = spawn_stub(
+            Router::new()
+                .route("/v1/chat/completions", post(completions))
+                .with_state(captured.clone()),
+        )
+        .await;
+        let agent = OpenAi {
+            read_env: |var| match var {
+                "GIGI_TEST_OPENAI_KEY" => Ok("test-key".to_string()),
+                _ => Err(std::env::VarError::NotPresent),
+            },
+            ..OpenAi::new(OpenAiConfig {
+                base_url,
+                api_key_env: Some("GIGI_TEST_OPENAI_KEY".to_string()),
+                ..OpenAiConfig::default()
+            })
+        };
+
+        let output = agent.run(request("review", AiTask::Review)).await.unwrap();
+
+        assert_eq!(output.text, "REQUIRES_CODE_CHANGES: NO\nLooks good");
+        assert_eq!(
+            *captured.authorization.lock().unwrap(),
+            Some("Bearer test-key".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_http_errors() {
+        let base_url = spawn_stub(Router::new().route(
+            "/v1/chat/completions",
+            post(async || (StatusCode::NOT_FOUND, "model not found")),
+        ))
+        .await;
+        let agent = OpenAi::new(OpenAiConfig {
+            base_url,
+            ..OpenAiConfig::default()
+        });
+
+        let err = agent
+            .run(request("review", AiTask::Review))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("404"), "{err}");
+        assert!(err.to_string().contains("model not found"), "{err}");
+    }
+
+    #[test]
+    fn stream_parser_handles_events_split_across_chunks() {
+        let mut parser = StreamParser::default();
+        assert_eq!(
+            parser
+                .push(b"data: {\"choices\":[{\"delta\":{\"content\":\"He")
+                .unwrap(),
+            ""
+        );
+        assert_eq!(parser.push(b"llo\"}}]}\n\n").unwrap(), "Hello");
+        assert_eq!(parser.push(b": keep-alive\ndata: [DONE]\n").unwrap(), "");
+        assert_eq!(parser.finish().unwrap(), "Hello");
+    }
+}
@@ -1,3 +1,7 @@
+use std::path::PathBuf;
+
+use crate::{config::AiProvider, maintenance::ExportFormat, review::Severity};
+
 #[derive(clap::Parser, Debug)]
 #[command(about, version, author)]
 pub struct CliArgs {
@@ -9,14 +13,6 @@ pub struct CliArgs {
     pub command: Command,
 }
 
-#[derive(clap::ValueEnum, Clone, Debug, Default)]
-pub enum Agent {
-    #[default]
-    Copilot,
-    Gemini,
-    Kiro,
-}
-
 #[derive(clap::Subcommand, Debug)]
 pub enum Command {
     /// Creates a new branch and opens a pull request preview in the browser
@@ -26,7 +22,7 @@ pub enum Command {
         message: Option<String>,
         /// AI agent to generate commit message
         #[arg(long, value_enum)]
-        agent: Option<Agent>,
+        agent: Option<AiProvider>,
         /// Model to use for commit message generation (defaults to gpt-5-mini for copilot, gemini-3-flash-preview for gemini)
         #[arg(long)]
         model: Option<String>,
@@ -34,22 +30,74 @@ pub enum Command {
 
     /// Clone the PR repo into ~/proj/<owner>/<repo>, update default branch, checkout PR, and open VS Code
     CheckoutPr {
-        /// GitHub PR URL to checkout
+        /// GitHub PR or GitLab merge request URL to checkout
         #[arg(value_name = "PR_URL")]
         pr: String,
     },
 
-    /// Review a GitHub pull request using an AI agent
+    /// Review a GitHub pull request or a GitLab merge request using an AI agent
     Review {
-        /// GitHub PR URL to review
+        /// GitHub PR or GitLab merge request URL to review
         #[arg(value_name = "PR_URL")]
         pr: String,
-        /// AI agent to generate the review
+        /// AI agent to generate the review (defaults to `[ai] provider` of the config)
         #[arg(long, value_enum)]
-        agent: Option<Agent>,
+        agent: Option<AiProvider>,
         /// Model to use for review generation (defaults to gpt-5-mini for copilot, gemini-3-flash-preview for gemini)
         #[arg(long)]
         model: Option<String>,
+        /// Only print findings at or above this severity
+        #[arg(long, value_enum)]
+        min_severity: Option<Severity>,
+        /// Publish the review on the forge as a pending review with inline comments,
+        /// after picking the findings to include
+        #[arg(long)]
+        post: bool,
+        /// Submit the published review instead of leaving it pending
+        #[arg(long, requires = "post")]
+        submit: bool,
+        /// Check out the PR and review it with the surrounding repository
+        /// context (changed files, their usages and related tests)
+        #[arg(long)]
+        full_context: bool,
+        /// Only review the commits pushed since the latest stored review of
+        /// the PR, and report which of its findings are resolved
+        #[arg(long)]
+        incremental: bool,
+        /// Discuss the PR with the agent in an interactive session, in the
+        /// current directory. The review is not stored
+        #[arg(long, conflicts_with_all = ["min_severity", "post", "full_context", "incremental"])]
+        interactive: bool,
+    },
+    /// List and count the findings of the latest stored reviews
+    Findings {
+        /// Only show findings of this PR
+        #[arg(value_name = "PR_URL")]
+        pr: Option<String>,
+        /// Only show findings at or above this severity
+        #[arg(long, value_enum)]
+        min_severity: Option<Severity>,
+    },
+    /// Add a GitHub PR to the merge queue, or enable auto-merge when its base
+    /// branch has no merge queue
+    Queue {
+        /// GitHub PR URL to queue
+        #[arg(value_name = "PR_URL")]
+        pr: String,
+    },
+    /// Search the stored reviews, thread titles and fix run outputs
+    Search {
+        /// Words to look for, all of them in each hit
+        #[arg(required = true, value_name = "QUERY")]
+        query: Vec<String>,
+        /// Maximum number of hits
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Maintain the DB used by `serve`
+    Db {
+        #[command(subcommand)]
+        command: DbCommand,
     },
     /// Initialize the default config file used by `serve`
     Init,
@@ -66,3 +114,35 @@ pub enum Command {
     /// Sync a fork with the upstream repository and update local default branch
     Sync,
 }
+
+#[derive(clap::Subcommand, Debug)]
+pub enum DbCommand {
+    /// Delete the data the `[retention]` of the config no longer keeps, then
+    /// VACUUM the DB
+    Prune {
+        /// Only print what would be deleted
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Copy the DB with the sqlite online backup, safe while `serve` runs
+    Backup {
+        /// Backup file to create (defaults to a timestamped file next to the DB)
+        #[arg(value_name = "PATH")]
+        destination: Option<PathBuf>,
+    },
+    /// Export every table of the DB
+    Export {
+        #[arg(long, value_enum, default_value_t)]
+        format: ExportFormat,
+        /// File to write instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Merge an export into the DB, matching rows by their natural keys
+    /// (`pr_url`, `thread_key`, ...)
+    Import {
+        /// JSON or NDJSON file written by `gigi db export`
+        #[arg(value_name = "FILE")]
+        input: PathBuf,
+    },
+}
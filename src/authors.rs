@@ -40,6 +40,22 @@ struct GitCommitAuthor {
     email: String,
 }
 
+impl PullRequestCommit {
+    /// Commit listed by a forge other than GitHub.
+    pub fn new(sha: String, author_name: String, author_email: String, message: String) -> Self {
+        Self {
+            sha,
+            commit: GitCommit {
+                author: GitCommitAuthor {
+                    name: author_name,
+                    email: author_email,
+                },
+                message,
+            },
+        }
+    }
+}
+
 fn parse_pull_request_commits(output: &str) -> anyhow::Result<Vec<PullRequestCommit>> {
     let pages: Vec<Vec<PullRequestCommit>> =
         serde_json::from_str(output).context("Invalid pull request commits JSON")?;
@@ -302,16 +318,12 @@ mod tests {
     use super::*;
 
     fn pull_request_commit(sha: &str, name: &str, email: &str, message: &str) -> PullRequestCommit {
-        PullRequestCommit {
-            sha: sha.to_string(),
-            commit: GitCommit {
-                author: GitCommitAuthor {
-                    name: name.to_string(),
-                    email: email.to_string(),
-                },
-                message: message.to_string(),
-            },
-        }
+        PullRequestCommit::new(
+            sha.to_string(),
+            name.to_string(),
+            email.to_string(),
+            message.to_string(),
+        )
     }
 
     #[test]
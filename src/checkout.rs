@@ -1,19 +1,19 @@
 use anyhow::Context as _;
 
-use crate::{github, launcher};
+use crate::{forge, github, launcher};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GitHubPrRef {
+    /// Web host, e.g. `github.com`.
+    pub host: String,
     pub owner: String,
     pub repo: String,
     pub number: u64,
 }
 
 pub async fn checkout_pr(pr_url: &str) -> anyhow::Result<()> {
-    let local_pr = github::ensure_local_repo_for_pr(pr_url).await?;
-    github::prepare_repo_for_pr_checkout(&local_pr.repo_dir).await?;
-    github::checkout_pr(&local_pr.repo_dir, pr_url).await?;
-    launcher::open_vscode(&local_pr.repo_dir).await?;
+    let repo_dir = forge::forge_for_url(pr_url)?.checkout_pr(pr_url).await?;
+    launcher::open_vscode(&repo_dir).await?;
     Ok(())
 }
 
@@ -26,15 +26,10 @@ pub fn parse_github_pr_url(input: &str) -> anyhow::Result<GitHubPrRef> {
         s = before;
     }
 
-    s = s
-        .strip_prefix("https://")
-        .or_else(|| s.strip_prefix("http://"))
-        .unwrap_or(s);
-
-    s = s
-        .strip_prefix("github.com/")
-        .or_else(|| s.strip_prefix("www.github.com/"))
-        .context("Expected a github.com PR URL")?;
+    let (host, path) = github::hosts()
+        .split_web_url(s)
+        .context("Expected a PR URL of github.com or of a configured GitHub host")?;
+    s = path;
 
     let parts: Vec<&str> = s.split('/').filter(|p| !p.is_empty()).collect();
     anyhow::ensure!(
@@ -51,12 +46,23 @@ pub fn parse_github_pr_url(input: &str) -> anyhow::Result<GitHubPrRef> {
         .with_context(|| format!("Invalid PR number: {}", parts[3]))?;
 
     Ok(GitHubPrRef {
+        host: host.host.clone(),
         owner: owner.to_string(),
         repo: repo.to_string(),
         number,
     })
 }
 
+impl GitHubPrRef {
+    /// Canonical URL of the PR.
+    pub fn url(&self) -> String {
+        format!(
+            "https://{}/{}/{}/pull/{}",
+            self.host, self.owner, self.repo, self.number
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -67,6 +73,7 @@ mod tests {
         assert_eq!(
             pr,
             GitHubPrRef {
+                host: "github.com".to_string(),
                 owner: "owner".to_string(),
                 repo: "repo".to_string(),
                 number: 123
@@ -88,6 +95,14 @@ mod tests {
         assert_eq!(pr.number, 1);
     }
 
+    #[test]
+    fn parse_url_of_a_configured_host() {
+        let pr = parse_github_pr_url("https://github.example.com/o/r/pull/3").unwrap();
+        assert_eq!(pr.host, "github.example.com");
+        assert_eq!(pr.url(), "https://github.example.com/o/r/pull/3");
+        assert!(parse_github_pr_url("https://gitlab.com/o/r/pull/3").is_err());
+    }
+
     #[test]
     fn reject_non_pr_url() {
         assert!(parse_github_pr_url("https://github.com/o/r/issues/1").is_err());
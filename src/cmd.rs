@@ -7,7 +7,7 @@ use std::{
 
 use anyhow::Context as _;
 use camino::Utf8PathBuf;
-use tokio::{fs, process::Command};
+use tokio::{fs, io::AsyncWriteExt as _, process::Command};
 
 static VERBOSE: AtomicBool = AtomicBool::new(false);
 
@@ -111,6 +111,7 @@ pub struct Cmd {
     hide_stdout: bool,
     hide_stderr: bool,
     title: Option<String>,
+    stdin: Option<String>,
 }
 
 impl Cmd {
@@ -130,6 +131,7 @@ impl Cmd {
             hide_stdout: false,
             hide_stderr: false,
             title: None,
+            stdin: None,
         }
     }
 
@@ -153,6 +155,12 @@ impl Cmd {
         self
     }
 
+    /// Feed `input` to the command's stdin. Only honored by [`Self::run`].
+    pub fn with_stdin(&mut self, input: impl Into<String>) -> &mut Self {
+        self.stdin = Some(input.into());
+        self
+    }
+
     fn build_command_description(&self) -> String {
         let mut description = self
             .title
@@ -186,6 +194,9 @@ impl Cmd {
 
     fn configure_command(&self) -> Command {
         let mut command = Command::new(&self.name);
+        // Cancelling a `serve` job drops the future running the command: the
+        // child must not keep editing the clone after that.
+        command.kill_on_drop(true);
         if let Some(dir) = &self.current_dir {
             command.current_dir(dir);
         }
@@ -215,12 +226,15 @@ impl Cmd {
             println!("{}", self.build_command_description());
         }
 
-        let output = self
-            .configure_command()
-            .args(&self.args)
-            .output()
-            .await
-            .with_context(|| self.spawn_context())?;
+        let output = match &self.stdin {
+            Some(input) => self.output_with_stdin(input).await?,
+            None => self
+                .configure_command()
+                .args(&self.args)
+                .output()
+                .await
+                .with_context(|| self.spawn_context())?,
+        };
 
         let output_stdout =
             String::from_utf8(output.stdout).context("command produced non-UTF-8 stdout")?;
@@ -237,6 +251,42 @@ impl Cmd {
         })
     }
 
+    async fn output_with_stdin(&self, input: &str) -> anyhow::Result<std::process::Output> {
+        let mut child = self
+            .configure_command()
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| self.spawn_context())?;
+
+        let mut stdin = child
+            .stdin
+            .take()
+            .context("failed to open stdin of spawned command")?;
+        let input = input.to_string();
+        // Write from a separate task so a child that fills its stdout before
+        // draining stdin cannot deadlock us.
+        // A child that exits without reading all of its input is not an error.
+        let writer = tokio::spawn(async move {
+            match stdin.write_all(input.as_bytes()).await {
+                Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+                result => result,
+            }
+        });
+
+        let output = child
+            .wait_with_output()
+            .await
+            .with_context(|| self.spawn_context())?;
+        writer
+            .await
+            .context("stdin writer task panicked")?
+            .context("failed to write to stdin of spawned command")?;
+        Ok(output)
+    }
+
     pub async fn run_interactive(&self) -> anyhow::Result<CmdOutput> {
         if is_verbose() {
             println!("{}", self.build_command_description());
@@ -312,6 +362,17 @@ mod tests {
         assert_eq!(desc, "Checking status 👉 /repo");
     }
 
+    #[tokio::test]
+    async fn test_run_feeds_stdin() {
+        let output = Cmd::new("cat", Vec::<&str>::new())
+            .with_stdin("from stdin\n")
+            .run()
+            .await
+            .unwrap();
+        assert!(output.status().success());
+        assert_eq!(output.stdout(), "from stdin");
+    }
+
     #[test]
     fn test_cmd_output_stdout_trims() {
         use std::process::ExitStatus;
@@ -4,12 +4,10 @@ use camino::{Utf8Path, Utf8PathBuf};
 use inquire::validator::Validation;
 use tokio::fs;
 
-use crate::cmd::{Cmd, CmdOutput, ensure_command_available};
-
-/// Check if copilot CLI is installed.
-async fn is_copilot_installed() -> bool {
-    ensure_command_available("copilot").await.is_ok()
-}
+use crate::{
+    ai::{AiAgent, AiRequest, AiTask},
+    cmd::Cmd,
+};
 
 async fn get_untracked_files(repo_root: &Utf8Path) -> Vec<String> {
     let status_output = Cmd::new("git", ["status", "--porcelain", "-z"])
@@ -102,145 +100,59 @@ fn build_commit_prompt(diff: &str) -> String {
     )
 }
 
-/// Generate a commit message using GitHub Copilot CLI.
-pub async fn generate_copilot_commit_message(
+/// Generate a commit message with the given AI agent.
+/// Returns an empty message when no agent is selected.
+pub async fn generate_commit_message(
     repo_root: &Utf8Path,
+    agent: Option<&dyn AiAgent>,
     model: Option<&str>,
 ) -> anyhow::Result<String> {
-    if !is_copilot_installed().await {
-        anyhow::bail!("❌ GitHub Copilot CLI is not installed");
-    }
-
-    let diff = get_diff(repo_root)
-        .await
-        .context("can't get repository diff")?
-        .context("no changes to generate commit message for")?;
-
-    println!("🤖 Generating commit message with GitHub Copilot...");
-
-    let prompt = build_commit_prompt(&diff);
-    let model = model.unwrap_or("gpt-5-mini");
-    let output = Cmd::new(
-        "copilot",
-        ["--silent", "--model", model, "--prompt", &prompt],
-    )
-    .hide_stdout()
-    .with_title(format!("🚀 copilot --silent --model {model} --prompt ..."))
-    .with_current_dir(repo_root)
-    .run()
-    .await?;
-
-    process_model_output(&output)
-}
-
-fn process_model_output(output: &CmdOutput) -> anyhow::Result<String> {
-    if output.status().success() {
-        let msg = output.stdout().trim().to_string();
-        if msg.is_empty() {
-            anyhow::bail!("❌ Generated commit message is empty")
-        } else {
-            if !is_commit_message_valid(&msg) {
-                eprintln!(
-                    "⚠️ {} Please adjust it before submitting.",
-                    commit_message_size_rule(&msg)
-                );
-            }
-            Ok(msg)
-        }
-    } else {
-        anyhow::bail!(
-            "❌ Failed to generate commit message with Gemini: {}",
-            output.stderr()
-        );
-    }
-}
+    let Some(agent) = agent else {
+        return Ok(String::new());
+    };
 
-/// Generate a commit message using Gemini CLI.
-pub async fn generate_gemini_commit_message(
-    repo_root: &Utf8Path,
-    model: Option<&str>,
-) -> anyhow::Result<String> {
     let diff = get_diff(repo_root)
         .await
         .context("can't get repository diff")?
         .context("no changes to generate commit message for")?;
 
-    println!("🤖 Generating commit message with Gemini...");
+    println!(
+        "🤖 Generating commit message with {}...",
+        agent.display_name()
+    );
 
     let prompt = build_commit_prompt(&diff);
-    let model = model.unwrap_or("gemini-3-flash-preview");
-    let output = Cmd::new(
-        "gemini",
-        [
-            "--model",
+    let output = agent
+        .run(AiRequest {
+            repo_root,
+            prompt: &prompt,
             model,
-            "--sandbox",
-            "--output-format",
-            "text",
-            "--prompt",
-            &prompt,
-        ],
-    )
-    .hide_stdout()
-    .with_title(format!(
-        "🚀 gemini --model {model} --sandbox --output-format text --prompt ..."
-    ))
-    .with_current_dir(repo_root)
-    .run()
-    .await?;
-
-    process_model_output(&output)
-}
-
-/// Generate a commit message using Kiro CLI.
-pub async fn generate_kiro_commit_message(
-    repo_root: &Utf8Path,
-    model: Option<&str>,
-) -> anyhow::Result<String> {
-    ensure_command_available("kiro-cli").await?;
-
-    let diff = get_diff(repo_root)
+            task: AiTask::CommitMessage,
+            interactive: false,
+        })
         .await
-        .context("can't get repository diff")?
-        .context("no changes to generate commit message for")?;
-
-    println!("🤖 Generating commit message with Kiro...");
+        .with_context(|| {
+            format!(
+                "❌ Failed to generate commit message with {}",
+                agent.display_name()
+            )
+        })?;
 
-    let prompt = build_commit_prompt(&diff);
-    let model = model.unwrap_or(crate::config::DEFAULT_KIRO_MODEL);
-    let mut args = vec![
-        "chat".to_string(),
-        "--no-interactive".to_string(),
-        "--model".to_string(),
-        model.to_string(),
-    ];
-    args.push(prompt);
-
-    let output = Cmd::new("kiro-cli", args)
-        .hide_stdout()
-        .with_title(format!(
-            "🚀 kiro-cli chat --no-interactive --model {model} ..."
-        ))
-        .with_current_dir(repo_root)
-        .run()
-        .await?;
-
-    process_model_output(&output)
+    process_model_output(&output.text)
 }
 
-pub async fn generate_commit_message(
-    repo_root: &Utf8Path,
-    agent: Option<&crate::args::Agent>,
-    model: Option<&str>,
-) -> anyhow::Result<String> {
-    match agent {
-        Some(crate::args::Agent::Gemini) => generate_gemini_commit_message(repo_root, model).await,
-        Some(crate::args::Agent::Kiro) => generate_kiro_commit_message(repo_root, model).await,
-        Some(crate::args::Agent::Copilot) => {
-            generate_copilot_commit_message(repo_root, model).await
-        }
-        None => Ok("".to_string()),
+fn process_model_output(output: &str) -> anyhow::Result<String> {
+    let msg = output.trim().to_string();
+    if msg.is_empty() {
+        anyhow::bail!("❌ Generated commit message is empty")
     }
+    if !is_commit_message_valid(&msg) {
+        eprintln!(
+            "⚠️ {} Please adjust it before submitting.",
+            commit_message_size_rule(&msg)
+        );
+    }
+    Ok(msg)
 }
 
 /// Ask the user for a commit message and enforce size rules.
@@ -4,8 +4,6 @@ use anyhow::Context as _;
 use serde::{Deserialize, Serialize};
 use tokio::fs;
 
-pub const DEFAULT_KIRO_MODEL: &str = "claude-opus-4.7";
-
 #[derive(Debug, Clone)]
 pub struct AppPaths {
     pub config_path: PathBuf,
@@ -20,7 +18,14 @@ pub struct AppConfig {
     pub initial_review_lookback_days: u64,
     pub initial_review_max_prs: usize,
     pub ai: AiConfig,
+    pub review: ReviewConfig,
     pub dashboard: DashboardConfig,
+    pub jobs: JobsConfig,
+    pub webhooks: WebhooksConfig,
+    pub retention: RetentionConfig,
+    pub github: GitHubConfig,
+    pub gitlab: GitLabConfig,
+    pub forgejo: ForgejoConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +33,95 @@ pub struct AppConfig {
 pub struct AiConfig {
     pub provider: AiProvider,
     pub model: Option<String>,
+    /// Only used when `provider = "custom"`.
+    pub custom: CustomAiConfig,
+    /// Only used when `provider = "openai"`.
+    pub openai: OpenAiConfig,
+}
+
+impl AiConfig {
+    /// `model`, if `provider` is the configured one: the model of another
+    /// provider would not exist there.
+    pub fn model_for(&self, provider: AiProvider) -> Option<String> {
+        (provider == self.provider)
+            .then(|| self.model.clone())
+            .flatten()
+    }
+}
+
+/// External command used as AI agent when `provider = "custom"`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct CustomAiConfig {
+    /// Executable name (looked up in PATH) or path.
+    pub command: String,
+    /// Arguments passed to `command`. `{model}` and `{prompt}` are replaced
+    /// with the resolved model and the prompt.
+    pub args: Vec<String>,
+    pub prompt_input: PromptInput,
+    pub output: CustomOutputFormat,
+    /// JSON pointer (e.g. `/result`) to the answer when `output = "json"`.
+    pub output_json_pointer: Option<String>,
+    /// Model used when neither `ai.model` nor `--model` is set.
+    pub default_model: Option<String>,
+}
+
+/// OpenAI-compatible chat completions server used when `provider = "openai"`
+/// (e.g. Ollama or llama.cpp).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct OpenAiConfig {
+    /// Server root; requests go to `{base_url}/v1/chat/completions`.
+    pub base_url: String,
+    /// Name of the env var holding the API key. No auth header when unset.
+    pub api_key_env: Option<String>,
+    pub timeout_seconds: u64,
+    pub stream: bool,
+    /// Model used when neither `ai.model` nor `--model` is set.
+    pub default_model: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptInput {
+    /// Prompt is passed as an argument, replacing `{prompt}` in `args`
+    /// (appended as last argument if the placeholder is missing).
+    #[default]
+    Argv,
+    /// Prompt is written to the command's stdin.
+    Stdin,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CustomOutputFormat {
+    /// The whole stdout is the answer.
+    #[default]
+    Text,
+    /// Stdout is a JSON document; the answer is the string at
+    /// `output_json_pointer`.
+    Json,
+}
+
+/// How PR diffs are sent to the agent for review.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ReviewConfig {
+    /// Approximate token budget of the diff sent in a single prompt. Larger
+    /// diffs are reviewed in chunks, then merged.
+    pub max_diff_tokens: usize,
+    /// Globs of files left out of reviews (e.g. lock files). Globs without
+    /// `/` match the file name.
+    pub exclude: Vec<String>,
+    /// Check out the PR head in a worktree of its clone under `~/proj` and run
+    /// the agent there, sending the changed files, their usages and related
+    /// tests along with the diff.
+    pub full_context: bool,
+    /// Approximate token budget of that repository context, per prompt.
+    pub max_context_tokens: usize,
+    /// Re-reviews in `serve` only look at the commits pushed since the
+    /// previous review and track which of its findings are resolved.
+    pub incremental: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +131,111 @@ pub struct DashboardConfig {
     pub port: u16,
 }
 
+/// Background job queue of `serve`, running reviews and fix runs.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct JobsConfig {
+    /// Jobs run concurrently, at most one per repository.
+    pub workers: usize,
+    /// Attempts before a review job is marked failed. Fix runs are only
+    /// attempted once.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each further attempt.
+    pub retry_backoff_seconds: u64,
+}
+
+/// `/webhooks/github` of `serve`, applying GitHub events without waiting for
+/// the next poll.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct WebhooksConfig {
+    /// Env var holding the secret of the webhooks, `GIGI_WEBHOOK_SECRET` if
+    /// unset. Webhooks are rejected while it is empty.
+    pub secret_env: Option<String>,
+}
+
+/// What `serve` and `gigi db prune` delete from the DB. A limit of 0 keeps
+/// everything, which is the default: pruning is opt-in.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// Latest reviews kept for each PR, with their findings.
+    pub reviews_per_pr: usize,
+    /// Days fix runs are kept.
+    pub fix_runs_days: u64,
+    /// Days sync events are kept.
+    pub sync_events_days: u64,
+    /// Days done threads, and the ones of closed issues and of merged or
+    /// closed PRs, are kept once the polls stop returning them.
+    pub closed_threads_days: u64,
+    /// Hours between two prunes of `serve`, 0 to only prune with
+    /// `gigi db prune`.
+    pub prune_interval_hours: u64,
+}
+
+/// GitHub instances, besides github.com.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct GitHubConfig {
+    pub hosts: Vec<GitHubHostConfig>,
+}
+
+/// A GitHub Enterprise Server instance, or github.com to change its
+/// settings.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct GitHubHostConfig {
+    /// Web host, e.g. `github.example.com`.
+    pub host: String,
+    /// REST API root. Defaults to `https://{host}/api/v3`.
+    pub api_url: Option<String>,
+    /// Name of the env var holding the token. Defaults to the token of
+    /// `gh auth token --hostname {host}`.
+    pub token_env: Option<String>,
+    /// Watch the notifications, PRs and issues of this host in `serve`.
+    pub watch: bool,
+    /// Teams, as `org/team`, whose mentions are listed by `serve`.
+    pub team_mentions: Vec<String>,
+}
+
+/// GitLab instances, besides gitlab.com.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct GitLabConfig {
+    pub hosts: Vec<GitLabHostConfig>,
+}
+
+/// A self-hosted GitLab instance, or gitlab.com to change its settings.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct GitLabHostConfig {
+    /// Web host, e.g. `gitlab.example.com`.
+    pub host: String,
+    /// REST API root. Defaults to `https://{host}/api/v4`.
+    pub api_url: Option<String>,
+    /// Name of the env var holding the token. Defaults to `GITLAB_TOKEN`.
+    pub token_env: Option<String>,
+}
+
+/// Forgejo or Gitea instances watched by `gigi serve`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct ForgejoConfig {
+    pub hosts: Vec<ForgejoHostConfig>,
+}
+
+/// A Forgejo or Gitea instance.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct ForgejoHostConfig {
+    /// Web host, e.g. `codeberg.org`.
+    pub host: String,
+    /// REST API root. Defaults to `https://{host}/api/v1`.
+    pub api_url: Option<String>,
+    /// Name of the env var holding the token. Defaults to `FORGEJO_TOKEN`.
+    pub token_env: Option<String>,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum RereviewMode {
@@ -45,13 +244,21 @@ pub enum RereviewMode {
     Manual,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+/// AI backend used to generate reviews, fixes and commit messages.
+/// See [`crate::ai::agent_for`] for the matching implementations.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, clap::ValueEnum)]
 #[serde(rename_all = "snake_case")]
 pub enum AiProvider {
     #[default]
     Copilot,
     Gemini,
     Kiro,
+    /// Any command described by `[ai.custom]`.
+    Custom,
+    /// OpenAI-compatible HTTP server described by `[ai.openai]`.
+    #[serde(rename = "openai")]
+    #[value(name = "openai")]
+    OpenAi,
 }
 
 impl Default for AppConfig {
@@ -62,7 +269,14 @@ impl Default for AppConfig {
             initial_review_lookback_days: 3,
             initial_review_max_prs: 10,
             ai: AiConfig::default(),
+            review: ReviewConfig::default(),
             dashboard: DashboardConfig::default(),
+            jobs: JobsConfig::default(),
+            webhooks: WebhooksConfig::default(),
+            retention: RetentionConfig::default(),
+            github: GitHubConfig::default(),
+            gitlab: GitLabConfig::default(),
+            forgejo: ForgejoConfig::default(),
         }
     }
 }
@@ -72,6 +286,43 @@ impl Default for AiConfig {
         Self {
             provider: AiProvider::Copilot,
             model: None,
+            custom: CustomAiConfig::default(),
+            openai: OpenAiConfig::default(),
+        }
+    }
+}
+
+impl Default for OpenAiConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "http://127.0.0.1:11434".to_string(),
+            api_key_env: None,
+            timeout_seconds: 600,
+            stream: true,
+            default_model: None,
+        }
+    }
+}
+
+impl Default for ReviewConfig {
+    fn default() -> Self {
+        Self {
+            max_diff_tokens: 60_000,
+            exclude: [
+                "*.lock",
+                "package-lock.json",
+                "pnpm-lock.yaml",
+                "go.sum",
+                "*.min.js",
+                "*.min.css",
+                "*.snap",
+            ]
+            .into_iter()
+            .map(ToString::to_string)
+            .collect(),
+            full_context: false,
+            max_context_tokens: 20_000,
+            incremental: true,
         }
     }
 }
@@ -85,6 +336,50 @@ impl Default for DashboardConfig {
     }
 }
 
+impl Default for GitHubHostConfig {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            api_url: None,
+            token_env: None,
+            watch: true,
+            team_mentions: Vec::new(),
+        }
+    }
+}
+
+impl RetentionConfig {
+    /// Whether any limit is set, so that pruning may delete something.
+    pub fn is_enabled(&self) -> bool {
+        self.reviews_per_pr > 0
+            || self.fix_runs_days > 0
+            || self.sync_events_days > 0
+            || self.closed_threads_days > 0
+    }
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            reviews_per_pr: 0,
+            fix_runs_days: 0,
+            sync_events_days: 0,
+            closed_threads_days: 0,
+            prune_interval_hours: 24,
+        }
+    }
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            workers: 2,
+            max_attempts: 3,
+            retry_backoff_seconds: 60,
+        }
+    }
+}
+
 pub fn default_config_toml() -> &'static str {
     r#"watch_period_seconds = 60
 rereview_mode = "on_update" # or "manual"
@@ -92,13 +387,66 @@ initial_review_lookback_days = 3
 initial_review_max_prs = 10
 
 [ai]
-provider = "copilot" # or "gemini" or "kiro"
+provider = "copilot" # or "gemini", "kiro", "custom" or "openai"
 # model = "gpt-5.3-codex"
 # when provider = "kiro", the default model is "claude-opus-4.7"
 
+# [ai.custom] # used when provider = "custom"
+# command = "claude"
+# args = ["--model", "{model}", "--print", "{prompt}"]
+# prompt_input = "argv" # or "stdin"
+# output = "text" # or "json", together with output_json_pointer = "/result"
+# default_model = "sonnet"
+
+# [ai.openai] # used when provider = "openai"
+# base_url = "http://127.0.0.1:11434"
+# api_key_env = "OPENAI_API_KEY"
+# timeout_seconds = 600
+# stream = true
+# default_model = "qwen2.5-coder"
+
+[review]
+max_diff_tokens = 60000 # larger diffs are reviewed in chunks
+exclude = ["*.lock", "package-lock.json", "pnpm-lock.yaml", "go.sum", "*.min.js", "*.min.css", "*.snap"]
+full_context = false # check out the PR and send changed files, usages and tests
+max_context_tokens = 20000
+incremental = true # re-reviews only look at the commits pushed since the previous review
+
 [dashboard]
 host = "127.0.0.1"
 port = 8787
+
+[jobs] # reviews and fix runs started by `gigi serve`
+workers = 2
+max_attempts = 3 # of reviews, fix runs are only retried from the dashboard
+retry_backoff_seconds = 60 # doubled after each failed attempt
+
+# [retention] # pruned by `gigi serve` and `gigi db prune`, 0 (default) keeps everything
+# reviews_per_pr = 20
+# fix_runs_days = 90
+# sync_events_days = 90
+# closed_threads_days = 30 # done threads, closed issues, merged or closed PRs
+# prune_interval_hours = 24
+
+# [webhooks] # GitHub webhooks sent to /webhooks/github
+# secret_env = "GIGI_WEBHOOK_SECRET"
+
+# [[github.hosts]] # GitHub Enterprise Server, besides github.com
+# host = "github.example.com"
+# api_url = "https://github.example.com/api/v3"
+# token_env = "GHES_TOKEN" # defaults to `gh auth token --hostname <host>`
+# watch = true # watched by `gigi serve`
+# team_mentions = ["org/team"] # mentions of these teams are listed by `gigi serve`
+
+# [[gitlab.hosts]] # self-hosted GitLab, besides gitlab.com
+# host = "gitlab.example.com"
+# api_url = "https://gitlab.example.com/api/v4"
+# token_env = "GITLAB_EXAMPLE_TOKEN" # defaults to GITLAB_TOKEN
+
+# [[forgejo.hosts]] # Forgejo or Gitea, watched by `gigi serve`
+# host = "codeberg.org"
+# api_url = "https://codeberg.org/api/v1"
+# token_env = "CODEBERG_TOKEN" # defaults to FORGEJO_TOKEN
 "#
 }
 
@@ -129,6 +477,12 @@ pub async fn load_config(config_path: &PathBuf) -> anyhow::Result<AppConfig> {
     Ok(config)
 }
 
+/// User config, for CLI commands that run an agent.
+pub async fn load_user_config() -> anyhow::Result<AppConfig> {
+    let paths = resolve_paths()?;
+    load_config(&paths.config_path).await
+}
+
 pub async fn ensure_parent_dirs(paths: &AppPaths) -> anyhow::Result<()> {
     if let Some(parent) = paths.config_path.parent() {
         fs::create_dir_all(parent)
@@ -143,24 +497,6 @@ pub async fn ensure_parent_dirs(paths: &AppPaths) -> anyhow::Result<()> {
     Ok(())
 }
 
-impl AiProvider {
-    pub fn as_agent(self) -> crate::args::Agent {
-        match self {
-            Self::Copilot => crate::args::Agent::Copilot,
-            Self::Gemini => crate::args::Agent::Gemini,
-            Self::Kiro => crate::args::Agent::Kiro,
-        }
-    }
-
-    pub fn as_str(self) -> &'static str {
-        match self {
-            Self::Copilot => "copilot",
-            Self::Gemini => "gemini",
-            Self::Kiro => "kiro",
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -201,10 +537,63 @@ port = 9000
         assert_eq!(cfg.initial_review_max_prs, 5);
         assert_eq!(cfg.ai.provider, AiProvider::Kiro);
         assert_eq!(cfg.ai.model.as_deref(), Some("x"));
+        assert_eq!(cfg.ai.model_for(AiProvider::Kiro).as_deref(), Some("x"));
+        assert_eq!(cfg.ai.model_for(AiProvider::Gemini), None);
         assert_eq!(cfg.dashboard.host, "0.0.0.0");
         assert_eq!(cfg.dashboard.port, 9000);
     }
 
+    #[test]
+    fn toml_parses_custom_provider() {
+        let raw = r#"
+[ai]
+provider = "custom"
+
+[ai.custom]
+command = "llm"
+args = ["-m", "{model}"]
+prompt_input = "stdin"
+output = "json"
+output_json_pointer = "/choices/0/text"
+default_model = "local"
+"#;
+
+        let cfg: AppConfig = toml::from_str(raw).unwrap();
+        assert_eq!(cfg.ai.provider, AiProvider::Custom);
+        assert_eq!(
+            cfg.ai.custom,
+            CustomAiConfig {
+                command: "llm".to_string(),
+                args: vec!["-m".to_string(), "{model}".to_string()],
+                prompt_input: PromptInput::Stdin,
+                output: CustomOutputFormat::Json,
+                output_json_pointer: Some("/choices/0/text".to_string()),
+                default_model: Some("local".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn toml_parses_openai_provider() {
+        let raw = r#"
+[ai]
+provider = "openai"
+model = "llama3"
+
+[ai.openai]
+base_url = "http://build-box:8080"
+api_key_env = "LLM_KEY"
+stream = false
+"#;
+
+        let cfg: AppConfig = toml::from_str(raw).unwrap();
+        assert_eq!(cfg.ai.provider, AiProvider::OpenAi);
+        assert_eq!(cfg.ai.openai.base_url, "http://build-box:8080");
+        assert_eq!(cfg.ai.openai.api_key_env.as_deref(), Some("LLM_KEY"));
+        assert_eq!(cfg.ai.openai.timeout_seconds, 600);
+        assert!(!cfg.ai.openai.stream);
+    }
+
     #[test]
     fn path_resolution_uses_home() {
         let paths = resolve_paths().unwrap();
@@ -221,7 +610,83 @@ port = 9000
         assert_eq!(cfg.initial_review_max_prs, 10);
         assert_eq!(cfg.ai.provider, AiProvider::Copilot);
         assert_eq!(cfg.ai.model, None);
+        assert_eq!(cfg.review, ReviewConfig::default());
         assert_eq!(cfg.dashboard.host, "127.0.0.1");
         assert_eq!(cfg.dashboard.port, 8787);
+        assert_eq!(cfg.jobs, JobsConfig::default());
+        assert_eq!(cfg.webhooks, WebhooksConfig::default());
+        assert_eq!(cfg.github, GitHubConfig::default());
+        assert_eq!(cfg.gitlab, GitLabConfig::default());
+        assert_eq!(cfg.forgejo, ForgejoConfig::default());
+    }
+
+    #[test]
+    fn toml_parses_github_hosts() {
+        let raw = r#"
+[[github.hosts]]
+host = "github.example.com"
+token_env = "GHES_TOKEN"
+
+[[github.hosts]]
+host = "github.com"
+watch = false
+"#;
+
+        let cfg: AppConfig = toml::from_str(raw).unwrap();
+        assert_eq!(
+            cfg.github.hosts,
+            [
+                GitHubHostConfig {
+                    host: "github.example.com".to_string(),
+                    api_url: None,
+                    token_env: Some("GHES_TOKEN".to_string()),
+                    watch: true,
+                    team_mentions: Vec::new(),
+                },
+                GitHubHostConfig {
+                    host: "github.com".to_string(),
+                    watch: false,
+                    ..GitHubHostConfig::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn toml_parses_gitlab_hosts() {
+        let raw = r#"
+[[gitlab.hosts]]
+host = "gitlab.example.com"
+token_env = "GITLAB_EXAMPLE_TOKEN"
+"#;
+
+        let cfg: AppConfig = toml::from_str(raw).unwrap();
+        assert_eq!(
+            cfg.gitlab.hosts,
+            [GitLabHostConfig {
+                host: "gitlab.example.com".to_string(),
+                api_url: None,
+                token_env: Some("GITLAB_EXAMPLE_TOKEN".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn toml_parses_forgejo_hosts() {
+        let raw = r#"
+[[forgejo.hosts]]
+host = "codeberg.org"
+api_url = "https://codeberg.org/api/v1/"
+"#;
+
+        let cfg: AppConfig = toml::from_str(raw).unwrap();
+        assert_eq!(
+            cfg.forgejo.hosts,
+            [ForgejoHostConfig {
+                host: "codeberg.org".to_string(),
+                api_url: Some("https://codeberg.org/api/v1/".to_string()),
+                token_env: None,
+            }]
+        );
     }
 }
@@ -1,23 +1,36 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use leptos::prelude::*;
 
 use crate::{
-    db::{DashboardThread, DashboardThreadFilters},
+    checkout::parse_github_pr_url,
+    db::{
+        self, DashboardThread, DashboardThreadFilters, JobStatus, PrHistory, SNIPPET_MATCH_END,
+        SNIPPET_MATCH_START, SearchHit, SearchHitKind, StoredFinding, StoredFixRun, StoredJob,
+        StoredReview, StoredSyncEvent,
+    },
+    github::{self, CheckOutcome, PrCheck, RateLimit},
     icons::{
         CHECKMARK_ICON, DISCUSSION_ANSWERED_ICON, DISCUSSION_OPEN_ICON, ISSUE_CLOSED_ICON,
-        ISSUE_OPEN_ICON, MAIL_ICON, MY_PR_ICON, NOTIFICATION_ICON, PR_CLOSED_ICON, PR_DRAFT_ICON,
-        PR_MERGED_ICON, PR_OPEN_ICON, PR_QUEUED_ICON, REFRESH_ICON, TAG_ICON, TERMINAL_ICON,
-        VSCODE_ICON,
+        ISSUE_OPEN_ICON, MAIL_ICON, MENTION_ICON, MY_PR_ICON, NOTIFICATION_ICON, PR_CLOSED_ICON,
+        PR_DRAFT_ICON, PR_MERGED_ICON, PR_OPEN_ICON, PR_QUEUED_ICON, REFRESH_ICON,
+        REVIEW_REQUESTED_ICON, TAG_ICON, TEAM_MENTION_ICON, TEAM_REVIEW_REQUESTED_ICON,
+        TERMINAL_ICON, VSCODE_ICON,
     },
+    line_diff::{self, DiffLine},
+    review::{Severity, SeverityCounts, finding_location},
 };
 
 #[derive(Debug, Clone)]
 pub struct DashboardSnapshot {
     pub filters: DashboardThreadFilters,
     pub threads: Vec<DashboardThread>,
+    /// Background reviews and fix runs, active ones first.
+    pub jobs: Vec<StoredJob>,
     pub available_repositories: Vec<String>,
     pub status_message: String,
+    /// Remaining GitHub API quota of each rate-limited resource.
+    pub api_quota: BTreeMap<String, RateLimit>,
 }
 
 pub fn render_page(snapshot: &DashboardSnapshot) -> String {
@@ -58,7 +71,9 @@ fn render_fragment_view(snapshot: DashboardSnapshot) -> impl IntoView {
             <header class="header">
                 <h1>"gigi dashboard"</h1>
                 <div class="actions">
+                    <SearchForm query=String::new() />
                     <span id="status-text" class="status">{snapshot.status_message}</span>
+                    <ApiQuota quota=snapshot.api_quota />
                     <form action="/dashboard/actions/refresh" method="post" data-async-form>
                         <button class="btn icon-btn" type="submit" data-loading-label="Refreshing..." aria-label="Refresh" title="Refresh">{svg_icon(REFRESH_ICON)}</button>
                     </form>
@@ -83,6 +98,8 @@ fn render_fragment_view(snapshot: DashboardSnapshot) -> impl IntoView {
                         <FilterCheckbox name="show_my_prs" label="My PRs" checked=snapshot.filters.show_my_prs />
                         <FilterCheckbox name="show_assigned_prs" label="Assigned PRs" checked=snapshot.filters.show_assigned_prs />
                         <FilterCheckbox name="show_assigned_issues" label="Assigned issues" checked=snapshot.filters.show_assigned_issues />
+                        <FilterCheckbox name="show_review_requested" label="Review requested" checked=snapshot.filters.show_review_requested />
+                        <FilterCheckbox name="show_mentions" label="Mentions" checked=snapshot.filters.show_mentions />
                     </fieldset>
                     <fieldset class="filter-group">
                         <legend>"Status"</legend>
@@ -93,6 +110,14 @@ fn render_fragment_view(snapshot: DashboardSnapshot) -> impl IntoView {
                         <legend>"Display"</legend>
                         <FilterCheckbox name="group_by_repository" label="Group by repository" checked=snapshot.filters.group_by_repository />
                     </fieldset>
+                    <fieldset class="filter-group">
+                        <legend>"Findings"</legend>
+                        <SeverityFilter selected=snapshot.filters.min_finding_severity />
+                    </fieldset>
+                    <fieldset class="filter-group">
+                        <legend>"CI"</legend>
+                        <FilterCheckbox name="failing_ci_only" label="Failing CI on my PRs" checked=snapshot.filters.failing_ci_only />
+                    </fieldset>
                 </form>
 
                 {if available_repos.is_empty() {
@@ -108,7 +133,7 @@ fn render_fragment_view(snapshot: DashboardSnapshot) -> impl IntoView {
                         format!("{active_count}/{total_count}")
                     };
                     view! {
-                        <details class="repo-dropdown">
+                        <details class="repo-dropdown" data-dropdown="repositories">
                             <summary class="btn repo-dropdown-toggle">
                                 "Repositories "
                                 <span class="repo-badge">{badge_label}</span>
@@ -130,6 +155,8 @@ fn render_fragment_view(snapshot: DashboardSnapshot) -> impl IntoView {
                         </details>
                     }.into_any()
                 }}
+
+                <JobsPanel jobs=snapshot.jobs />
             </div>
 
             <section>
@@ -165,13 +192,349 @@ fn render_fragment_view(snapshot: DashboardSnapshot) -> impl IntoView {
                             <button id="close-modal" class="btn" type="button">"Close"</button>
                         </div>
                     </header>
+                    <p id="review-guidelines" class="review-guidelines" hidden></p>
                     <pre id="review-content"></pre>
+                    <form id="publish-form" class="publish-form" action="" method="post" data-async-form style="display:none">
+                        <h3>"Publish on GitHub"</h3>
+                        <input type="hidden" name="review_id" value="" />
+                        <div id="publish-findings" class="publish-findings"></div>
+                        <div class="publish-actions">
+                            <label class="filter-option">
+                                <input type="checkbox" name="submit" />
+                                <span>"Submit (otherwise left pending)"</span>
+                            </label>
+                            <button class="btn" type="submit" data-loading-label="Publishing...">"Publish"</button>
+                        </div>
+                    </form>
                 </article>
             </dialog>
         </main>
     }
 }
 
+/// Matches of `query` in the reviews, thread titles and fix run outputs.
+pub fn render_search_page(query: &str, hits: Vec<SearchHit>) -> String {
+    let query = query.to_string();
+    let summary = match hits.len() {
+        0 => format!("No matches for \"{query}\""),
+        1 => "1 match".to_string(),
+        count => format!("{count} matches"),
+    };
+    view! {
+        <!doctype html>
+        <html lang="en">
+            <head>
+                <meta charset="utf-8" />
+                <meta name="viewport" content="width=device-width, initial-scale=1" />
+                <title>{format!("{query} - gigi search")}</title>
+                <link rel="icon" href="data:image/svg+xml,<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 100 100'><text y='.9em' font-size='90'>🎤</text></svg>" />
+                <link rel="stylesheet" href="/styles.css" />
+            </head>
+            <body>
+                <main class="layout">
+                    <header class="header">
+                        <h1><a class="home-link" href="/">"gigi dashboard"</a></h1>
+                        <div class="actions">
+                            <SearchForm query=query />
+                        </div>
+                    </header>
+                    <p class="meta">{summary}</p>
+                    <div class="search-hits">
+                        {hits.into_iter().map(|hit| view! { <SearchHitCard hit /> }).collect::<Vec<_>>()}
+                    </div>
+                </main>
+            </body>
+        </html>
+    }
+    .to_html()
+}
+
+#[component]
+fn SearchForm(query: String) -> impl IntoView {
+    view! {
+        <form class="search-form" action="/search" method="get" role="search">
+            <input
+                type="search"
+                name="q"
+                value=query
+                placeholder="Search reviews, threads, fixes"
+                aria-label="Search reviews, threads and fix runs"
+                data-search-input
+            />
+        </form>
+    }
+}
+
+#[component]
+fn SearchHitCard(hit: SearchHit) -> impl IntoView {
+    let (relative, absolute) = format_timestamp(&hit.date);
+    let url = hit.url.clone().or_else(|| hit.pr_url.clone());
+    view! {
+        <article class="thread search-hit">
+            <p class="meta">
+                <span class=format!("search-kind {}", search_kind_class(hit.kind))>{hit.kind.label()}</span>
+                " "
+                <span title=absolute>{relative}</span>
+            </p>
+            <h3>
+                {match url {
+                    Some(url) => view! { <a href=url target="_blank" rel="noreferrer">{hit.title}</a> }.into_any(),
+                    None => view! { <span>{hit.title}</span> }.into_any(),
+                }}
+            </h3>
+            <p class="search-snippet">
+                {snippet_segments(&hit.snippet).into_iter().map(|(matched, text)| {
+                    if matched {
+                        view! { <mark>{text}</mark> }.into_any()
+                    } else {
+                        text.into_any()
+                    }
+                }).collect::<Vec<_>>()}
+            </p>
+        </article>
+    }
+}
+
+/// Stored history of a PR, for `/prs/{owner}/{repo}/{number}`.
+#[derive(Debug, Clone)]
+pub struct PrPage {
+    pub pr_url: String,
+    /// `None` for PRs that are not in the `prs` table.
+    pub title: Option<String>,
+    /// GitHub Enterprise Server host of the PR, github.com if unset.
+    pub host: Option<String>,
+    pub history: PrHistory,
+    /// Ids of the older and the newer of the reviews to diff.
+    pub compare: Option<(i64, i64)>,
+}
+
+/// Timeline of the reviews, fix runs and sync events of a PR, newest first,
+/// with the diff between the two reviews of `page.compare`.
+pub fn render_pr_page(page: PrPage) -> String {
+    let PrPage {
+        pr_url,
+        title,
+        host,
+        history,
+        compare,
+    } = page;
+    let title = title.unwrap_or_else(|| pr_url.clone());
+    let review = |id: i64| {
+        history
+            .reviews
+            .iter()
+            .find(|review| review.id == id)
+            .cloned()
+    };
+    let diff = compare.and_then(|(old, new)| Some((review(old)?, review(new)?)));
+    let can_compare = history.reviews.len() > 1;
+    let is_empty = history.is_empty();
+
+    let mut entries: Vec<(i64, AnyView)> = Vec::new();
+    for review in history.reviews {
+        let created_at = review.created_at;
+        let from = compare.is_some_and(|(from, _)| from == review.id);
+        let to = compare.is_some_and(|(_, to)| to == review.id);
+        entries.push((
+            created_at,
+            view! { <ReviewEntry review from to can_compare /> }.into_any(),
+        ));
+    }
+    for fix_run in history.fix_runs {
+        entries.push((
+            fix_run.created_at,
+            view! { <FixRunEntry fix_run /> }.into_any(),
+        ));
+    }
+    for event in history.sync_events {
+        entries.push((
+            event.created_at,
+            view! { <SyncEventEntry event /> }.into_any(),
+        ));
+    }
+    entries.sort_by_key(|(created_at, _)| std::cmp::Reverse(*created_at));
+
+    view! {
+        <!doctype html>
+        <html lang="en">
+            <head>
+                <meta charset="utf-8" />
+                <meta name="viewport" content="width=device-width, initial-scale=1" />
+                <title>{format!("{title} - gigi")}</title>
+                <link rel="icon" href="data:image/svg+xml,<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 100 100'><text y='.9em' font-size='90'>🎤</text></svg>" />
+                <link rel="stylesheet" href="/styles.css" />
+            </head>
+            <body>
+                <main class="layout">
+                    <header class="header">
+                        <h1><a class="home-link" href="/">"gigi dashboard"</a></h1>
+                        <div class="actions">
+                            <SearchForm query=String::new() />
+                        </div>
+                    </header>
+                    <h2 class="pr-title">
+                        <a class="thread-link" href=pr_url.clone() target="_blank" rel="noreferrer">{title}</a>
+                    </h2>
+                    <p class="meta">{pr_url}</p>
+                    {diff.map(|(old, new)| view! { <ReviewDiff old new /> })}
+                    {if is_empty {
+                        view! { <p class="meta">"Nothing stored for this PR yet."</p> }.into_any()
+                    } else {
+                        view! {
+                            <form class="pr-timeline" method="get">
+                                {host.map(|host| view! { <input type="hidden" name="host" value=host /> })}
+                                {can_compare.then(|| view! {
+                                    <button class="btn" type="submit">"Compare selected reviews"</button>
+                                })}
+                                <ol class="timeline">
+                                    {entries.into_iter().map(|(_, entry)| view! { <li>{entry}</li> }).collect::<Vec<_>>()}
+                                </ol>
+                            </form>
+                        }.into_any()
+                    }}
+                </main>
+            </body>
+        </html>
+    }
+    .to_html()
+}
+
+#[component]
+fn ReviewEntry(review: StoredReview, from: bool, to: bool, can_compare: bool) -> impl IntoView {
+    let (relative, absolute) = format_unix_timestamp(review.created_at);
+    let agent = match review.model {
+        Some(model) => format!("{} ({model})", review.provider),
+        None => review.provider,
+    };
+    let (tone, label) = if review.requires_code_changes {
+        ("unsafe", "Fixes needed")
+    } else {
+        ("safe", "Safe")
+    };
+    let id = review.id.to_string();
+    view! {
+        <article class="thread timeline-entry">
+            <p class="meta">
+                <span class="search-kind kind-review">"Review"</span>
+                " "
+                <span title=absolute>{relative}</span>
+                <span class="meta-separator">"•"</span>
+                <span>{agent}</span>
+                <span class="meta-separator">"•"</span>
+                <HeadSha sha=review.head_sha />
+                <span class="meta-separator">"•"</span>
+                <span class=format!("pill {tone}")>{label}</span>
+            </p>
+            {can_compare.then(|| view! {
+                <p class="review-pick">
+                    <label><input type="radio" name="from" value=id.clone() checked=from />" Old"</label>
+                    <label><input type="radio" name="to" value=id checked=to />" New"</label>
+                </p>
+            })}
+            {review.summary.map(|summary| view! { <p>{summary}</p> })}
+            <details>
+                <summary>"Review"</summary>
+                <pre class="timeline-output">{review.content_md}</pre>
+            </details>
+        </article>
+    }
+}
+
+#[component]
+fn FixRunEntry(fix_run: StoredFixRun) -> impl IntoView {
+    let (relative, absolute) = format_unix_timestamp(fix_run.created_at);
+    view! {
+        <article class="thread timeline-entry">
+            <p class="meta">
+                <span class="search-kind kind-fix-run">"Fix run"</span>
+                " "
+                <span title=absolute>{relative}</span>
+                <span class="meta-separator">"•"</span>
+                <span>{fix_run.provider}</span>
+                <span class="meta-separator">"•"</span>
+                <span>{fix_run.status}</span>
+            </p>
+            <details>
+                <summary>"Output"</summary>
+                <pre class="timeline-output">{fix_run.output}</pre>
+            </details>
+        </article>
+    }
+}
+
+#[component]
+fn SyncEventEntry(event: StoredSyncEvent) -> impl IntoView {
+    let (relative, absolute) = format_unix_timestamp(event.created_at);
+    view! {
+        <article class="thread timeline-entry">
+            <p class="meta">
+                <span class="search-kind kind-sync">"Sync"</span>
+                " "
+                <span title=absolute>{relative}</span>
+                <span class="meta-separator">"•"</span>
+                <span>{event.status}</span>
+            </p>
+            <p>{event.message}</p>
+        </article>
+    }
+}
+
+#[component]
+fn HeadSha(sha: Option<String>) -> impl IntoView {
+    match sha {
+        Some(sha) => {
+            let short = sha.chars().take(7).collect::<String>();
+            view! { <code class="head-sha" title=sha>{short}</code> }.into_any()
+        }
+        None => view! { <span class="head-sha">"unknown head"</span> }.into_any(),
+    }
+}
+
+/// Side-by-side diff of the markdown of two reviews.
+#[component]
+fn ReviewDiff(old: StoredReview, new: StoredReview) -> impl IntoView {
+    let rows = line_diff::side_by_side(&old.content_md, &new.content_md)
+        .into_iter()
+        .map(|row| {
+            let side = |line: Option<DiffLine<'_>>, class: &'static str| match line {
+                Some(line) => view! {
+                    <td class="line-number">{line.number}</td>
+                    <td class=class>{line.text.to_string()}</td>
+                }
+                .into_any(),
+                None => {
+                    view! { <td class="line-number"></td><td class="diff-empty"></td> }.into_any()
+                }
+            };
+            let (old_class, new_class) = if row.changed {
+                ("diff-removed", "diff-added")
+            } else {
+                ("diff-line", "diff-line")
+            };
+            view! { <tr>{side(row.old, old_class)}{side(row.new, new_class)}</tr> }
+        })
+        .collect::<Vec<_>>();
+    let side_header = |review: &StoredReview| {
+        let (_, absolute) = format_unix_timestamp(review.created_at);
+        view! {
+            <th colspan="2">
+                {format!("Review of {absolute} at ")}
+                <HeadSha sha=review.head_sha.clone() />
+            </th>
+        }
+    };
+    view! {
+        <section class="review-diff">
+            <table>
+                <thead>
+                    <tr>{side_header(&old)}{side_header(&new)}</tr>
+                </thead>
+                <tbody>{rows}</tbody>
+            </table>
+        </section>
+    }
+}
+
 #[component]
 fn FilterCheckbox(name: &'static str, label: &'static str, checked: bool) -> impl IntoView {
     view! {
@@ -182,13 +545,151 @@ fn FilterCheckbox(name: &'static str, label: &'static str, checked: bool) -> imp
     }
 }
 
+#[component]
+fn SeverityFilter(selected: Option<Severity>) -> impl IntoView {
+    view! {
+        <label class="filter-option">
+            <span>"At least"</span>
+            <select name="min_finding_severity">
+                <option value="" selected=selected.is_none()>"Any"</option>
+                {Severity::ALL.into_iter().map(|severity| view! {
+                    <option value=severity.as_str() selected=selected == Some(severity)>{severity.as_str()}</option>
+                }).collect::<Vec<_>>()}
+            </select>
+        </label>
+    }
+}
+
+#[component]
+fn FindingCounts(counts: SeverityCounts) -> impl IntoView {
+    Severity::ALL
+        .into_iter()
+        .filter(|severity| counts.get(*severity) > 0)
+        .map(|severity| {
+            let count = counts.get(severity);
+            let label = format!("{count} {severity} finding(s)");
+            view! {
+                <span class=format!("finding-badge {severity}") title=label.clone() aria-label=label>
+                    {format!("{count} {severity}")}
+                </span>
+            }
+        })
+        .collect::<Vec<_>>()
+}
+
+/// Share of a quota below which it is highlighted.
+const LOW_QUOTA_PERCENT: u64 = 10;
+
+#[component]
+fn ApiQuota(quota: BTreeMap<String, RateLimit>) -> impl IntoView {
+    quota
+        .into_iter()
+        .map(|(resource, limit)| {
+            let class = if limit.remaining.saturating_mul(100)
+                < limit.limit.saturating_mul(LOW_QUOTA_PERCENT)
+            {
+                "status api-quota low"
+            } else {
+                "status api-quota"
+            };
+            let resets_at = chrono::DateTime::from_timestamp(limit.reset, 0)
+                .map(|dt| format!("Resets {}", format_timestamp(&dt.to_rfc3339()).1))
+                .unwrap_or_default();
+            view! {
+                <span class=class title=resets_at>
+                    {format!("{resource} {}/{}", limit.remaining, limit.limit)}
+                </span>
+            }
+        })
+        .collect::<Vec<_>>()
+}
+
+#[component]
+fn JobsPanel(jobs: Vec<StoredJob>) -> impl IntoView {
+    if jobs.is_empty() {
+        return ().into_any();
+    }
+    let active_count = jobs.iter().filter(|job| job.status.is_active()).count();
+    let badge_class = if active_count > 0 {
+        "repo-badge jobs-badge active"
+    } else {
+        "repo-badge jobs-badge"
+    };
+
+    view! {
+        <details class="repo-dropdown" data-dropdown="jobs">
+            <summary class="btn repo-dropdown-toggle">
+                "Jobs "
+                <span class=badge_class>{format!("{active_count} active")}</span>
+            </summary>
+            <div class="repo-dropdown-panel jobs-list">
+                {jobs.into_iter().map(|job| view! { <JobRow job /> }).collect::<Vec<_>>()}
+            </div>
+        </details>
+    }
+    .into_any()
+}
+
+#[component]
+fn JobRow(job: StoredJob) -> impl IntoView {
+    let status = job.status.as_str();
+    let pr_label = parse_github_pr_url(&job.pr_url).map_or_else(
+        |_| job.pr_url.clone(),
+        |pr| format!("{}/{}#{}", pr.owner, pr.repo, pr.number),
+    );
+    let timestamp = job.finished_at.or(job.started_at).unwrap_or(job.created_at);
+    let (relative_time, absolute_time) = chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|dt| format_timestamp(&dt.to_rfc3339()))
+        .unwrap_or_default();
+    let attempts = format!("attempt {}/{}", job.attempts, job.max_attempts);
+    let retry_label = (job.status == JobStatus::Queued && job.attempts > 0)
+        .then(|| "retry scheduled".to_string());
+    let error = job.last_error.clone();
+    let action = if job.status.is_active() {
+        Some((
+            format!("/dashboard/actions/jobs/{}/cancel", job.id),
+            "Cancel",
+            "Cancelling...",
+        ))
+    } else if matches!(job.status, JobStatus::Failed | JobStatus::Cancelled) {
+        Some((
+            format!("/dashboard/actions/jobs/{}/retry", job.id),
+            "Retry",
+            "Retrying...",
+        ))
+    } else {
+        None
+    };
+
+    view! {
+        <div class="job-row" data-job-status=status>
+            <span class=format!("job-status {status}")>{status}</span>
+            <span class="job-kind">{job.kind.as_str()}</span>
+            <a class="thread-link" href=job.pr_url target="_blank" rel="noreferrer">{pr_label}</a>
+            <span class="meta" title=absolute_time>{relative_time}</span>
+            <span class="meta">{attempts}</span>
+            {retry_label.map(|label| view! { <span class="meta">{label}</span> })}
+            {error.map(|error| {
+                let title = error.clone();
+                view! { <span class="job-error" title=title>{error}</span> }
+            })}
+            {action.map(|(path, label, loading_label)| view! {
+                <form action=path method="post" data-async-form>
+                    <button class="btn btn-subtle" type="submit" data-loading-label=loading_label>{label}</button>
+                </form>
+            })}
+        </div>
+    }
+}
+
 #[component]
 fn RepositorySection(
     repository: String,
     threads: Vec<DashboardThread>,
     can_hide: bool,
 ) -> impl IntoView {
-    let repo_link = format!("https://github.com/{repository}");
+    let host = threads.first().map_or(github::GITHUB_COM, thread_host);
+    let repo_link = format!("https://{host}/{repository}");
     let hide_repository = repository.clone();
 
     view! {
@@ -222,7 +723,7 @@ fn ThreadCard(thread: DashboardThread) -> impl IntoView {
         .subject_url
         .clone()
         .or_else(|| thread.pr_url.clone())
-        .unwrap_or_else(|| format!("https://github.com/{}", thread.repository));
+        .unwrap_or_else(|| format!("https://{}/{}", thread_host(&thread), thread.repository));
     let review_content = thread.latest_review_content_md.clone();
     let review_tone = if thread.latest_requires_code_changes == Some(true) {
         "unsafe"
@@ -234,15 +735,35 @@ fn ThreadCard(thread: DashboardThread) -> impl IntoView {
     } else {
         "Safe"
     };
-    let can_review =
-        thread.pr_owner.is_some() && thread.pr_repo.is_some() && thread.pr_number.is_some();
+    // Only GitHub PRs can be reviewed and checked out.
+    let is_github = thread.forge().is_none();
+    let can_review = is_github
+        && thread.pr_owner.is_some()
+        && thread.pr_repo.is_some()
+        && thread.pr_number.is_some();
     let can_fix = can_review && thread.latest_requires_code_changes == Some(true);
     let shows_review_pill = thread_supports_review_pill(thread.subject_type.as_deref());
     let fix_action_for_modal = can_fix.then(|| fix_action_path(&thread));
-    let mark_authored_pr = thread.sources.iter().any(|source| source == "my_pr");
-    let mark_assigned_pr = thread.sources.iter().any(|source| source == "assigned_pr");
-    let mark_assigned_issue = thread.sources.iter().any(|source| source == "my_issue");
+    let publish_action_for_modal = can_review.then(|| publish_action_path(&thread));
+    let review_id = thread.latest_review_id.map(|id| id.to_string());
+    let review_findings = review_findings_json(&thread.findings);
+    let review_guidelines = thread.latest_review_guidelines_source.clone();
+    let has_source = |kind: &str| {
+        thread
+            .sources
+            .iter()
+            .any(|source| db::source_kind(source) == kind)
+    };
+    let mark_authored_pr = has_source("my_pr");
+    let mark_assigned_pr = has_source("assigned_pr");
+    let mark_assigned_issue = has_source("my_issue");
+    let mark_review_requested =
+        has_source("review_requested") || has_source("team_review_requested");
+    let mark_mention = has_source("mention") || has_source("team_mention");
+    let forge_badge = thread.forge().map(forge_label);
     let review_action = review_action_path(&thread);
+    let host = thread_host(&thread).to_string();
+    let open_host = (host != github::GITHUB_COM).then(|| host.clone());
     let mark_read_thread_id = thread
         .unread
         .then(|| thread.github_thread_id.clone())
@@ -256,7 +777,10 @@ fn ThreadCard(thread: DashboardThread) -> impl IntoView {
     );
 
     view! {
-        <article class="thread">
+        <article class=if thread.attention.is_some() { "thread needs-attention" } else { "thread" }>
+            {thread.attention.clone().map(|reason| view! {
+                <p class="attention-banner" role="alert">"Needs attention: "{reason}</p>
+            })}
             <h3>
                 <span class=state_icon_class aria-label=state_icon_label title=state_icon_label>{svg_icon(state_icon_paths)}</span>
                 {if thread.unread {
@@ -284,7 +808,8 @@ fn ThreadCard(thread: DashboardThread) -> impl IntoView {
             </h3>
 
             <div class="meta">
-                <a class="thread-link repo-link" href=format!("https://github.com/{}", thread.repository) target="_blank" rel="noreferrer">{thread.repository.clone()}</a>
+                <a class="thread-link repo-link" href=format!("https://{host}/{}", thread.repository) target="_blank" rel="noreferrer">{thread.repository.clone()}</a>
+                {forge_badge.map(|label| view! { <span class="forge-badge" title=host.clone()>{label}</span> })}
                 <span class="meta-separator">"•"</span>
                 {thread.sources.iter().map(|source| view! { <SourceBadge source=source.clone() /> }).collect::<Vec<_>>()}
                 <span class="meta-separator">"•"</span>
@@ -309,7 +834,7 @@ fn ThreadCard(thread: DashboardThread) -> impl IntoView {
                         } else {
                             format!("{}?s=40", p.avatar_url)
                         };
-                        let profile = format!("https://github.com/{}", p.login);
+                        let profile = format!("https://{host}/{}", p.login);
                         view! { <a class="avatar-link" href=profile target="_blank" rel="noreferrer" title=alt.clone()><img class="avatar" src=src alt=alt.clone() loading="lazy" /></a> }
                     }).collect::<Vec<_>>();
                     view! { <span class="meta-separator">"•"</span><span class="avatar-stack">{avatars}</span> }.into_any()
@@ -322,49 +847,69 @@ fn ThreadCard(thread: DashboardThread) -> impl IntoView {
                     ().into_any()
                 } else if let Some(review) = review_content {
                     let fix_attr = fix_action_for_modal.clone();
+                    let counts = thread.latest_review_findings;
                     view! {
                         <button
                             class=format!("pill {review_tone} review-open")
                             type="button"
                             data-review-content=review
                             data-fix-action=fix_attr
+                            data-publish-action=publish_action_for_modal.clone()
+                            data-review-id=review_id.clone()
+                            data-review-findings=review_findings.clone()
+                            data-review-guidelines=review_guidelines.clone()
+                            title=review_guidelines.as_ref().map(|source| format!("Reviewed following {source}"))
                         >
                             {review_label}
                         </button>
+                        <FindingCounts counts />
                     }.into_any()
                 } else {
                     ().into_any()
                 }}
+                {thread.ci_status.map(|status| view! { <CiBadge status failing_checks=thread.failing_checks.clone() /> })}
                 {if can_review {
                     view! {
                         <form action=review_action method="post" data-async-form>
                             <button class="btn" type="submit" data-loading-label="Reviewing..." data-loading-mode="spinner">"Review"</button>
                         </form>
+                        <a class="btn" href=pr_page_path(&thread) title="Reviews, fix runs and sync events">"History"</a>
                     }.into_any()
                 } else {
                     ().into_any()
                 }}
+                {if is_github {
+                view! {
                 <div class="icon-actions">
                     <form action="/dashboard/actions/open/vscode" method="post" data-async-form>
                         <input type="hidden" name="repository" value=thread.repository.clone() />
                         {thread.pr_url.clone().map(|pr_url| view! { <input type="hidden" name="pr_url" value=pr_url /> })}
+                        {open_host.clone().map(|host| view! { <input type="hidden" name="host" value=host /> })}
                         <button class="btn icon-btn" type="submit" data-loading-label="Opening..." aria-label="Open in VS Code" title="Open in VS Code">{svg_icon(VSCODE_ICON)}</button>
                     </form>
                     <form action="/dashboard/actions/open/terminal" method="post" data-async-form>
                         <input type="hidden" name="repository" value=thread.repository.clone() />
                         {thread.pr_url.clone().map(|pr_url| view! { <input type="hidden" name="pr_url" value=pr_url /> })}
+                        {open_host.clone().map(|host| view! { <input type="hidden" name="host" value=host /> })}
                         <button class="btn icon-btn" type="submit" data-loading-label="Opening..." aria-label="Open in Terminal" title="Open in Terminal">{svg_icon(TERMINAL_ICON)}</button>
                     </form>
                 </div>
-                {if thread.github_thread_id.is_some() || mark_authored_pr || mark_assigned_pr || mark_assigned_issue {
+                }.into_any()
+                } else {
+                    ().into_any()
+                }}
+                {if thread.github_thread_id.is_some() || mark_authored_pr || mark_assigned_pr || mark_assigned_issue || mark_review_requested || mark_mention {
                     view! {
                         <form action="/dashboard/actions/done" method="post" data-async-form>
                             {thread.github_thread_id.clone().map(|thread_id| view! { <input type="hidden" name="github_thread_id" value=thread_id /> })}
                             {thread.pr_url.clone().map(|pr_url| view! { <input type="hidden" name="pr_url" value=pr_url /> })}
+                        {open_host.clone().map(|host| view! { <input type="hidden" name="host" value=host /> })}
                             {thread.subject_url.clone().map(|subject_url| view! { <input type="hidden" name="subject_url" value=subject_url /> })}
                             <input type="hidden" name="mark_authored_pr" value=mark_authored_pr.to_string() />
                             <input type="hidden" name="mark_assigned_pr" value=mark_assigned_pr.to_string() />
                             <input type="hidden" name="mark_assigned_issue" value=mark_assigned_issue.to_string() />
+                            <input type="hidden" name="mark_review_requested" value=mark_review_requested.to_string() />
+                            <input type="hidden" name="mark_mention" value=mark_mention.to_string() />
                             <button class="btn icon-btn" type="submit" data-loading-label="Saving..." aria-label="Mark done" title="Mark done">{svg_icon(CHECKMARK_ICON)}</button>
                         </form>
                     }.into_any()
@@ -376,6 +921,35 @@ fn ThreadCard(thread: DashboardThread) -> impl IntoView {
     }
 }
 
+#[component]
+fn CiBadge(status: CheckOutcome, failing_checks: Vec<PrCheck>) -> impl IntoView {
+    let label = match status {
+        CheckOutcome::Pass => "CI passing",
+        CheckOutcome::Fail => "CI failing",
+        CheckOutcome::Pending => "CI pending",
+    };
+
+    view! {
+        <span class=format!("ci-badge ci-{}", status.as_str())>{label}</span>
+        {if failing_checks.is_empty() {
+            ().into_any()
+        } else {
+            view! {
+                <ul class="failing-checks" aria-label="Failing checks">
+                    {failing_checks.into_iter().map(|check| {
+                        let title = check.conclusion.to_lowercase();
+                        match check.url {
+                            Some(url) => view! { <li><a class="thread-link" href=url target="_blank" rel="noreferrer" title=title>{check.name}</a></li> }.into_any(),
+                            None => view! { <li title=title>{check.name}</li> }.into_any(),
+                        }
+                    }).collect::<Vec<_>>()}
+                </ul>
+            }
+                .into_any()
+        }}
+    }
+}
+
 #[component]
 fn SourceBadge(source: String) -> impl IntoView {
     let label = source_label(&source);
@@ -440,25 +1014,74 @@ fn format_timestamp(raw: &str) -> (String, String) {
     (relative, absolute)
 }
 
+fn format_unix_timestamp(ts: i64) -> (String, String) {
+    chrono::DateTime::from_timestamp(ts, 0).map_or_else(
+        || (ts.to_string(), ts.to_string()),
+        |dt| format_timestamp(&dt.to_rfc3339()),
+    )
+}
+
+fn search_kind_class(kind: SearchHitKind) -> &'static str {
+    match kind {
+        SearchHitKind::Review => "kind-review",
+        SearchHitKind::Thread => "kind-thread",
+        SearchHitKind::FixRun => "kind-fix-run",
+    }
+}
+
+/// Split a search snippet into its text and its matches (`true`).
+fn snippet_segments(snippet: &str) -> Vec<(bool, String)> {
+    let mut segments = Vec::new();
+    let mut rest = snippet;
+    while let Some(start) = rest.find(SNIPPET_MATCH_START) {
+        let (before, after) = rest.split_at(start);
+        let after = &after[SNIPPET_MATCH_START.len_utf8()..];
+        let end = after.find(SNIPPET_MATCH_END).unwrap_or(after.len());
+        segments.push((false, before.to_string()));
+        segments.push((true, after[..end].to_string()));
+        rest = after
+            .get(end + SNIPPET_MATCH_END.len_utf8()..)
+            .unwrap_or_default();
+    }
+    segments.push((false, rest.to_string()));
+    segments.retain(|(_, text)| !text.is_empty());
+    segments
+}
+
 fn source_label(source: &str) -> &'static str {
-    match source {
+    match db::source_kind(source) {
         "notification" => "Notification",
         "my_pr" => "My PR",
         "assigned_pr" => "Assigned PR",
         "my_issue" => "Assigned issue",
+        "review_requested" => "Review requested",
+        "team_review_requested" => "Team review requested",
+        "mention" => "Mention",
+        "team_mention" => "Team mention",
         _ => "Other",
     }
 }
 
 fn source_icon(source: &str) -> &'static str {
-    match source {
+    match db::source_kind(source) {
         "my_pr" => MY_PR_ICON,
         "assigned_pr" => PR_OPEN_ICON,
         "my_issue" => ISSUE_OPEN_ICON,
+        "review_requested" => REVIEW_REQUESTED_ICON,
+        "team_review_requested" => TEAM_REVIEW_REQUESTED_ICON,
+        "mention" => MENTION_ICON,
+        "team_mention" => TEAM_MENTION_ICON,
         _ => NOTIFICATION_ICON,
     }
 }
 
+fn forge_label(forge: &str) -> &'static str {
+    match forge {
+        "forgejo" => "Forgejo",
+        _ => "Other forge",
+    }
+}
+
 fn thread_state_data(
     subject_type: Option<&str>,
     state: Option<&str>,
@@ -549,22 +1172,88 @@ fn merge_queue_state_label(state: &str) -> &'static str {
     }
 }
 
-fn review_action_path(thread: &DashboardThread) -> String {
-    format!(
-        "/dashboard/actions/prs/{}/{}/{}/review",
+/// Web host of the thread: github.com, a configured GitHub Enterprise
+/// Server, or the host of the URL of a thread of another forge.
+fn thread_host(thread: &DashboardThread) -> &str {
+    let url = thread.pr_url.as_deref().or(thread.subject_url.as_deref());
+    if thread.forge().is_some()
+        && let Some(host) = url.and_then(url_host)
+    {
+        return host;
+    }
+    url.and_then(|url| github::hosts().split_web_url(url))
+        .map_or(github::GITHUB_COM, |(host, _)| host.host.as_str())
+}
+
+fn url_host(url: &str) -> Option<&str> {
+    let without_scheme = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    without_scheme
+        .split('/')
+        .next()
+        .filter(|host| !host.is_empty())
+}
+
+fn pr_action_path(thread: &DashboardThread, action: &str) -> String {
+    let path = format!(
+        "/dashboard/actions/prs/{}/{}/{}/{action}",
         thread.pr_owner.clone().unwrap_or_default(),
         thread.pr_repo.clone().unwrap_or_default(),
         thread.pr_number.unwrap_or_default()
-    )
+    );
+    with_host_query(thread, path)
 }
 
-fn fix_action_path(thread: &DashboardThread) -> String {
-    format!(
-        "/dashboard/actions/prs/{}/{}/{}/fix",
+fn with_host_query(thread: &DashboardThread, path: String) -> String {
+    match thread_host(thread) {
+        github::GITHUB_COM => path,
+        host => format!("{path}?host={host}"),
+    }
+}
+
+fn pr_page_path(thread: &DashboardThread) -> String {
+    let path = format!(
+        "/prs/{}/{}/{}",
         thread.pr_owner.clone().unwrap_or_default(),
         thread.pr_repo.clone().unwrap_or_default(),
         thread.pr_number.unwrap_or_default()
-    )
+    );
+    with_host_query(thread, path)
+}
+
+fn review_action_path(thread: &DashboardThread) -> String {
+    pr_action_path(thread, "review")
+}
+
+fn fix_action_path(thread: &DashboardThread) -> String {
+    pr_action_path(thread, "fix")
+}
+
+fn publish_action_path(thread: &DashboardThread) -> String {
+    pr_action_path(thread, "publish")
+}
+
+/// Findings listed in the publish form of the review modal. Findings resolved
+/// since the previous review are left out.
+fn review_findings_json(findings: &[StoredFinding]) -> String {
+    let items: Vec<_> = findings
+        .iter()
+        .filter(|item| !item.finding.is_resolved())
+        .map(|item| {
+            serde_json::json!({
+                "id": item.id,
+                "severity": item.finding.severity,
+                "label": format!(
+                    "{} ({}): {}",
+                    finding_location(&item.finding),
+                    item.finding.category,
+                    item.finding.suggestion
+                ),
+            })
+        })
+        .collect();
+    serde_json::Value::Array(items).to_string()
 }
 
 fn svg_icon(inner: &'static str) -> impl IntoView {
@@ -603,8 +1292,15 @@ mod tests {
             latest_review_content_md: None,
             latest_review_created_at: None,
             latest_review_provider: None,
+            latest_review_id: None,
+            latest_review_findings: SeverityCounts::default(),
+            latest_review_guidelines_source: None,
             is_draft: false,
             participants: Vec::new(),
+            findings: Vec::new(),
+            ci_status: None,
+            failing_checks: Vec::new(),
+            attention: None,
         }
     }
 
@@ -697,11 +1393,20 @@ mod tests {
                 latest_review_content_md: None,
                 latest_review_created_at: None,
                 latest_review_provider: None,
+                latest_review_id: None,
+                latest_review_findings: SeverityCounts::default(),
+                latest_review_guidelines_source: None,
                 is_draft: false,
                 participants: Vec::new(),
+                findings: Vec::new(),
+                ci_status: None,
+                failing_checks: Vec::new(),
+                attention: None,
             }],
+            jobs: Vec::new(),
             available_repositories: vec!["a/b".to_string()],
             status_message: "ok".to_string(),
+            api_quota: BTreeMap::new(),
         });
 
         assert!(!html.contains("No review"));
@@ -736,11 +1441,20 @@ mod tests {
                 latest_review_content_md: None,
                 latest_review_created_at: None,
                 latest_review_provider: None,
+                latest_review_id: None,
+                latest_review_findings: SeverityCounts::default(),
+                latest_review_guidelines_source: None,
                 is_draft: false,
                 participants: Vec::new(),
+                findings: Vec::new(),
+                ci_status: None,
+                failing_checks: Vec::new(),
+                attention: None,
             }],
+            jobs: Vec::new(),
             available_repositories: vec!["a/b".to_string()],
             status_message: "ok".to_string(),
+            api_quota: BTreeMap::new(),
         });
 
         assert!(html.contains("class=\"unread-dot\""));
@@ -775,11 +1489,20 @@ mod tests {
                 latest_review_content_md: None,
                 latest_review_created_at: None,
                 latest_review_provider: None,
+                latest_review_id: None,
+                latest_review_findings: SeverityCounts::default(),
+                latest_review_guidelines_source: None,
                 is_draft: false,
                 participants: Vec::new(),
+                findings: Vec::new(),
+                ci_status: None,
+                failing_checks: Vec::new(),
+                attention: None,
             }],
+            jobs: Vec::new(),
             available_repositories: vec!["a/b".to_string()],
             status_message: "ok".to_string(),
+            api_quota: BTreeMap::new(),
         });
 
         assert!(!html.contains("class=\"unread-dot\""));
@@ -813,11 +1536,20 @@ mod tests {
                 latest_review_content_md: None,
                 latest_review_created_at: None,
                 latest_review_provider: None,
+                latest_review_id: None,
+                latest_review_findings: SeverityCounts::default(),
+                latest_review_guidelines_source: None,
                 is_draft: false,
                 participants: Vec::new(),
+                findings: Vec::new(),
+                ci_status: None,
+                failing_checks: Vec::new(),
+                attention: None,
             }],
+            jobs: Vec::new(),
             available_repositories: vec!["a/b".to_string()],
             status_message: "ok".to_string(),
+            api_quota: BTreeMap::new(),
         });
 
         assert!(html.contains("data-mark-read-thread-id=\"123\""));
@@ -851,13 +1583,256 @@ mod tests {
                 latest_review_content_md: None,
                 latest_review_created_at: None,
                 latest_review_provider: None,
+                latest_review_id: None,
+                latest_review_findings: SeverityCounts::default(),
+                latest_review_guidelines_source: None,
                 is_draft: false,
                 participants: Vec::new(),
+                findings: Vec::new(),
+                ci_status: None,
+                failing_checks: Vec::new(),
+                attention: None,
             }],
+            jobs: Vec::new(),
             available_repositories: vec!["a/b".to_string()],
             status_message: "ok".to_string(),
+            api_quota: BTreeMap::new(),
         });
 
         assert!(!html.contains("data-mark-read-thread-id="));
     }
+
+    #[test]
+    fn render_fragment_lists_jobs_with_their_actions() {
+        let job = |id, status| StoredJob {
+            id,
+            kind: crate::db::JobKind::Review,
+            pr_url: "https://github.com/a/b/pull/7".to_string(),
+            head_sha: None,
+            status,
+            attempts: 1,
+            max_attempts: 3,
+            run_after: 0,
+            last_error: None,
+            created_at: 0,
+            started_at: None,
+            finished_at: None,
+        };
+        let html = render_fragment(DashboardSnapshot {
+            filters: DashboardThreadFilters::default(),
+            threads: Vec::new(),
+            jobs: vec![
+                job(1, JobStatus::Running),
+                StoredJob {
+                    last_error: Some("agent crashed".to_string()),
+                    ..job(2, JobStatus::Failed)
+                },
+                job(3, JobStatus::Succeeded),
+            ],
+            available_repositories: Vec::new(),
+            status_message: "ok".to_string(),
+            api_quota: BTreeMap::new(),
+        });
+
+        assert!(html.contains("1 active"));
+        assert!(html.contains("a/b#7"));
+        assert!(html.contains("/dashboard/actions/jobs/1/cancel"));
+        assert!(html.contains("/dashboard/actions/jobs/2/retry"));
+        assert!(html.contains("agent crashed"));
+        assert!(!html.contains("/dashboard/actions/jobs/3/"));
+    }
+
+    #[test]
+    fn render_fragment_shows_the_api_quota() {
+        let quota = |remaining| RateLimit {
+            limit: 5000,
+            remaining,
+            reset: 1_760_000_000,
+        };
+        let html = render_fragment(DashboardSnapshot {
+            filters: DashboardThreadFilters::default(),
+            threads: Vec::new(),
+            jobs: Vec::new(),
+            available_repositories: Vec::new(),
+            status_message: "ok".to_string(),
+            api_quota: BTreeMap::from([
+                ("core".to_string(), quota(4200)),
+                ("graphql".to_string(), quota(120)),
+            ]),
+        });
+
+        assert!(html.contains("core 4200/5000"));
+        assert!(html.contains(r#"class="status api-quota low""#));
+        assert!(html.contains("graphql 120/5000"));
+    }
+
+    #[test]
+    fn render_fragment_badges_forgejo_threads_without_github_actions() {
+        let pr_url = "https://codeberg.org/a/b/pulls/3";
+        let html = render_fragment(DashboardSnapshot {
+            filters: DashboardThreadFilters::default(),
+            threads: vec![DashboardThread {
+                sources: vec!["forgejo:my_pr".to_string()],
+                pr_owner: Some("a".to_string()),
+                pr_repo: Some("b".to_string()),
+                pr_number: Some(3),
+                subject_type: Some("PullRequest".to_string()),
+                subject_url: Some(pr_url.to_string()),
+                pr_url: Some(pr_url.to_string()),
+                ..test_thread("a/b", "2026-01-02T00:00:00Z")
+            }],
+            jobs: Vec::new(),
+            available_repositories: vec!["a/b".to_string()],
+            status_message: "ok".to_string(),
+            api_quota: BTreeMap::new(),
+        });
+
+        assert!(html.contains(r#"title="codeberg.org" class="forge-badge">Forgejo</span>"#));
+        assert!(html.contains(r#"href="https://codeberg.org/a/b""#));
+        assert!(html.contains(r#"aria-label="My PR""#));
+        assert!(!html.contains("Reviewing..."));
+        assert!(!html.contains("Open in VS Code"));
+        assert!(html.contains(r#"name="mark_authored_pr" value="true""#));
+    }
+
+    #[test]
+    fn render_fragment_shows_ci_status_and_failing_checks() {
+        let pr_url = "https://github.com/a/b/pull/3";
+        let html = render_fragment(DashboardSnapshot {
+            filters: DashboardThreadFilters::default(),
+            threads: vec![DashboardThread {
+                sources: vec!["my_pr".to_string()],
+                pr_owner: Some("a".to_string()),
+                pr_repo: Some("b".to_string()),
+                pr_number: Some(3),
+                subject_type: Some("PullRequest".to_string()),
+                subject_url: Some(pr_url.to_string()),
+                pr_url: Some(pr_url.to_string()),
+                ci_status: Some(CheckOutcome::Fail),
+                failing_checks: vec![PrCheck {
+                    name: "test".to_string(),
+                    conclusion: "FAILURE".to_string(),
+                    url: Some("https://ci.example/test".to_string()),
+                }],
+                ..test_thread("a/b", "2026-01-02T00:00:00Z")
+            }],
+            jobs: Vec::new(),
+            available_repositories: vec!["a/b".to_string()],
+            status_message: "ok".to_string(),
+            api_quota: BTreeMap::new(),
+        });
+
+        assert!(html.contains(r#"class="ci-badge ci-fail">CI failing</span>"#));
+        assert!(html.contains(r#"href="https://ci.example/test""#));
+        assert!(html.contains(">test</a>"));
+        assert!(html.contains(r#"name="failing_ci_only""#));
+    }
+
+    #[test]
+    fn render_fragment_highlights_prs_needing_attention() {
+        let html = render_fragment(DashboardSnapshot {
+            filters: DashboardThreadFilters::default(),
+            threads: vec![DashboardThread {
+                sources: vec!["my_pr".to_string()],
+                attention: Some("Removed from the merge queue".to_string()),
+                ..test_thread("a/b", "2026-01-02T00:00:00Z")
+            }],
+            jobs: Vec::new(),
+            available_repositories: vec!["a/b".to_string()],
+            status_message: "ok".to_string(),
+            api_quota: BTreeMap::new(),
+        });
+
+        assert!(html.contains(r#"class="thread needs-attention""#));
+        assert!(html.contains("Needs attention: "));
+        assert!(html.contains("Removed from the merge queue</p>"));
+    }
+
+    #[test]
+    fn render_search_page_highlights_the_matches() {
+        let html = render_search_page(
+            "flaky",
+            vec![SearchHit {
+                kind: SearchHitKind::FixRun,
+                pr_url: Some("https://github.com/a/b/pull/1".to_string()),
+                url: Some("https://github.com/a/b/pull/1".to_string()),
+                title: "Fix the <tests>".to_string(),
+                date: "2026-01-02T00:00:00Z".to_string(),
+                snippet: format!("the {SNIPPET_MATCH_START}flaky{SNIPPET_MATCH_END} test"),
+            }],
+        );
+
+        assert!(html.contains(r#"class="search-kind kind-fix-run">Fix run</span>"#));
+        assert!(html.contains(r#"href="https://github.com/a/b/pull/1""#));
+        assert!(html.contains("Fix the &lt;tests&gt;"));
+        assert!(html.contains("<mark>flaky</mark>"));
+        assert!(html.contains(r#"name="q" value="flaky""#));
+        assert!(render_search_page("nothing", Vec::new()).contains("No matches for"));
+    }
+
+    #[test]
+    fn snippet_segments_split_on_the_match_markers() {
+        let snippet = format!("{SNIPPET_MATCH_START}a{SNIPPET_MATCH_END} b {SNIPPET_MATCH_START}c");
+        assert_eq!(
+            snippet_segments(&snippet),
+            vec![
+                (true, "a".to_string()),
+                (false, " b ".to_string()),
+                (true, "c".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn render_pr_page_diffs_the_selected_reviews() {
+        let review = |id, head_sha: &str, content_md: &str| StoredReview {
+            id,
+            pr_url: "https://github.com/a/b/pull/1".to_string(),
+            provider: "copilot".to_string(),
+            model: None,
+            requires_code_changes: false,
+            content_md: content_md.to_string(),
+            created_at: 1_767_225_600 + id,
+            summary: None,
+            verdict: None,
+            guidelines_source: None,
+            previous_review_id: None,
+            head_sha: Some(head_sha.to_string()),
+            base_sha: None,
+        };
+        let html = render_pr_page(PrPage {
+            pr_url: "https://github.com/a/b/pull/1".to_string(),
+            title: Some("Add the parser".to_string()),
+            host: None,
+            history: PrHistory {
+                reviews: vec![
+                    review(1, "1111111aaaa", "Looks good\nold line"),
+                    review(2, "2222222bbbb", "Looks good\nnew line"),
+                ],
+                fix_runs: vec![StoredFixRun {
+                    provider: "copilot".to_string(),
+                    status: "ok".to_string(),
+                    output: "fixed".to_string(),
+                    created_at: 1_767_225_600,
+                }],
+                sync_events: vec![StoredSyncEvent {
+                    status: "error".to_string(),
+                    message: "push rejected".to_string(),
+                    created_at: 1_767_225_600,
+                }],
+            },
+            compare: Some((1, 2)),
+        });
+
+        assert!(html.contains("Add the parser</a>"));
+        assert!(html.contains(r#"title="1111111aaaa" class="head-sha">1111111</code>"#));
+        assert!(html.contains(r#"title="2222222bbbb" class="head-sha">2222222</code>"#));
+        assert!(html.contains(r#"class="diff-removed">old line</td>"#));
+        assert!(html.contains(r#"class="diff-added">new line</td>"#));
+        assert!(html.contains(r#"class="diff-line">Looks good</td>"#));
+        assert!(html.contains(r#"name="from" value="1" checked"#));
+        assert!(html.contains(r#"name="to" value="2" checked"#));
+        assert!(html.contains("push rejected"));
+        assert!(html.contains(">fixed</pre>"));
+    }
 }
@@ -1,12 +1,15 @@
 use rusqlite::{Connection, OptionalExtension, params};
 
 use crate::{
-    github::Participant,
-    review::{parse_requires_code_changes, sanitize_review_markdown},
+    github::{Participant, PrCheck},
+    review::{
+        FindingStatus, ReviewFinding, Severity, Verdict, parse_requires_code_changes,
+        sanitize_review_markdown,
+    },
 };
 
 use super::{
-    Db, NewPr, NewReview, NewThread, StoredPr, StoredReview,
+    Db, NewPr, NewReview, NewThread, StoredFinding, StoredPr, StoredReview,
     util::{bool_to_int, normalize_review_storage, unix_ts},
 };
 
@@ -107,46 +110,99 @@ impl Db {
         })
     }
 
+    /// Delete the threads of `source` whose PR URL starts with `url_prefix`
+    /// and is not in `pr_urls`.
     pub fn delete_threads_by_source_except_pr_urls(
         &self,
         source: &str,
+        url_prefix: &str,
         pr_urls: &[String],
     ) -> anyhow::Result<()> {
         self.with_conn(|conn| {
-            delete_threads_by_source_except_urls(conn, source, RetainedUrlColumn::PrUrl, pr_urls)
+            delete_threads_by_source_except_urls(
+                conn,
+                source,
+                url_prefix,
+                RetainedUrlColumn::PrUrl,
+                pr_urls,
+            )
         })
     }
 
+    /// Delete the threads of `source` whose subject URL starts with
+    /// `url_prefix` and is not in `subject_urls`.
     pub fn delete_threads_by_source_except_subject_urls(
         &self,
         source: &str,
+        url_prefix: &str,
         subject_urls: &[String],
     ) -> anyhow::Result<()> {
         self.with_conn(|conn| {
             delete_threads_by_source_except_urls(
                 conn,
                 source,
+                url_prefix,
                 RetainedUrlColumn::SubjectUrl,
                 subject_urls,
             )
         })
     }
 
+    /// Threads about the PR or the issue at `url`, e.g. to refresh them
+    /// when a webhook reports a change.
+    pub fn threads_for_url(&self, url: &str) -> anyhow::Result<Vec<NewThread>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT
+                    thread_key, github_thread_id, source, repository, subject_type,
+                    subject_title, subject_url, issue_state, discussion_answered, reason, pr_url,
+                    unread, done, updated_at, is_draft
+                FROM threads
+                WHERE pr_url = ?1 OR subject_url = ?1
+                ORDER BY thread_key
+                "#,
+            )?;
+            let rows = stmt.query_map([url], |row| {
+                Ok(NewThread {
+                    thread_key: row.get(0)?,
+                    github_thread_id: row.get(1)?,
+                    source: row.get(2)?,
+                    repository: row.get(3)?,
+                    subject_type: row.get(4)?,
+                    subject_title: row.get(5)?,
+                    subject_url: row.get(6)?,
+                    issue_state: row.get(7)?,
+                    discussion_answered: row.get::<_, Option<i64>>(8)?.map(|value| value != 0),
+                    reason: row.get(9)?,
+                    pr_url: row.get(10)?,
+                    unread: row.get::<_, i64>(11)? != 0,
+                    done: row.get::<_, i64>(12)? != 0,
+                    updated_at: row.get(13)?,
+                    is_draft: row.get::<_, i64>(14)? != 0,
+                })
+            })?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(anyhow::Error::from)
+        })
+    }
+
     pub fn upsert_pr(&self, row: &NewPr) -> anyhow::Result<()> {
         let now = unix_ts();
         self.with_conn(|conn| {
             conn.execute(
                 r#"
                 INSERT INTO prs (
-                    pr_url, owner, repo, number, state, merge_queue_state, title, head_ref,
-                    base_ref, head_sha, updated_at, is_archived, is_draft, last_seen_at
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+                    pr_url, owner, repo, number, state, merge_queue_state, auto_merge, title,
+                    head_ref, base_ref, head_sha, updated_at, is_archived, is_draft, last_seen_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
                 ON CONFLICT(pr_url) DO UPDATE SET
                     owner = excluded.owner,
                     repo = excluded.repo,
                     number = excluded.number,
                     state = excluded.state,
                     merge_queue_state = excluded.merge_queue_state,
+                    auto_merge = excluded.auto_merge,
                     title = excluded.title,
                     head_ref = excluded.head_ref,
                     base_ref = excluded.base_ref,
@@ -163,6 +219,7 @@ impl Db {
                     row.number,
                     row.state,
                     row.merge_queue_state,
+                    bool_to_int(row.auto_merge),
                     row.title,
                     row.head_ref,
                     row.base_ref,
@@ -184,7 +241,7 @@ impl Db {
                 SELECT
                     pr_url, owner, repo, number, state, merge_queue_state, title, head_ref,
                     base_ref, head_sha, updated_at, is_archived, last_reviewed_sha,
-                    last_reviewed_updated_at
+                    last_reviewed_updated_at, auto_merge, attention
                 FROM prs
                 WHERE pr_url = ?1
                 "#,
@@ -205,6 +262,8 @@ impl Db {
                         is_archived: row.get::<_, i64>(11)? != 0,
                         last_reviewed_sha: row.get(12)?,
                         last_reviewed_updated_at: row.get(13)?,
+                        auto_merge: row.get::<_, i64>(14)? != 0,
+                        attention: row.get(15)?,
                     })
                 },
             )
@@ -213,6 +272,17 @@ impl Db {
         })
     }
 
+    /// Flag the PR as needing attention with `reason`, or clear the flag.
+    pub fn set_pr_attention(&self, pr_url: &str, reason: Option<&str>) -> anyhow::Result<()> {
+        self.with_conn(|conn| {
+            conn.execute(
+                "UPDATE prs SET attention = ?2 WHERE pr_url = ?1",
+                params![pr_url, reason],
+            )?;
+            Ok(())
+        })
+    }
+
     pub fn set_pr_review_marker(
         &self,
         pr_url: &str,
@@ -232,16 +302,20 @@ impl Db {
         })
     }
 
-    pub fn insert_review(&self, row: &NewReview) -> anyhow::Result<()> {
+    /// Inserts the review and its findings, returning the review id.
+    pub fn insert_review(&self, row: &NewReview) -> anyhow::Result<i64> {
         let now = unix_ts();
         let (content_md, requires_code_changes) =
             normalize_review_storage(&row.content_md, row.requires_code_changes);
         self.with_conn(|conn| {
-            conn.execute(
+            let tx = conn.unchecked_transaction()?;
+            tx.execute(
                 r#"
                 INSERT INTO reviews (
-                    pr_url, provider, model, requires_code_changes, content_md, created_at
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                    pr_url, provider, model, requires_code_changes, content_md, created_at,
+                    summary, verdict, guidelines_source, previous_review_id, head_sha,
+                    base_sha
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
                 "#,
                 params![
                     row.pr_url,
@@ -250,12 +324,73 @@ impl Db {
                     bool_to_int(requires_code_changes),
                     content_md,
                     now,
+                    row.summary,
+                    row.verdict.map(Verdict::as_str),
+                    row.guidelines_source,
+                    row.previous_review_id,
+                    row.head_sha,
+                    row.base_sha,
                 ],
             )?;
-            Ok(())
+            let review_id = tx.last_insert_rowid();
+
+            let mut stmt = tx.prepare(
+                r#"
+                INSERT INTO review_findings (
+                    review_id, file, start_line, end_line, severity, category, suggestion,
+                    status
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                "#,
+            )?;
+            for finding in &row.findings {
+                stmt.execute(params![
+                    review_id,
+                    finding.file,
+                    finding.start_line,
+                    finding.end_line,
+                    finding.severity.as_str(),
+                    finding.category,
+                    finding.suggestion,
+                    finding.status.map(FindingStatus::as_str),
+                ])?;
+            }
+            drop(stmt);
+            tx.commit()?;
+            Ok(review_id)
+        })
+    }
+
+    /// Findings of the latest review of each PR (or of `pr_url` only),
+    /// grouped by PR with the most severe first.
+    pub fn latest_review_findings(
+        &self,
+        pr_url: Option<&str>,
+        min_severity: Option<Severity>,
+    ) -> anyhow::Result<Vec<StoredFinding>> {
+        self.with_conn(|conn| {
+            let mut out = query_findings(
+                conn,
+                r#"
+                r.id = (SELECT MAX(id) FROM reviews WHERE pr_url = r.pr_url)
+                AND (?1 IS NULL OR r.pr_url = ?1)
+                AND (f.status IS NULL OR f.status != 'resolved')
+                "#,
+                params![pr_url],
+            )?;
+            out.retain(|item| min_severity.is_none_or(|min| item.finding.severity >= min));
+            out.sort_by(|a, b| {
+                a.pr_url
+                    .cmp(&b.pr_url)
+                    .then(b.finding.severity.cmp(&a.finding.severity))
+            });
+            Ok(out)
         })
     }
 
+    pub fn review_findings(&self, review_id: i64) -> anyhow::Result<Vec<StoredFinding>> {
+        self.with_conn(|conn| query_findings(conn, "r.id = ?1", params![review_id]))
+    }
+
     pub fn insert_fix_run(
         &self,
         pr_url: &str,
@@ -318,7 +453,7 @@ impl Db {
     pub fn mark_authored_pr_done_local(&self, pr_url: &str) -> anyhow::Result<()> {
         self.with_conn(|conn| {
             conn.execute(
-                "UPDATE threads SET done = 1, unread = 0 WHERE source = 'my_pr' AND pr_url = ?1",
+                "UPDATE threads SET done = 1, unread = 0 WHERE (source = 'my_pr' OR source LIKE '%:my_pr') AND pr_url = ?1",
                 [pr_url],
             )?;
             Ok(())
@@ -328,17 +463,37 @@ impl Db {
     pub fn mark_assigned_pr_done_local(&self, pr_url: &str) -> anyhow::Result<bool> {
         self.with_conn(|conn| {
             let changed = conn.execute(
-                "UPDATE threads SET done = 1, unread = 0 WHERE source = 'assigned_pr' AND pr_url = ?1",
+                "UPDATE threads SET done = 1, unread = 0 WHERE (source = 'assigned_pr' OR source LIKE '%:assigned_pr') AND pr_url = ?1",
                 [pr_url],
             )?;
             Ok(changed > 0)
         })
     }
 
+    pub fn mark_review_requested_done_local(&self, pr_url: &str) -> anyhow::Result<bool> {
+        self.with_conn(|conn| {
+            let changed = conn.execute(
+                "UPDATE threads SET done = 1, unread = 0 WHERE source IN ('review_requested', 'team_review_requested') AND pr_url = ?1",
+                [pr_url],
+            )?;
+            Ok(changed > 0)
+        })
+    }
+
+    pub fn mark_mention_done_local(&self, subject_url: &str) -> anyhow::Result<bool> {
+        self.with_conn(|conn| {
+            let changed = conn.execute(
+                "UPDATE threads SET done = 1, unread = 0 WHERE source IN ('mention', 'team_mention') AND subject_url = ?1",
+                [subject_url],
+            )?;
+            Ok(changed > 0)
+        })
+    }
+
     pub fn mark_assigned_issue_done_local(&self, subject_url: &str) -> anyhow::Result<bool> {
         self.with_conn(|conn| {
             let changed = conn.execute(
-                "UPDATE threads SET done = 1, unread = 0 WHERE source = 'my_issue' AND subject_url = ?1",
+                "UPDATE threads SET done = 1, unread = 0 WHERE (source = 'my_issue' OR source LIKE '%:my_issue') AND subject_url = ?1",
                 [subject_url],
             )?;
             Ok(changed > 0)
@@ -348,28 +503,11 @@ impl Db {
     pub fn latest_review_by_url(&self, pr_url: &str) -> anyhow::Result<Option<StoredReview>> {
         self.with_conn(|conn| {
             conn.query_row(
-                r#"
-                SELECT id, pr_url, provider, model, requires_code_changes, content_md, created_at
-                FROM reviews
-                WHERE pr_url = ?1
-                ORDER BY id DESC
-                LIMIT 1
-                "#,
+                &format!(
+                    "SELECT {REVIEW_COLUMNS} FROM reviews WHERE pr_url = ?1 ORDER BY id DESC LIMIT 1"
+                ),
                 [pr_url],
-                |row| {
-                    let stored_requires_code_changes: i64 = row.get(4)?;
-                    let content_md = sanitize_review_markdown(&row.get::<_, String>(5)?);
-                    Ok(StoredReview {
-                        id: row.get(0)?,
-                        pr_url: row.get(1)?,
-                        provider: row.get(2)?,
-                        model: row.get(3)?,
-                        requires_code_changes: parse_requires_code_changes(&content_md)
-                            .unwrap_or(stored_requires_code_changes != 0),
-                        content_md,
-                        created_at: row.get(6)?,
-                    })
-                },
+                review_from_row,
             )
             .optional()
             .map_err(anyhow::Error::from)
@@ -402,6 +540,48 @@ impl Db {
         })
     }
 
+    /// Replace the checks of a PR with those of its current head commit.
+    pub fn replace_pr_checks(&self, pr_url: &str, checks: &[PrCheck]) -> anyhow::Result<()> {
+        self.with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute("DELETE FROM pr_checks WHERE pr_url = ?1", params![pr_url])?;
+            {
+                let mut stmt = tx.prepare(
+                    "INSERT OR REPLACE INTO pr_checks (pr_url, name, conclusion, outcome, url) VALUES (?1, ?2, ?3, ?4, ?5)",
+                )?;
+                for check in checks {
+                    stmt.execute(params![
+                        pr_url,
+                        check.name,
+                        check.conclusion,
+                        check.outcome().as_str(),
+                        check.url
+                    ])?;
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        })
+    }
+
+    /// Failing checks of a PR, by name.
+    pub fn failing_pr_checks(&self, pr_url: &str) -> anyhow::Result<Vec<PrCheck>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT name, conclusion, url FROM pr_checks WHERE pr_url = ?1 AND outcome = 'fail' ORDER BY name",
+            )?;
+            let rows = stmt.query_map(params![pr_url], |row| {
+                Ok(PrCheck {
+                    name: row.get(0)?,
+                    conclusion: row.get(1)?,
+                    url: row.get(2)?,
+                })
+            })?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(anyhow::Error::from)
+        })
+    }
+
     /// Returns the stored participants for a PR, ordered by last activity (most recent first).
     pub fn get_pr_participants(&self, pr_url: &str) -> anyhow::Result<Vec<Participant>> {
         self.with_conn(|conn| {
@@ -458,6 +638,48 @@ impl Db {
     }
 }
 
+fn query_findings(
+    conn: &Connection,
+    filter: &str,
+    params: impl rusqlite::Params,
+) -> anyhow::Result<Vec<StoredFinding>> {
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT f.id, f.review_id, r.pr_url, f.file, f.start_line, f.end_line,
+               f.severity, f.category, f.suggestion, f.status
+        FROM review_findings f
+        JOIN reviews r ON r.id = f.review_id
+        WHERE {filter}
+        ORDER BY r.pr_url, f.file, f.start_line, f.id
+        "#
+    ))?;
+    let rows = stmt.query_map(params, |row| {
+        Ok(StoredFinding {
+            id: row.get(0)?,
+            review_id: row.get(1)?,
+            pr_url: row.get(2)?,
+            finding: ReviewFinding {
+                file: row.get(3)?,
+                start_line: row.get(4)?,
+                end_line: row.get(5)?,
+                severity: Severity::parse(&row.get::<_, String>(6)?).unwrap_or(Severity::Info),
+                category: row.get(7)?,
+                suggestion: row.get(8)?,
+                status: row
+                    .get::<_, Option<String>>(9)?
+                    .as_deref()
+                    .and_then(FindingStatus::parse),
+            },
+        })
+    })?;
+
+    let mut out = Vec::new();
+    for row in rows {
+        out.push(row?);
+    }
+    Ok(out)
+}
+
 #[derive(Clone, Copy)]
 enum RetainedUrlColumn {
     PrUrl,
@@ -483,11 +705,18 @@ impl RetainedUrlColumn {
 fn delete_threads_by_source_except_urls(
     conn: &Connection,
     source: &str,
+    url_prefix: &str,
     retained_column: RetainedUrlColumn,
     urls: &[String],
 ) -> anyhow::Result<()> {
+    let thread_column = retained_column.thread_column();
+    // Threads without URL only match the empty prefix.
+    let in_scope = format!("substr(COALESCE({thread_column}, ''), 1, length(?2)) = ?2");
     if urls.is_empty() {
-        conn.execute("DELETE FROM threads WHERE source = ?1", [source])?;
+        conn.execute(
+            &format!("DELETE FROM threads WHERE source = ?1 AND {in_scope}"),
+            [source, url_prefix],
+        )?;
         return Ok(());
     }
 
@@ -505,12 +734,12 @@ fn delete_threads_by_source_except_urls(
         insert_stmt.execute([url])?;
     }
 
-    let thread_column = retained_column.thread_column();
     conn.execute(
         &format!(
             r#"
             DELETE FROM threads
             WHERE source = ?1
+              AND {in_scope}
               AND (
                   {thread_column} IS NULL
                   OR NOT EXISTS (
@@ -521,7 +750,36 @@ fn delete_threads_by_source_except_urls(
               )
             "#
         ),
-        [source],
+        [source, url_prefix],
     )?;
     Ok(())
 }
+
+/// Columns read by [`review_from_row`].
+pub(super) const REVIEW_COLUMNS: &str = "id, pr_url, provider, model, requires_code_changes, \
+    content_md, created_at, summary, verdict, guidelines_source, previous_review_id, head_sha, \
+    base_sha";
+
+pub(super) fn review_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<StoredReview> {
+    let stored_requires_code_changes: i64 = row.get(4)?;
+    let content_md = sanitize_review_markdown(&row.get::<_, String>(5)?);
+    Ok(StoredReview {
+        id: row.get(0)?,
+        pr_url: row.get(1)?,
+        provider: row.get(2)?,
+        model: row.get(3)?,
+        requires_code_changes: parse_requires_code_changes(&content_md)
+            .unwrap_or(stored_requires_code_changes != 0),
+        content_md,
+        created_at: row.get(6)?,
+        summary: row.get(7)?,
+        verdict: row
+            .get::<_, Option<String>>(8)?
+            .as_deref()
+            .and_then(Verdict::parse),
+        guidelines_source: row.get(9)?,
+        previous_review_id: row.get(10)?,
+        head_sha: row.get(11)?,
+        base_sha: row.get(12)?,
+    })
+}
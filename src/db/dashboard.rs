@@ -2,8 +2,13 @@ use std::collections::HashMap;
 
 use rusqlite::{OptionalExtension, params};
 
+use crate::{
+    github::CheckOutcome,
+    review::{Severity, SeverityCounts},
+};
+
 use super::{
-    DashboardThread, DashboardThreadFilters, Db,
+    DashboardThread, DashboardThreadFilters, Db, source_kind,
     util::{bool_to_int, unix_ts},
 };
 
@@ -43,18 +48,29 @@ impl Db {
                     p.merge_queue_state,
                     COALESCE(p.is_archived, 0),
                     MAX(COALESCE(t.is_draft, 0), COALESCE(p.is_draft, 0)) AS is_draft,
+                    lr.id AS latest_review_id,
                     lr.content_md AS latest_review_content_md,
                     lr.created_at AS latest_review_created_at,
-                    lr.provider AS latest_review_provider
+                    lr.provider AS latest_review_provider,
+                    COALESCE(lf.critical, 0),
+                    COALESCE(lf.major, 0),
+                    COALESCE(lf.minor, 0),
+                    COALESCE(lf.info, 0),
+                    lr.guidelines_source AS latest_review_guidelines_source,
+                    pc.failing,
+                    pc.pending,
+                    p.attention
                 FROM threads t
                 LEFT JOIN prs p ON p.pr_url = t.pr_url
                 LEFT JOIN (
                     SELECT
+                        r.id,
                         r.pr_url,
                         r.requires_code_changes,
                         r.content_md,
                         r.created_at,
-                        r.provider
+                        r.provider,
+                        r.guidelines_source
                     FROM reviews r
                     INNER JOIN (
                         SELECT pr_url, MAX(id) AS max_id
@@ -62,6 +78,25 @@ impl Db {
                         GROUP BY pr_url
                     ) latest ON latest.pr_url = r.pr_url AND latest.max_id = r.id
                 ) lr ON lr.pr_url = t.pr_url
+                LEFT JOIN (
+                    SELECT
+                        review_id,
+                        SUM(severity = 'critical') AS critical,
+                        SUM(severity = 'major') AS major,
+                        SUM(severity = 'minor') AS minor,
+                        SUM(severity = 'info') AS info
+                    FROM review_findings
+                    WHERE status IS NULL OR status != 'resolved'
+                    GROUP BY review_id
+                ) lf ON lf.review_id = lr.id
+                LEFT JOIN (
+                    SELECT
+                        pr_url,
+                        SUM(outcome = 'fail') AS failing,
+                        SUM(outcome = 'pending') AS pending
+                    FROM pr_checks
+                    GROUP BY pr_url
+                ) pc ON pc.pr_url = t.pr_url
                 ORDER BY t.updated_at DESC
                 "#,
             )?;
@@ -93,9 +128,19 @@ impl Db {
                     pr_merge_queue_state: row.get(19)?,
                     is_archived_pr: row.get::<_, i64>(20)? != 0,
                     is_draft: row.get::<_, i64>(21)? != 0,
-                    latest_review_content_md: row.get(22)?,
-                    latest_review_created_at: row.get(23)?,
-                    latest_review_provider: row.get(24)?,
+                    latest_review_id: row.get(22)?,
+                    latest_review_content_md: row.get(23)?,
+                    latest_review_created_at: row.get(24)?,
+                    latest_review_provider: row.get(25)?,
+                    latest_review_findings: SeverityCounts {
+                        critical: row.get(26)?,
+                        major: row.get(27)?,
+                        minor: row.get(28)?,
+                        info: row.get(29)?,
+                    },
+                    latest_review_guidelines_source: row.get(30)?,
+                    ci_status: ci_status(row.get(31)?, row.get(32)?),
+                    attention: row.get(33)?,
                 })
             })?;
 
@@ -115,6 +160,8 @@ impl Db {
                 .into_iter()
                 .filter(|thread| filters.include_done_state(thread.done))
                 .filter(|thread| filters.include_repository(&thread.repository))
+                .filter(|thread| filters.include_findings(&thread.latest_review_findings))
+                .filter(|thread| filters.include_ci(thread))
                 .collect())
         })
     }
@@ -131,7 +178,11 @@ impl Db {
                     show_assigned_issues,
                     show_done,
                     show_not_done,
-                    group_by_repository
+                    group_by_repository,
+                    min_finding_severity,
+                    failing_ci_only,
+                    show_review_requested,
+                    show_mentions
                 FROM dashboard_preferences
                 WHERE id = 1
                 "#,
@@ -146,6 +197,13 @@ impl Db {
                             show_not_done: row.get::<_, i64>(5)? != 0,
                             group_by_repository: row.get::<_, i64>(6)? != 0,
                             hidden_repositories: Vec::new(),
+                            min_finding_severity: row
+                                .get::<_, Option<String>>(7)?
+                                .as_deref()
+                                .and_then(Severity::parse),
+                            failing_ci_only: row.get::<_, i64>(8)? != 0,
+                            show_review_requested: row.get::<_, i64>(9)? != 0,
+                            show_mentions: row.get::<_, i64>(10)? != 0,
                         })
                     },
                 )
@@ -180,8 +238,12 @@ impl Db {
                     show_done,
                     show_not_done,
                     group_by_repository,
+                    min_finding_severity,
+                    failing_ci_only,
+                    show_review_requested,
+                    show_mentions,
                     updated_at
-                ) VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                ) VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
                 ON CONFLICT(id) DO UPDATE SET
                     show_notifications = excluded.show_notifications,
                     show_my_prs = excluded.show_my_prs,
@@ -190,6 +252,10 @@ impl Db {
                     show_done = excluded.show_done,
                     show_not_done = excluded.show_not_done,
                     group_by_repository = excluded.group_by_repository,
+                    min_finding_severity = excluded.min_finding_severity,
+                    failing_ci_only = excluded.failing_ci_only,
+                    show_review_requested = excluded.show_review_requested,
+                    show_mentions = excluded.show_mentions,
                     updated_at = excluded.updated_at
                 "#,
                 params![
@@ -200,6 +266,10 @@ impl Db {
                     bool_to_int(filters.show_done),
                     bool_to_int(filters.show_not_done),
                     bool_to_int(filters.group_by_repository),
+                    filters.min_finding_severity.map(Severity::as_str),
+                    bool_to_int(filters.failing_ci_only),
+                    bool_to_int(filters.show_review_requested),
+                    bool_to_int(filters.show_mentions),
                     now,
                 ],
             )?;
@@ -259,9 +329,13 @@ fn dashboard_thread_identity(thread: &DashboardThread) -> Option<String> {
         return Some(format!("pr:{pr_url}"));
     }
 
+    // Mentions link to a comment of the issue.
     thread.subject_url.as_ref().map(|subject_url| {
         let subject_type = thread.subject_type.as_deref().unwrap_or("unknown");
-        format!("subject:{subject_type}:{subject_url}")
+        let issue_url = subject_url
+            .split_once('#')
+            .map_or(subject_url.as_str(), |(url, _)| url);
+        format!("subject:{subject_type}:{issue_url}")
     })
 }
 
@@ -288,10 +362,15 @@ struct DashboardThreadRow {
     pr_state: Option<String>,
     pr_merge_queue_state: Option<String>,
     is_archived_pr: bool,
+    latest_review_id: Option<i64>,
     latest_review_content_md: Option<String>,
     latest_review_created_at: Option<i64>,
     latest_review_provider: Option<String>,
+    latest_review_findings: SeverityCounts,
+    latest_review_guidelines_source: Option<String>,
     is_draft: bool,
+    ci_status: Option<CheckOutcome>,
+    attention: Option<String>,
 }
 
 impl DashboardThreadRow {
@@ -317,22 +396,31 @@ impl DashboardThreadRow {
             latest_requires_code_changes: self.latest_requires_code_changes,
             pr_state: self.pr_state,
             pr_merge_queue_state: self.pr_merge_queue_state,
+            latest_review_id: self.latest_review_id,
             latest_review_content_md: self.latest_review_content_md,
             latest_review_created_at: self.latest_review_created_at,
             latest_review_provider: self.latest_review_provider,
+            latest_review_findings: self.latest_review_findings,
+            latest_review_guidelines_source: self.latest_review_guidelines_source,
             is_draft: self.is_draft,
             participants: Vec::new(),
+            findings: Vec::new(),
+            ci_status: self.ci_status,
+            failing_checks: Vec::new(),
+            attention: self.attention,
         }
     }
 }
 
 impl DashboardThreadFilters {
     fn include_sources(&self, sources: &[String]) -> bool {
-        sources.iter().any(|source| match source.as_str() {
+        sources.iter().any(|source| match source_kind(source) {
             "notification" => self.show_notifications,
             "my_pr" => self.show_my_prs,
             "assigned_pr" => self.show_assigned_prs,
             "my_issue" => self.show_assigned_issues,
+            "review_requested" | "team_review_requested" => self.show_review_requested,
+            "mention" | "team_mention" => self.show_mentions,
             _ => false,
         })
     }
@@ -347,6 +435,20 @@ impl DashboardThreadFilters {
             .iter()
             .any(|hidden| hidden == repository)
     }
+
+    fn include_findings(&self, counts: &SeverityCounts) -> bool {
+        self.min_finding_severity
+            .is_none_or(|min| counts.at_least(min) > 0)
+    }
+
+    fn include_ci(&self, thread: &DashboardThread) -> bool {
+        !self.failing_ci_only
+            || (thread.ci_status == Some(CheckOutcome::Fail)
+                && thread
+                    .sources
+                    .iter()
+                    .any(|source| source_kind(source) == "my_pr"))
+    }
 }
 
 fn merge_dashboard_thread(existing: &mut DashboardThread, incoming: DashboardThread) {
@@ -369,6 +471,8 @@ fn merge_dashboard_thread(existing: &mut DashboardThread, incoming: DashboardThr
         .latest_requires_code_changes
         .or(incoming.latest_requires_code_changes);
     existing.pr_state = existing_snapshot.pr_state.or(incoming.pr_state);
+    existing.ci_status = existing_snapshot.ci_status.or(incoming.ci_status);
+    existing.attention = existing_snapshot.attention.or(incoming.attention);
     existing.pr_merge_queue_state = existing_snapshot
         .pr_merge_queue_state
         .or(incoming.pr_merge_queue_state);
@@ -379,9 +483,26 @@ fn merge_dashboard_thread(existing: &mut DashboardThread, incoming: DashboardThr
     existing.discussion_answered = existing_snapshot
         .discussion_answered
         .or(incoming.discussion_answered);
+    existing.latest_review_id = existing_snapshot
+        .latest_review_id
+        .or(incoming.latest_review_id);
     existing.latest_review_content_md = existing_snapshot
         .latest_review_content_md
         .or(incoming.latest_review_content_md);
+    (
+        existing.latest_review_findings,
+        existing.latest_review_guidelines_source,
+    ) = if existing_snapshot.latest_review_created_at.is_some() {
+        (
+            existing_snapshot.latest_review_findings,
+            existing_snapshot.latest_review_guidelines_source,
+        )
+    } else {
+        (
+            incoming.latest_review_findings,
+            incoming.latest_review_guidelines_source,
+        )
+    };
     existing.latest_review_created_at = existing_snapshot
         .latest_review_created_at
         .or(incoming.latest_review_created_at);
@@ -393,11 +514,16 @@ fn merge_dashboard_thread(existing: &mut DashboardThread, incoming: DashboardThr
         existing_snapshot.reason,
         incoming.reason,
     );
-    existing.subject_url = merge_optional_string(
-        incoming_preferred,
-        existing_snapshot.subject_url,
-        incoming.subject_url,
-    );
+    // The link to the comment of a mention beats the link to the issue.
+    existing.subject_url = match (existing_snapshot.subject_url, incoming.subject_url) {
+        (Some(existing), Some(incoming)) if incoming.contains('#') && !existing.contains('#') => {
+            Some(incoming)
+        }
+        (Some(existing), Some(incoming)) if existing.contains('#') && !incoming.contains('#') => {
+            Some(existing)
+        }
+        (existing, incoming) => merge_optional_string(incoming_preferred, existing, incoming),
+    };
     existing.subject_type = merge_optional_string(
         incoming_preferred,
         existing_snapshot.subject_type,
@@ -412,9 +538,26 @@ fn merge_dashboard_thread(existing: &mut DashboardThread, incoming: DashboardThr
 fn dashboard_thread_priority(thread: &DashboardThread) -> usize {
     match (thread.github_thread_id.is_some(), thread.sources.as_slice()) {
         (true, _) => 2,
+        // Notifications of other forges than GitHub.
+        (false, sources)
+            if sources
+                .iter()
+                .any(|source| source_kind(source) == "notification") =>
+        {
+            2
+        }
         (false, sources)
             if sources.iter().any(|source| {
-                source == "my_pr" || source == "assigned_pr" || source == "my_issue"
+                matches!(
+                    source_kind(source),
+                    "my_pr"
+                        | "assigned_pr"
+                        | "review_requested"
+                        | "team_review_requested"
+                        | "my_issue"
+                        | "mention"
+                        | "team_mention"
+                )
             }) =>
         {
             1
@@ -430,12 +573,14 @@ fn merge_sources(left: &[String], right: &[String]) -> Vec<String> {
             sources.push(source.clone());
         }
     }
-    sources.sort_by_key(|source| match source.as_str() {
+    sources.sort_by_key(|source| match source_kind(source) {
         "notification" => 0,
         "my_pr" => 1,
         "assigned_pr" => 2,
-        "my_issue" => 3,
-        _ => 4,
+        "review_requested" | "team_review_requested" => 3,
+        "my_issue" => 4,
+        "mention" | "team_mention" => 5,
+        _ => 6,
     });
     sources
 }
@@ -452,6 +597,16 @@ fn merge_optional_string(
     }
 }
 
+/// CI of a PR from its counts of failing and pending checks, NULL when it
+/// has no checks.
+fn ci_status(failing: Option<i64>, pending: Option<i64>) -> Option<CheckOutcome> {
+    Some(match (failing?, pending?) {
+        (failing, _) if failing > 0 => CheckOutcome::Fail,
+        (_, pending) if pending > 0 => CheckOutcome::Pending,
+        _ => CheckOutcome::Pass,
+    })
+}
+
 fn max_string(left: String, right: String) -> String {
     if right > left { right } else { left }
 }
@@ -0,0 +1,99 @@
+-- Oldest layout, before the columns added with ALTER TABLE.
+
+CREATE TABLE threads (
+    thread_key TEXT PRIMARY KEY,
+    github_thread_id TEXT,
+    source TEXT NOT NULL,
+    repository TEXT NOT NULL,
+    subject_type TEXT,
+    subject_title TEXT NOT NULL,
+    subject_url TEXT,
+    reason TEXT,
+    pr_url TEXT,
+    unread INTEGER NOT NULL,
+    done INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL,
+    last_seen_at INTEGER NOT NULL
+);
+
+CREATE UNIQUE INDEX idx_threads_github_thread_id
+    ON threads(github_thread_id)
+    WHERE github_thread_id IS NOT NULL;
+
+CREATE INDEX idx_threads_pr_url ON threads(pr_url);
+
+CREATE INDEX idx_threads_source_subject_url ON threads(source, subject_url);
+
+CREATE TABLE prs (
+    pr_url TEXT PRIMARY KEY,
+    owner TEXT NOT NULL,
+    repo TEXT NOT NULL,
+    number INTEGER NOT NULL,
+    state TEXT NOT NULL,
+    title TEXT NOT NULL,
+    head_ref TEXT NOT NULL,
+    base_ref TEXT NOT NULL,
+    head_sha TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    last_seen_at INTEGER NOT NULL,
+    last_reviewed_sha TEXT,
+    last_reviewed_updated_at TEXT
+);
+
+CREATE UNIQUE INDEX idx_prs_owner_repo_number
+    ON prs(owner, repo, number);
+
+CREATE TABLE reviews (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    pr_url TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    model TEXT,
+    requires_code_changes INTEGER NOT NULL,
+    content_md TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX idx_reviews_pr_url ON reviews(pr_url);
+
+CREATE TABLE fix_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    pr_url TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    status TEXT NOT NULL,
+    output TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+
+CREATE TABLE sync_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    pr_url TEXT NOT NULL,
+    status TEXT NOT NULL,
+    message TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+
+CREATE TABLE dashboard_preferences (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    show_notifications INTEGER NOT NULL,
+    show_done INTEGER NOT NULL,
+    show_not_done INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE kv (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+
+CREATE TABLE pr_participants (
+    pr_url TEXT NOT NULL,
+    login TEXT NOT NULL,
+    avatar_url TEXT NOT NULL,
+    PRIMARY KEY (pr_url, login)
+);
+
+CREATE INDEX idx_pr_participants_pr_url ON pr_participants(pr_url);
+
+CREATE TABLE repository_filter (
+    repository TEXT PRIMARY KEY
+);
@@ -0,0 +1,75 @@
+use rusqlite::params;
+
+use super::{
+    Db, StoredFixRun, StoredReview, StoredSyncEvent,
+    core::{REVIEW_COLUMNS, review_from_row},
+};
+
+/// Everything stored about a PR, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct PrHistory {
+    pub reviews: Vec<StoredReview>,
+    pub fix_runs: Vec<StoredFixRun>,
+    pub sync_events: Vec<StoredSyncEvent>,
+}
+
+impl PrHistory {
+    pub fn is_empty(&self) -> bool {
+        self.reviews.is_empty() && self.fix_runs.is_empty() && self.sync_events.is_empty()
+    }
+}
+
+impl Db {
+    /// Every review, fix run and sync event stored for `pr_url`, not only
+    /// the latest review.
+    pub fn pr_history(&self, pr_url: &str) -> anyhow::Result<PrHistory> {
+        self.with_conn(|conn| {
+            let reviews = conn
+                .prepare(&format!(
+                    "SELECT {REVIEW_COLUMNS} FROM reviews WHERE pr_url = ?1 ORDER BY created_at, id"
+                ))?
+                .query_map(params![pr_url], review_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            let fix_runs = conn
+                .prepare(
+                    r#"
+                    SELECT provider, status, output, created_at
+                    FROM fix_runs
+                    WHERE pr_url = ?1
+                    ORDER BY created_at, id
+                    "#,
+                )?
+                .query_map(params![pr_url], |row| {
+                    Ok(StoredFixRun {
+                        provider: row.get(0)?,
+                        status: row.get(1)?,
+                        output: row.get(2)?,
+                        created_at: row.get(3)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            let sync_events = conn
+                .prepare(
+                    r#"
+                    SELECT status, message, created_at
+                    FROM sync_events
+                    WHERE pr_url = ?1
+                    ORDER BY created_at, id
+                    "#,
+                )?
+                .query_map(params![pr_url], |row| {
+                    Ok(StoredSyncEvent {
+                        status: row.get(0)?,
+                        message: row.get(1)?,
+                        created_at: row.get(2)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(PrHistory {
+                reviews,
+                fix_runs,
+                sync_events,
+            })
+        })
+    }
+}
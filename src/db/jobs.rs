@@ -0,0 +1,288 @@
+use std::collections::HashSet;
+
+use rusqlite::{OptionalExtension, Row, Transaction, TransactionBehavior, params, types::Type};
+
+use super::{Db, JobKind, JobStatus, StoredJob, util::unix_ts};
+
+const JOB_COLUMNS: &str = "id, kind, pr_url, head_sha, status, attempts, max_attempts, \
+                           run_after, last_error, created_at, started_at, finished_at";
+
+impl Db {
+    /// Queue a job, unless the same work is already queued or running for
+    /// the PR. Returns the id of the job doing the work, or `None` when a job
+    /// already ran for `head_sha`: a failed or cancelled automatic review is
+    /// not queued again by every poll.
+    pub fn enqueue_job(
+        &self,
+        kind: JobKind,
+        pr_url: &str,
+        head_sha: Option<&str>,
+        max_attempts: u32,
+    ) -> anyhow::Result<Option<i64>> {
+        let now = unix_ts();
+        self.with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            let existing: Option<i64> = tx
+                .query_row(
+                    r#"
+                    SELECT id FROM jobs
+                    WHERE kind = ?1 AND pr_url = ?2 AND status IN ('queued', 'running')
+                    ORDER BY id
+                    LIMIT 1
+                    "#,
+                    params![kind.as_str(), pr_url],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if let Some(id) = existing {
+                return Ok(Some(id));
+            }
+            if let Some(head_sha) = head_sha {
+                let already_ran: bool = tx.query_row(
+                    "SELECT EXISTS(SELECT 1 FROM jobs WHERE kind = ?1 AND pr_url = ?2 AND head_sha = ?3)",
+                    params![kind.as_str(), pr_url, head_sha],
+                    |row| row.get(0),
+                )?;
+                if already_ran {
+                    return Ok(None);
+                }
+            }
+
+            tx.execute(
+                r#"
+                INSERT INTO jobs (kind, pr_url, head_sha, status, max_attempts, run_after, created_at)
+                VALUES (?1, ?2, ?3, 'queued', ?4, ?5, ?5)
+                "#,
+                params![kind.as_str(), pr_url, head_sha, max_attempts.max(1), now],
+            )?;
+            let id = tx.last_insert_rowid();
+            tx.commit()?;
+            Ok(Some(id))
+        })
+    }
+
+    /// Mark the oldest job due at `now` as running and return it. Jobs of a
+    /// repository that already has a running job wait: the reviews and fixes
+    /// of a repository check its PRs out in the same clone, so only one of
+    /// them may run at a time.
+    pub fn claim_next_job(&self, now: i64) -> anyhow::Result<Option<StoredJob>> {
+        self.with_conn(|conn| {
+            // An immediate transaction, so that two workers never claim the
+            // same job, nor two jobs of the same repository.
+            let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+            let busy = tx
+                .prepare("SELECT pr_url FROM jobs WHERE status = 'running'")?
+                .query_map([], |row| row.get::<_, String>(0))?
+                .map(|pr_url| pr_url.map(|pr_url| repository_of(&pr_url).to_string()))
+                .collect::<Result<HashSet<_>, _>>()?;
+            let next = tx
+                .prepare(
+                    r#"
+                    SELECT id, pr_url FROM jobs
+                    WHERE status = 'queued' AND run_after <= ?1
+                    ORDER BY run_after, id
+                    "#,
+                )?
+                .query_map([now], |row| {
+                    Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .find(|(_, pr_url)| !busy.contains(repository_of(pr_url)));
+            let Some((id, _)) = next else {
+                return Ok(None);
+            };
+            let job = tx.query_row(
+                &format!(
+                    r#"
+                    UPDATE jobs
+                    SET status = 'running', attempts = attempts + 1, started_at = ?2,
+                        finished_at = NULL
+                    WHERE id = ?1
+                    RETURNING {JOB_COLUMNS}
+                    "#
+                ),
+                params![id, now],
+                job_from_row,
+            )?;
+            tx.commit()?;
+            Ok(Some(job))
+        })
+    }
+
+    /// Record the success of a running job. Cancelled jobs stay cancelled.
+    pub fn complete_job(&self, id: i64) -> anyhow::Result<()> {
+        let now = unix_ts();
+        self.with_conn(|conn| {
+            conn.execute(
+                r#"
+                UPDATE jobs
+                SET status = 'succeeded', last_error = NULL, finished_at = ?2
+                WHERE id = ?1 AND status = 'running'
+                "#,
+                params![id, now],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Record the failure of a running job: queue it again at `retry_at`, or
+    /// mark it failed when `None`.
+    pub fn fail_job(&self, id: i64, error: &str, retry_at: Option<i64>) -> anyhow::Result<()> {
+        let now = unix_ts();
+        self.with_conn(|conn| {
+            match retry_at {
+                Some(retry_at) => conn.execute(
+                    r#"
+                    UPDATE jobs
+                    SET status = 'queued', last_error = ?2, run_after = ?3
+                    WHERE id = ?1 AND status = 'running'
+                    "#,
+                    params![id, error, retry_at],
+                )?,
+                None => conn.execute(
+                    r#"
+                    UPDATE jobs
+                    SET status = 'failed', last_error = ?2, finished_at = ?3
+                    WHERE id = ?1 AND status = 'running'
+                    "#,
+                    params![id, error, now],
+                )?,
+            };
+            Ok(())
+        })
+    }
+
+    /// Cancel a queued or running job. Returns the status it had, or `None`
+    /// when it was already finished.
+    pub fn cancel_job(&self, id: i64) -> anyhow::Result<Option<JobStatus>> {
+        let now = unix_ts();
+        self.with_conn(|conn| {
+            let tx = conn.unchecked_transaction()?;
+            let Some(job) = get_job(&tx, id)? else {
+                anyhow::bail!("❌ Job #{id} not found");
+            };
+            if !job.status.is_active() {
+                return Ok(None);
+            }
+            tx.execute(
+                "UPDATE jobs SET status = 'cancelled', finished_at = ?2 WHERE id = ?1",
+                params![id, now],
+            )?;
+            tx.commit()?;
+            Ok(Some(job.status))
+        })
+    }
+
+    /// Queue a failed or cancelled job again, with a fresh set of attempts.
+    pub fn retry_job(&self, id: i64) -> anyhow::Result<()> {
+        let now = unix_ts();
+        self.with_conn(|conn| {
+            let Some(job) = get_job(conn, id)? else {
+                anyhow::bail!("❌ Job #{id} not found");
+            };
+            anyhow::ensure!(
+                matches!(job.status, JobStatus::Failed | JobStatus::Cancelled),
+                "❌ Job #{id} is {}, only failed or cancelled jobs can be retried",
+                job.status.as_str()
+            );
+            conn.execute(
+                r#"
+                UPDATE jobs
+                SET status = 'queued', attempts = 0, run_after = ?2, started_at = NULL,
+                    finished_at = NULL
+                WHERE id = ?1
+                "#,
+                params![id, now],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Queue again the jobs that were running when `serve` stopped. The
+    /// interrupted attempt is not counted.
+    pub fn requeue_interrupted_jobs(&self) -> anyhow::Result<usize> {
+        let now = unix_ts();
+        self.with_conn(|conn| {
+            let changed = conn.execute(
+                r#"
+                UPDATE jobs
+                SET status = 'queued', attempts = MAX(attempts - 1, 0), run_after = ?1,
+                    started_at = NULL
+                WHERE status = 'running'
+                "#,
+                [now],
+            )?;
+            Ok(changed)
+        })
+    }
+
+    /// Active jobs first, then the most recent ones.
+    pub fn list_jobs(&self, limit: usize) -> anyhow::Result<Vec<StoredJob>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                r#"
+                SELECT {JOB_COLUMNS}
+                FROM jobs
+                ORDER BY status IN ('queued', 'running') DESC, id DESC
+                LIMIT ?1
+                "#
+            ))?;
+            let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+            let jobs = stmt
+                .query_map([limit], job_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(jobs)
+        })
+    }
+}
+
+fn get_job(conn: &rusqlite::Connection, id: i64) -> anyhow::Result<Option<StoredJob>> {
+    conn.query_row(
+        &format!("SELECT {JOB_COLUMNS} FROM jobs WHERE id = ?1"),
+        [id],
+        job_from_row,
+    )
+    .optional()
+    .map_err(anyhow::Error::from)
+}
+
+/// Repository of a PR or merge request URL, e.g. `https://github.com/o/r`
+/// for `https://github.com/o/r/pull/1`.
+fn repository_of(pr_url: &str) -> &str {
+    pr_url
+        .rsplitn(3, '/')
+        .nth(2)
+        .map_or(pr_url, |repository| repository.trim_end_matches("/-"))
+}
+
+fn job_from_row(row: &Row<'_>) -> rusqlite::Result<StoredJob> {
+    let kind: String = row.get(1)?;
+    let status: String = row.get(4)?;
+    Ok(StoredJob {
+        id: row.get(0)?,
+        kind: JobKind::parse(&kind).ok_or_else(|| {
+            rusqlite::Error::FromSqlConversionFailure(
+                1,
+                Type::Text,
+                format!("unknown job kind '{kind}'").into(),
+            )
+        })?,
+        pr_url: row.get(2)?,
+        head_sha: row.get(3)?,
+        status: JobStatus::parse(&status).ok_or_else(|| {
+            rusqlite::Error::FromSqlConversionFailure(
+                4,
+                Type::Text,
+                format!("unknown job status '{status}'").into(),
+            )
+        })?,
+        attempts: row.get(5)?,
+        max_attempts: row.get(6)?,
+        run_after: row.get(7)?,
+        last_error: row.get(8)?,
+        created_at: row.get(9)?,
+        started_at: row.get(10)?,
+        finished_at: row.get(11)?,
+    })
+}
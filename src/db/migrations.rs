@@ -1,8 +1,181 @@
-use rusqlite::Connection;
+use anyhow::Context as _;
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+
+use super::util::unix_ts;
+
+/// A step of the schema. The version of a DB is the number of migrations
+/// applied to it, recorded in `schema_version`.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Migration {
+    pub(super) description: &'static str,
+    pub(super) apply: fn(&Connection) -> anyhow::Result<()>,
+}
+
+/// Applied in order, each in its own transaction. Append new migrations:
+/// editing one breaks the DBs it was already applied to.
+///
+/// The migrations up to `pr_participants.last_activity_at` were applied by
+/// gigi before `schema_version` existed: a DB of that time is at version 0,
+/// whatever its layout, and goes through all of them. They must stay
+/// idempotent.
+pub(super) const MIGRATIONS: &[Migration] = &[
+    Migration {
+        description: "threads, prs, reviews, fix runs, sync events and dashboard tables",
+        apply: initial_tables,
+    },
+    Migration {
+        description: "threads.issue_state",
+        apply: |conn| add_column_if_missing(conn, "threads", "issue_state", "TEXT"),
+    },
+    Migration {
+        description: "threads.discussion_answered",
+        apply: |conn| add_column_if_missing(conn, "threads", "discussion_answered", "INTEGER"),
+    },
+    Migration {
+        description: "threads.is_draft",
+        apply: |conn| {
+            add_column_if_missing(conn, "threads", "is_draft", "INTEGER NOT NULL DEFAULT 0")
+        },
+    },
+    Migration {
+        description: "prs.merge_queue_state",
+        apply: |conn| add_column_if_missing(conn, "prs", "merge_queue_state", "TEXT"),
+    },
+    Migration {
+        description: "prs.is_archived",
+        apply: |conn| {
+            add_column_if_missing(conn, "prs", "is_archived", "INTEGER NOT NULL DEFAULT 0")
+        },
+    },
+    Migration {
+        description: "prs.is_draft",
+        apply: |conn| add_column_if_missing(conn, "prs", "is_draft", "INTEGER NOT NULL DEFAULT 0"),
+    },
+    Migration {
+        description: "dashboard filters of my PRs, assigned PRs and assigned issues",
+        apply: source_filters,
+    },
+    Migration {
+        description: "dashboard_preferences.group_by_repository",
+        apply: |conn| {
+            add_column_if_missing(
+                conn,
+                "dashboard_preferences",
+                "group_by_repository",
+                "INTEGER NOT NULL DEFAULT 1",
+            )
+        },
+    },
+    Migration {
+        description: "pr_participants.last_activity_at",
+        apply: |conn| add_column_if_missing(conn, "pr_participants", "last_activity_at", "TEXT"),
+    },
+    Migration {
+        description: "review findings, verdicts, guidelines and re-reviews",
+        apply: structured_reviews,
+    },
+    Migration {
+        description: "jobs",
+        apply: jobs,
+    },
+    Migration {
+        description: "pr_checks and the failing CI filter",
+        apply: pr_checks,
+    },
+    Migration {
+        description: "dashboard filters of review requests and mentions",
+        apply: |conn| {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE dashboard_preferences
+                    ADD COLUMN show_review_requested INTEGER NOT NULL DEFAULT 1;
+                ALTER TABLE dashboard_preferences
+                    ADD COLUMN show_mentions INTEGER NOT NULL DEFAULT 1;
+                "#,
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        description: "prs.auto_merge and prs.attention",
+        apply: |conn| {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE prs ADD COLUMN auto_merge INTEGER NOT NULL DEFAULT 0;
+                ALTER TABLE prs ADD COLUMN attention TEXT;
+                "#,
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        description: "full-text search of reviews, thread titles and fix runs",
+        apply: search_index,
+    },
+];
 
 pub(super) fn run_migrations(conn: &Connection) -> anyhow::Result<()> {
     // Enable WAL mode so the axum handlers and the poll task can access the DB concurrently.
     conn.execute_batch("PRAGMA journal_mode=WAL;")?;
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at INTEGER NOT NULL
+        );
+        "#,
+    )?;
+    apply_migrations(conn, MIGRATIONS)
+}
+
+/// Apply the `migrations` the DB is missing. A failing migration is rolled
+/// back and leaves the DB at the previous version.
+pub(super) fn apply_migrations(conn: &Connection, migrations: &[Migration]) -> anyhow::Result<()> {
+    let current = schema_version(conn)?;
+    let latest = migrations.len();
+    anyhow::ensure!(
+        current <= latest,
+        "❌ The DB is at schema version {current}, written by a newer gigi \
+         (this one reads up to version {latest}): upgrade gigi to open it"
+    );
+
+    for (idx, migration) in migrations.iter().enumerate().skip(current) {
+        let version = idx + 1;
+        // Immediate, so that two gigi processes starting together don't both
+        // apply the migration.
+        let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+        if schema_version(&tx)? >= version {
+            continue;
+        }
+        (migration.apply)(&tx).with_context(|| {
+            format!(
+                "❌ Failed to migrate the DB to version {version}: {}",
+                migration.description
+            )
+        })?;
+        tx.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, ?2, ?3)",
+            rusqlite::params![i64::try_from(version)?, migration.description, unix_ts()],
+        )?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+/// Latest applied migration, 0 for a new DB or one from before
+/// `schema_version`.
+pub(super) fn schema_version(conn: &Connection) -> anyhow::Result<usize> {
+    let version: Option<i64> =
+        conn.query_row("SELECT MAX(version) FROM schema_version", [], |row| {
+            row.get(0)
+        })?;
+    Ok(usize::try_from(version.unwrap_or(0))?)
+}
+
+/// The oldest layout. Later columns are added by the next migrations.
+fn initial_tables(conn: &Connection) -> anyhow::Result<()> {
     conn.execute_batch(
         r#"
         CREATE TABLE IF NOT EXISTS threads (
@@ -13,8 +186,6 @@ pub(super) fn run_migrations(conn: &Connection) -> anyhow::Result<()> {
             subject_type TEXT,
             subject_title TEXT NOT NULL,
             subject_url TEXT,
-            issue_state TEXT,
-            discussion_answered INTEGER,
             reason TEXT,
             pr_url TEXT,
             unread INTEGER NOT NULL,
@@ -36,13 +207,11 @@ pub(super) fn run_migrations(conn: &Connection) -> anyhow::Result<()> {
             repo TEXT NOT NULL,
             number INTEGER NOT NULL,
             state TEXT NOT NULL,
-            merge_queue_state TEXT,
             title TEXT NOT NULL,
             head_ref TEXT NOT NULL,
             base_ref TEXT NOT NULL,
             head_sha TEXT NOT NULL,
             updated_at TEXT NOT NULL,
-            is_archived INTEGER NOT NULL DEFAULT 0,
             last_seen_at INTEGER NOT NULL,
             last_reviewed_sha TEXT,
             last_reviewed_updated_at TEXT
@@ -83,12 +252,8 @@ pub(super) fn run_migrations(conn: &Connection) -> anyhow::Result<()> {
         CREATE TABLE IF NOT EXISTS dashboard_preferences (
             id INTEGER PRIMARY KEY CHECK (id = 1),
             show_notifications INTEGER NOT NULL,
-            show_my_prs INTEGER NOT NULL DEFAULT 1,
-            show_assigned_prs INTEGER NOT NULL DEFAULT 1,
-            show_assigned_issues INTEGER NOT NULL DEFAULT 1,
             show_done INTEGER NOT NULL,
             show_not_done INTEGER NOT NULL,
-            group_by_repository INTEGER NOT NULL DEFAULT 1,
             updated_at INTEGER NOT NULL
         );
 
@@ -105,49 +270,170 @@ pub(super) fn run_migrations(conn: &Connection) -> anyhow::Result<()> {
         );
 
         CREATE INDEX IF NOT EXISTS idx_pr_participants_pr_url ON pr_participants(pr_url);
+
+        CREATE TABLE IF NOT EXISTS repository_filter (
+            repository TEXT PRIMARY KEY
+        );
         "#,
     )?;
+    Ok(())
+}
 
-    add_column_if_missing(conn, "prs", "is_archived", "INTEGER NOT NULL DEFAULT 0")?;
-    add_column_if_missing(conn, "prs", "is_draft", "INTEGER NOT NULL DEFAULT 0")?;
-    add_column_if_missing(conn, "prs", "merge_queue_state", "TEXT")?;
-    add_column_if_missing(conn, "threads", "issue_state", "TEXT")?;
-    add_column_if_missing(conn, "threads", "discussion_answered", "INTEGER")?;
-    add_column_if_missing(conn, "threads", "is_draft", "INTEGER NOT NULL DEFAULT 0")?;
-    add_column_if_missing(
-        conn,
-        "dashboard_preferences",
-        "group_by_repository",
-        "INTEGER NOT NULL DEFAULT 1",
-    )?;
-    add_column_if_missing(
-        conn,
-        "dashboard_preferences",
-        "show_my_prs",
-        "INTEGER NOT NULL DEFAULT 1",
+fn source_filters(conn: &Connection) -> anyhow::Result<()> {
+    for column in ["show_my_prs", "show_assigned_prs", "show_assigned_issues"] {
+        add_column_if_missing(
+            conn,
+            "dashboard_preferences",
+            column,
+            "INTEGER NOT NULL DEFAULT 1",
+        )?;
+    }
+    Ok(())
+}
+
+/// Findings of structured reviews, what the reviews were published and
+/// computed with, and the severity filter of the dashboard.
+fn structured_reviews(conn: &Connection) -> anyhow::Result<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE review_findings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            review_id INTEGER NOT NULL REFERENCES reviews(id) ON DELETE CASCADE,
+            file TEXT NOT NULL,
+            start_line INTEGER NOT NULL,
+            end_line INTEGER NOT NULL,
+            severity TEXT NOT NULL,
+            category TEXT NOT NULL,
+            suggestion TEXT NOT NULL,
+            status TEXT
+        );
+
+        CREATE INDEX idx_review_findings_review_id ON review_findings(review_id);
+
+        ALTER TABLE reviews ADD COLUMN summary TEXT;
+        ALTER TABLE reviews ADD COLUMN verdict TEXT;
+        ALTER TABLE reviews ADD COLUMN guidelines_source TEXT;
+        ALTER TABLE reviews
+            ADD COLUMN previous_review_id INTEGER REFERENCES reviews(id) ON DELETE SET NULL;
+        ALTER TABLE reviews ADD COLUMN head_sha TEXT;
+        ALTER TABLE reviews ADD COLUMN base_sha TEXT;
+
+        ALTER TABLE dashboard_preferences ADD COLUMN min_finding_severity TEXT;
+        "#,
     )?;
-    add_column_if_missing(
-        conn,
-        "dashboard_preferences",
-        "show_assigned_prs",
-        "INTEGER NOT NULL DEFAULT 1",
+    Ok(())
+}
+
+fn jobs(conn: &Connection) -> anyhow::Result<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE jobs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL,
+            pr_url TEXT NOT NULL,
+            head_sha TEXT,
+            status TEXT NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            max_attempts INTEGER NOT NULL,
+            run_after INTEGER NOT NULL,
+            last_error TEXT,
+            created_at INTEGER NOT NULL,
+            started_at INTEGER,
+            finished_at INTEGER
+        );
+
+        CREATE INDEX idx_jobs_status_run_after ON jobs(status, run_after);
+        "#,
     )?;
-    add_column_if_missing(
-        conn,
-        "dashboard_preferences",
-        "show_assigned_issues",
-        "INTEGER NOT NULL DEFAULT 1",
+    Ok(())
+}
+
+fn pr_checks(conn: &Connection) -> anyhow::Result<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE pr_checks (
+            pr_url TEXT NOT NULL,
+            name TEXT NOT NULL,
+            conclusion TEXT NOT NULL,
+            outcome TEXT NOT NULL,
+            url TEXT,
+            PRIMARY KEY (pr_url, name)
+        );
+
+        ALTER TABLE dashboard_preferences
+            ADD COLUMN failing_ci_only INTEGER NOT NULL DEFAULT 0;
+        "#,
     )?;
-    add_column_if_missing(conn, "pr_participants", "last_activity_at", "TEXT")?;
+    Ok(())
+}
 
+/// FTS5 indexes over the text of their table (external content), kept in
+/// sync by triggers. `threads` has no integer primary key, so `VACUUM`
+/// renumbers its rowids: [`rebuild_search_index`] has to run after it.
+fn search_index(conn: &Connection) -> anyhow::Result<()> {
     conn.execute_batch(
         r#"
-        CREATE TABLE IF NOT EXISTS repository_filter (
-            repository TEXT PRIMARY KEY
+        CREATE VIRTUAL TABLE reviews_fts USING fts5(
+            content_md, content = 'reviews', content_rowid = 'id', tokenize = 'porter unicode61'
+        );
+        CREATE TRIGGER reviews_fts_insert AFTER INSERT ON reviews BEGIN
+            INSERT INTO reviews_fts (rowid, content_md) VALUES (new.id, new.content_md);
+        END;
+        CREATE TRIGGER reviews_fts_delete AFTER DELETE ON reviews BEGIN
+            INSERT INTO reviews_fts (reviews_fts, rowid, content_md)
+            VALUES ('delete', old.id, old.content_md);
+        END;
+        CREATE TRIGGER reviews_fts_update AFTER UPDATE OF content_md ON reviews BEGIN
+            INSERT INTO reviews_fts (reviews_fts, rowid, content_md)
+            VALUES ('delete', old.id, old.content_md);
+            INSERT INTO reviews_fts (rowid, content_md) VALUES (new.id, new.content_md);
+        END;
+
+        CREATE VIRTUAL TABLE threads_fts USING fts5(
+            subject_title, content = 'threads', content_rowid = 'rowid', tokenize = 'porter unicode61'
+        );
+        CREATE TRIGGER threads_fts_insert AFTER INSERT ON threads BEGIN
+            INSERT INTO threads_fts (rowid, subject_title) VALUES (new.rowid, new.subject_title);
+        END;
+        CREATE TRIGGER threads_fts_delete AFTER DELETE ON threads BEGIN
+            INSERT INTO threads_fts (threads_fts, rowid, subject_title)
+            VALUES ('delete', old.rowid, old.subject_title);
+        END;
+        CREATE TRIGGER threads_fts_update AFTER UPDATE OF subject_title ON threads BEGIN
+            INSERT INTO threads_fts (threads_fts, rowid, subject_title)
+            VALUES ('delete', old.rowid, old.subject_title);
+            INSERT INTO threads_fts (rowid, subject_title) VALUES (new.rowid, new.subject_title);
+        END;
+
+        CREATE VIRTUAL TABLE fix_runs_fts USING fts5(
+            output, content = 'fix_runs', content_rowid = 'id', tokenize = 'porter unicode61'
         );
+        CREATE TRIGGER fix_runs_fts_insert AFTER INSERT ON fix_runs BEGIN
+            INSERT INTO fix_runs_fts (rowid, output) VALUES (new.id, new.output);
+        END;
+        CREATE TRIGGER fix_runs_fts_delete AFTER DELETE ON fix_runs BEGIN
+            INSERT INTO fix_runs_fts (fix_runs_fts, rowid, output)
+            VALUES ('delete', old.id, old.output);
+        END;
+        CREATE TRIGGER fix_runs_fts_update AFTER UPDATE OF output ON fix_runs BEGIN
+            INSERT INTO fix_runs_fts (fix_runs_fts, rowid, output)
+            VALUES ('delete', old.id, old.output);
+            INSERT INTO fix_runs_fts (rowid, output) VALUES (new.id, new.output);
+        END;
         "#,
     )?;
+    rebuild_search_index(conn)
+}
 
+/// Index the tables again from scratch.
+pub(super) fn rebuild_search_index(conn: &Connection) -> anyhow::Result<()> {
+    conn.execute_batch(
+        r#"
+        INSERT INTO reviews_fts (reviews_fts) VALUES ('rebuild');
+        INSERT INTO threads_fts (threads_fts) VALUES ('rebuild');
+        INSERT INTO fix_runs_fts (fix_runs_fts) VALUES ('rebuild');
+        "#,
+    )?;
     Ok(())
 }
 
@@ -156,17 +442,17 @@ fn add_column_if_missing(
     table: &str,
     column: &str,
     definition: &str,
-) -> anyhow::Result<bool> {
+) -> anyhow::Result<()> {
     let pragma = format!("PRAGMA table_info({table})");
     let mut stmt = conn.prepare(&pragma)?;
     let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
     for existing in columns {
         if existing? == column {
-            return Ok(false);
+            return Ok(());
         }
     }
 
     let alter = format!("ALTER TABLE {table} ADD COLUMN {column} {definition}");
     conn.execute(&alter, [])?;
-    Ok(true)
+    Ok(())
 }
@@ -1,7 +1,12 @@
 mod core;
 mod dashboard;
+mod history;
+mod jobs;
 mod migrations;
 mod models;
+mod retention;
+mod search;
+mod transfer;
 mod util;
 
 #[cfg(test)]
@@ -12,9 +17,14 @@ use std::path::{Path, PathBuf};
 use anyhow::Context as _;
 use rusqlite::Connection;
 
+pub use history::PrHistory;
 pub use models::{
-    DashboardThread, DashboardThreadFilters, NewPr, NewReview, NewThread, StoredPr, StoredReview,
+    DashboardThread, DashboardThreadFilters, JobKind, JobStatus, NewPr, NewReview, NewThread,
+    StoredFinding, StoredFixRun, StoredJob, StoredPr, StoredReview, StoredSyncEvent, source_kind,
 };
+pub use retention::PruneStats;
+pub use search::{SNIPPET_MATCH_END, SNIPPET_MATCH_START, SearchHit, SearchHitKind};
+pub use transfer::{DbExport, ExportRow};
 
 #[derive(Debug, Clone)]
 pub struct Db {
@@ -47,6 +57,8 @@ impl Db {
         let conn = Connection::open(&self.path)
             .with_context(|| format!("Failed to open sqlite DB at {}", self.path.display()))?;
         conn.execute("PRAGMA foreign_keys = ON", [])?;
+        // The job workers, the poll task and the axum handlers write concurrently.
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
         f(&conn)
     }
 }
@@ -1,4 +1,7 @@
-use crate::github::Participant;
+use crate::{
+    github::{CheckOutcome, Participant, PrCheck},
+    review::{ReviewFinding, Severity, SeverityCounts, Verdict},
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone)]
@@ -29,6 +32,7 @@ pub struct NewPr {
     pub number: i64,
     pub state: String,
     pub merge_queue_state: Option<String>,
+    pub auto_merge: bool,
     pub title: String,
     pub head_ref: String,
     pub base_ref: String,
@@ -39,7 +43,7 @@ pub struct NewPr {
 }
 
 /// PR row as read from the DB. Extends [`NewPr`] with DB-managed fields
-/// (`last_reviewed_sha`, `last_reviewed_updated_at`).
+/// (`last_reviewed_sha`, `last_reviewed_updated_at`, `attention`).
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct StoredPr {
@@ -49,6 +53,7 @@ pub struct StoredPr {
     pub number: i64,
     pub state: String,
     pub merge_queue_state: Option<String>,
+    pub auto_merge: bool,
     pub title: String,
     pub head_ref: String,
     pub base_ref: String,
@@ -57,6 +62,8 @@ pub struct StoredPr {
     pub is_archived: bool,
     pub last_reviewed_sha: Option<String>,
     pub last_reviewed_updated_at: Option<String>,
+    /// Why the PR needs attention, e.g. it left the merge queue.
+    pub attention: Option<String>,
 }
 
 /// Review row as read from the DB. Extends [`NewReview`] with DB-managed fields
@@ -70,6 +77,13 @@ pub struct StoredReview {
     pub requires_code_changes: bool,
     pub content_md: String,
     pub created_at: i64,
+    /// `None` for reviews stored before structured output.
+    pub summary: Option<String>,
+    pub verdict: Option<Verdict>,
+    pub guidelines_source: Option<String>,
+    pub previous_review_id: Option<i64>,
+    pub head_sha: Option<String>,
+    pub base_sha: Option<String>,
 }
 
 /// Data required to insert a new review row. Does not include DB-managed fields.
@@ -80,6 +94,130 @@ pub struct NewReview {
     pub model: Option<String>,
     pub requires_code_changes: bool,
     pub content_md: String,
+    pub summary: Option<String>,
+    pub verdict: Option<Verdict>,
+    /// Repository guidelines the review followed, e.g. `.gigi/review.md`.
+    pub guidelines_source: Option<String>,
+    /// Review a re-review builds on.
+    pub previous_review_id: Option<i64>,
+    /// PR head the review was computed against.
+    pub head_sha: Option<String>,
+    /// Start of the reviewed diff, for re-reviews of the new commits only.
+    pub base_sha: Option<String>,
+    /// Stored in `review_findings`.
+    pub findings: Vec<ReviewFinding>,
+}
+
+/// Finding row as read from the DB, with the PR it belongs to.
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredFinding {
+    pub id: i64,
+    pub review_id: i64,
+    pub pr_url: String,
+    pub finding: ReviewFinding,
+}
+
+/// Fix run row as read from the DB.
+#[derive(Debug, Clone)]
+pub struct StoredFixRun {
+    pub provider: String,
+    pub status: String,
+    pub output: String,
+    pub created_at: i64,
+}
+
+/// Sync event row as read from the DB.
+#[derive(Debug, Clone)]
+pub struct StoredSyncEvent {
+    pub status: String,
+    pub message: String,
+    pub created_at: i64,
+}
+
+/// Work run in the background by the `serve` job workers.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Review the PR with the configured agent.
+    Review,
+    /// Apply the latest review of the PR with the configured agent.
+    Fix,
+}
+
+impl JobKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Review => "review",
+            Self::Fix => "fix",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        [Self::Review, Self::Fix]
+            .into_iter()
+            .find(|kind| kind.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// Waiting for a worker, possibly until `run_after` for retries.
+    Queued,
+    Running,
+    Succeeded,
+    /// Failed `max_attempts` times, or retried manually from the dashboard.
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        [
+            Self::Queued,
+            Self::Running,
+            Self::Succeeded,
+            Self::Failed,
+            Self::Cancelled,
+        ]
+        .into_iter()
+        .find(|status| status.as_str() == value)
+    }
+
+    /// Queued or running jobs, which can be cancelled.
+    pub fn is_active(self) -> bool {
+        matches!(self, Self::Queued | Self::Running)
+    }
+}
+
+/// Job row as read from the DB.
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredJob {
+    pub id: i64,
+    pub kind: JobKind,
+    pub pr_url: String,
+    /// PR head that triggered an automatic review. Manual jobs have none.
+    pub head_sha: Option<String>,
+    pub status: JobStatus,
+    /// Attempts started so far, including the running one.
+    pub attempts: u32,
+    pub max_attempts: u32,
+    /// Unix timestamp before which a queued job is not picked up.
+    pub run_after: i64,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    pub started_at: Option<i64>,
+    pub finished_at: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -104,13 +242,47 @@ pub struct DashboardThread {
     pub latest_requires_code_changes: Option<bool>,
     pub pr_state: Option<String>,
     pub pr_merge_queue_state: Option<String>,
+    pub latest_review_id: Option<i64>,
     pub latest_review_content_md: Option<String>,
     pub latest_review_created_at: Option<i64>,
     pub latest_review_provider: Option<String>,
+    pub latest_review_findings: SeverityCounts,
+    /// Guidelines file the latest review followed.
+    pub latest_review_guidelines_source: Option<String>,
     pub is_draft: bool,
     /// Participants who interacted with this PR (not persisted, populated at runtime).
     #[serde(skip_serializing)]
     pub participants: Vec<Participant>,
+    /// Findings of the latest review (not persisted, populated at runtime).
+    #[serde(skip_serializing)]
+    pub findings: Vec<StoredFinding>,
+    /// CI of the head commit of the PR, `None` without checks.
+    #[serde(skip_serializing)]
+    pub ci_status: Option<CheckOutcome>,
+    /// Failing checks of the PR (not persisted, populated at runtime).
+    #[serde(skip_serializing)]
+    pub failing_checks: Vec<PrCheck>,
+    /// Why the PR needs attention, e.g. it left the merge queue.
+    pub attention: Option<String>,
+}
+
+impl DashboardThread {
+    /// Forge of the thread, `None` for GitHub.
+    pub fn forge(&self) -> Option<&str> {
+        self.sources.iter().find_map(|source| source_forge(source))
+    }
+}
+
+/// Forge namespace of a `threads.source`, e.g. `forgejo` for
+/// `forgejo:my_pr`. GitHub sources have none.
+fn source_forge(source: &str) -> Option<&str> {
+    source.split_once(':').map(|(forge, _)| forge)
+}
+
+/// Kind of a `threads.source` whatever its forge, e.g. `my_pr` for
+/// `forgejo:my_pr`.
+pub fn source_kind(source: &str) -> &str {
+    source.split_once(':').map_or(source, |(_, kind)| kind)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -119,10 +291,18 @@ pub struct DashboardThreadFilters {
     pub show_my_prs: bool,
     pub show_assigned_prs: bool,
     pub show_assigned_issues: bool,
+    /// PRs waiting for your review, or for the review of one of your teams.
+    pub show_review_requested: bool,
+    /// Issues and PRs mentioning you, or one of your teams.
+    pub show_mentions: bool,
     pub show_done: bool,
     pub show_not_done: bool,
     pub group_by_repository: bool,
     pub hidden_repositories: Vec<String>,
+    /// Only show threads whose latest review has a finding at or above this.
+    pub min_finding_severity: Option<Severity>,
+    /// Only show my PRs whose CI is failing.
+    pub failing_ci_only: bool,
 }
 
 impl Default for DashboardThreadFilters {
@@ -132,10 +312,14 @@ impl Default for DashboardThreadFilters {
             show_my_prs: true,
             show_assigned_prs: true,
             show_assigned_issues: true,
+            show_review_requested: true,
+            show_mentions: true,
             show_done: false,
             show_not_done: true,
             group_by_repository: true,
             hidden_repositories: Vec::new(),
+            min_finding_severity: None,
+            failing_ci_only: false,
         }
     }
 }
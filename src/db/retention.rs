@@ -0,0 +1,106 @@
+use rusqlite::{Transaction, TransactionBehavior, params};
+
+use crate::config::RetentionConfig;
+
+use super::{Db, migrations};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Rows deleted by [`Db::prune`], or that would be with `dry_run`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneStats {
+    pub reviews: usize,
+    pub fix_runs: usize,
+    pub sync_events: usize,
+    pub threads: usize,
+}
+
+impl PruneStats {
+    pub fn total(&self) -> usize {
+        self.reviews + self.fix_runs + self.sync_events + self.threads
+    }
+}
+
+impl Db {
+    /// Delete what `retention` no longer keeps, as of `now`. With `dry_run`
+    /// the deletes are rolled back, so only the counts are returned.
+    pub fn prune(
+        &self,
+        retention: &RetentionConfig,
+        now: i64,
+        dry_run: bool,
+    ) -> anyhow::Result<PruneStats> {
+        let cutoff = |days: u64| {
+            now.saturating_sub(
+                i64::try_from(days.saturating_mul(SECONDS_PER_DAY)).unwrap_or(i64::MAX),
+            )
+        };
+        self.with_conn(|conn| {
+            let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+            let mut stats = PruneStats::default();
+
+            if retention.reviews_per_pr > 0 {
+                // Findings go with their review; reviews building on a deleted
+                // one lose their `previous_review_id`.
+                stats.reviews = tx.execute(
+                    r#"
+                    DELETE FROM reviews WHERE id IN (
+                        SELECT id FROM (
+                            SELECT id, ROW_NUMBER() OVER (
+                                PARTITION BY pr_url ORDER BY created_at DESC, id DESC
+                            ) AS position
+                            FROM reviews
+                        )
+                        WHERE position > ?1
+                    )
+                    "#,
+                    params![i64::try_from(retention.reviews_per_pr)?],
+                )?;
+            }
+            if retention.fix_runs_days > 0 {
+                stats.fix_runs = tx.execute(
+                    "DELETE FROM fix_runs WHERE created_at < ?1",
+                    params![cutoff(retention.fix_runs_days)],
+                )?;
+            }
+            if retention.sync_events_days > 0 {
+                stats.sync_events = tx.execute(
+                    "DELETE FROM sync_events WHERE created_at < ?1",
+                    params![cutoff(retention.sync_events_days)],
+                )?;
+            }
+            if retention.closed_threads_days > 0 {
+                // Only threads the polls stopped returning: the others would
+                // come back on the next poll, as not done.
+                stats.threads = tx.execute(
+                    r#"
+                    DELETE FROM threads
+                    WHERE last_seen_at < ?1
+                      AND (
+                        done = 1
+                        OR COALESCE(issue_state, 'OPEN') != 'OPEN'
+                        OR pr_url IN (SELECT pr_url FROM prs WHERE state IN ('MERGED', 'CLOSED'))
+                      )
+                    "#,
+                    params![cutoff(retention.closed_threads_days)],
+                )?;
+            }
+
+            if dry_run {
+                tx.rollback()?;
+            } else {
+                tx.commit()?;
+            }
+            Ok(stats)
+        })
+    }
+
+    /// Give the space of deleted rows back to the file system.
+    pub fn vacuum(&self) -> anyhow::Result<()> {
+        self.with_conn(|conn| {
+            conn.execute_batch("VACUUM;")?;
+            // The rowids of `threads` may have changed.
+            migrations::rebuild_search_index(conn)
+        })
+    }
+}
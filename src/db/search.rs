@@ -0,0 +1,136 @@
+use std::collections::HashSet;
+
+use super::Db;
+use rusqlite::params;
+
+/// Wrap the matched terms of [`SearchHit::snippet`].
+pub const SNIPPET_MATCH_START: char = '\u{2}';
+pub const SNIPPET_MATCH_END: char = '\u{3}';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchHitKind {
+    Review,
+    Thread,
+    FixRun,
+}
+
+impl SearchHitKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Review => "Review",
+            Self::Thread => "Thread",
+            Self::FixRun => "Fix run",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    pub kind: SearchHitKind,
+    pub pr_url: Option<String>,
+    /// Issue or PR of the thread, the PR of reviews and fix runs.
+    pub url: Option<String>,
+    pub title: String,
+    /// UTC, RFC 3339.
+    pub date: String,
+    /// Text around the matches, which are wrapped in
+    /// [`SNIPPET_MATCH_START`] and [`SNIPPET_MATCH_END`].
+    pub snippet: String,
+}
+
+impl Db {
+    /// Reviews, thread titles and fix run outputs matching every word of
+    /// `query`, best matches first. A thread listed by several sources is
+    /// returned once.
+    pub fn search(&self, query: &str, limit: usize) -> anyhow::Result<Vec<SearchHit>> {
+        let Some(fts_query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+        let hits = self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT kind, pr_url, url, title, date, snippet FROM (
+                    SELECT
+                        'review' AS kind,
+                        r.pr_url AS pr_url,
+                        r.pr_url AS url,
+                        COALESCE(p.title, r.pr_url) AS title,
+                        strftime('%Y-%m-%dT%H:%M:%SZ', r.created_at, 'unixepoch') AS date,
+                        snippet(reviews_fts, 0, ?2, ?3, '…', 16) AS snippet,
+                        bm25(reviews_fts) AS rank
+                    FROM reviews_fts
+                    JOIN reviews r ON r.id = reviews_fts.rowid
+                    LEFT JOIN prs p ON p.pr_url = r.pr_url
+                    WHERE reviews_fts MATCH ?1
+                    UNION ALL
+                    SELECT
+                        'thread',
+                        t.pr_url,
+                        COALESCE(t.pr_url, t.subject_url),
+                        t.subject_title,
+                        t.updated_at,
+                        snippet(threads_fts, 0, ?2, ?3, '…', 16),
+                        bm25(threads_fts)
+                    FROM threads_fts
+                    JOIN threads t ON t.rowid = threads_fts.rowid
+                    WHERE threads_fts MATCH ?1
+                    UNION ALL
+                    SELECT
+                        'fix_run',
+                        f.pr_url,
+                        f.pr_url,
+                        COALESCE(p.title, f.pr_url),
+                        strftime('%Y-%m-%dT%H:%M:%SZ', f.created_at, 'unixepoch'),
+                        snippet(fix_runs_fts, 0, ?2, ?3, '…', 16),
+                        bm25(fix_runs_fts)
+                    FROM fix_runs_fts
+                    JOIN fix_runs f ON f.id = fix_runs_fts.rowid
+                    LEFT JOIN prs p ON p.pr_url = f.pr_url
+                    WHERE fix_runs_fts MATCH ?1
+                )
+                ORDER BY rank, date DESC
+                "#,
+            )?;
+            let rows = stmt.query_map(
+                params![
+                    fts_query,
+                    SNIPPET_MATCH_START.to_string(),
+                    SNIPPET_MATCH_END.to_string()
+                ],
+                |row| {
+                    let kind = match row.get::<_, String>(0)?.as_str() {
+                        "review" => SearchHitKind::Review,
+                        "thread" => SearchHitKind::Thread,
+                        _ => SearchHitKind::FixRun,
+                    };
+                    Ok(SearchHit {
+                        kind,
+                        pr_url: row.get(1)?,
+                        url: row.get(2)?,
+                        title: row.get(3)?,
+                        date: row.get(4)?,
+                        snippet: row.get(5)?,
+                    })
+                },
+            )?;
+            Ok(rows.collect::<Result<Vec<_>, _>>()?)
+        })?;
+
+        let mut seen_threads = HashSet::new();
+        Ok(hits
+            .into_iter()
+            .filter(|hit| hit.kind != SearchHitKind::Thread || seen_threads.insert(hit.url.clone()))
+            .take(limit)
+            .collect())
+    }
+}
+
+/// Quote each word, so that the FTS5 operators and punctuation of `query`
+/// are searched as text. `None` without words.
+fn fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
@@ -3,6 +3,8 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use rusqlite::params;
 
 use super::*;
+use crate::github::{CheckOutcome, PrCheck};
+use crate::review::{FindingStatus, PreviousReview, ReviewFinding, Severity, SeverityCounts};
 
 impl Db {
     pub fn latest_review_for_pr(
@@ -88,7 +90,7 @@ fn delete_threads_by_source_except_subject_urls_handles_large_keep_sets() {
     let keep_urls: Vec<String> = (0..1100)
         .map(|idx| format!("https://github.com/o/r/issues/{idx}"))
         .collect();
-    db.delete_threads_by_source_except_subject_urls("my_issue", &keep_urls)
+    db.delete_threads_by_source_except_subject_urls("my_issue", "https://github.com/", &keep_urls)
         .unwrap();
 
     let threads = db.list_dashboard_threads().unwrap();
@@ -107,6 +109,7 @@ fn latest_review_roundtrip() {
         number: 1,
         state: "OPEN".to_string(),
         merge_queue_state: None,
+        auto_merge: false,
         title: "Title".to_string(),
         head_ref: "feat".to_string(),
         base_ref: "main".to_string(),
@@ -123,6 +126,13 @@ fn latest_review_roundtrip() {
         model: None,
         requires_code_changes: true,
         content_md: "review".to_string(),
+        summary: None,
+        verdict: None,
+        guidelines_source: None,
+        previous_review_id: None,
+        head_sha: None,
+        base_sha: None,
+        findings: Vec::new(),
     })
     .unwrap();
 
@@ -142,6 +152,7 @@ fn insert_review_strips_control_sequences() {
         number: 1,
         state: "OPEN".to_string(),
         merge_queue_state: None,
+        auto_merge: false,
         title: "Title".to_string(),
         head_ref: "feat".to_string(),
         base_ref: "main".to_string(),
@@ -158,6 +169,13 @@ fn insert_review_strips_control_sequences() {
         model: None,
         requires_code_changes: false,
         content_md: "\u{1b}[38;5;141mSummary\u{1b}[0m".to_string(),
+        summary: None,
+        verdict: None,
+        guidelines_source: None,
+        previous_review_id: None,
+        head_sha: None,
+        base_sha: None,
+        findings: Vec::new(),
     })
     .unwrap();
 
@@ -183,6 +201,7 @@ fn db_init_cleans_existing_reviews() {
             number: 1,
             state: "OPEN".to_string(),
             merge_queue_state: None,
+            auto_merge: false,
             title: "Title".to_string(),
             head_ref: "feat".to_string(),
             base_ref: "main".to_string(),
@@ -225,6 +244,7 @@ fn insert_review_prefers_requires_code_changes_header() {
         number: 1,
         state: "OPEN".to_string(),
         merge_queue_state: None,
+        auto_merge: false,
         title: "Title".to_string(),
         head_ref: "feat".to_string(),
         base_ref: "main".to_string(),
@@ -241,6 +261,13 @@ fn insert_review_prefers_requires_code_changes_header() {
         model: None,
         requires_code_changes: true,
         content_md: "REQUIRES_CODE_CHANGES: NO\nSummary".to_string(),
+        summary: None,
+        verdict: None,
+        guidelines_source: None,
+        previous_review_id: None,
+        head_sha: None,
+        base_sha: None,
+        findings: Vec::new(),
     })
     .unwrap();
 
@@ -248,6 +275,114 @@ fn insert_review_prefers_requires_code_changes_header() {
     assert!(!review.requires_code_changes);
 }
 
+fn finding(file: &str, severity: Severity) -> ReviewFinding {
+    ReviewFinding {
+        file: file.to_string(),
+        start_line: 1,
+        end_line: 2,
+        severity,
+        category: "bug".to_string(),
+        suggestion: "Fix it".to_string(),
+        status: None,
+    }
+}
+
+#[test]
+fn review_findings_are_counted_and_filtered_on_latest_review() {
+    let db = test_db();
+    let pr_url = "https://github.com/a/b/pull/1";
+    db.upsert_thread(&NewThread {
+        is_draft: false,
+        thread_key: "my_pr:1".to_string(),
+        github_thread_id: None,
+        source: "my_pr".to_string(),
+        repository: "a/b".to_string(),
+        subject_type: Some("PullRequest".to_string()),
+        subject_title: "t".to_string(),
+        subject_url: Some(pr_url.to_string()),
+        issue_state: None,
+        discussion_answered: None,
+        reason: None,
+        pr_url: Some(pr_url.to_string()),
+        unread: false,
+        done: false,
+        updated_at: "2026-01-01T00:00:00Z".to_string(),
+    })
+    .unwrap();
+
+    let review = |findings| NewReview {
+        pr_url: pr_url.to_string(),
+        provider: "copilot".to_string(),
+        model: None,
+        requires_code_changes: true,
+        content_md: "REQUIRES_CODE_CHANGES: YES\nSummary".to_string(),
+        summary: None,
+        verdict: None,
+        guidelines_source: None,
+        previous_review_id: None,
+        head_sha: None,
+        base_sha: None,
+        findings,
+    };
+    db.insert_review(&review(vec![finding("old.rs", Severity::Critical)]))
+        .unwrap();
+    db.insert_review(&NewReview {
+        guidelines_source: Some(".gigi/review.md".to_string()),
+        ..review(vec![
+            finding("a.rs", Severity::Minor),
+            finding("b.rs", Severity::Major),
+            finding("c.rs", Severity::Minor),
+        ])
+    })
+    .unwrap();
+
+    let threads = db.list_dashboard_threads().unwrap();
+    assert_eq!(
+        threads[0].latest_review_findings,
+        SeverityCounts {
+            critical: 0,
+            major: 1,
+            minor: 2,
+            info: 0,
+        }
+    );
+    assert_eq!(
+        threads[0].latest_review_guidelines_source.as_deref(),
+        Some(".gigi/review.md")
+    );
+    let latest = db.latest_review_by_url(pr_url).unwrap().unwrap();
+    assert_eq!(latest.guidelines_source.as_deref(), Some(".gigi/review.md"));
+
+    let stored = db.latest_review_findings(Some(pr_url), None).unwrap();
+    let files: Vec<_> = stored
+        .iter()
+        .map(|item| item.finding.file.as_str())
+        .collect();
+    assert_eq!(files, ["b.rs", "a.rs", "c.rs"]);
+    assert_eq!(
+        db.latest_review_findings(None, Some(Severity::Major))
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let filters = |min| DashboardThreadFilters {
+        min_finding_severity: Some(min),
+        ..DashboardThreadFilters::default()
+    };
+    assert_eq!(
+        db.list_dashboard_threads_with_filters(&filters(Severity::Major))
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(
+        db.list_dashboard_threads_with_filters(&filters(Severity::Critical))
+            .unwrap()
+            .is_empty()
+    );
+}
+
 #[test]
 fn db_init_repairs_stale_requires_code_changes() {
     let mut path = std::env::temp_dir();
@@ -266,6 +401,7 @@ fn db_init_repairs_stale_requires_code_changes() {
             number: 1,
             state: "OPEN".to_string(),
             merge_queue_state: None,
+            auto_merge: false,
             title: "Title".to_string(),
             head_ref: "feat".to_string(),
             base_ref: "main".to_string(),
@@ -515,10 +651,14 @@ fn dashboard_can_show_done_items_when_requested() {
             show_my_prs: true,
             show_assigned_prs: true,
             show_assigned_issues: true,
+            show_review_requested: true,
+            show_mentions: true,
             show_done: true,
             show_not_done: false,
             group_by_repository: true,
             hidden_repositories: Vec::new(),
+            min_finding_severity: None,
+            failing_ci_only: false,
         })
         .unwrap();
 
@@ -616,10 +756,14 @@ fn dashboard_filters_by_source_type() {
             show_my_prs: true,
             show_assigned_prs: true,
             show_assigned_issues: true,
+            show_review_requested: true,
+            show_mentions: true,
             show_done: false,
             show_not_done: true,
             group_by_repository: true,
             hidden_repositories: Vec::new(),
+            min_finding_severity: None,
+            failing_ci_only: false,
         })
         .unwrap();
 
@@ -632,6 +776,132 @@ fn dashboard_filters_by_source_type() {
     assert_eq!(threads[2].subject_title, "authored");
 }
 
+#[test]
+fn dashboard_filters_forge_sources_by_kind() {
+    let db = test_db();
+    let pr_url = "https://codeberg.org/a/b/pulls/2".to_string();
+    db.upsert_thread(&NewThread {
+        is_draft: false,
+        thread_key: format!("forgejo:mypr:{pr_url}"),
+        github_thread_id: None,
+        source: "forgejo:my_pr".to_string(),
+        repository: "a/b".to_string(),
+        subject_type: Some("PullRequest".to_string()),
+        subject_title: "authored".to_string(),
+        subject_url: Some(pr_url.clone()),
+        issue_state: None,
+        discussion_answered: None,
+        reason: Some("authored".to_string()),
+        pr_url: Some(pr_url.clone()),
+        unread: false,
+        done: false,
+        updated_at: "2026-01-01T00:00:00Z".to_string(),
+    })
+    .unwrap();
+    let filters = |show_my_prs| DashboardThreadFilters {
+        show_my_prs,
+        ..DashboardThreadFilters::default()
+    };
+
+    let threads = db
+        .list_dashboard_threads_with_filters(&filters(true))
+        .unwrap();
+    assert_eq!(threads.len(), 1);
+    assert_eq!(threads[0].sources, vec!["forgejo:my_pr"]);
+    assert_eq!(threads[0].forge(), Some("forgejo"));
+    assert!(
+        db.list_dashboard_threads_with_filters(&filters(false))
+            .unwrap()
+            .is_empty()
+    );
+
+    db.mark_authored_pr_done_local(&pr_url).unwrap();
+    assert!(
+        db.list_dashboard_threads_with_filters(&filters(true))
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn dashboard_filters_my_prs_with_failing_ci() {
+    let db = test_db();
+    let check = |name: &str, conclusion: &str| PrCheck {
+        name: name.to_string(),
+        conclusion: conclusion.to_string(),
+        url: Some(format!("https://ci.example/{name}")),
+    };
+    for (number, source, checks) in [
+        (
+            1,
+            "my_pr",
+            vec![check("test", "FAILURE"), check("lint", "SUCCESS")],
+        ),
+        (2, "my_pr", vec![check("test", "IN_PROGRESS")]),
+        (3, "assigned_pr", vec![check("test", "FAILURE")]),
+        (4, "my_pr", Vec::new()),
+    ] {
+        let pr_url = format!("https://github.com/a/b/pull/{number}");
+        db.upsert_thread(&NewThread {
+            is_draft: false,
+            thread_key: format!("{source}:{pr_url}"),
+            github_thread_id: None,
+            source: source.to_string(),
+            repository: "a/b".to_string(),
+            subject_type: Some("PullRequest".to_string()),
+            subject_title: format!("pr {number}"),
+            subject_url: Some(pr_url.clone()),
+            issue_state: None,
+            discussion_answered: None,
+            reason: None,
+            pr_url: Some(pr_url.clone()),
+            unread: false,
+            done: false,
+            updated_at: format!("2026-01-0{number}T00:00:00Z"),
+        })
+        .unwrap();
+        db.replace_pr_checks(&pr_url, &checks).unwrap();
+    }
+
+    let statuses: Vec<_> = db
+        .list_dashboard_threads()
+        .unwrap()
+        .into_iter()
+        .map(|thread| (thread.subject_title, thread.ci_status))
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            ("pr 4".to_string(), None),
+            ("pr 3".to_string(), Some(CheckOutcome::Fail)),
+            ("pr 2".to_string(), Some(CheckOutcome::Pending)),
+            ("pr 1".to_string(), Some(CheckOutcome::Fail)),
+        ]
+    );
+
+    let filters = DashboardThreadFilters {
+        failing_ci_only: true,
+        ..DashboardThreadFilters::default()
+    };
+    let threads = db.list_dashboard_threads_with_filters(&filters).unwrap();
+    assert_eq!(threads.len(), 1);
+    assert_eq!(threads[0].subject_title, "pr 1");
+    assert_eq!(
+        db.failing_pr_checks("https://github.com/a/b/pull/1")
+            .unwrap(),
+        vec![check("test", "FAILURE")]
+    );
+
+    // The checks of a new head commit replace the previous ones.
+    db.replace_pr_checks("https://github.com/a/b/pull/1", &[check("test", "SUCCESS")])
+        .unwrap();
+    assert!(
+        db.list_dashboard_threads_with_filters(&filters)
+            .unwrap()
+            .is_empty()
+    );
+}
+
 #[test]
 fn dashboard_filter_preferences_default_when_unset() {
     let db = test_db();
@@ -650,10 +920,14 @@ fn dashboard_filter_preferences_roundtrip() {
         show_my_prs: true,
         show_assigned_prs: true,
         show_assigned_issues: false,
+        show_review_requested: false,
+        show_mentions: false,
         show_done: true,
         show_not_done: false,
         group_by_repository: false,
         hidden_repositories: vec!["a/b".to_string(), "c/d".to_string()],
+        min_finding_severity: Some(Severity::Major),
+        failing_ci_only: true,
     };
 
     db.set_dashboard_thread_filters(&filters).unwrap();
@@ -675,6 +949,7 @@ fn dashboard_threads_deduplicate_notification_and_my_pr() {
         number: 1,
         state: "MERGED".to_string(),
         merge_queue_state: None,
+        auto_merge: false,
         title: "Title".to_string(),
         head_ref: "feat".to_string(),
         base_ref: "main".to_string(),
@@ -734,6 +1009,78 @@ fn dashboard_threads_deduplicate_notification_and_my_pr() {
     assert!(threads[0].unread);
 }
 
+#[test]
+fn dashboard_threads_deduplicate_notification_and_mention() {
+    let db = test_db();
+    let issue_url = "https://github.com/a/b/issues/7";
+    let comment_url = format!("{issue_url}#issuecomment-42");
+
+    db.upsert_thread(&NewThread {
+        is_draft: false,
+        thread_key: "notif:123".to_string(),
+        github_thread_id: Some("123".to_string()),
+        source: "notification".to_string(),
+        repository: "a/b".to_string(),
+        subject_type: Some("Issue".to_string()),
+        subject_title: "Crash on start".to_string(),
+        subject_url: Some(issue_url.to_string()),
+        issue_state: Some("OPEN".to_string()),
+        discussion_answered: None,
+        reason: Some("mention".to_string()),
+        pr_url: None,
+        unread: true,
+        done: false,
+        updated_at: "2026-01-01T00:00:00Z".to_string(),
+    })
+    .unwrap();
+    db.upsert_thread(&NewThread {
+        is_draft: false,
+        thread_key: format!("mention:{issue_url}"),
+        github_thread_id: None,
+        source: "mention".to_string(),
+        repository: "a/b".to_string(),
+        subject_type: Some("Issue".to_string()),
+        subject_title: "Crash on start".to_string(),
+        subject_url: Some(comment_url.clone()),
+        issue_state: None,
+        discussion_answered: None,
+        reason: Some("mention".to_string()),
+        pr_url: None,
+        unread: false,
+        done: false,
+        updated_at: "2026-01-02T00:00:00Z".to_string(),
+    })
+    .unwrap();
+
+    let threads = db.list_dashboard_threads().unwrap();
+    assert_eq!(threads.len(), 1);
+    assert_eq!(threads[0].thread_key, "notif:123");
+    assert_eq!(threads[0].sources, vec!["notification", "mention"]);
+    assert_eq!(
+        threads[0].subject_url.as_deref(),
+        Some(comment_url.as_str())
+    );
+    assert_eq!(threads[0].issue_state.as_deref(), Some("OPEN"));
+    assert!(threads[0].unread);
+
+    let filters = DashboardThreadFilters {
+        show_notifications: false,
+        ..DashboardThreadFilters::default()
+    };
+    assert_eq!(
+        db.list_dashboard_threads_with_filters(&filters)
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(db.mark_mention_done_local(&comment_url).unwrap());
+    assert!(
+        db.list_dashboard_threads_with_filters(&filters)
+            .unwrap()
+            .is_empty()
+    );
+}
+
 #[test]
 fn dashboard_threads_deduplicate_notification_and_assigned_pr() {
     let db = test_db();
@@ -746,6 +1093,7 @@ fn dashboard_threads_deduplicate_notification_and_assigned_pr() {
         number: 1,
         state: "OPEN".to_string(),
         merge_queue_state: None,
+        auto_merge: false,
         title: "Title".to_string(),
         head_ref: "feat".to_string(),
         base_ref: "main".to_string(),
@@ -817,6 +1165,7 @@ fn archived_open_prs_are_hidden_from_dashboard() {
         number: 1,
         state: "OPEN".to_string(),
         merge_queue_state: Some("QUEUED".to_string()),
+        auto_merge: false,
         title: "Title".to_string(),
         head_ref: "feat".to_string(),
         base_ref: "main".to_string(),
@@ -862,6 +1211,7 @@ fn archived_closed_prs_remain_visible_on_dashboard() {
         number: 1,
         state: "MERGED".to_string(),
         merge_queue_state: None,
+        auto_merge: false,
         title: "Title".to_string(),
         head_ref: "feat".to_string(),
         base_ref: "main".to_string(),
@@ -908,6 +1258,7 @@ fn dashboard_threads_expose_merge_queue_state() {
         number: 1,
         state: "OPEN".to_string(),
         merge_queue_state: Some("QUEUED".to_string()),
+        auto_merge: false,
         title: "Title".to_string(),
         head_ref: "feat".to_string(),
         base_ref: "main".to_string(),
@@ -1129,10 +1480,14 @@ fn assigned_issue_cards_remain_visible_when_notifications_are_hidden() {
             show_my_prs: false,
             show_assigned_prs: false,
             show_assigned_issues: true,
+            show_review_requested: true,
+            show_mentions: true,
             show_done: false,
             show_not_done: true,
             group_by_repository: true,
             hidden_repositories: Vec::new(),
+            min_finding_severity: None,
+            failing_ci_only: false,
         })
         .unwrap();
 
@@ -1140,3 +1495,722 @@ fn assigned_issue_cards_remain_visible_when_notifications_are_hidden() {
     assert_eq!(threads[0].sources, vec!["my_issue"]);
     assert_eq!(threads[0].subject_title, "Assigned title");
 }
+
+#[test]
+fn rereviews_link_the_previous_review_and_skip_resolved_findings() {
+    let db = test_db();
+    let pr_url = "https://github.com/a/b/pull/1";
+    let review = |findings, previous_review_id| NewReview {
+        pr_url: pr_url.to_string(),
+        provider: "copilot".to_string(),
+        model: None,
+        requires_code_changes: true,
+        content_md: "REQUIRES_CODE_CHANGES: YES\nSummary".to_string(),
+        summary: Some("Summary".to_string()),
+        verdict: None,
+        guidelines_source: None,
+        previous_review_id,
+        head_sha: None,
+        base_sha: None,
+        findings,
+    };
+    let first_id = db
+        .insert_review(&review(vec![finding("a.rs", Severity::Major)], None))
+        .unwrap();
+
+    let with_status = |file, status| ReviewFinding {
+        status: Some(status),
+        ..finding(file, Severity::Major)
+    };
+    let second_id = db
+        .insert_review(&NewReview {
+            head_sha: Some("sha2".to_string()),
+            base_sha: Some("sha1".to_string()),
+            ..review(
+                vec![
+                    with_status("b.rs", FindingStatus::New),
+                    with_status("a.rs", FindingStatus::Resolved),
+                ],
+                Some(first_id),
+            )
+        })
+        .unwrap();
+
+    let latest = db.latest_review_by_url(pr_url).unwrap().unwrap();
+    assert_eq!(latest.id, second_id);
+    assert_eq!(latest.previous_review_id, Some(first_id));
+    assert_eq!(latest.base_sha.as_deref(), Some("sha1"));
+
+    let open = db.latest_review_findings(Some(pr_url), None).unwrap();
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0].finding.status, Some(FindingStatus::New));
+
+    let previous = PreviousReview::load(&db, pr_url, Some("fallback"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(previous.head_sha.as_deref(), Some("sha2"));
+    assert_eq!(previous.findings.len(), 1);
+    assert_eq!(previous.findings[0].file, "b.rs");
+}
+
+#[test]
+fn jobs_are_deduplicated_claimed_and_retried() {
+    let db = test_db();
+    let pr_url = "https://github.com/o/r/pull/1";
+
+    let review_id = db
+        .enqueue_job(JobKind::Review, pr_url, Some("sha1"), 2)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        db.enqueue_job(JobKind::Review, pr_url, None, 2).unwrap(),
+        Some(review_id)
+    );
+    let fix_id = db
+        .enqueue_job(JobKind::Fix, pr_url, None, 2)
+        .unwrap()
+        .unwrap();
+    // Read after enqueuing, so the jobs are due even across a second boundary.
+    let now = util::unix_ts();
+
+    let job = db.claim_next_job(now).unwrap().unwrap();
+    assert_eq!(
+        (job.id, job.status, job.attempts),
+        (review_id, JobStatus::Running, 1)
+    );
+    // The fix waits for the review of the same PR.
+    assert!(db.claim_next_job(now).unwrap().is_none());
+
+    db.fail_job(review_id, "boom", Some(now + 60)).unwrap();
+    let job = db.claim_next_job(now).unwrap().unwrap();
+    assert_eq!(job.id, fix_id);
+    db.complete_job(fix_id).unwrap();
+    assert!(db.claim_next_job(now).unwrap().is_none());
+
+    let job = db.claim_next_job(now + 60).unwrap().unwrap();
+    assert_eq!((job.id, job.attempts), (review_id, 2));
+    db.fail_job(review_id, "boom again", None).unwrap();
+    // A failed automatic review is not queued again for the same head.
+    assert_eq!(
+        db.enqueue_job(JobKind::Review, pr_url, Some("sha1"), 2)
+            .unwrap(),
+        None
+    );
+
+    db.retry_job(review_id).unwrap();
+    let jobs = db.list_jobs(10).unwrap();
+    assert_eq!(jobs[0].id, review_id);
+    assert_eq!(jobs[0].status, JobStatus::Queued);
+    assert_eq!(jobs[0].attempts, 0);
+    assert_eq!(jobs[0].last_error.as_deref(), Some("boom again"));
+    assert_eq!(jobs[1].status, JobStatus::Succeeded);
+    assert!(db.retry_job(fix_id).is_err());
+}
+
+#[test]
+fn jobs_of_a_repository_run_one_at_a_time() {
+    let db = test_db();
+    let enqueue = |pr_url: &str| {
+        db.enqueue_job(JobKind::Review, pr_url, None, 1)
+            .unwrap()
+            .unwrap()
+    };
+    let first = enqueue("https://github.com/o/r/pull/1");
+    let same_repo = enqueue("https://github.com/o/r/pull/2");
+    let other_repo = enqueue("https://github.com/o/other/pull/1");
+    let merge_request = enqueue("https://gitlab.com/g/r/-/merge_requests/1");
+    let same_project = enqueue("https://gitlab.com/g/r/-/merge_requests/2");
+    let now = util::unix_ts();
+    let claim = || db.claim_next_job(now).unwrap().map(|job| job.id);
+
+    // Both PRs of `o/r` would be checked out in the same clone.
+    assert_eq!(claim(), Some(first));
+    assert_eq!(claim(), Some(other_repo));
+    assert_eq!(claim(), Some(merge_request));
+    assert_eq!(claim(), None);
+
+    db.complete_job(first).unwrap();
+    db.complete_job(merge_request).unwrap();
+    assert_eq!(claim(), Some(same_repo));
+    assert_eq!(claim(), Some(same_project));
+}
+
+#[test]
+fn jobs_can_be_cancelled_and_survive_a_restart() {
+    let db = test_db();
+    let first = db
+        .enqueue_job(JobKind::Review, "https://github.com/o/r/pull/1", None, 3)
+        .unwrap()
+        .unwrap();
+    let second = db
+        .enqueue_job(JobKind::Review, "https://github.com/o/r/pull/2", None, 3)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        db.claim_next_job(util::unix_ts()).unwrap().unwrap().id,
+        first
+    );
+    assert_eq!(db.cancel_job(second).unwrap(), Some(JobStatus::Queued));
+    assert_eq!(db.cancel_job(second).unwrap(), None);
+
+    // `serve` stopped while the first job was running.
+    assert_eq!(db.requeue_interrupted_jobs().unwrap(), 1);
+    let job = db.claim_next_job(util::unix_ts()).unwrap().unwrap();
+    assert_eq!((job.id, job.attempts), (first, 1));
+    assert_eq!(db.cancel_job(first).unwrap(), Some(JobStatus::Running));
+    db.complete_job(first).unwrap();
+
+    let statuses: Vec<_> = db
+        .list_jobs(10)
+        .unwrap()
+        .into_iter()
+        .map(|job| job.status)
+        .collect();
+    assert_eq!(statuses, [JobStatus::Cancelled, JobStatus::Cancelled]);
+}
+
+/// Layouts of the DBs written by gigi before `schema_version`, oldest first.
+const UNVERSIONED_FIXTURES: &[(&str, &str)] = &[
+    ("00_initial", include_str!("fixtures/00_initial.sql")),
+    ("01_baseline", include_str!("fixtures/01_baseline.sql")),
+];
+
+fn test_db_path(name: &str) -> std::path::PathBuf {
+    let ts = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    std::env::temp_dir().join(format!("gigi-test-{name}-{ts}.sqlite"))
+}
+
+/// Sorted columns of each table.
+fn schema_columns(db: &Db) -> Vec<(String, Vec<String>)> {
+    db.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+        )?;
+        let tables = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut schema = Vec::new();
+        for table in tables {
+            let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+            let mut columns = stmt
+                .query_map([], |row| row.get::<_, String>(1))?
+                .collect::<Result<Vec<_>, _>>()?;
+            columns.sort();
+            schema.push((table, columns));
+        }
+        Ok(schema)
+    })
+    .unwrap()
+}
+
+#[test]
+fn unversioned_dbs_of_every_layout_upgrade_to_the_latest_schema() {
+    let latest = schema_columns(&test_db());
+    let pr_url = "https://github.com/a/b/pull/1";
+
+    for (name, fixture) in UNVERSIONED_FIXTURES {
+        let path = test_db_path(name);
+        {
+            let conn = rusqlite::Connection::open(&path).unwrap();
+            conn.execute_batch(fixture).unwrap();
+            conn.execute(
+                "INSERT INTO prs (pr_url, owner, repo, number, state, title, head_ref, base_ref, head_sha, updated_at, last_seen_at)
+                 VALUES (?1, 'a', 'b', 1, 'OPEN', 'Title', 'feat', 'main', 'sha1', '2026-01-01T00:00:00Z', 0)",
+                params![pr_url],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO reviews (pr_url, provider, model, requires_code_changes, content_md, created_at)
+                 VALUES (?1, 'copilot', NULL, 0, 'Looks good', 0)",
+                params![pr_url],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO dashboard_preferences (id, show_notifications, show_done, show_not_done, updated_at)
+                 VALUES (1, 0, 1, 1, 0)",
+                [],
+            )
+            .unwrap();
+        }
+
+        let db = Db::new(&path).unwrap();
+
+        assert_eq!(schema_columns(&db), latest, "{name}");
+        let version = db.with_conn(migrations::schema_version).unwrap();
+        assert_eq!(version, migrations::MIGRATIONS.len(), "{name}");
+        let pr = db.get_pr(pr_url).unwrap().unwrap();
+        assert_eq!(
+            (pr.title.as_str(), pr.auto_merge),
+            ("Title", false),
+            "{name}"
+        );
+        let review = db.latest_review_by_url(pr_url).unwrap().unwrap();
+        assert_eq!(review.content_md, "Looks good", "{name}");
+        let filters = db.dashboard_thread_filters().unwrap();
+        assert!(!filters.show_notifications, "{name}");
+        assert!(filters.show_mentions, "{name}");
+
+        // Opening it again applies nothing.
+        drop(db);
+        let db = Db::new(&path).unwrap();
+        let applied: i64 = db
+            .with_conn(|conn| {
+                Ok(conn.query_row("SELECT COUNT(*) FROM schema_version", [], |row| row.get(0))?)
+            })
+            .unwrap();
+        assert_eq!(
+            usize::try_from(applied).unwrap(),
+            migrations::MIGRATIONS.len()
+        );
+    }
+}
+
+#[test]
+fn migrations_from_before_schema_version_are_idempotent() {
+    let db = test_db();
+    let unversioned = migrations::MIGRATIONS
+        .iter()
+        .position(|migration| migration.description == "pr_participants.last_activity_at")
+        .unwrap()
+        + 1;
+    let before = schema_columns(&db);
+
+    db.with_conn(|conn| {
+        for migration in &migrations::MIGRATIONS[..unversioned] {
+            (migration.apply)(conn)?;
+        }
+        Ok(())
+    })
+    .unwrap();
+
+    assert_eq!(schema_columns(&db), before);
+}
+
+#[test]
+fn dbs_from_a_newer_gigi_are_refused() {
+    let path = test_db_path("newer");
+    let db = Db::new(&path).unwrap();
+    db.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, 'from the future', 0)",
+            params![i64::try_from(migrations::MIGRATIONS.len() + 1)?],
+        )?;
+        Ok(())
+    })
+    .unwrap();
+
+    let err = Db::new(&path).unwrap_err();
+    assert!(err.to_string().contains("newer gigi"), "{err}");
+}
+
+#[test]
+fn failing_migrations_are_rolled_back() {
+    fn create_notes(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+        conn.execute_batch("CREATE TABLE notes (body TEXT NOT NULL);")?;
+        Ok(())
+    }
+    fn add_note_authors(conn: &rusqlite::Connection) -> anyhow::Result<()> {
+        conn.execute_batch("ALTER TABLE notes ADD COLUMN author TEXT; SELECT * FROM missing;")?;
+        Ok(())
+    }
+    let mut schema = migrations::MIGRATIONS.to_vec();
+    schema.push(migrations::Migration {
+        description: "notes",
+        apply: create_notes,
+    });
+    schema.push(migrations::Migration {
+        description: "note authors",
+        apply: add_note_authors,
+    });
+
+    let db = test_db();
+    let err = db
+        .with_conn(|conn| migrations::apply_migrations(conn, &schema))
+        .unwrap_err();
+
+    assert!(format!("{err:#}").contains("note authors"), "{err:#}");
+    let (version, note_columns) = db
+        .with_conn(|conn| {
+            let note_columns: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('notes')",
+                [],
+                |row| row.get(0),
+            )?;
+            Ok((migrations::schema_version(conn)?, note_columns))
+        })
+        .unwrap();
+    assert_eq!(version, migrations::MIGRATIONS.len() + 1);
+    assert_eq!(note_columns, 1);
+}
+
+#[test]
+fn prune_applies_the_retention_and_dry_runs_change_nothing() {
+    let db = test_db();
+    let open_pr = "https://github.com/a/b/pull/1";
+    let merged_pr = "https://github.com/a/b/pull/2";
+    for (pr_url, state) in [(open_pr, "OPEN"), (merged_pr, "MERGED")] {
+        db.upsert_pr(&NewPr {
+            pr_url: pr_url.to_string(),
+            owner: "a".to_string(),
+            repo: "b".to_string(),
+            number: if pr_url == open_pr { 1 } else { 2 },
+            state: state.to_string(),
+            merge_queue_state: None,
+            auto_merge: false,
+            title: "Title".to_string(),
+            head_ref: "feat".to_string(),
+            base_ref: "main".to_string(),
+            head_sha: "sha1".to_string(),
+            updated_at: "2026-01-01T00:00:00Z".to_string(),
+            is_archived: false,
+            is_draft: false,
+        })
+        .unwrap();
+    }
+    let review_ids: Vec<_> = (1..=3)
+        .map(|idx| {
+            db.insert_review(&NewReview {
+                pr_url: open_pr.to_string(),
+                provider: "copilot".to_string(),
+                model: None,
+                requires_code_changes: true,
+                content_md: format!("review {idx}"),
+                summary: None,
+                verdict: None,
+                guidelines_source: None,
+                previous_review_id: None,
+                head_sha: None,
+                base_sha: None,
+                findings: vec![finding("src/lib.rs", Severity::Major)],
+            })
+            .unwrap()
+        })
+        .collect();
+    let threads = [
+        ("done-old", open_pr, true),
+        ("open-old", open_pr, false),
+        ("merged-old", merged_pr, false),
+        ("done-recent", open_pr, true),
+    ];
+    for (thread_key, pr_url, done) in threads {
+        db.upsert_thread(&NewThread {
+            thread_key: thread_key.to_string(),
+            github_thread_id: None,
+            source: "notification".to_string(),
+            repository: "a/b".to_string(),
+            subject_type: Some("PullRequest".to_string()),
+            subject_title: "Title".to_string(),
+            subject_url: Some(pr_url.to_string()),
+            issue_state: None,
+            discussion_answered: None,
+            reason: None,
+            pr_url: Some(pr_url.to_string()),
+            unread: false,
+            done,
+            updated_at: "2026-01-01T00:00:00Z".to_string(),
+            is_draft: false,
+        })
+        .unwrap();
+    }
+    let day = 24 * 60 * 60;
+    let now = 100 * day;
+    db.with_conn(|conn| {
+        conn.execute(
+            "UPDATE threads SET last_seen_at = ?1 WHERE thread_key != 'done-recent'",
+            params![now - 40 * day],
+        )?;
+        conn.execute("UPDATE threads SET last_seen_at = ?1 WHERE thread_key = 'done-recent'", params![now])?;
+        for created_at in [now - 100 * day, now - day] {
+            conn.execute(
+                "INSERT INTO fix_runs (pr_url, provider, status, output, created_at) VALUES (?1, 'copilot', 'ok', '', ?2)",
+                params![open_pr, created_at],
+            )?;
+            conn.execute(
+                "INSERT INTO sync_events (pr_url, status, message, created_at) VALUES (?1, 'ok', '', ?2)",
+                params![open_pr, created_at],
+            )?;
+        }
+        Ok(())
+    })
+    .unwrap();
+    let retention = crate::config::RetentionConfig {
+        reviews_per_pr: 2,
+        fix_runs_days: 90,
+        sync_events_days: 90,
+        closed_threads_days: 30,
+        ..crate::config::RetentionConfig::default()
+    };
+    // Nothing is pruned by default.
+    assert_eq!(
+        db.prune(&crate::config::RetentionConfig::default(), now, true)
+            .unwrap(),
+        PruneStats::default()
+    );
+    let expected = PruneStats {
+        reviews: 1,
+        fix_runs: 1,
+        sync_events: 1,
+        threads: 2,
+    };
+
+    assert_eq!(db.prune(&retention, now, true).unwrap(), expected);
+    assert_eq!(db.prune(&retention, now, true).unwrap(), expected);
+    assert_eq!(db.prune(&retention, now, false).unwrap(), expected);
+    assert_eq!(
+        db.prune(&retention, now, false).unwrap(),
+        PruneStats::default()
+    );
+
+    let latest = db.latest_review_by_url(open_pr).unwrap().unwrap();
+    assert_eq!(latest.id, review_ids[2]);
+    let remaining = db
+        .with_conn(|conn| {
+            let findings: i64 =
+                conn.query_row("SELECT COUNT(*) FROM review_findings", [], |row| row.get(0))?;
+            let mut stmt = conn.prepare("SELECT thread_key FROM threads ORDER BY thread_key")?;
+            let threads = stmt
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((findings, threads))
+        })
+        .unwrap();
+    assert_eq!(
+        remaining,
+        (2, vec!["done-recent".to_string(), "open-old".to_string()])
+    );
+}
+
+fn review_of(pr_url: &str, content_md: &str, previous_review_id: Option<i64>) -> NewReview {
+    NewReview {
+        pr_url: pr_url.to_string(),
+        provider: "copilot".to_string(),
+        model: None,
+        requires_code_changes: true,
+        content_md: content_md.to_string(),
+        summary: None,
+        verdict: None,
+        guidelines_source: None,
+        previous_review_id,
+        head_sha: None,
+        base_sha: None,
+        findings: vec![finding("src/lib.rs", Severity::Major)],
+    }
+}
+
+#[test]
+fn imports_merge_by_natural_keys() {
+    let pr_url = "https://github.com/a/b/pull/1";
+    let thread = |done, title: &str| NewThread {
+        thread_key: "notif:1".to_string(),
+        github_thread_id: Some("1".to_string()),
+        source: "notification".to_string(),
+        repository: "a/b".to_string(),
+        subject_type: Some("PullRequest".to_string()),
+        subject_title: title.to_string(),
+        subject_url: Some(pr_url.to_string()),
+        issue_state: None,
+        discussion_answered: None,
+        reason: None,
+        pr_url: Some(pr_url.to_string()),
+        unread: false,
+        done,
+        updated_at: "2026-01-01T00:00:00Z".to_string(),
+        is_draft: false,
+    };
+    let source = test_db();
+    source.upsert_thread(&thread(true, "exported")).unwrap();
+    let first = source
+        .insert_review(&review_of(pr_url, "first", None))
+        .unwrap();
+    source
+        .insert_review(&review_of(pr_url, "second", Some(first)))
+        .unwrap();
+    source
+        .insert_fix_run(pr_url, "copilot", "ok", "fixed")
+        .unwrap();
+    let export = source.export().unwrap();
+
+    // The target has a newer copy of the thread, not done, and its own
+    // review, taking the ids of the exported ones.
+    let target = test_db();
+    target
+        .insert_review(&review_of(pr_url, "local", None))
+        .unwrap();
+    target.upsert_thread(&thread(false, "local")).unwrap();
+    target
+        .with_conn(|conn| {
+            conn.execute("UPDATE threads SET last_seen_at = last_seen_at + 60", [])?;
+            Ok(())
+        })
+        .unwrap();
+
+    let stats = target.import(&export).unwrap();
+    let merged = |table: &str| stats.get(table).map(|imported| imported.merged);
+    assert_eq!(merged("threads"), Some(1));
+    assert_eq!(merged("reviews"), Some(2));
+    assert_eq!(merged("review_findings"), Some(2));
+    assert_eq!(merged("fix_runs"), Some(1));
+
+    let threads = target.threads_for_url(pr_url).unwrap();
+    assert_eq!(threads.len(), 1);
+    assert!(threads[0].done);
+    assert_eq!(threads[0].subject_title, "local");
+    let latest = target.latest_review_by_url(pr_url).unwrap().unwrap();
+    assert_eq!(latest.content_md, "second");
+    let previous = latest.previous_review_id.unwrap();
+    assert_ne!(previous, first);
+    let (content, findings): (String, i64) = target
+        .with_conn(|conn| {
+            Ok(conn.query_row(
+                "SELECT content_md, (SELECT COUNT(*) FROM review_findings WHERE review_id = ?1) FROM reviews WHERE id = ?1",
+                params![previous],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?)
+        })
+        .unwrap();
+    assert_eq!((content.as_str(), findings), ("first", 1));
+
+    // Importing it again adds nothing.
+    let again = target.import(&export).unwrap();
+    assert!(
+        again.values().all(|imported| imported.merged == 0),
+        "{again:?}"
+    );
+}
+
+#[test]
+fn backups_hold_the_same_data() {
+    let db = test_db();
+    let pr_url = "https://github.com/a/b/pull/1";
+    db.insert_review(&review_of(pr_url, "review", None))
+        .unwrap();
+    let destination = test_db_path("backup");
+
+    db.backup_to(&destination).unwrap();
+
+    let backup = Db::new(&destination).unwrap();
+    assert_eq!(backup.export().unwrap(), db.export().unwrap());
+    assert!(db.backup_to(&destination).is_err());
+}
+
+#[test]
+fn search_follows_reviews_threads_and_fix_runs() {
+    let db = test_db();
+    let pr_url = "https://github.com/a/b/pull/1";
+    let thread = |thread_key: &str, title: &str| NewThread {
+        thread_key: thread_key.to_string(),
+        github_thread_id: None,
+        source: "notification".to_string(),
+        repository: "a/b".to_string(),
+        subject_type: Some("PullRequest".to_string()),
+        subject_title: title.to_string(),
+        subject_url: Some(format!("https://github.com/a/b/pull/{thread_key}")),
+        issue_state: None,
+        discussion_answered: None,
+        reason: None,
+        pr_url: None,
+        unread: false,
+        done: false,
+        updated_at: "2026-01-01T00:00:00Z".to_string(),
+        is_draft: false,
+    };
+    let kinds = |query: &str| {
+        db.search(query, 10)
+            .unwrap()
+            .into_iter()
+            .map(|hit| hit.kind)
+            .collect::<Vec<_>>()
+    };
+    db.insert_review(&review_of(pr_url, "The parser panics on empty input", None))
+        .unwrap();
+    db.insert_fix_run(
+        pr_url,
+        "copilot",
+        "ok",
+        "Handled the empty input of the parser",
+    )
+    .unwrap();
+    db.upsert_thread(&thread("1", "Retry flaky uploads"))
+        .unwrap();
+    db.upsert_thread(&thread("2", "Parser rewrite")).unwrap();
+
+    let hits = db.search("parsers", 10).unwrap();
+    assert_eq!(hits.len(), 3, "{hits:?}");
+    assert!(
+        hits.iter()
+            .all(|hit| hit.snippet.contains(SNIPPET_MATCH_START))
+    );
+    let review = hits
+        .iter()
+        .find(|hit| hit.kind == SearchHitKind::Review)
+        .unwrap();
+    assert_eq!(review.pr_url.as_deref(), Some(pr_url));
+    assert!(review.date.ends_with('Z'));
+    assert_eq!(kinds("empty panics"), vec![SearchHitKind::Review]);
+    assert_eq!(kinds("\"flaky OR* ("), Vec::<SearchHitKind>::new());
+    assert!(kinds("   ").is_empty());
+
+    // The index follows updates and deletes, also after a vacuum
+    // renumbered the threads.
+    db.upsert_thread(&thread("2", "Lexer rewrite")).unwrap();
+    assert_eq!(kinds("lexer"), vec![SearchHitKind::Thread]);
+    db.with_conn(|conn| {
+        conn.execute("DELETE FROM threads WHERE thread_key = '1'", [])?;
+        conn.execute("DELETE FROM fix_runs", [])?;
+        Ok(())
+    })
+    .unwrap();
+    db.vacuum().unwrap();
+    assert_eq!(kinds("parser"), vec![SearchHitKind::Review]);
+    assert!(kinds("flaky").is_empty());
+    let hits = db.search("lexer", 10).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].title, "Lexer rewrite");
+}
+
+#[test]
+fn pr_history_lists_every_review_fix_run_and_sync_event() {
+    let db = test_db();
+    let pr_url = "https://github.com/a/b/pull/1";
+    let other_pr_url = "https://github.com/a/b/pull/2";
+    let first = db
+        .insert_review(&NewReview {
+            head_sha: Some("aaa".to_string()),
+            ..review_of(pr_url, "first", None)
+        })
+        .unwrap();
+    db.insert_review(&NewReview {
+        head_sha: Some("bbb".to_string()),
+        ..review_of(pr_url, "second", Some(first))
+    })
+    .unwrap();
+    db.insert_review(&review_of(other_pr_url, "other", None))
+        .unwrap();
+    db.insert_fix_run(pr_url, "copilot", "ok", "fixed").unwrap();
+    db.insert_sync_event(pr_url, "ok", "synced").unwrap();
+    db.insert_sync_event(other_pr_url, "ok", "other").unwrap();
+
+    let history = db.pr_history(pr_url).unwrap();
+    let reviews: Vec<_> = history
+        .reviews
+        .iter()
+        .map(|review| (review.content_md.as_str(), review.head_sha.as_deref()))
+        .collect();
+    assert_eq!(
+        reviews,
+        vec![("first", Some("aaa")), ("second", Some("bbb"))]
+    );
+    assert_eq!(history.fix_runs.len(), 1);
+    assert_eq!(history.fix_runs[0].output, "fixed");
+    assert_eq!(history.sync_events.len(), 1);
+    assert_eq!(history.sync_events[0].message, "synced");
+    assert!(
+        db.pr_history("https://github.com/a/b/pull/3")
+            .unwrap()
+            .is_empty()
+    );
+}
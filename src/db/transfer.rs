@@ -0,0 +1,381 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::Path,
+    time::Duration,
+};
+
+use anyhow::Context as _;
+use rusqlite::{
+    Connection, Transaction, TransactionBehavior,
+    backup::Backup,
+    params_from_iter,
+    types::{Value as SqlValue, ValueRef},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::{Db, migrations};
+
+/// Column name to value.
+pub type ExportRow = Map<String, Value>;
+
+/// Every table of the DB, as written by `gigi db export`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DbExport {
+    pub schema_version: usize,
+    pub tables: BTreeMap<String, Vec<ExportRow>>,
+}
+
+/// Rows of a table added or updated by [`Db::import`], and the ones already
+/// in the DB.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TableImport {
+    pub merged: usize,
+    pub skipped: usize,
+}
+
+/// How the rows of a table are matched with the ones already in the DB.
+enum Merge {
+    /// Keyed by `key`: the row seen last by a poll (`last_seen_at`) wins,
+    /// and a thread done on either side stays done.
+    LatestSeen { key: &'static str },
+    /// Rows without a natural key column, told apart by `natural_key`. New
+    /// rows get a new `id`.
+    Dedupe {
+        natural_key: &'static [&'static str],
+    },
+    /// Findings of the reviews added by the import; the reviews already in
+    /// the DB have theirs.
+    ReviewFindings,
+    /// Local settings and caches: only missing rows are added.
+    KeepLocal,
+    /// The job queue belongs to the `serve` that runs it.
+    Skip,
+}
+
+/// Tables in import order: reviews before their findings.
+const MERGES: &[(&str, Merge)] = &[
+    ("prs", Merge::LatestSeen { key: "pr_url" }),
+    ("threads", Merge::LatestSeen { key: "thread_key" }),
+    (
+        "reviews",
+        Merge::Dedupe {
+            natural_key: &["pr_url", "created_at", "content_md"],
+        },
+    ),
+    ("review_findings", Merge::ReviewFindings),
+    (
+        "fix_runs",
+        Merge::Dedupe {
+            natural_key: &["pr_url", "created_at", "output"],
+        },
+    ),
+    (
+        "sync_events",
+        Merge::Dedupe {
+            natural_key: &["pr_url", "created_at", "status", "message"],
+        },
+    ),
+    ("pr_participants", Merge::KeepLocal),
+    ("pr_checks", Merge::KeepLocal),
+    ("kv", Merge::KeepLocal),
+    ("dashboard_preferences", Merge::KeepLocal),
+    ("repository_filter", Merge::KeepLocal),
+    ("jobs", Merge::Skip),
+];
+
+impl Db {
+    /// Copy the DB to `destination` with the sqlite online backup, which
+    /// goes on while `serve` writes to it.
+    pub fn backup_to(&self, destination: &Path) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !destination.exists(),
+            "❌ {} already exists",
+            destination.display()
+        );
+        self.with_conn(|conn| {
+            let mut target = Connection::open(destination).with_context(|| {
+                format!(
+                    "❌ Failed to create the backup at {}",
+                    destination.display()
+                )
+            })?;
+            Backup::new(conn, &mut target)?.run_to_completion(
+                100,
+                Duration::from_millis(25),
+                None,
+            )?;
+            Ok(())
+        })
+    }
+
+    pub fn export(&self) -> anyhow::Result<DbExport> {
+        self.with_conn(|conn| {
+            let mut tables = BTreeMap::new();
+            for table in table_names(conn)? {
+                let mut stmt = conn.prepare(&format!("SELECT * FROM {table} ORDER BY rowid"))?;
+                let columns: Vec<String> =
+                    stmt.column_names().into_iter().map(String::from).collect();
+                let mut rows = stmt.query([])?;
+                let mut exported = Vec::new();
+                while let Some(row) = rows.next()? {
+                    let mut object = ExportRow::new();
+                    for (idx, column) in columns.iter().enumerate() {
+                        object.insert(column.clone(), json_value(row.get_ref(idx)?, &table)?);
+                    }
+                    exported.push(object);
+                }
+                tables.insert(table, exported);
+            }
+            Ok(DbExport {
+                schema_version: migrations::schema_version(conn)?,
+                tables,
+            })
+        })
+    }
+
+    /// Merge an export into the DB, matching rows by their natural keys, so
+    /// that importing the same export twice adds nothing. Either every table
+    /// is imported or none is.
+    pub fn import(&self, export: &DbExport) -> anyhow::Result<BTreeMap<String, TableImport>> {
+        self.with_conn(|conn| {
+            let schema_version = migrations::schema_version(conn)?;
+            anyhow::ensure!(
+                export.schema_version <= schema_version,
+                "❌ The export is at schema version {}, newer than the DB ({schema_version}): \
+                 upgrade gigi to import it",
+                export.schema_version
+            );
+            let known = table_names(conn)?;
+            if let Some(unknown) = export.tables.keys().find(|table| !known.contains(table)) {
+                anyhow::bail!("❌ Unknown table {unknown} in the export");
+            }
+
+            let mut importer = Importer {
+                tx: Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?,
+                review_ids: HashMap::new(),
+                new_review_ids: HashSet::new(),
+            };
+            let mut stats = BTreeMap::new();
+            for (table, merge) in MERGES {
+                let Some(rows) = export.tables.get(*table) else {
+                    continue;
+                };
+                let columns = table_columns(&importer.tx, table)?;
+                let mut imported = TableImport::default();
+                for row in rows {
+                    if let Some(column) = row.keys().find(|column| !columns.contains(column)) {
+                        anyhow::bail!("❌ Unknown column {table}.{column} in the export");
+                    }
+                    if importer.merge_row(table, merge, row)? {
+                        imported.merged += 1;
+                    } else {
+                        imported.skipped += 1;
+                    }
+                }
+                stats.insert((*table).to_string(), imported);
+            }
+            importer.tx.commit()?;
+            Ok(stats)
+        })
+    }
+}
+
+struct Importer<'conn> {
+    tx: Transaction<'conn>,
+    /// Ids in this DB of the exported reviews.
+    review_ids: HashMap<i64, i64>,
+    /// Reviews added by the import.
+    new_review_ids: HashSet<i64>,
+}
+
+impl Importer<'_> {
+    /// Returns whether the row was added or updated.
+    fn merge_row(&mut self, table: &str, merge: &Merge, row: &ExportRow) -> anyhow::Result<bool> {
+        let tx = &self.tx;
+        match merge {
+            Merge::LatestSeen { key } => upsert_latest_seen(tx, table, key, row),
+            Merge::KeepLocal => Ok(insert_row(tx, table, row, &[], "ON CONFLICT DO NOTHING")? > 0),
+            Merge::Skip => Ok(false),
+            Merge::ReviewFindings => {
+                let review_id = row
+                    .get("review_id")
+                    .and_then(Value::as_i64)
+                    .and_then(|id| self.review_ids.get(&id).copied())
+                    .filter(|id| self.new_review_ids.contains(id));
+                let Some(review_id) = review_id else {
+                    return Ok(false);
+                };
+                insert_row(
+                    tx,
+                    table,
+                    row,
+                    &[("id", None), ("review_id", Some(review_id))],
+                    "",
+                )?;
+                Ok(true)
+            }
+            Merge::Dedupe { natural_key } => {
+                if let Some(id) = find_by_natural_key(tx, table, natural_key, row)? {
+                    self.map_review(table, row, id);
+                    return Ok(false);
+                }
+                // Reviews come in id order: the previous review is mapped.
+                let previous = row
+                    .get("previous_review_id")
+                    .and_then(Value::as_i64)
+                    .and_then(|id| self.review_ids.get(&id).copied());
+                insert_row(
+                    tx,
+                    table,
+                    row,
+                    &[("id", None), ("previous_review_id", previous)],
+                    "",
+                )?;
+                let id = tx.last_insert_rowid();
+                self.map_review(table, row, id);
+                if table == "reviews" {
+                    self.new_review_ids.insert(id);
+                }
+                Ok(true)
+            }
+        }
+    }
+
+    fn map_review(&mut self, table: &str, row: &ExportRow, id: i64) {
+        if table == "reviews"
+            && let Some(exported_id) = row.get("id").and_then(Value::as_i64)
+        {
+            self.review_ids.insert(exported_id, id);
+        }
+    }
+}
+
+/// Tables holding data, without the sqlite and migration bookkeeping.
+fn table_names(conn: &Connection) -> anyhow::Result<Vec<String>> {
+    // The search indexes are virtual tables, rebuilt by their triggers.
+    let mut stmt = conn.prepare(
+        "SELECT name FROM pragma_table_list WHERE schema = 'main' AND type = 'table' AND name NOT LIKE 'sqlite_%' AND name != 'schema_version' ORDER BY name",
+    )?;
+    let names = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(names)
+}
+
+fn table_columns(conn: &Connection, table: &str) -> anyhow::Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    let columns = stmt
+        .query_map([], |row| row.get(1))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(columns)
+}
+
+/// Insert `row` into `table`, with `overrides` replacing some of its
+/// values (`None` leaves the column to its default). Returns the rows
+/// changed. The column names were checked against the table.
+fn insert_row(
+    conn: &Connection,
+    table: &str,
+    row: &ExportRow,
+    overrides: &[(&str, Option<i64>)],
+    on_conflict: &str,
+) -> anyhow::Result<usize> {
+    let mut columns = Vec::new();
+    let mut values = Vec::new();
+    for (column, value) in row {
+        match overrides.iter().find(|(name, _)| name == column) {
+            Some((_, None)) => continue,
+            Some((_, Some(id))) => values.push(SqlValue::Integer(*id)),
+            None => values.push(sql_value(value, table, column)?),
+        }
+        columns.push(column.as_str());
+    }
+    let placeholders = vec!["?"; columns.len()].join(", ");
+    let sql = format!(
+        "INSERT INTO {table} ({}) VALUES ({placeholders}) {on_conflict}",
+        columns.join(", ")
+    );
+    Ok(conn.execute(&sql, params_from_iter(values))?)
+}
+
+/// Insert the row, or update the local one when the imported row was seen
+/// later by a poll. Returns whether anything changed.
+fn upsert_latest_seen(
+    conn: &Connection,
+    table: &str,
+    key: &str,
+    row: &ExportRow,
+) -> anyhow::Result<bool> {
+    let updates: Vec<String> = row
+        .keys()
+        .filter(|column| *column != key)
+        .map(|column| {
+            if table == "threads" && column == "done" {
+                format!("done = MAX({table}.done, excluded.done)")
+            } else {
+                format!(
+                    "{column} = CASE WHEN excluded.last_seen_at > {table}.last_seen_at THEN excluded.{column} ELSE {table}.{column} END"
+                )
+            }
+        })
+        .collect();
+    let on_conflict = format!(
+        "ON CONFLICT({key}) DO UPDATE SET {} WHERE excluded.last_seen_at > {table}.last_seen_at{}",
+        updates.join(", "),
+        if table == "threads" {
+            " OR excluded.done > threads.done"
+        } else {
+            ""
+        }
+    );
+    Ok(insert_row(conn, table, row, &[], &on_conflict)? > 0)
+}
+
+fn find_by_natural_key(
+    conn: &Connection,
+    table: &str,
+    natural_key: &[&str],
+    row: &ExportRow,
+) -> anyhow::Result<Option<i64>> {
+    let conditions: Vec<String> = natural_key
+        .iter()
+        .map(|column| format!("{column} IS ?"))
+        .collect();
+    let values = natural_key
+        .iter()
+        .map(|column| sql_value(row.get(*column).unwrap_or(&Value::Null), table, column))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let sql = format!(
+        "SELECT id FROM {table} WHERE {} ORDER BY id LIMIT 1",
+        conditions.join(" AND ")
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query(params_from_iter(values))?;
+    Ok(rows.next()?.map(|row| row.get(0)).transpose()?)
+}
+
+fn json_value(value: ValueRef<'_>, table: &str) -> anyhow::Result<Value> {
+    Ok(match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(value) => Value::from(value),
+        ValueRef::Real(value) => Value::from(value),
+        ValueRef::Text(text) => Value::from(String::from_utf8_lossy(text).into_owned()),
+        ValueRef::Blob(_) => anyhow::bail!("❌ Can't export the binary data of {table}"),
+    })
+}
+
+fn sql_value(value: &Value, table: &str, column: &str) -> anyhow::Result<SqlValue> {
+    Ok(match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(value) => SqlValue::Integer(i64::from(*value)),
+        Value::Number(number) => match number.as_i64() {
+            Some(value) => SqlValue::Integer(value),
+            None => SqlValue::Real(number.as_f64().unwrap_or_default()),
+        },
+        Value::String(text) => SqlValue::Text(text.clone()),
+        Value::Array(_) | Value::Object(_) => {
+            anyhow::bail!("❌ Unexpected nested value in {table}.{column}")
+        }
+    })
+}
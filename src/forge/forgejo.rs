@@ -0,0 +1,616 @@
+use std::time::Duration;
+
+use anyhow::Context as _;
+use serde::{Deserialize, de::DeserializeOwned};
+
+use crate::{
+    config::{ForgejoConfig, ForgejoHostConfig},
+    github::{
+        AssignedIssueSummary, AssignedIssuesSearchResult, AuthoredPrSummary, NotificationThread,
+        PrDetails,
+    },
+};
+
+const USER_AGENT: &str = concat!("gigi/", env!("CARGO_PKG_VERSION"));
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Env var holding the token of the hosts without `token_env`.
+const DEFAULT_TOKEN_ENV: &str = "FORGEJO_TOKEN";
+
+/// Items per page of list endpoints, the default maximum of Forgejo.
+const PAGE_LIMIT: usize = 50;
+
+/// Pages followed by the list fetches.
+const MAX_PAGES: usize = 10;
+
+/// A Forgejo or Gitea instance watched by `serve`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForgejoHost {
+    /// Web host, e.g. `codeberg.org`.
+    pub host: String,
+    /// REST API root, e.g. `https://codeberg.org/api/v1`.
+    pub api_url: String,
+    /// Env var holding the token of this host.
+    pub token_env: String,
+}
+
+/// Async client of the REST API of a Forgejo or Gitea host. Items are
+/// mapped onto the GitHub types, so `serve` stores them the same way.
+#[derive(Debug, Clone)]
+pub struct ForgejoClient {
+    http: reqwest::Client,
+    host: ForgejoHost,
+    token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Notification {
+    id: u64,
+    unread: bool,
+    updated_at: String,
+    repository: Repository,
+    subject: Subject,
+}
+
+#[derive(Debug, Deserialize)]
+struct Subject {
+    title: String,
+    /// `Issue`, `Pull`, `Commit` or `Repository`.
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    html_url: Option<String>,
+    /// `open`, `closed` or `merged`.
+    #[serde(default)]
+    state: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Repository {
+    name: String,
+    full_name: String,
+    owner: User,
+    #[serde(default)]
+    archived: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    login: String,
+}
+
+/// Result of the issue search, for issues and PRs.
+#[derive(Debug, Deserialize)]
+struct Issue {
+    html_url: String,
+    title: String,
+    /// `open` or `closed`.
+    state: String,
+    updated_at: String,
+    repository: RepositoryMeta,
+    #[serde(default)]
+    pull_request: Option<PullRequestMeta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepositoryMeta {
+    full_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequestMeta {
+    #[serde(default)]
+    draft: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequest {
+    number: i64,
+    title: String,
+    /// `open` or `closed`, merged PRs included.
+    state: String,
+    #[serde(default)]
+    merged: bool,
+    #[serde(default)]
+    draft: bool,
+    created_at: String,
+    updated_at: String,
+    #[serde(default)]
+    user: Option<User>,
+    head: Branch,
+    base: Branch,
+}
+
+#[derive(Debug, Deserialize)]
+struct Branch {
+    #[serde(rename = "ref")]
+    ref_name: String,
+    sha: String,
+    /// `None` when the head repository was deleted.
+    #[serde(default)]
+    repo: Option<Repository>,
+}
+
+impl ForgejoHost {
+    pub fn new(config: &ForgejoHostConfig) -> Self {
+        let host = config
+            .host
+            .trim()
+            .trim_end_matches('/')
+            .to_ascii_lowercase();
+        Self {
+            api_url: config.api_url.as_deref().map_or_else(
+                || format!("https://{host}/api/v1"),
+                |url| url.trim_end_matches('/').to_string(),
+            ),
+            token_env: config
+                .token_env
+                .as_deref()
+                .unwrap_or(DEFAULT_TOKEN_ENV)
+                .to_string(),
+            host,
+        }
+    }
+}
+
+/// Hosts of `[forgejo]`.
+pub fn forgejo_hosts(config: &ForgejoConfig) -> Vec<ForgejoHost> {
+    config
+        .hosts
+        .iter()
+        .filter(|host| !host.host.trim().is_empty())
+        .map(ForgejoHost::new)
+        .collect()
+}
+
+impl ForgejoClient {
+    pub fn new(host: ForgejoHost, token: String) -> anyhow::Result<Self> {
+        let http = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| anyhow::anyhow!("❌ Failed to build the Forgejo HTTP client: {e}"))?;
+        Ok(Self { http, host, token })
+    }
+
+    /// Client of `host`, authenticated with the token of its `token_env`.
+    pub fn for_host(host: &ForgejoHost) -> anyhow::Result<Self> {
+        let token = std::env::var(&host.token_env)
+            .ok()
+            .filter(|token| !token.trim().is_empty())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "❌ Set {} to a Forgejo token of {}",
+                    host.token_env,
+                    host.host
+                )
+            })?;
+        Self::new(host.clone(), token)
+    }
+
+    pub fn host(&self) -> &ForgejoHost {
+        &self.host
+    }
+
+    /// Notifications updated since `since`, read ones included. Thread ids
+    /// are prefixed with the host, as for GitHub Enterprise Server.
+    pub async fn fetch_notifications(
+        &self,
+        since: Option<&str>,
+    ) -> anyhow::Result<Vec<NotificationThread>> {
+        let mut endpoint = "notifications?all=true".to_string();
+        if let Some(since) = since {
+            endpoint.push_str(&format!("&since={}", encode_query_component(since)));
+        }
+        let (notifications, _) = self
+            .get_pages::<Notification>(&endpoint)
+            .await
+            .context("❌ Failed to fetch Forgejo notifications")?;
+        Ok(notifications
+            .into_iter()
+            .map(|notification| self.notification_thread(notification))
+            .collect())
+    }
+
+    /// PRs opened by the user and updated since `since`, in any state.
+    pub async fn fetch_authored_prs(
+        &self,
+        since: Option<&str>,
+    ) -> anyhow::Result<Vec<AuthoredPrSummary>> {
+        let mut endpoint = "repos/issues/search?type=pulls&created=true&state=all".to_string();
+        if let Some(since) = since {
+            endpoint.push_str(&format!("&since={}", encode_query_component(since)));
+        }
+        let (prs, _) = self
+            .get_pages::<Issue>(&endpoint)
+            .await
+            .context("❌ Failed to fetch Forgejo authored pull requests")?;
+        Ok(prs
+            .into_iter()
+            .map(|pr| AuthoredPrSummary {
+                is_open: pr.state == "open",
+                is_draft: pr.pull_request.is_some_and(|meta| meta.draft),
+                pr_url: pr.html_url,
+                repository: pr.repository.full_name,
+                title: pr.title,
+                updated_at: pr.updated_at,
+            })
+            .collect())
+    }
+
+    pub async fn fetch_assigned_issues(&self) -> anyhow::Result<AssignedIssuesSearchResult> {
+        let (issues, is_complete) = self
+            .get_pages::<Issue>("repos/issues/search?type=issues&assigned=true&state=open")
+            .await
+            .context("❌ Failed to fetch Forgejo assigned issues")?;
+        Ok(AssignedIssuesSearchResult {
+            issues: issues
+                .into_iter()
+                .map(|issue| AssignedIssueSummary {
+                    issue_url: issue.html_url,
+                    repository: issue.repository.full_name,
+                    title: issue.title,
+                    updated_at: issue.updated_at,
+                    state: issue.state.to_ascii_uppercase(),
+                })
+                .collect(),
+            is_complete,
+        })
+    }
+
+    /// Details of a PR URL like `https://codeberg.org/owner/repo/pulls/3`.
+    pub async fn fetch_pr_details(&self, pr_url: &str) -> anyhow::Result<PrDetails> {
+        let (owner, repo, number) = self
+            .parse_pr_url(pr_url)
+            .ok_or_else(|| anyhow::anyhow!("❌ Invalid Forgejo PR URL: {pr_url}"))?;
+        let pr: PullRequest = self
+            .get(&format!("repos/{owner}/{repo}/pulls/{number}"))
+            .await
+            .with_context(|| format!("❌ Failed to fetch Forgejo PR details of {pr_url}"))?;
+        Ok(pr_details(pr_url, pr))
+    }
+
+    fn notification_thread(&self, notification: Notification) -> NotificationThread {
+        let subject = notification.subject;
+        let is_pr = subject.kind == "Pull";
+        NotificationThread {
+            thread_id: format!("{}:{}", self.host.host, notification.id),
+            unread: notification.unread,
+            reason: None,
+            updated_at: notification.updated_at,
+            repository: notification.repository.full_name,
+            subject_type: Some(if is_pr {
+                "PullRequest".to_string()
+            } else {
+                subject.kind
+            }),
+            subject_title: subject.title,
+            pr_url: subject.html_url.clone().filter(|_| is_pr),
+            subject_url: subject.html_url,
+            issue_api_url: None,
+            discussion_api_url: None,
+            issue_state: subject.state.map(|state| state.to_ascii_uppercase()),
+            discussion_answered: None,
+        }
+    }
+
+    /// Owner, name and number of a PR of this host.
+    fn parse_pr_url(&self, pr_url: &str) -> Option<(String, String, i64)> {
+        let path = pr_url
+            .trim()
+            .strip_prefix("https://")
+            .or_else(|| pr_url.trim().strip_prefix("http://"))?
+            .strip_prefix(&self.host.host)?
+            .strip_prefix('/')?;
+        let mut parts = path.split(['?', '#']).next()?.split('/');
+        let (owner, repo) = (parts.next()?, parts.next()?);
+        if parts.next()? != "pulls" || owner.is_empty() || repo.is_empty() {
+            return None;
+        }
+        let number = parts.next()?.parse().ok()?;
+        Some((owner.to_string(), repo.to_string(), number))
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> anyhow::Result<T> {
+        let url = format!("{}/{}", self.host.api_url, path.trim_start_matches('/'));
+        let response = self
+            .http
+            .get(&url)
+            .header("authorization", format!("token {}", self.token))
+            .header("accept", "application/json")
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("❌ Forgejo API GET {url} failed: {e}"))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| anyhow::anyhow!("❌ Forgejo API GET {url} failed: {e}"))?;
+        anyhow::ensure!(
+            status.is_success(),
+            "❌ Forgejo API GET {url} failed with {status}: {}",
+            text.trim()
+        );
+        serde_json::from_str(&text)
+            .map_err(|e| anyhow::anyhow!("❌ Invalid Forgejo API response for {path}: {e}"))
+    }
+
+    /// `GET` a list page by page, until a page is not full or after
+    /// [`MAX_PAGES`] pages. Returns whether the whole list was read.
+    async fn get_pages<T: DeserializeOwned>(&self, path: &str) -> anyhow::Result<(Vec<T>, bool)> {
+        let separator = if path.contains('?') { '&' } else { '?' };
+        let mut items = Vec::new();
+        for page in 1..=MAX_PAGES {
+            let page_items: Vec<T> = self
+                .get(&format!("{path}{separator}limit={PAGE_LIMIT}&page={page}"))
+                .await?;
+            let is_last = page_items.len() < PAGE_LIMIT;
+            items.extend(page_items);
+            if is_last {
+                return Ok((items, true));
+            }
+        }
+        Ok((items, false))
+    }
+}
+
+fn pr_details(pr_url: &str, pr: PullRequest) -> PrDetails {
+    let state = if pr.merged {
+        "MERGED"
+    } else if pr.state == "open" {
+        "OPEN"
+    } else {
+        "CLOSED"
+    };
+    let base_repo = pr.base.repo;
+    let head_repo = pr.head.repo;
+    let is_cross_repository = match (&head_repo, &base_repo) {
+        (Some(head), Some(base)) => head.full_name != base.full_name,
+        _ => false,
+    };
+    PrDetails {
+        pr_url: pr_url.to_string(),
+        owner: base_repo
+            .as_ref()
+            .map(|repo| repo.owner.login.clone())
+            .unwrap_or_default(),
+        repo: base_repo
+            .as_ref()
+            .map(|repo| repo.name.clone())
+            .unwrap_or_default(),
+        number: pr.number,
+        state: state.to_string(),
+        merge_queue_state: None,
+        auto_merge: false,
+        title: pr.title,
+        head_ref: pr.head.ref_name,
+        base_ref: pr.base.ref_name,
+        head_sha: pr.head.sha,
+        created_at: pr.created_at,
+        updated_at: pr.updated_at,
+        is_archived: base_repo.as_ref().is_some_and(|repo| repo.archived),
+        author_login: pr.user.map(|user| user.login),
+        head_repo_owner: head_repo.as_ref().map(|repo| repo.owner.login.clone()),
+        head_repo_name: head_repo.map(|repo| repo.name),
+        is_cross_repository,
+        is_draft: pr.draft,
+    }
+}
+
+fn encode_query_component(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use axum::{
+        Json, Router,
+        extract::{Path, Query},
+        http::HeaderMap,
+        routing::get,
+    };
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::test_util::spawn_stub;
+
+    async fn stub_client(router: Router) -> ForgejoClient {
+        let host = ForgejoHost {
+            api_url: spawn_stub(router).await,
+            ..ForgejoHost::new(&ForgejoHostConfig {
+                host: "codeberg.org".to_string(),
+                ..ForgejoHostConfig::default()
+            })
+        };
+        ForgejoClient::new(host, "test-token".to_string()).unwrap()
+    }
+
+    fn repository(full_name: &str) -> Value {
+        let (owner, name) = full_name.split_once('/').unwrap();
+        json!({ "name": name, "full_name": full_name, "owner": { "login": owner }, "archived": false })
+    }
+
+    fn issue(number: u32, kind: &str, state: &str) -> Value {
+        json!({
+            "html_url": format!("https://codeberg.org/o/r/{kind}/{number}"),
+            "title": format!("Item {number}"),
+            "state": state,
+            "updated_at": "2026-10-01T10:00:00Z",
+            "repository": { "id": 1, "name": "r", "owner": "o", "full_name": "o/r" },
+            "pull_request": (kind == "pulls").then(|| json!({ "merged": false, "draft": number == 2 }))
+        })
+    }
+
+    #[test]
+    fn hosts_default_to_the_v1_api_and_forgejo_token() {
+        let hosts = forgejo_hosts(&ForgejoConfig {
+            hosts: vec![
+                ForgejoHostConfig {
+                    host: "Codeberg.org/".to_string(),
+                    ..ForgejoHostConfig::default()
+                },
+                ForgejoHostConfig::default(),
+            ],
+        });
+        assert_eq!(
+            hosts,
+            [ForgejoHost {
+                host: "codeberg.org".to_string(),
+                api_url: "https://codeberg.org/api/v1".to_string(),
+                token_env: "FORGEJO_TOKEN".to_string(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn notifications_map_onto_threads() {
+        async fn notifications(
+            headers: HeaderMap,
+            Query(query): Query<HashMap<String, String>>,
+        ) -> Json<Value> {
+            assert_eq!(headers["authorization"], "token test-token");
+            assert_eq!(query["since"], "2026-10-01T00:00:00Z");
+            assert_eq!(query["limit"], "50");
+            Json(json!([
+                {
+                    "id": 7,
+                    "unread": true,
+                    "updated_at": "2026-10-01T10:00:00Z",
+                    "repository": repository("o/r"),
+                    "subject": {
+                        "title": "Fix the build",
+                        "type": "Pull",
+                        "url": "https://codeberg.org/api/v1/repos/o/r/pulls/3",
+                        "html_url": "https://codeberg.org/o/r/pulls/3",
+                        "state": "merged"
+                    }
+                },
+                {
+                    "id": 8,
+                    "unread": false,
+                    "updated_at": "2026-10-01T11:00:00Z",
+                    "repository": repository("o/r"),
+                    "subject": {
+                        "title": "Crash on start",
+                        "type": "Issue",
+                        "html_url": "https://codeberg.org/o/r/issues/4",
+                        "state": "open"
+                    }
+                }
+            ]))
+        }
+
+        let client = stub_client(Router::new().route("/notifications", get(notifications))).await;
+        let threads = client
+            .fetch_notifications(Some("2026-10-01T00:00:00Z"))
+            .await
+            .unwrap();
+
+        assert_eq!(threads[0].thread_id, "codeberg.org:7");
+        assert_eq!(threads[0].subject_type.as_deref(), Some("PullRequest"));
+        assert_eq!(
+            threads[0].pr_url.as_deref(),
+            Some("https://codeberg.org/o/r/pulls/3")
+        );
+        assert_eq!(threads[0].issue_state.as_deref(), Some("MERGED"));
+        assert_eq!(threads[1].subject_type.as_deref(), Some("Issue"));
+        assert_eq!(threads[1].pr_url, None);
+        assert_eq!(
+            threads[1].subject_url.as_deref(),
+            Some("https://codeberg.org/o/r/issues/4")
+        );
+    }
+
+    #[tokio::test]
+    async fn searches_follow_pages_until_a_short_one() {
+        async fn search(Query(query): Query<HashMap<String, String>>) -> Json<Value> {
+            let page: usize = query["page"].parse().unwrap();
+            match query["type"].as_str() {
+                "pulls" => {
+                    assert_eq!(query["created"], "true");
+                    Json(json!([
+                        issue(1, "pulls", "closed"),
+                        issue(2, "pulls", "open")
+                    ]))
+                }
+                _ => {
+                    assert_eq!(query["assigned"], "true");
+                    let count = if page == 1 { PAGE_LIMIT } else { 1 };
+                    Json(Value::Array(
+                        std::iter::repeat_with(|| issue(5, "issues", "open"))
+                            .take(count)
+                            .collect(),
+                    ))
+                }
+            }
+        }
+
+        let client = stub_client(Router::new().route("/repos/issues/search", get(search))).await;
+
+        let authored = client.fetch_authored_prs(None).await.unwrap();
+        assert_eq!(authored.len(), 2);
+        assert!(!authored[0].is_open);
+        assert!(authored[1].is_open);
+        assert!(authored[1].is_draft);
+        assert_eq!(authored[1].pr_url, "https://codeberg.org/o/r/pulls/2");
+
+        let assigned = client.fetch_assigned_issues().await.unwrap();
+        assert_eq!(assigned.issues.len(), PAGE_LIMIT + 1);
+        assert!(assigned.is_complete);
+        assert_eq!(assigned.issues[0].state, "OPEN");
+        assert_eq!(assigned.issues[0].repository, "o/r");
+    }
+
+    #[tokio::test]
+    async fn pr_details_of_forks_are_fetched() {
+        async fn pull(Path((owner, repo, number)): Path<(String, String, u32)>) -> Json<Value> {
+            assert_eq!((owner.as_str(), repo.as_str(), number), ("o", "r", 3));
+            Json(json!({
+                "number": 3,
+                "title": "Fix the build",
+                "state": "closed",
+                "merged": true,
+                "draft": false,
+                "html_url": "https://codeberg.org/o/r/pulls/3",
+                "created_at": "2026-10-01T10:00:00Z",
+                "updated_at": "2026-10-02T10:00:00Z",
+                "user": { "login": "contributor" },
+                "head": { "ref": "fix", "sha": "abc", "repo": repository("contributor/r") },
+                "base": { "ref": "main", "sha": "def", "repo": repository("o/r") }
+            }))
+        }
+
+        let client =
+            stub_client(Router::new().route("/repos/{owner}/{repo}/pulls/{number}", get(pull)))
+                .await;
+        let details = client
+            .fetch_pr_details("https://codeberg.org/o/r/pulls/3")
+            .await
+            .unwrap();
+
+        assert_eq!(details.owner, "o");
+        assert_eq!(details.repo, "r");
+        assert_eq!(details.number, 3);
+        assert_eq!(details.state, "MERGED");
+        assert_eq!(details.head_ref, "fix");
+        assert_eq!(details.head_sha, "abc");
+        assert_eq!(details.head_repo_owner.as_deref(), Some("contributor"));
+        assert!(details.is_cross_repository);
+        assert!(
+            client
+                .fetch_pr_details("https://github.com/o/r/pull/3")
+                .await
+                .is_err()
+        );
+    }
+}
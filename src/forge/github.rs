@@ -0,0 +1,423 @@
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::Deserialize;
+
+use crate::{
+    authors::{self, PullRequestCommit},
+    checkout::{GitHubPrRef, parse_github_pr_url},
+    cmd::Cmd,
+    github::{self, PrDetails},
+    review::{DiffPositions, build_review_payload},
+};
+
+use super::{BranchPr, Forge, ForgeFuture, ReviewToPublish};
+
+/// github.com and the GitHub Enterprise Server hosts, through `gh` and the
+/// GitHub API client.
+#[derive(Debug, Clone, Copy)]
+pub struct GitHub;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PullRequest {
+    number: u64,
+    title: String,
+    base_ref_name: String,
+    url: String,
+}
+
+impl Forge for GitHub {
+    fn prepare_repo<'a>(&'a self, _repo_root: &'a Utf8Path) -> ForgeFuture<'a, ()> {
+        Box::pin(async move {
+            if !is_default_repo_set().await? {
+                set_default_repo().await?;
+            }
+            Ok(())
+        })
+    }
+
+    fn default_branch<'a>(&'a self, repo_root: &'a Utf8Path) -> ForgeFuture<'a, String> {
+        Box::pin(github::default_branch(repo_root))
+    }
+
+    fn branch_has_pr<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        branch: &'a str,
+    ) -> ForgeFuture<'a, bool> {
+        Box::pin(async move {
+            let output = Cmd::new(
+                "gh",
+                [
+                    "pr",
+                    "list",
+                    "--state",
+                    "all",
+                    "--head",
+                    branch,
+                    "--json",
+                    "number",
+                    "--limit",
+                    "1",
+                    "-q",
+                    ".[0].number",
+                ],
+            )
+            .with_current_dir(repo_root)
+            .hide_stderr()
+            .run()
+            .await?;
+            Ok(output.status().success() && !output.stdout().trim().is_empty())
+        })
+    }
+
+    fn pr_for_branch<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        branch: &'a str,
+    ) -> ForgeFuture<'a, Option<BranchPr>> {
+        Box::pin(async move {
+            let output = Cmd::new(
+                "gh",
+                [
+                    "pr",
+                    "list",
+                    "--head",
+                    branch,
+                    "--json",
+                    "number,title,baseRefName,url",
+                    "--limit",
+                    "1",
+                ],
+            )
+            .with_current_dir(repo_root)
+            .run()
+            .await?;
+            output.ensure_success("❌ Failed to get current PR")?;
+            let pull_requests: Vec<PullRequest> = serde_json::from_str(output.stdout())?;
+            let Some(pull_request) = pull_requests.into_iter().next() else {
+                return Ok(None);
+            };
+            let base_repo = parse_github_pr_url(&pull_request.url)?;
+            Ok(Some(BranchPr {
+                number: pull_request.number,
+                title: pull_request.title,
+                base_ref: pull_request.base_ref_name,
+                url: pull_request.url,
+                owner: base_repo.owner,
+                repo: base_repo.repo,
+            }))
+        })
+    }
+
+    fn open_pr_in_browser<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        _branch: &'a str,
+        title: &'a str,
+    ) -> ForgeFuture<'a, ()> {
+        Box::pin(async move {
+            let pr_exists = Cmd::new("gh", ["pr", "view", "--json", "number", "-q", ".number"])
+                .with_current_dir(repo_root)
+                .run()
+                .await?
+                .status()
+                .success();
+
+            if pr_exists {
+                Cmd::new("gh", ["pr", "view", "--web"])
+                    .with_current_dir(repo_root)
+                    .run()
+                    .await?
+                    .ensure_success("❌ Failed to open existing PR in browser")?;
+            } else {
+                Cmd::new(
+                    "gh",
+                    ["pr", "create", "--title", title, "--body", "", "--web"],
+                )
+                .with_current_dir(repo_root)
+                .run()
+                .await?
+                .ensure_success("❌ Failed to create PR in browser")?;
+            }
+            Ok(())
+        })
+    }
+
+    fn pr_details<'a>(&'a self, pr_url: &'a str) -> ForgeFuture<'a, PrDetails> {
+        Box::pin(github::fetch_pr_details(pr_url))
+    }
+
+    fn pr_commits<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        pr: &'a BranchPr,
+    ) -> ForgeFuture<'a, Vec<PullRequestCommit>> {
+        Box::pin(authors::get_pull_request_commits(repo_root, pr.number))
+    }
+
+    fn remote_repo_name(&self, remote_url: &str) -> Option<String> {
+        github::parse_github_name_with_owner(remote_url)
+    }
+
+    fn clone_url<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        owner: &'a str,
+        repo: &'a str,
+    ) -> ForgeFuture<'a, String> {
+        Box::pin(async move {
+            // `owner/repo` is on the host of the repository, e.g. its upstream.
+            let origin = super::remote_url(repo_root, "origin")
+                .await?
+                .unwrap_or_default();
+            let host = github::split_github_remote_url(&origin)
+                .map_or(github::GITHUB_COM, |(host, _)| host.host.as_str());
+            let protocol_output = Cmd::new("gh", ["config", "get", "git_protocol", "--host", host])
+                .with_current_dir(repo_root)
+                .run()
+                .await?;
+            protocol_output
+                .ensure_success("❌ Failed to detect the configured GitHub git protocol")?;
+
+            clone_url_for_protocol(protocol_output.stdout(), host, owner, repo)
+        })
+    }
+
+    fn checkout_pr<'a>(&'a self, pr_url: &'a str) -> ForgeFuture<'a, Utf8PathBuf> {
+        Box::pin(async move {
+            let local_pr = github::ensure_local_repo_for_pr(pr_url).await?;
+            github::prepare_repo_for_pr_checkout(&local_pr.repo_dir).await?;
+            github::checkout_pr(&local_pr.repo_dir, pr_url).await?;
+            Ok(local_pr.repo_dir)
+        })
+    }
+
+    fn fetch_pr_head<'a>(&'a self, pr_url: &'a str) -> ForgeFuture<'a, (Utf8PathBuf, String)> {
+        Box::pin(async move {
+            let pr = parse_github_pr_url(pr_url)?;
+            let repo_dir = github::ensure_local_repo(&pr.host, &pr.owner, &pr.repo).await?;
+            let head =
+                super::fetch_head(&repo_dir, &format!("refs/pull/{}/head", pr.number)).await?;
+            Ok((repo_dir, head))
+        })
+    }
+
+    fn pr_metadata<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        pr_url: &'a str,
+    ) -> ForgeFuture<'a, String> {
+        Box::pin(async move {
+            let output = Cmd::new(
+                "gh",
+                [
+                    "pr",
+                    "view",
+                    pr_url,
+                    "--json",
+                    "title,body,author,baseRefName,headRefName,headRefOid,createdAt,updatedAt,assignees,reviews,comments,commits,url",
+                ],
+            )
+            .with_current_dir(repo_root)
+            .run()
+            .await?;
+            output.ensure_success("❌ Failed to fetch PR metadata")?;
+            anyhow::ensure!(
+                !output.stdout().trim().is_empty(),
+                "❌ Failed to fetch PR metadata: command returned empty output"
+            );
+            Ok(output.stdout().to_string())
+        })
+    }
+
+    fn pr_diff<'a>(&'a self, repo_root: &'a Utf8Path, pr_url: &'a str) -> ForgeFuture<'a, String> {
+        Box::pin(async move {
+            let output = Cmd::new("gh", ["pr", "diff", pr_url, "--color=never"])
+                .with_current_dir(repo_root)
+                .run()
+                .await?;
+            output.ensure_success("❌ Failed to fetch PR diff")?;
+            anyhow::ensure!(
+                !output.stdout().trim().is_empty(),
+                "❌ Failed to fetch PR diff: command returned empty output"
+            );
+            Ok(output.stdout().to_string())
+        })
+    }
+
+    fn incremental_diff<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        pr_url: &'a str,
+        base_sha: &'a str,
+        head_sha: &'a str,
+    ) -> ForgeFuture<'a, Option<String>> {
+        Box::pin(async move {
+            let pr = parse_github_pr_url(pr_url)?;
+            let endpoint = format!(
+                "repos/{}/{}/compare/{base_sha}...{head_sha}",
+                pr.owner, pr.repo
+            );
+
+            let status = Cmd::new("gh", gh_api_args(&pr, &[&endpoint, "--jq", ".status"]))
+                .with_current_dir(repo_root)
+                .hide_stdout()
+                .hide_stderr()
+                .run()
+                .await?;
+            // The compare API returns 404 when a force-push removed `base_sha`.
+            if !status.status().success() || status.stdout().trim() != "ahead" {
+                return Ok(None);
+            }
+
+            let output = Cmd::new(
+                "gh",
+                gh_api_args(
+                    &pr,
+                    &["-H", "Accept: application/vnd.github.diff", &endpoint],
+                ),
+            )
+            .with_current_dir(repo_root)
+            .hide_stdout()
+            .run()
+            .await?;
+            output.ensure_success(format!(
+                "❌ Failed to fetch the diff of {pr_url} since {base_sha}"
+            ))?;
+            Ok(Some(output.stdout().to_string()))
+        })
+    }
+
+    fn publish_review<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        pr_url: &'a str,
+        review: ReviewToPublish<'a>,
+    ) -> ForgeFuture<'a, String> {
+        Box::pin(async move {
+            let pr = parse_github_pr_url(pr_url)?;
+            let diff = self.pr_diff(repo_root, pr_url).await?;
+            let positions = DiffPositions::parse(&diff);
+            let payload = build_review_payload(
+                review.summary,
+                review.verdict,
+                review.findings,
+                &positions,
+                review.mode,
+            );
+
+            let endpoint = format!("repos/{}/{}/pulls/{}/reviews", pr.owner, pr.repo, pr.number);
+            let output = Cmd::new(
+                "gh",
+                gh_api_args(
+                    &pr,
+                    &[
+                        "--method",
+                        "POST",
+                        &endpoint,
+                        "--input",
+                        "-",
+                        "--jq",
+                        ".html_url",
+                    ],
+                ),
+            )
+            .with_current_dir(repo_root)
+            .with_stdin(serde_json::to_string(&payload)?)
+            .hide_stdout()
+            .run()
+            .await?;
+            output.ensure_success(format!("❌ Failed to publish review on {pr_url}"))?;
+
+            Ok(output.stdout().trim().to_string())
+        })
+    }
+}
+
+/// `gh api` arguments calling the API of the host of `pr`: without
+/// `--hostname`, `gh` calls the host of the current repository, or
+/// github.com.
+fn gh_api_args(pr: &GitHubPrRef, args: &[&str]) -> Vec<String> {
+    ["api", "--hostname", pr.host.as_str()]
+        .into_iter()
+        .chain(args.iter().copied())
+        .map(str::to_string)
+        .collect()
+}
+
+async fn is_default_repo_set() -> anyhow::Result<bool> {
+    let output = Cmd::new("gh", ["repo", "set-default", "--view"])
+        .hide_stdout()
+        .hide_stderr()
+        .run()
+        .await?;
+    Ok(!output.stdout().trim().is_empty())
+}
+
+async fn set_default_repo() -> anyhow::Result<()> {
+    let remotes_output = Cmd::new("git", ["remote"]).hide_stdout().run().await?;
+    let has_upstream = remotes_output
+        .stdout()
+        .lines()
+        .any(|line| line.trim() == "upstream");
+
+    let remote = if has_upstream { "upstream" } else { "origin" };
+    Cmd::new("gh", ["repo", "set-default", remote])
+        .run()
+        .await?
+        .ensure_success(format!(
+            "❌ Failed to set default GitHub repo to remote '{remote}'"
+        ))?;
+    Ok(())
+}
+
+fn clone_url_for_protocol(
+    protocol: &str,
+    host: &str,
+    owner: &str,
+    repo: &str,
+) -> anyhow::Result<String> {
+    match protocol {
+        "ssh" => Ok(format!("git@{host}:{owner}/{repo}.git")),
+        "https" => Ok(format!("https://{host}/{owner}/{repo}.git")),
+        protocol => anyhow::bail!("❌ Unsupported GitHub git protocol '{protocol}'"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clone_url_for_protocol, gh_api_args};
+    use crate::checkout::parse_github_pr_url;
+
+    #[test]
+    fn api_calls_go_to_the_host_of_the_pr() {
+        let pr = parse_github_pr_url("https://github.example.com/o/r/pull/7").unwrap();
+        assert_eq!(
+            gh_api_args(&pr, &["repos/o/r/pulls/7/reviews"]),
+            [
+                "api",
+                "--hostname",
+                "github.example.com",
+                "repos/o/r/pulls/7/reviews"
+            ]
+        );
+        let pr = parse_github_pr_url("https://github.com/o/r/pull/7").unwrap();
+        assert_eq!(
+            gh_api_args(&pr, &["user"]),
+            ["api", "--hostname", "github.com", "user"]
+        );
+    }
+
+    #[test]
+    fn base_repo_fallback_uses_configured_git_protocol() {
+        assert_eq!(
+            clone_url_for_protocol("ssh", "github.com", "organization", "project").unwrap(),
+            "git@github.com:organization/project.git"
+        );
+        assert_eq!(
+            clone_url_for_protocol("https", "github.example.com", "organization", "project")
+                .unwrap(),
+            "https://github.example.com/organization/project.git"
+        );
+    }
+}
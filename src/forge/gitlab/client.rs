@@ -0,0 +1,237 @@
+use std::time::Duration;
+
+use reqwest::Method;
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use super::hosts::GitLabHost;
+
+const USER_AGENT: &str = concat!("gigi/", env!("CARGO_PKG_VERSION"));
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Pages followed by [`GitLabClient::get_pages`].
+const MAX_PAGES: usize = 20;
+
+/// Async client of the GitLab REST API of a host.
+#[derive(Debug, Clone)]
+pub struct GitLabClient {
+    http: reqwest::Client,
+    host: GitLabHost,
+    token: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MergeRequest {
+    pub iid: u64,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// `opened`, `closed`, `locked` or `merged`.
+    pub state: String,
+    pub source_branch: String,
+    pub target_branch: String,
+    #[serde(default)]
+    pub sha: Option<String>,
+    pub web_url: String,
+    pub created_at: String,
+    pub updated_at: String,
+    #[serde(default)]
+    pub author: Option<User>,
+    #[serde(default)]
+    pub assignees: Vec<User>,
+    pub source_project_id: u64,
+    pub target_project_id: u64,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub diff_refs: Option<DiffRefs>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct User {
+    pub username: String,
+}
+
+/// Commits a merge request diff is computed from, needed to anchor
+/// comments on it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiffRefs {
+    pub base_sha: String,
+    pub head_sha: String,
+    pub start_sha: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Project {
+    pub id: u64,
+    pub path_with_namespace: String,
+    #[serde(default)]
+    pub default_branch: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Commit {
+    pub id: String,
+    pub title: String,
+    pub message: String,
+    pub author_name: String,
+    pub author_email: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Note {
+    pub id: u64,
+    pub body: String,
+    pub author: User,
+    #[serde(default)]
+    pub system: bool,
+}
+
+/// A changed file of a merge request or of a comparison.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FileDiff {
+    pub old_path: String,
+    pub new_path: String,
+    #[serde(default)]
+    pub a_mode: Option<String>,
+    #[serde(default)]
+    pub b_mode: Option<String>,
+    /// Hunks of the file, without the file headers.
+    pub diff: String,
+    #[serde(default)]
+    pub new_file: bool,
+    #[serde(default)]
+    pub renamed_file: bool,
+    #[serde(default)]
+    pub deleted_file: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Comparison {
+    pub diffs: Vec<FileDiff>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MergeBase {
+    pub id: String,
+}
+
+impl GitLabClient {
+    pub fn new(host: GitLabHost, token: String) -> anyhow::Result<Self> {
+        let http = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| anyhow::anyhow!("❌ Failed to build the GitLab HTTP client: {e}"))?;
+        Ok(Self { http, host, token })
+    }
+
+    pub fn host(&self) -> &GitLabHost {
+        &self.host
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> anyhow::Result<T> {
+        let (_, text) = self.send(Method::GET, path, None).await?;
+        parse_body(&text, path)
+    }
+
+    /// `GET` a paginated list, following `X-Next-Page` for at most
+    /// [`MAX_PAGES`] pages.
+    pub async fn get_pages<T: DeserializeOwned>(&self, path: &str) -> anyhow::Result<Vec<T>> {
+        let separator = if path.contains('?') { '&' } else { '?' };
+        let mut items = Vec::new();
+        let mut page = Some("1".to_string());
+        for _ in 0..MAX_PAGES {
+            let Some(current) = page.take() else {
+                break;
+            };
+            let page_path = format!("{path}{separator}per_page=100&page={current}");
+            let (headers, text) = self.send(Method::GET, &page_path, None).await?;
+            items.extend(parse_body::<Vec<T>>(&text, path)?);
+            page = headers
+                .get("x-next-page")
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+                .filter(|next| !next.is_empty())
+                .map(ToString::to_string);
+        }
+        Ok(items)
+    }
+
+    pub async fn post<T: DeserializeOwned>(&self, path: &str, body: &Value) -> anyhow::Result<T> {
+        let (_, text) = self.send(Method::POST, path, Some(body)).await?;
+        parse_body(&text, path)
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&Value>,
+    ) -> anyhow::Result<(reqwest::header::HeaderMap, String)> {
+        let url = format!("{}/{}", self.host.api_url, path.trim_start_matches('/'));
+        let mut request = self
+            .http
+            .request(method.clone(), &url)
+            .header("private-token", &self.token);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("❌ GitLab API {method} {url} failed: {e}"))?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| anyhow::anyhow!("❌ GitLab API {method} {url} failed: {e}"))?;
+        anyhow::ensure!(
+            status.is_success(),
+            "❌ GitLab API {method} {url} failed with {status}: {}",
+            error_message(&text)
+        );
+        Ok((headers, text))
+    }
+}
+
+/// API path of a project, e.g. `projects/group%2Fproject`.
+pub fn project_api_path(project: &str) -> String {
+    format!("projects/{}", encode_component(project))
+}
+
+/// Percent-encode everything but the unreserved characters, for path
+/// segments and query values.
+pub fn encode_component(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+fn parse_body<T: DeserializeOwned>(text: &str, path: &str) -> anyhow::Result<T> {
+    serde_json::from_str(text)
+        .map_err(|e| anyhow::anyhow!("❌ Invalid GitLab API response for {path}: {e}"))
+}
+
+/// `message` or `error` of a GitLab error body, or the body itself.
+fn error_message(text: &str) -> String {
+    serde_json::from_str::<Value>(text)
+        .ok()
+        .and_then(|value| {
+            let message = value.get("message").or_else(|| value.get("error"))?;
+            Some(match message.as_str() {
+                Some(message) => message.to_string(),
+                None => message.to_string(),
+            })
+        })
+        .unwrap_or_else(|| text.trim().to_string())
+}
@@ -0,0 +1,1225 @@
+mod client;
+mod hosts;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use serde_json::{Value, json};
+
+pub use hosts::configure_gitlab_hosts;
+pub(super) use hosts::gitlab_hosts;
+
+use crate::{
+    authors::PullRequestCommit,
+    cmd::Cmd,
+    github::{self, PrDetails},
+    launcher,
+    review::{
+        DiffLine, DiffPositions, PublishMode, ReviewFinding, Verdict, comment_body, review_body,
+    },
+};
+
+use self::{
+    client::{
+        Commit, Comparison, DiffRefs, FileDiff, GitLabClient, MergeBase, MergeRequest, Note,
+        Project, encode_component, project_api_path,
+    },
+    hosts::GitLabHost,
+};
+
+use super::{BranchPr, Forge, ForgeFuture, ReviewToPublish};
+
+/// A GitLab instance, where PRs are merge requests, through its REST API.
+#[derive(Debug, Clone)]
+pub struct GitLab {
+    client: GitLabClient,
+}
+
+/// Merge request of a web URL like
+/// `https://gitlab.com/group/sub/project/-/merge_requests/7`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MergeRequestRef {
+    /// Path of the target project, e.g. `group/sub/project`.
+    project: String,
+    iid: u64,
+}
+
+impl MergeRequestRef {
+    fn api_path(&self) -> String {
+        format!(
+            "{}/merge_requests/{}",
+            project_api_path(&self.project),
+            self.iid
+        )
+    }
+}
+
+impl GitLab {
+    /// Client of `host`, authenticated with the token of its `token_env`.
+    pub fn for_host(host: &GitLabHost) -> anyhow::Result<Self> {
+        let token = std::env::var(&host.token_env)
+            .ok()
+            .filter(|token| !token.trim().is_empty())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "❌ Set {} to a GitLab token of {}",
+                    host.token_env,
+                    host.host
+                )
+            })?;
+        Ok(Self {
+            client: GitLabClient::new(host.clone(), token)?,
+        })
+    }
+
+    fn merge_request_ref(&self, pr_url: &str) -> anyhow::Result<MergeRequestRef> {
+        gitlab_hosts()
+            .split_web_url(pr_url)
+            .filter(|(host, _)| host.host == self.client.host().host)
+            .and_then(|(_, path)| parse_merge_request_path(path))
+            .ok_or_else(|| anyhow::anyhow!("❌ Invalid GitLab merge request URL: {pr_url}"))
+    }
+
+    async fn merge_request(&self, mr: &MergeRequestRef) -> anyhow::Result<MergeRequest> {
+        self.client.get(&mr.api_path()).await
+    }
+
+    async fn project(&self, project: &str) -> anyhow::Result<Project> {
+        self.client.get(&project_api_path(project)).await
+    }
+
+    /// Local branch of the head of `mr`: its source branch, namespaced by
+    /// [`fork_branch`] when it comes from a fork.
+    async fn local_branch(&self, mr: &MergeRequest) -> anyhow::Result<String> {
+        if mr.source_project_id == mr.target_project_id {
+            return Ok(mr.source_branch.clone());
+        }
+        let fork: Project = self
+            .client
+            .get(&format!("projects/{}", mr.source_project_id))
+            .await?;
+        Ok(fork_branch(&fork.path_with_namespace, &mr.source_branch))
+    }
+
+    /// Changed files of a merge request.
+    async fn merge_request_diffs(&self, mr: &MergeRequestRef) -> anyhow::Result<Vec<FileDiff>> {
+        self.client
+            .get_pages(&format!("{}/diffs", mr.api_path()))
+            .await
+    }
+
+    /// Project of `remote`, or `None` when it is not a project of this host.
+    async fn remote_project(
+        &self,
+        repo_root: &Utf8Path,
+        remote: &str,
+    ) -> anyhow::Result<Option<String>> {
+        Ok(super::remote_url(repo_root, remote)
+            .await?
+            .and_then(|url| self.remote_repo_name(&url)))
+    }
+
+    async fn origin_project(&self, repo_root: &Utf8Path) -> anyhow::Result<String> {
+        self.remote_project(repo_root, "origin")
+            .await?
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "❌ The origin remote of {repo_root} is not a project of {}",
+                    self.client.host().host
+                )
+            })
+    }
+
+    /// Project merge requests are opened against: `upstream` for forks,
+    /// `origin` otherwise.
+    async fn base_project(&self, repo_root: &Utf8Path) -> anyhow::Result<String> {
+        match self.remote_project(repo_root, "upstream").await? {
+            Some(project) => Ok(project),
+            None => self.origin_project(repo_root).await,
+        }
+    }
+
+    /// Merge requests opened from `branch` of `origin`, in `state`.
+    async fn branch_merge_requests(
+        &self,
+        repo_root: &Utf8Path,
+        branch: &str,
+        state: &str,
+    ) -> anyhow::Result<Vec<MergeRequest>> {
+        let origin = self.project(&self.origin_project(repo_root).await?).await?;
+        let base = self.base_project(repo_root).await?;
+        let merge_requests: Vec<MergeRequest> = self
+            .client
+            .get(&format!(
+                "{}/merge_requests?source_branch={}&state={state}",
+                project_api_path(&base),
+                encode_component(branch)
+            ))
+            .await?;
+        Ok(merge_requests
+            .into_iter()
+            .filter(|mr| mr.source_project_id == origin.id)
+            .collect())
+    }
+
+    /// Clone `project` under `~/proj/HOST` unless it is already there.
+    async fn ensure_local_project(&self, project: &str) -> anyhow::Result<Utf8PathBuf> {
+        let (owner, repo) = split_project_path(project);
+        let host = self.client.host();
+        let repo_dir = github::local_repo_dir(&host.host, owner, repo)?;
+        if repo_dir.exists() {
+            return Ok(repo_dir);
+        }
+
+        if let Some(parent) = repo_dir.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| anyhow::anyhow!("❌ Failed to create {parent}: {e}"))?;
+        }
+        let url = host.web_url(&format!("{project}.git"));
+        Cmd::new("git", ["clone", url.as_str(), repo_dir.as_str()])
+            .run()
+            .await?
+            .ensure_success(format!("❌ Failed to clone {url}"))?;
+        Ok(repo_dir)
+    }
+
+    /// Clean `repo_dir` and pull the default branch of `project`.
+    async fn prepare_for_checkout(&self, repo_dir: &Utf8Path, project: &str) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            github::is_clean_repo(repo_dir).await?,
+            "❌ Repository is not clean. Commit or stash changes first."
+        );
+        let default_branch = self.project(project).await?.default_branch.ok_or_else(|| {
+            anyhow::anyhow!("❌ Failed to detect default branch: {project} has none")
+        })?;
+        Cmd::new("git", ["fetch", "--prune"])
+            .with_current_dir(repo_dir)
+            .run()
+            .await?
+            .ensure_success("❌ git fetch failed")?;
+        github::checkout_branch(repo_dir, &default_branch).await?;
+        github::pull_ff_only(repo_dir).await
+    }
+
+    /// Publish each anchored finding with `post`, returning the findings
+    /// left for the review body.
+    async fn publish_findings<'a>(
+        &self,
+        mr: &MergeRequestRef,
+        findings: &'a [ReviewFinding],
+        diff_refs: Option<&DiffRefs>,
+        mode: PublishMode,
+    ) -> anyhow::Result<Vec<&'a ReviewFinding>> {
+        let files = self.merge_request_diffs(mr).await?;
+        let positions = DiffPositions::parse(&unified_diff(&files));
+        let endpoint = match mode {
+            PublishMode::Submit => "discussions",
+            PublishMode::Pending => "draft_notes",
+        };
+
+        let mut unanchored = Vec::new();
+        for finding in findings {
+            let (Some(diff_refs), Some(line)) = (diff_refs, positions.line_for(finding)) else {
+                unanchored.push(finding);
+                continue;
+            };
+            let position = note_position(diff_refs, &files, finding, line);
+            let body = comment_body(finding);
+            let payload = match mode {
+                PublishMode::Submit => json!({ "body": body, "position": position }),
+                PublishMode::Pending => json!({ "note": body, "position": position }),
+            };
+            if let Err(err) = self
+                .client
+                .post::<Value>(&format!("{}/{endpoint}", mr.api_path()), &payload)
+                .await
+            {
+                eprintln!("⚠️ Failed to anchor the finding on {}: {err}", finding.file);
+                unanchored.push(finding);
+            }
+        }
+        Ok(unanchored)
+    }
+}
+
+impl Forge for GitLab {
+    fn prepare_repo<'a>(&'a self, _repo_root: &'a Utf8Path) -> ForgeFuture<'a, ()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn default_branch<'a>(&'a self, repo_root: &'a Utf8Path) -> ForgeFuture<'a, String> {
+        Box::pin(async move {
+            let project = self.base_project(repo_root).await?;
+            self.project(&project).await?.default_branch.ok_or_else(|| {
+                anyhow::anyhow!("❌ Failed to detect default branch: {project} has none")
+            })
+        })
+    }
+
+    fn branch_has_pr<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        branch: &'a str,
+    ) -> ForgeFuture<'a, bool> {
+        Box::pin(async move {
+            Ok(!self
+                .branch_merge_requests(repo_root, branch, "all")
+                .await?
+                .is_empty())
+        })
+    }
+
+    fn pr_for_branch<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        branch: &'a str,
+    ) -> ForgeFuture<'a, Option<BranchPr>> {
+        Box::pin(async move {
+            let Some(mr) = self
+                .branch_merge_requests(repo_root, branch, "opened")
+                .await?
+                .into_iter()
+                .next()
+            else {
+                return Ok(None);
+            };
+            let mr_ref = self.merge_request_ref(&mr.web_url)?;
+            let (owner, repo) = split_project_path(&mr_ref.project);
+            Ok(Some(BranchPr {
+                number: mr.iid,
+                title: mr.title,
+                base_ref: mr.target_branch,
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+                url: mr.web_url,
+            }))
+        })
+    }
+
+    fn open_pr_in_browser<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        branch: &'a str,
+        title: &'a str,
+    ) -> ForgeFuture<'a, ()> {
+        Box::pin(async move {
+            let existing = self
+                .branch_merge_requests(repo_root, branch, "opened")
+                .await?
+                .into_iter()
+                .next();
+            let url = match existing {
+                Some(mr) => mr.web_url,
+                None => {
+                    let origin = self.origin_project(repo_root).await?;
+                    new_merge_request_url(self.client.host(), &origin, branch, title)
+                }
+            };
+            launcher::open_url(&url).await
+        })
+    }
+
+    fn pr_details<'a>(&'a self, pr_url: &'a str) -> ForgeFuture<'a, PrDetails> {
+        Box::pin(async move {
+            let mr_ref = self.merge_request_ref(pr_url)?;
+            let project = self.project(&mr_ref.project).await?;
+            let mr = self.merge_request(&mr_ref).await?;
+            let head_project = if mr.source_project_id == mr.target_project_id {
+                None
+            } else {
+                Some(
+                    self.client
+                        .get::<Project>(&format!("projects/{}", mr.source_project_id))
+                        .await?,
+                )
+            };
+            Ok(pr_details(pr_url, &project, &mr, head_project.as_ref()))
+        })
+    }
+
+    fn pr_commits<'a>(
+        &'a self,
+        _repo_root: &'a Utf8Path,
+        pr: &'a BranchPr,
+    ) -> ForgeFuture<'a, Vec<PullRequestCommit>> {
+        Box::pin(async move {
+            let mr_ref = MergeRequestRef {
+                project: format!("{}/{}", pr.owner, pr.repo),
+                iid: pr.number,
+            };
+            let commits: Vec<Commit> = self
+                .client
+                .get_pages(&format!("{}/commits", mr_ref.api_path()))
+                .await?;
+            // GitLab lists the newest commit first, GitHub the oldest.
+            Ok(commits
+                .into_iter()
+                .rev()
+                .map(|commit| {
+                    PullRequestCommit::new(
+                        commit.id,
+                        commit.author_name,
+                        commit.author_email,
+                        commit.message,
+                    )
+                })
+                .collect())
+        })
+    }
+
+    fn remote_repo_name(&self, remote_url: &str) -> Option<String> {
+        gitlab_hosts()
+            .split_remote_url(remote_url)
+            .filter(|(host, _)| host.host == self.client.host().host)
+            .map(|(_, project)| project.to_string())
+    }
+
+    fn clone_url<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        owner: &'a str,
+        repo: &'a str,
+    ) -> ForgeFuture<'a, String> {
+        Box::pin(async move {
+            let origin = super::remote_url(repo_root, "origin")
+                .await?
+                .unwrap_or_default();
+            Ok(clone_url_like(
+                &origin,
+                &self.client.host().host,
+                owner,
+                repo,
+            ))
+        })
+    }
+
+    fn checkout_pr<'a>(&'a self, pr_url: &'a str) -> ForgeFuture<'a, Utf8PathBuf> {
+        Box::pin(async move {
+            let mr_ref = self.merge_request_ref(pr_url)?;
+            let repo_dir = self.ensure_local_project(&mr_ref.project).await?;
+            self.prepare_for_checkout(&repo_dir, &mr_ref.project)
+                .await?;
+            let mr = self.merge_request(&mr_ref).await?;
+            let branch = self.local_branch(&mr).await?;
+            checkout_merge_request(&repo_dir, &mr, &branch).await?;
+            Ok(repo_dir)
+        })
+    }
+
+    fn fetch_pr_head<'a>(&'a self, pr_url: &'a str) -> ForgeFuture<'a, (Utf8PathBuf, String)> {
+        Box::pin(async move {
+            let mr_ref = self.merge_request_ref(pr_url)?;
+            let repo_dir = self.ensure_local_project(&mr_ref.project).await?;
+            let head = super::fetch_head(
+                &repo_dir,
+                &format!("refs/merge-requests/{}/head", mr_ref.iid),
+            )
+            .await?;
+            Ok((repo_dir, head))
+        })
+    }
+
+    fn pr_metadata<'a>(
+        &'a self,
+        _repo_root: &'a Utf8Path,
+        pr_url: &'a str,
+    ) -> ForgeFuture<'a, String> {
+        Box::pin(async move {
+            let mr_ref = self.merge_request_ref(pr_url)?;
+            let mr = self.merge_request(&mr_ref).await?;
+            let notes: Vec<Note> = self
+                .client
+                .get_pages(&format!("{}/notes?sort=asc", mr_ref.api_path()))
+                .await?;
+            let commits: Vec<Commit> = self
+                .client
+                .get_pages(&format!("{}/commits", mr_ref.api_path()))
+                .await?;
+            Ok(merge_request_metadata(&mr, &notes, &commits).to_string())
+        })
+    }
+
+    fn pr_diff<'a>(&'a self, _repo_root: &'a Utf8Path, pr_url: &'a str) -> ForgeFuture<'a, String> {
+        Box::pin(async move {
+            let mr_ref = self.merge_request_ref(pr_url)?;
+            let diff = unified_diff(&self.merge_request_diffs(&mr_ref).await?);
+            anyhow::ensure!(
+                !diff.trim().is_empty(),
+                "❌ Failed to fetch PR diff: the merge request has no changes"
+            );
+            Ok(diff)
+        })
+    }
+
+    fn incremental_diff<'a>(
+        &'a self,
+        _repo_root: &'a Utf8Path,
+        pr_url: &'a str,
+        base_sha: &'a str,
+        head_sha: &'a str,
+    ) -> ForgeFuture<'a, Option<String>> {
+        Box::pin(async move {
+            let project = project_api_path(&self.merge_request_ref(pr_url)?.project);
+            // The merge base is `base_sha` only if `head_sha` descends from
+            // it. The API fails when a force-push removed `base_sha`.
+            let merge_base = self
+                .client
+                .get::<MergeBase>(&format!(
+                    "{project}/repository/merge_base?refs%5B%5D={base_sha}&refs%5B%5D={head_sha}"
+                ))
+                .await;
+            if !merge_base.is_ok_and(|merge_base| merge_base.id == base_sha) {
+                return Ok(None);
+            }
+
+            let comparison: Comparison = self
+                .client
+                .get(&format!(
+                    "{project}/repository/compare?from={base_sha}&to={head_sha}&straight=true"
+                ))
+                .await?;
+            Ok(Some(unified_diff(&comparison.diffs)))
+        })
+    }
+
+    fn publish_review<'a>(
+        &'a self,
+        _repo_root: &'a Utf8Path,
+        pr_url: &'a str,
+        review: ReviewToPublish<'a>,
+    ) -> ForgeFuture<'a, String> {
+        Box::pin(async move {
+            let mr_ref = self.merge_request_ref(pr_url)?;
+            let mr = self.merge_request(&mr_ref).await?;
+            let unanchored = self
+                .publish_findings(&mr_ref, review.findings, mr.diff_refs.as_ref(), review.mode)
+                .await?;
+            let body = review_body(review.summary, &unanchored);
+
+            match review.mode {
+                PublishMode::Pending => {
+                    self.client
+                        .post::<Value>(
+                            &format!("{}/draft_notes", mr_ref.api_path()),
+                            &json!({ "note": body }),
+                        )
+                        .await?;
+                    Ok(mr.web_url)
+                }
+                PublishMode::Submit => {
+                    let note: Note = self
+                        .client
+                        .post(
+                            &format!("{}/notes", mr_ref.api_path()),
+                            &json!({ "body": body }),
+                        )
+                        .await?;
+                    // GitLab has no "request changes" review outside of its
+                    // paid tiers: the body carries the verdict.
+                    if review.verdict == Verdict::Approve {
+                        self.client
+                            .post::<Value>(&format!("{}/approve", mr_ref.api_path()), &json!({}))
+                            .await?;
+                    }
+                    Ok(format!("{}#note_{}", mr.web_url, note.id))
+                }
+            }
+        })
+    }
+}
+
+/// Parse the path of a merge request URL after the host, e.g.
+/// `group/sub/project/-/merge_requests/7/diffs`.
+fn parse_merge_request_path(path: &str) -> Option<MergeRequestRef> {
+    let path = path.split(['?', '#']).next()?;
+    let (project, rest) = path.split_once("/merge_requests/")?;
+    let project = project
+        .strip_suffix("/-")
+        .unwrap_or(project)
+        .trim_matches('/');
+    if project.split('/').filter(|part| !part.is_empty()).count() < 2 {
+        return None;
+    }
+    let iid = rest.split('/').next()?.parse().ok()?;
+    Some(MergeRequestRef {
+        project: project.to_string(),
+        iid,
+    })
+}
+
+/// Namespace and name of a project path. Nested groups stay in the
+/// namespace.
+fn split_project_path(project: &str) -> (&str, &str) {
+    project.rsplit_once('/').unwrap_or(("", project))
+}
+
+fn pr_details(
+    pr_url: &str,
+    project: &Project,
+    mr: &MergeRequest,
+    head_project: Option<&Project>,
+) -> PrDetails {
+    let (owner, repo) = split_project_path(&project.path_with_namespace);
+    let (head_owner, head_repo) =
+        split_project_path(&head_project.unwrap_or(project).path_with_namespace);
+    let state = match mr.state.as_str() {
+        "merged" => "MERGED",
+        "closed" => "CLOSED",
+        _ => "OPEN",
+    };
+    PrDetails {
+        pr_url: pr_url.to_string(),
+        owner: owner.to_string(),
+        repo: repo.to_string(),
+        number: i64::try_from(mr.iid).unwrap_or(i64::MAX),
+        state: state.to_string(),
+        merge_queue_state: None,
+        auto_merge: false,
+        title: mr.title.clone(),
+        head_ref: mr.source_branch.clone(),
+        base_ref: mr.target_branch.clone(),
+        head_sha: mr.sha.clone().unwrap_or_default(),
+        created_at: mr.created_at.clone(),
+        updated_at: mr.updated_at.clone(),
+        is_archived: project.archived,
+        author_login: mr.author.as_ref().map(|author| author.username.clone()),
+        head_repo_owner: Some(head_owner.to_string()),
+        head_repo_name: Some(head_repo.to_string()),
+        is_cross_repository: head_project.is_some(),
+        is_draft: mr.draft,
+    }
+}
+
+/// The merge request in the shape of `gh pr view --json`, so reviews see
+/// the same metadata on both forges.
+fn merge_request_metadata(mr: &MergeRequest, notes: &[Note], commits: &[Commit]) -> Value {
+    let login = |username: &str| json!({ "login": username });
+    json!({
+        "title": mr.title,
+        "body": mr.description.as_deref().unwrap_or_default(),
+        "author": mr.author.as_ref().map(|author| login(&author.username)),
+        "baseRefName": mr.target_branch,
+        "headRefName": mr.source_branch,
+        "headRefOid": mr.sha,
+        "createdAt": mr.created_at,
+        "updatedAt": mr.updated_at,
+        "assignees": mr
+            .assignees
+            .iter()
+            .map(|assignee| login(&assignee.username))
+            .collect::<Vec<_>>(),
+        "comments": notes
+            .iter()
+            .filter(|note| !note.system)
+            .map(|note| json!({ "author": login(&note.author.username), "body": note.body }))
+            .collect::<Vec<_>>(),
+        "commits": commits
+            .iter()
+            .rev()
+            .map(|commit| json!({ "oid": commit.id, "messageHeadline": commit.title }))
+            .collect::<Vec<_>>(),
+        "url": mr.web_url,
+    })
+}
+
+/// Unified diff with `git diff` headers of the files GitLab returns
+/// without them.
+fn unified_diff(files: &[FileDiff]) -> String {
+    let mut diff = String::new();
+    for file in files {
+        diff.push_str(&format!(
+            "diff --git a/{} b/{}\n",
+            file.old_path, file.new_path
+        ));
+        if file.new_file {
+            let mode = file.b_mode.as_deref().unwrap_or("100644");
+            diff.push_str(&format!("new file mode {mode}\n"));
+        } else if file.deleted_file {
+            let mode = file.a_mode.as_deref().unwrap_or("100644");
+            diff.push_str(&format!("deleted file mode {mode}\n"));
+        } else if file.renamed_file {
+            diff.push_str(&format!(
+                "rename from {}\nrename to {}\n",
+                file.old_path, file.new_path
+            ));
+        }
+        // Binary files, pure renames and collapsed diffs have no hunks.
+        if file.diff.is_empty() {
+            continue;
+        }
+
+        let old = if file.new_file {
+            "/dev/null".to_string()
+        } else {
+            format!("a/{}", file.old_path)
+        };
+        let new = if file.deleted_file {
+            "/dev/null".to_string()
+        } else {
+            format!("b/{}", file.new_path)
+        };
+        diff.push_str(&format!("--- {old}\n+++ {new}\n{}", file.diff));
+        if !file.diff.ends_with('\n') {
+            diff.push('\n');
+        }
+    }
+    diff
+}
+
+/// `position` of a comment on `line`: GitLab needs the old line of
+/// unchanged lines too.
+fn note_position(
+    diff_refs: &DiffRefs,
+    files: &[FileDiff],
+    finding: &ReviewFinding,
+    line: DiffLine,
+) -> Value {
+    let old_path = files
+        .iter()
+        .find(|file| file.new_path == finding.file)
+        .map_or(finding.file.as_str(), |file| file.old_path.as_str());
+    let mut position = json!({
+        "position_type": "text",
+        "base_sha": diff_refs.base_sha,
+        "start_sha": diff_refs.start_sha,
+        "head_sha": diff_refs.head_sha,
+        "old_path": old_path,
+        "new_path": finding.file,
+        "new_line": line.new_line,
+    });
+    if let Some(old_line) = line.old_line {
+        position["old_line"] = json!(old_line);
+    }
+    position
+}
+
+/// Page creating a merge request from `branch` of `project`.
+fn new_merge_request_url(host: &GitLabHost, project: &str, branch: &str, title: &str) -> String {
+    host.web_url(&format!(
+        "{project}/-/merge_requests/new?merge_request%5Bsource_branch%5D={}&merge_request%5Btitle%5D={}",
+        encode_component(branch),
+        encode_component(title)
+    ))
+}
+
+/// Clone URL of `owner/repo` using the protocol of the `origin` remote.
+fn clone_url_like(origin_url: &str, host: &str, owner: &str, repo: &str) -> String {
+    if origin_url.starts_with("https://") || origin_url.starts_with("http://") {
+        format!("https://{host}/{owner}/{repo}.git")
+    } else {
+        format!("git@{host}:{owner}/{repo}.git")
+    }
+}
+
+/// Local branch for the source branch of a fork, prefixed with the
+/// namespace of the fork: its `main` must not reset the local `main`.
+fn fork_branch(fork_path: &str, branch: &str) -> String {
+    let namespace = fork_path
+        .rsplit_once('/')
+        .map_or(fork_path, |(namespace, _)| namespace);
+    format!("{}-{branch}", namespace.replace('/', "-"))
+}
+
+/// Check out the head of `mr` on `branch`. The target project exposes it as
+/// `refs/merge-requests/IID/head`, forks included.
+async fn checkout_merge_request(
+    repo_dir: &Utf8Path,
+    mr: &MergeRequest,
+    branch: &str,
+) -> anyhow::Result<()> {
+    let head_ref = format!("refs/merge-requests/{}/head", mr.iid);
+    Cmd::new("git", ["fetch", "origin", head_ref.as_str()])
+        .with_current_dir(repo_dir)
+        .run()
+        .await?
+        .ensure_success(format!("❌ Failed to fetch {head_ref}"))?;
+
+    let local_ref = format!("refs/heads/{branch}");
+    let has_branch = Cmd::new(
+        "git",
+        ["rev-parse", "--verify", "--quiet", local_ref.as_str()],
+    )
+    .with_current_dir(repo_dir)
+    .hide_stdout()
+    .run()
+    .await?
+    .status()
+    .success();
+    if has_branch {
+        let is_behind = Cmd::new(
+            "git",
+            [
+                "merge-base",
+                "--is-ancestor",
+                local_ref.as_str(),
+                "FETCH_HEAD",
+            ],
+        )
+        .with_current_dir(repo_dir)
+        .run()
+        .await?
+        .status()
+        .success();
+        anyhow::ensure!(
+            is_behind,
+            "❌ Local branch '{branch}' has commits that are not in the merge request"
+        );
+    }
+
+    Cmd::new("git", ["checkout", "-B", branch, "FETCH_HEAD"])
+        .with_current_dir(repo_dir)
+        .run()
+        .await?
+        .ensure_success(format!("❌ Failed to checkout branch '{branch}'"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{
+        Json, Router,
+        extract::{Path, State},
+        http::HeaderMap,
+        response::IntoResponse,
+        routing::{get, post},
+    };
+
+    use crate::{review::Severity, test_util::spawn_stub};
+
+    use super::*;
+
+    const MR_URL: &str = "https://gitlab.example.com/group/sub/project/-/merge_requests/7";
+
+    type Posts = Arc<Mutex<Vec<(String, Value)>>>;
+
+    async fn stub_gitlab(router: Router) -> GitLab {
+        let host = GitLabHost {
+            api_url: spawn_stub(router).await,
+            ..gitlab_hosts().find("gitlab.example.com").unwrap().clone()
+        };
+        GitLab {
+            client: GitLabClient::new(host, "test-token".to_string()).unwrap(),
+        }
+    }
+
+    fn project(id: u64, path: &str) -> Value {
+        json!({
+            "id": id,
+            "path_with_namespace": path,
+            "default_branch": "main",
+            "archived": false
+        })
+    }
+
+    fn merge_request(source_project_id: u64) -> Value {
+        json!({
+            "iid": 7,
+            "title": "Add the widget",
+            "description": "Adds it",
+            "state": "opened",
+            "source_branch": "widget",
+            "target_branch": "main",
+            "sha": "head",
+            "web_url": MR_URL,
+            "created_at": "2026-10-01T10:00:00Z",
+            "updated_at": "2026-10-02T10:00:00Z",
+            "author": { "username": "contributor" },
+            "assignees": [{ "username": "maintainer" }],
+            "source_project_id": source_project_id,
+            "target_project_id": 1,
+            "draft": true,
+            "diff_refs": { "base_sha": "base", "head_sha": "head", "start_sha": "start" }
+        })
+    }
+
+    async fn projects(Path(path): Path<String>) -> Json<Value> {
+        Json(match path.as_str() {
+            "2" => project(2, "contributor/project"),
+            _ => {
+                assert_eq!(path, "group/sub/project");
+                project(1, "group/sub/project")
+            }
+        })
+    }
+
+    fn file_diffs() -> Value {
+        json!([
+            {
+                "old_path": "src/lib.rs",
+                "new_path": "src/lib.rs",
+                "a_mode": "100644",
+                "b_mode": "100644",
+                "diff": "@@ -1,2 +1,3 @@\n fn a() {}\n+fn b() {}\n fn c() {}\n",
+                "new_file": false,
+                "renamed_file": false,
+                "deleted_file": false
+            },
+            {
+                "old_path": "README.md",
+                "new_path": "README.md",
+                "a_mode": "0",
+                "b_mode": "100644",
+                "diff": "@@ -0,0 +1 @@\n+# Widget\n",
+                "new_file": true,
+                "renamed_file": false,
+                "deleted_file": false
+            }
+        ])
+    }
+
+    fn finding(file: &str, line: u32) -> ReviewFinding {
+        ReviewFinding {
+            file: file.to_string(),
+            start_line: line,
+            end_line: line,
+            severity: Severity::Major,
+            category: "correctness".to_string(),
+            suggestion: format!("Check line {line}"),
+            status: None,
+        }
+    }
+
+    #[test]
+    fn merge_request_paths_are_parsed() {
+        for path in [
+            "group/sub/project/-/merge_requests/7",
+            "group/sub/project/merge_requests/7",
+            "group/sub/project/-/merge_requests/7/diffs?view=inline#note_1",
+        ] {
+            assert_eq!(
+                parse_merge_request_path(path),
+                Some(MergeRequestRef {
+                    project: "group/sub/project".to_string(),
+                    iid: 7
+                }),
+                "{path}"
+            );
+        }
+        assert_eq!(parse_merge_request_path("project/-/merge_requests/7"), None);
+        assert_eq!(parse_merge_request_path("group/project/-/issues/7"), None);
+    }
+
+    #[tokio::test]
+    async fn merge_requests_from_forks_get_a_namespaced_local_branch() {
+        let gitlab = stub_gitlab(Router::new().route("/projects/{project}", get(projects))).await;
+        let from_fork: MergeRequest = serde_json::from_value(merge_request(2)).unwrap();
+        assert_eq!(
+            gitlab.local_branch(&from_fork).await.unwrap(),
+            "contributor-widget"
+        );
+        let same_project: MergeRequest = serde_json::from_value(merge_request(1)).unwrap();
+        assert_eq!(gitlab.local_branch(&same_project).await.unwrap(), "widget");
+        assert_eq!(fork_branch("group/sub/project", "main"), "group-sub-main");
+    }
+
+    #[tokio::test]
+    async fn merge_requests_from_forks_map_onto_pr_details() {
+        async fn merge_request_route(headers: HeaderMap) -> Json<Value> {
+            assert_eq!(headers["private-token"], "test-token");
+            Json(merge_request(2))
+        }
+
+        let gitlab = stub_gitlab(
+            Router::new()
+                .route("/projects/{project}", get(projects))
+                .route(
+                    "/projects/{project}/merge_requests/{iid}",
+                    get(merge_request_route),
+                ),
+        )
+        .await;
+
+        let details = gitlab.pr_details(MR_URL).await.unwrap();
+        assert_eq!(details.owner, "group/sub");
+        assert_eq!(details.repo, "project");
+        assert_eq!(details.number, 7);
+        assert_eq!(details.state, "OPEN");
+        assert_eq!(details.head_ref, "widget");
+        assert_eq!(details.base_ref, "main");
+        assert_eq!(details.head_sha, "head");
+        assert_eq!(details.author_login.as_deref(), Some("contributor"));
+        assert_eq!(details.head_repo_owner.as_deref(), Some("contributor"));
+        assert_eq!(details.head_repo_name.as_deref(), Some("project"));
+        assert!(details.is_cross_repository);
+        assert!(details.is_draft);
+    }
+
+    #[tokio::test]
+    async fn merge_request_commits_follow_pages_oldest_first() {
+        async fn commits(
+            axum::extract::Query(query): axum::extract::Query<
+                std::collections::HashMap<String, String>,
+            >,
+        ) -> impl IntoResponse {
+            let commit = |id: &str| {
+                json!({
+                    "id": id,
+                    "title": format!("commit {id}"),
+                    "message": format!("commit {id}\n\nbody"),
+                    "author_name": "Contributor",
+                    "author_email": "contributor@example.com"
+                })
+            };
+            let mut headers = HeaderMap::new();
+            if query.get("page").map(String::as_str) == Some("2") {
+                return (headers, Json(json!([commit("first")])));
+            }
+            headers.insert("x-next-page", "2".parse().unwrap());
+            (headers, Json(json!([commit("second")])))
+        }
+
+        let gitlab = stub_gitlab(Router::new().route(
+            "/projects/{project}/merge_requests/{iid}/commits",
+            get(commits),
+        ))
+        .await;
+
+        let pr = BranchPr {
+            number: 7,
+            title: "Add the widget".to_string(),
+            base_ref: "main".to_string(),
+            url: MR_URL.to_string(),
+            owner: "group/sub".to_string(),
+            repo: "project".to_string(),
+        };
+        let commits = gitlab.pr_commits(Utf8Path::new("."), &pr).await.unwrap();
+        let hashes: Vec<String> = crate::authors::get_commits_to_squash(&commits)
+            .into_iter()
+            .map(|commit| commit.hash)
+            .collect();
+        assert_eq!(hashes, ["first", "second"]);
+    }
+
+    #[tokio::test]
+    async fn merge_request_diff_and_metadata_look_like_github() {
+        async fn notes() -> Json<Value> {
+            Json(json!([
+                { "id": 1, "body": "added 1 commit", "author": { "username": "bot" }, "system": true },
+                { "id": 2, "body": "Looks good", "author": { "username": "maintainer" }, "system": false }
+            ]))
+        }
+        async fn commits() -> Json<Value> {
+            Json(json!([{
+                "id": "abc",
+                "title": "Add the widget",
+                "message": "Add the widget",
+                "author_name": "Contributor",
+                "author_email": "contributor@example.com"
+            }]))
+        }
+
+        let gitlab = stub_gitlab(
+            Router::new()
+                .route(
+                    "/projects/{project}/merge_requests/{iid}",
+                    get(async || Json(merge_request(1))),
+                )
+                .route(
+                    "/projects/{project}/merge_requests/{iid}/diffs",
+                    get(async || Json(file_diffs())),
+                )
+                .route("/projects/{project}/merge_requests/{iid}/notes", get(notes))
+                .route(
+                    "/projects/{project}/merge_requests/{iid}/commits",
+                    get(commits),
+                ),
+        )
+        .await;
+
+        let diff = gitlab.pr_diff(Utf8Path::new("."), MR_URL).await.unwrap();
+        assert_eq!(
+            diff,
+            "diff --git a/src/lib.rs b/src/lib.rs\n\
+             --- a/src/lib.rs\n\
+             +++ b/src/lib.rs\n\
+             @@ -1,2 +1,3 @@\n fn a() {}\n+fn b() {}\n fn c() {}\n\
+             diff --git a/README.md b/README.md\n\
+             new file mode 100644\n\
+             --- /dev/null\n\
+             +++ b/README.md\n\
+             @@ -0,0 +1 @@\n+# Widget\n"
+        );
+
+        let metadata: Value = serde_json::from_str(
+            &gitlab
+                .pr_metadata(Utf8Path::new("."), MR_URL)
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(metadata["author"]["login"], "contributor");
+        assert_eq!(metadata["baseRefName"], "main");
+        assert_eq!(metadata["headRefOid"], "head");
+        assert_eq!(metadata["assignees"][0]["login"], "maintainer");
+        assert_eq!(
+            metadata["comments"],
+            json!([{ "author": { "login": "maintainer" }, "body": "Looks good" }])
+        );
+        assert_eq!(
+            metadata["commits"],
+            json!([{ "oid": "abc", "messageHeadline": "Add the widget" }])
+        );
+    }
+
+    #[tokio::test]
+    async fn submitted_reviews_anchor_findings_and_approve() {
+        async fn record(
+            State(posts): State<Posts>,
+            Path((_, _, endpoint)): Path<(String, String, String)>,
+            Json(body): Json<Value>,
+        ) -> Json<Value> {
+            posts.lock().unwrap().push((endpoint, body));
+            Json(json!({
+                "id": 42,
+                "body": "",
+                "author": { "username": "reviewer" },
+                "system": false
+            }))
+        }
+
+        let posts = Posts::default();
+        let gitlab = stub_gitlab(
+            Router::new()
+                .route(
+                    "/projects/{project}/merge_requests/{iid}",
+                    get(async || Json(merge_request(1))),
+                )
+                .route(
+                    "/projects/{project}/merge_requests/{iid}/diffs",
+                    get(async || Json(file_diffs())),
+                )
+                .route(
+                    "/projects/{project}/merge_requests/{iid}/{endpoint}",
+                    post(record),
+                )
+                .with_state(posts.clone()),
+        )
+        .await;
+
+        let findings = [
+            finding("src/lib.rs", 2),
+            finding("src/lib.rs", 3),
+            finding("src/other.rs", 1),
+        ];
+        let url = gitlab
+            .publish_review(
+                Utf8Path::new("."),
+                MR_URL,
+                ReviewToPublish {
+                    summary: "Nice",
+                    verdict: Verdict::Approve,
+                    findings: &findings,
+                    mode: PublishMode::Submit,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(url, format!("{MR_URL}#note_42"));
+
+        let posts = posts.lock().unwrap();
+        let endpoints: Vec<&str> = posts
+            .iter()
+            .map(|(endpoint, _)| endpoint.as_str())
+            .collect();
+        assert_eq!(
+            endpoints,
+            ["discussions", "discussions", "notes", "approve"]
+        );
+        assert_eq!(
+            posts[0].1["position"],
+            json!({
+                "position_type": "text",
+                "base_sha": "base",
+                "start_sha": "start",
+                "head_sha": "head",
+                "old_path": "src/lib.rs",
+                "new_path": "src/lib.rs",
+                "new_line": 2
+            })
+        );
+        assert_eq!(posts[1].1["position"]["new_line"], 3);
+        assert_eq!(posts[1].1["position"]["old_line"], 2);
+        let body = posts[2].1["body"].as_str().unwrap();
+        assert!(body.starts_with("Nice"));
+        assert!(body.contains("`src/other.rs:1`"));
+    }
+
+    #[tokio::test]
+    async fn pending_reviews_are_draft_notes() {
+        async fn record(
+            State(posts): State<Posts>,
+            Path((_, _, endpoint)): Path<(String, String, String)>,
+            Json(body): Json<Value>,
+        ) -> Json<Value> {
+            posts.lock().unwrap().push((endpoint, body));
+            Json(json!({ "id": 1 }))
+        }
+
+        let posts = Posts::default();
+        let gitlab = stub_gitlab(
+            Router::new()
+                .route(
+                    "/projects/{project}/merge_requests/{iid}",
+                    get(async || Json(merge_request(1))),
+                )
+                .route(
+                    "/projects/{project}/merge_requests/{iid}/diffs",
+                    get(async || Json(file_diffs())),
+                )
+                .route(
+                    "/projects/{project}/merge_requests/{iid}/{endpoint}",
+                    post(record),
+                )
+                .with_state(posts.clone()),
+        )
+        .await;
+
+        let findings = [finding("README.md", 1)];
+        let url = gitlab
+            .publish_review(
+                Utf8Path::new("."),
+                MR_URL,
+                ReviewToPublish {
+                    summary: "Draft",
+                    verdict: Verdict::RequestChanges,
+                    findings: &findings,
+                    mode: PublishMode::Pending,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(url, MR_URL);
+
+        let posts = posts.lock().unwrap();
+        let endpoints: Vec<&str> = posts
+            .iter()
+            .map(|(endpoint, _)| endpoint.as_str())
+            .collect();
+        assert_eq!(endpoints, ["draft_notes", "draft_notes"]);
+        assert_eq!(posts[0].1["position"]["new_path"], "README.md");
+        assert!(
+            posts[0].1["note"]
+                .as_str()
+                .unwrap()
+                .contains("Check line 1")
+        );
+        assert_eq!(posts[1].1, json!({ "note": "Draft" }));
+    }
+
+    #[tokio::test]
+    async fn incremental_diff_is_none_after_a_force_push() {
+        let gitlab = stub_gitlab(Router::new().route(
+            "/projects/{project}/repository/merge_base",
+            get(async || Json(json!({ "id": "older" }))),
+        ))
+        .await;
+
+        assert_eq!(
+            gitlab
+                .incremental_diff(Utf8Path::new("."), MR_URL, "base", "head")
+                .await
+                .unwrap(),
+            None
+        );
+    }
+}
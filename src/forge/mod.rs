@@ -0,0 +1,192 @@
+mod forgejo;
+mod github;
+mod gitlab;
+
+use std::pin::Pin;
+
+use camino::{Utf8Path, Utf8PathBuf};
+
+pub use forgejo::{ForgejoClient, forgejo_hosts};
+pub use github::GitHub;
+pub use gitlab::{GitLab, configure_gitlab_hosts};
+
+use crate::{
+    authors::PullRequestCommit,
+    cmd::Cmd,
+    github::PrDetails,
+    review::{PublishMode, ReviewFinding, Verdict},
+};
+
+pub type ForgeFuture<'a, T> = Pin<Box<dyn Future<Output = anyhow::Result<T>> + Send + 'a>>;
+
+/// Open PR of a branch, as needed by `squash`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchPr {
+    pub number: u64,
+    pub title: String,
+    pub base_ref: String,
+    pub url: String,
+    /// Namespace of the base repository. Nested GitLab groups are joined
+    /// with `/`.
+    pub owner: String,
+    pub repo: String,
+}
+
+/// A code forge hosting the repositories and their PRs: GitHub, or GitLab
+/// where PRs are merge requests.
+///
+/// Adding a forge means implementing this trait and registering it in
+/// [`forge_for_url`] and [`forge_for_remote`].
+pub trait Forge: std::fmt::Debug + Send + Sync {
+    /// Setup of the repository of `repo_root` needed by the other calls,
+    /// e.g. the default repository of `gh`.
+    fn prepare_repo<'a>(&'a self, repo_root: &'a Utf8Path) -> ForgeFuture<'a, ()>;
+
+    fn default_branch<'a>(&'a self, repo_root: &'a Utf8Path) -> ForgeFuture<'a, String>;
+
+    /// Whether a PR was ever opened from `branch`, whatever its state.
+    fn branch_has_pr<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        branch: &'a str,
+    ) -> ForgeFuture<'a, bool>;
+
+    /// Open PR whose head is `branch`.
+    fn pr_for_branch<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        branch: &'a str,
+    ) -> ForgeFuture<'a, Option<BranchPr>>;
+
+    /// Open the PR of the pushed `branch` in the browser, or the form
+    /// creating it with `title` when there is none.
+    fn open_pr_in_browser<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        branch: &'a str,
+        title: &'a str,
+    ) -> ForgeFuture<'a, ()>;
+
+    fn pr_details<'a>(&'a self, pr_url: &'a str) -> ForgeFuture<'a, PrDetails>;
+
+    /// Commits the forge considers part of the PR.
+    fn pr_commits<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        pr: &'a BranchPr,
+    ) -> ForgeFuture<'a, Vec<PullRequestCommit>>;
+
+    /// `owner/repo` of a git remote URL of this forge.
+    fn remote_repo_name(&self, remote_url: &str) -> Option<String>;
+
+    /// URL to fetch `owner/repo` from, with the git protocol the user picked
+    /// for the forge.
+    fn clone_url<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        owner: &'a str,
+        repo: &'a str,
+    ) -> ForgeFuture<'a, String>;
+
+    /// Clone the PR repository under `~/proj` if needed, update its default
+    /// branch and check out the PR. Returns the clone.
+    fn checkout_pr<'a>(&'a self, pr_url: &'a str) -> ForgeFuture<'a, Utf8PathBuf>;
+
+    /// Clone the PR repository under `~/proj` if needed and fetch the PR head,
+    /// leaving its branches and working tree alone. Returns the clone and the
+    /// commit of the head.
+    fn fetch_pr_head<'a>(&'a self, pr_url: &'a str) -> ForgeFuture<'a, (Utf8PathBuf, String)>;
+
+    /// Description, comments and commits of the PR, as the JSON printed by
+    /// `gh pr view --json`.
+    fn pr_metadata<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        pr_url: &'a str,
+    ) -> ForgeFuture<'a, String>;
+
+    /// Unified diff of the PR.
+    fn pr_diff<'a>(&'a self, repo_root: &'a Utf8Path, pr_url: &'a str) -> ForgeFuture<'a, String>;
+
+    /// Diff of the commits pushed between `base_sha` and `head_sha`, or
+    /// `None` when `head_sha` does not descend from `base_sha` anymore.
+    fn incremental_diff<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        pr_url: &'a str,
+        base_sha: &'a str,
+        head_sha: &'a str,
+    ) -> ForgeFuture<'a, Option<String>>;
+
+    /// Publish a review of the PR, with the findings anchored on its diff.
+    /// Returns the URL of the review.
+    fn publish_review<'a>(
+        &'a self,
+        repo_root: &'a Utf8Path,
+        pr_url: &'a str,
+        review: ReviewToPublish<'a>,
+    ) -> ForgeFuture<'a, String>;
+}
+
+/// Review picked by the user to be published on the forge.
+#[derive(Debug, Clone, Copy)]
+pub struct ReviewToPublish<'a> {
+    pub summary: &'a str,
+    pub verdict: Verdict,
+    pub findings: &'a [ReviewFinding],
+    pub mode: PublishMode,
+}
+
+/// Forge of a PR URL: GitLab for the configured GitLab hosts, GitHub
+/// otherwise.
+pub fn forge_for_url(url: &str) -> anyhow::Result<Box<dyn Forge>> {
+    match gitlab::gitlab_hosts().split_web_url(url) {
+        Some((host, _)) => Ok(Box::new(GitLab::for_host(host)?)),
+        None => Ok(Box::new(GitHub)),
+    }
+}
+
+/// Forge of a git remote URL: GitLab for the configured GitLab hosts,
+/// GitHub otherwise.
+pub fn forge_for_remote(remote_url: &str) -> anyhow::Result<Box<dyn Forge>> {
+    match gitlab::gitlab_hosts().split_remote_url(remote_url) {
+        Some((host, _)) => Ok(Box::new(GitLab::for_host(host)?)),
+        None => Ok(Box::new(GitHub)),
+    }
+}
+
+/// Forge of the `origin` remote of `repo_root`.
+pub async fn forge_for_repo(repo_root: &Utf8Path) -> anyhow::Result<Box<dyn Forge>> {
+    forge_for_remote(&remote_url(repo_root, "origin").await?.unwrap_or_default())
+}
+
+/// URL of `remote`, or `None` when it is not configured.
+async fn remote_url(repo_root: &Utf8Path, remote: &str) -> anyhow::Result<Option<String>> {
+    let output = Cmd::new("git", ["remote", "get-url", remote])
+        .with_current_dir(repo_root)
+        .hide_stdout()
+        .hide_stderr()
+        .run()
+        .await?;
+    Ok(output
+        .status()
+        .success()
+        .then(|| output.stdout().to_string()))
+}
+
+/// Fetch `head_ref` from the `origin` remote of `repo_dir` and return the
+/// commit it points to.
+async fn fetch_head(repo_dir: &Utf8Path, head_ref: &str) -> anyhow::Result<String> {
+    Cmd::new("git", ["fetch", "origin", head_ref])
+        .with_current_dir(repo_dir)
+        .run()
+        .await?
+        .ensure_success(format!("❌ Failed to fetch {head_ref}"))?;
+    let output = Cmd::new("git", ["rev-parse", "FETCH_HEAD"])
+        .with_current_dir(repo_dir)
+        .hide_stdout()
+        .run()
+        .await?;
+    output.ensure_success(format!("❌ Failed to resolve the fetched {head_ref}"))?;
+    Ok(output.stdout().to_string())
+}
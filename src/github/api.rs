@@ -1,38 +1,305 @@
-use std::{collections::HashMap, fmt::Write as _};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write as _,
+};
 
 use anyhow::Context as _;
+use reqwest::Method;
 use serde_json::Value;
 
-use crate::{checkout::parse_github_pr_url, cmd::Cmd};
+use crate::checkout::parse_github_pr_url;
 
 use super::{
-    parsing::{api_url_to_html_url, api_url_to_pr_url, parse_repo_from_pr_url},
+    client::{GitHubClient, GitHubError, ResponseCache},
+    hosts::{GitHubHost, hosts},
+    parsing::{api_url_to_html_url, api_url_to_pr_url, parse_repo_from_pr_url, split_api_url},
     types::{
         AssignedIssueSummary, AssignedIssuesSearchResult, AssignedPrSummary, AuthoredPrSummary,
-        BatchFetchResult, NotificationThread, Participant, PrDetails,
+        BatchFetchResult, NotificationThread, Participant, PrCheck, PrDetails,
+        ReviewRequestedPrSummary,
     },
 };
 
-pub async fn fetch_notifications(since: Option<&str>) -> anyhow::Result<Vec<NotificationThread>> {
-    let endpoint = notifications_endpoint(since);
-    let output = Cmd::new("gh", ["api", &endpoint, "--paginate", "--slurp"])
-        .run()
-        .await?;
-    output.ensure_success("❌ Failed to fetch notifications")?;
+pub async fn fetch_notifications(
+    host: &GitHubHost,
+    since: Option<&str>,
+    cache: &mut ResponseCache,
+) -> anyhow::Result<Vec<NotificationThread>> {
+    GitHubClient::for_host(host)
+        .await?
+        .fetch_notifications(since, cache)
+        .await
+}
+
+pub async fn fetch_authored_prs(
+    host: &GitHubHost,
+    since: Option<&str>,
+    cache: &mut ResponseCache,
+) -> anyhow::Result<Vec<AuthoredPrSummary>> {
+    GitHubClient::for_host(host)
+        .await?
+        .fetch_authored_prs(since, cache)
+        .await
+}
+
+pub async fn fetch_assigned_prs(
+    host: &GitHubHost,
+    cache: &mut ResponseCache,
+) -> anyhow::Result<Vec<AssignedPrSummary>> {
+    GitHubClient::for_host(host)
+        .await?
+        .fetch_assigned_prs(cache)
+        .await
+}
+
+/// `cache` is for the search of all the review requests, `direct_cache`
+/// for the search of the requests sent to you.
+pub async fn fetch_review_requested_prs(
+    host: &GitHubHost,
+    cache: &mut ResponseCache,
+    direct_cache: &mut ResponseCache,
+) -> anyhow::Result<Vec<ReviewRequestedPrSummary>> {
+    GitHubClient::for_host(host)
+        .await?
+        .fetch_review_requested_prs(cache, direct_cache)
+        .await
+}
+
+pub async fn fetch_assigned_issues(
+    host: &GitHubHost,
+    cache: &mut ResponseCache,
+) -> anyhow::Result<AssignedIssuesSearchResult> {
+    GitHubClient::for_host(host)
+        .await?
+        .fetch_assigned_issues(cache)
+        .await
+}
+
+/// Fetch details for multiple PRs and issue states using batched GraphQL calls,
+/// one batch per host.
+/// `pr_urls` are HTML PR URLs like `https://github.com/owner/repo/pull/123`.
+/// `issue_api_urls` are REST API URLs like `https://api.github.com/repos/o/r/issues/42`.
+pub async fn fetch_batch(
+    pr_urls: &[String],
+    issue_api_urls: &[String],
+    discussion_api_urls: &[String],
+) -> anyhow::Result<BatchFetchResult> {
+    let mut result = BatchFetchResult::default();
+    for host in hosts().iter() {
+        let on_host = |urls: &[String], host_of: fn(&str) -> Option<String>| -> Vec<String> {
+            urls.iter()
+                .filter(|url| host_of(url).as_deref() == Some(host.host.as_str()))
+                .cloned()
+                .collect()
+        };
+        let host_pr_urls = on_host(pr_urls, |url| {
+            parse_github_pr_url(url).ok().map(|pr| pr.host)
+        });
+        let api_host = |url: &str| split_api_url(url).map(|(host, _)| host.host.clone());
+        let host_issue_api_urls = on_host(issue_api_urls, api_host);
+        let host_discussion_api_urls = on_host(discussion_api_urls, api_host);
+        if host_pr_urls.is_empty()
+            && host_issue_api_urls.is_empty()
+            && host_discussion_api_urls.is_empty()
+        {
+            continue;
+        }
+
+        let batch = GitHubClient::for_host(host)
+            .await?
+            .fetch_batch(
+                &host_pr_urls,
+                &host_issue_api_urls,
+                &host_discussion_api_urls,
+            )
+            .await?;
+        result.extend(batch);
+    }
+    Ok(result)
+}
+
+pub async fn mark_notification_read(thread_id: &str) -> anyhow::Result<()> {
+    let (host, thread_id) = split_thread_id(thread_id)?;
+    GitHubClient::for_host(host)
+        .await?
+        .request(Method::PATCH, &notification_thread_endpoint(thread_id))
+        .await
+        .context("❌ Failed to mark notification thread as read")
+}
+
+pub async fn mark_notification_done(thread_id: &str) -> anyhow::Result<()> {
+    let (host, thread_id) = split_thread_id(thread_id)?;
+    GitHubClient::for_host(host)
+        .await?
+        .request(Method::DELETE, &notification_thread_endpoint(thread_id))
+        .await
+        .context("❌ Failed to mark notification thread as done")
+}
+
+/// Host and GitHub id of a notification thread. The ids of the threads of
+/// GitHub Enterprise Server hosts are prefixed by `{host}:`, because ids
+/// are only unique within a host.
+fn split_thread_id(thread_id: &str) -> anyhow::Result<(&'static GitHubHost, &str)> {
+    match thread_id.split_once(':') {
+        Some((host, id)) => {
+            let host = hosts()
+                .find(host)
+                .ok_or_else(|| anyhow::anyhow!("❌ Unknown GitHub host '{host}'"))?;
+            Ok((host, id))
+        }
+        None => Ok((hosts().github_com(), thread_id)),
+    }
+}
+
+fn notification_thread_endpoint(thread_id: &str) -> String {
+    format!(
+        "/notifications/threads/{}",
+        encode_query_component(thread_id)
+    )
+}
 
-    if output.stdout().trim().is_empty() {
-        return Ok(Vec::new());
+impl GitHubClient {
+    pub async fn fetch_notifications(
+        &self,
+        since: Option<&str>,
+        cache: &mut ResponseCache,
+    ) -> anyhow::Result<Vec<NotificationThread>> {
+        let pages = self
+            .get_pages(&notifications_endpoint(since), None, cache)
+            .await
+            .context("❌ Failed to fetch notifications")?;
+        let mut notifications = parse_notifications(pages);
+        if !self.host().is_github_com() {
+            for notification in &mut notifications {
+                notification.thread_id = format!("{}:{}", self.host().host, notification.thread_id);
+            }
+        }
+        Ok(notifications)
     }
 
-    let value: Value =
-        serde_json::from_str(output.stdout()).context("Invalid notifications JSON")?;
+    pub async fn fetch_authored_prs(
+        &self,
+        since: Option<&str>,
+        cache: &mut ResponseCache,
+    ) -> anyhow::Result<Vec<AuthoredPrSummary>> {
+        let mut query = "is:pr author:@me".to_string();
+        if let Some(since) = since {
+            query.push_str(&format!(" updated:>={since}"));
+        }
+        let prs = self
+            .search_prs(&query, cache)
+            .await
+            .context("❌ Failed to fetch authored pull requests")?;
+        Ok(prs
+            .into_iter()
+            .map(|pr| AuthoredPrSummary {
+                pr_url: pr.pr_url,
+                repository: pr.repository,
+                title: pr.title,
+                updated_at: pr.updated_at,
+                is_open: pr.is_open,
+                is_draft: pr.is_draft,
+            })
+            .collect())
+    }
 
-    let pages: Vec<Value> = match value {
-        Value::Array(items) if items.iter().all(Value::is_array) => items,
-        Value::Array(items) => vec![Value::Array(items)],
-        _ => vec![],
-    };
+    pub async fn fetch_assigned_prs(
+        &self,
+        cache: &mut ResponseCache,
+    ) -> anyhow::Result<Vec<AssignedPrSummary>> {
+        let prs = self
+            .search_prs("is:pr assignee:@me state:open", cache)
+            .await
+            .context("❌ Failed to fetch assigned pull requests")?;
+        Ok(prs
+            .into_iter()
+            .map(|pr| AssignedPrSummary {
+                pr_url: pr.pr_url,
+                repository: pr.repository,
+                title: pr.title,
+                updated_at: pr.updated_at,
+                is_draft: pr.is_draft,
+            })
+            .collect())
+    }
 
+    /// Open PRs waiting for your review. `review-requested:@me` includes the
+    /// requests sent to your teams, `user-review-requested:@me` only the ones
+    /// sent to you: the PRs missing from the second are team requests.
+    pub async fn fetch_review_requested_prs(
+        &self,
+        cache: &mut ResponseCache,
+        direct_cache: &mut ResponseCache,
+    ) -> anyhow::Result<Vec<ReviewRequestedPrSummary>> {
+        let prs = self
+            .search_prs("is:pr review-requested:@me state:open", cache)
+            .await
+            .context("❌ Failed to fetch review-requested pull requests")?;
+        let direct_pr_urls: HashSet<_> = self
+            .search_prs("is:pr user-review-requested:@me state:open", direct_cache)
+            .await
+            .context("❌ Failed to fetch review requests sent to you")?
+            .into_iter()
+            .map(|pr| pr.pr_url)
+            .collect();
+        Ok(prs
+            .into_iter()
+            .map(|pr| ReviewRequestedPrSummary {
+                team_request: !direct_pr_urls.contains(&pr.pr_url),
+                pr_url: pr.pr_url,
+                repository: pr.repository,
+                title: pr.title,
+                updated_at: pr.updated_at,
+                is_draft: pr.is_draft,
+            })
+            .collect())
+    }
+
+    pub async fn fetch_assigned_issues(
+        &self,
+        cache: &mut ResponseCache,
+    ) -> anyhow::Result<AssignedIssuesSearchResult> {
+        let search_query = encode_query_component("assignee:@me state:open is:issue");
+        let endpoint = format!("search/issues?q={search_query}&per_page=100");
+        let pages = self
+            .get_pages(&endpoint, None, cache)
+            .await
+            .context("❌ Failed to fetch assigned issues")?;
+        Ok(parse_assigned_issues_search_results(&Value::Array(pages)))
+    }
+
+    /// PRs matching a search `query`, most recently updated first.
+    async fn search_prs(
+        &self,
+        query: &str,
+        cache: &mut ResponseCache,
+    ) -> Result<Vec<SearchedPr>, GitHubError> {
+        let endpoint = format!(
+            "search/issues?q={}&sort=updated&order=desc&per_page=100",
+            encode_query_component(query)
+        );
+        let pages = self
+            .get_pages(&endpoint, Some(SEARCH_MAX_PAGES), cache)
+            .await?;
+        Ok(parse_pr_search_results(&pages))
+    }
+}
+
+/// Searches stop after this many pages of 100 results.
+const SEARCH_MAX_PAGES: usize = 2;
+
+/// PR found by the search API.
+#[derive(Debug)]
+struct SearchedPr {
+    pr_url: String,
+    repository: String,
+    title: String,
+    updated_at: String,
+    is_open: bool,
+    is_draft: bool,
+}
+
+fn parse_notifications(pages: Vec<Value>) -> Vec<NotificationThread> {
     let mut results = Vec::new();
     for page in pages {
         let Value::Array(entries) = page else {
@@ -124,7 +391,7 @@ pub async fn fetch_notifications(since: Option<&str>) -> anyhow::Result<Vec<Noti
         }
     }
 
-    Ok(results)
+    results
 }
 
 fn notifications_endpoint(since: Option<&str>) -> String {
@@ -156,198 +423,60 @@ fn encode_query_component(value: &str) -> String {
     encoded
 }
 
-pub async fn fetch_authored_prs(since: Option<&str>) -> anyhow::Result<Vec<AuthoredPrSummary>> {
-    let mut args = vec![
-        "search",
-        "prs",
-        "--author",
-        "@me",
-        "--limit",
-        "200",
-        "--json",
-        "url,title,updatedAt,repository,state,isDraft",
-    ];
-    let updated_filter;
-    if let Some(since) = since {
-        updated_filter = format!(">={since}");
-        args.push("--updated");
-        args.push(&updated_filter);
-    }
-    let output = Cmd::new("gh", args).run().await?;
-
-    output.ensure_success("❌ Failed to fetch authored pull requests")?;
-    if output.stdout().trim().is_empty() {
-        return Ok(Vec::new());
-    }
-
-    let value: Value = serde_json::from_str(output.stdout()).context("Invalid authored PR JSON")?;
+fn parse_pr_search_results(pages: &[Value]) -> Vec<SearchedPr> {
     let mut results = Vec::new();
-
-    let Value::Array(items) = value else {
-        return Ok(results);
-    };
-
-    for item in items {
-        let pr_url = item
-            .get("url")
-            .and_then(Value::as_str)
-            .unwrap_or_default()
-            .to_string();
-        if pr_url.is_empty() {
-            continue;
-        }
-
-        let title = item
-            .get("title")
-            .and_then(Value::as_str)
-            .unwrap_or("(untitled)")
-            .to_string();
-        let updated_at = item
-            .get("updatedAt")
-            .and_then(Value::as_str)
-            .unwrap_or_default()
-            .to_string();
-        let is_open = item
-            .get("state")
-            .and_then(Value::as_str)
-            .map(|state| state.eq_ignore_ascii_case("open"))
-            .unwrap_or(true);
-        let is_draft = item
-            .get("isDraft")
-            .and_then(Value::as_bool)
-            .unwrap_or(false);
-        let repository = item
-            .get("repository")
-            .and_then(|value| value.get("nameWithOwner"))
-            .and_then(Value::as_str)
-            .map(ToString::to_string)
-            .or_else(|| {
-                item.get("repository")
-                    .and_then(|value| value.get("fullName"))
-                    .and_then(Value::as_str)
-                    .map(ToString::to_string)
-            })
-            .unwrap_or_else(|| parse_repo_from_pr_url(&pr_url).unwrap_or_default());
-
-        if repository.is_empty() {
+    for page in pages {
+        let Some(items) = page.get("items").and_then(Value::as_array) else {
             continue;
-        }
-
-        results.push(AuthoredPrSummary {
-            pr_url,
-            repository,
-            title,
-            updated_at,
-            is_open,
-            is_draft,
-        });
-    }
-
-    Ok(results)
-}
-
-pub async fn fetch_assigned_prs() -> anyhow::Result<Vec<AssignedPrSummary>> {
-    let output = Cmd::new(
-        "gh",
-        [
-            "search",
-            "prs",
-            "--assignee",
-            "@me",
-            "--state",
-            "open",
-            "--limit",
-            "200",
-            "--json",
-            "url,title,updatedAt,repository,isDraft",
-        ],
-    )
-    .run()
-    .await?;
-
-    output.ensure_success("❌ Failed to fetch assigned pull requests")?;
-    if output.stdout().trim().is_empty() {
-        return Ok(Vec::new());
-    }
-
-    let value: Value = serde_json::from_str(output.stdout()).context("Invalid assigned PR JSON")?;
-    let mut results = Vec::new();
+        };
 
-    let Value::Array(items) = value else {
-        return Ok(results);
-    };
+        for item in items {
+            let pr_url = item
+                .get("html_url")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            if pr_url.is_empty() {
+                continue;
+            }
 
-    for item in items {
-        let pr_url = item
-            .get("url")
-            .and_then(Value::as_str)
-            .unwrap_or_default()
-            .to_string();
-        if pr_url.is_empty() {
-            continue;
-        }
+            let title = item
+                .get("title")
+                .and_then(Value::as_str)
+                .unwrap_or("(untitled)")
+                .to_string();
+            let updated_at = item
+                .get("updated_at")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            let is_open = item
+                .get("state")
+                .and_then(Value::as_str)
+                .map(|state| state.eq_ignore_ascii_case("open"))
+                .unwrap_or(true);
+            let is_draft = item.get("draft").and_then(Value::as_bool).unwrap_or(false);
+            let repository = item
+                .get("repository_url")
+                .and_then(Value::as_str)
+                .and_then(parse_repo_api_url)
+                .unwrap_or_else(|| parse_repo_from_pr_url(&pr_url).unwrap_or_default());
 
-        let title = item
-            .get("title")
-            .and_then(Value::as_str)
-            .unwrap_or("(untitled)")
-            .to_string();
-        let updated_at = item
-            .get("updatedAt")
-            .and_then(Value::as_str)
-            .unwrap_or_default()
-            .to_string();
-        let is_draft = item
-            .get("isDraft")
-            .and_then(Value::as_bool)
-            .unwrap_or(false);
-        let repository = item
-            .get("repository")
-            .and_then(|value| value.get("nameWithOwner"))
-            .and_then(Value::as_str)
-            .map(ToString::to_string)
-            .or_else(|| {
-                item.get("repository")
-                    .and_then(|value| value.get("fullName"))
-                    .and_then(Value::as_str)
-                    .map(ToString::to_string)
-            })
-            .unwrap_or_else(|| parse_repo_from_pr_url(&pr_url).unwrap_or_default());
+            if repository.is_empty() {
+                continue;
+            }
 
-        if repository.is_empty() {
-            continue;
+            results.push(SearchedPr {
+                pr_url,
+                repository,
+                title,
+                updated_at,
+                is_open,
+                is_draft,
+            });
         }
-
-        results.push(AssignedPrSummary {
-            pr_url,
-            repository,
-            title,
-            updated_at,
-            is_draft,
-        });
-    }
-
-    Ok(results)
-}
-
-pub async fn fetch_assigned_issues() -> anyhow::Result<AssignedIssuesSearchResult> {
-    let search_query = encode_query_component("assignee:@me state:open is:issue");
-    let endpoint = format!("search/issues?q={search_query}&per_page=100");
-    let output = Cmd::new("gh", ["api", &endpoint, "--paginate", "--slurp"])
-        .run()
-        .await?;
-
-    output.ensure_success("❌ Failed to fetch assigned issues")?;
-    if output.stdout().trim().is_empty() {
-        return Ok(AssignedIssuesSearchResult {
-            issues: Vec::new(),
-            is_complete: true,
-        });
     }
-
-    let value: Value =
-        serde_json::from_str(output.stdout()).context("Invalid assigned issues JSON")?;
-    Ok(parse_assigned_issues_search_results(&value))
+    results
 }
 
 fn parse_assigned_issues_search_results(value: &Value) -> AssignedIssuesSearchResult {
@@ -433,11 +562,13 @@ fn parse_assigned_issues_search_results(value: &Value) -> AssignedIssuesSearchRe
     }
 }
 
+/// Path after `repos/` of a REST API URL of a known host.
+fn repo_api_path(api_url: &str) -> Option<&str> {
+    split_api_url(api_url)?.1.strip_prefix("repos/")
+}
+
 fn parse_repo_api_url(api_url: &str) -> Option<String> {
-    let path = api_url
-        .trim()
-        .strip_prefix("https://api.github.com/repos/")
-        .or_else(|| api_url.trim().strip_prefix("repos/"))?;
+    let path = repo_api_path(api_url)?;
     let mut parts = path.split('/');
     let owner = parts.next()?;
     let repo = parts.next()?;
@@ -448,7 +579,7 @@ fn parse_repo_api_url(api_url: &str) -> Option<String> {
 }
 
 fn parse_repo_from_issue_html_url(html_url: &str) -> Option<String> {
-    let path = html_url.trim().strip_prefix("https://github.com/")?;
+    let (_, path) = hosts().split_web_url(html_url)?;
     let mut parts = path.split('/');
     let owner = parts.next()?;
     let repo = parts.next()?;
@@ -459,11 +590,7 @@ fn parse_repo_from_issue_html_url(html_url: &str) -> Option<String> {
 }
 
 pub async fn fetch_pr_details(pr_url: &str) -> anyhow::Result<PrDetails> {
-    let parsed = parse_github_pr_url(pr_url)?;
-    let canonical_pr_url = format!(
-        "https://github.com/{}/{}/pull/{}",
-        parsed.owner, parsed.repo, parsed.number
-    );
+    let canonical_pr_url = parse_github_pr_url(pr_url)?.url();
     let batch = fetch_batch(std::slice::from_ref(&canonical_pr_url), &[], &[]).await?;
     batch
         .pr_details
@@ -494,32 +621,20 @@ struct GraphqlBatchRequest {
 }
 
 impl GraphqlBatchRequest {
-    fn into_gh_args(self) -> Vec<String> {
-        let mut args = vec![
-            "api".to_string(),
-            "graphql".to_string(),
-            "-f".to_string(),
-            format!("query={}", self.query),
-        ];
-
-        for (name, value) in self.string_variables {
-            args.push("-f".to_string());
-            args.push(format!("{name}={value}"));
+    fn variables(&self) -> Value {
+        let mut variables = serde_json::Map::new();
+        for (name, value) in &self.string_variables {
+            variables.insert(name.clone(), Value::from(value.as_str()));
         }
-
-        for (name, value) in self.int_variables {
-            args.push("-F".to_string());
-            args.push(format!("{name}={value}"));
+        for (name, value) in &self.int_variables {
+            variables.insert(name.clone(), Value::from(*value));
         }
-
-        args
+        Value::Object(variables)
     }
 }
 
 fn parse_issue_api_url(api_url: &str) -> Option<IssueRef> {
-    let path = api_url
-        .strip_prefix("https://api.github.com/repos/")
-        .or_else(|| api_url.strip_prefix("repos/"))?;
+    let path = repo_api_path(api_url)?;
     let parts: Vec<&str> = path.split('/').collect();
     if parts.len() >= 4 && parts[2] == "issues" {
         let number: u64 = parts[3].parse().ok()?;
@@ -533,9 +648,7 @@ fn parse_issue_api_url(api_url: &str) -> Option<IssueRef> {
 }
 
 fn parse_discussion_api_url(api_url: &str) -> Option<DiscussionRef> {
-    let path = api_url
-        .strip_prefix("https://api.github.com/repos/")
-        .or_else(|| api_url.strip_prefix("repos/"))?;
+    let path = repo_api_path(api_url)?;
     let parts: Vec<&str> = path.split('/').collect();
     if parts.len() >= 4 && parts[2] == "discussions" {
         let number: u64 = parts[3].parse().ok()?;
@@ -550,6 +663,7 @@ fn parse_discussion_api_url(api_url: &str) -> Option<DiscussionRef> {
 
 fn parse_pr_graphql_value(
     pr_val: &Value,
+    host: &GitHubHost,
     owner: &str,
     repo: &str,
     is_archived: bool,
@@ -558,7 +672,7 @@ fn parse_pr_graphql_value(
         .get("number")
         .and_then(Value::as_i64)
         .unwrap_or_default();
-    let pr_url = format!("https://github.com/{owner}/{repo}/pull/{number}");
+    let pr_url = host.web_url(&format!("{owner}/{repo}/pull/{number}"));
     Ok(PrDetails {
         pr_url,
         owner: owner.to_string(),
@@ -570,6 +684,7 @@ fn parse_pr_graphql_value(
             .unwrap_or("OPEN")
             .to_string(),
         merge_queue_state: parse_merge_queue_state(pr_val),
+        auto_merge: pr_val.get("autoMergeRequest").is_some_and(Value::is_object),
         title: pr_val
             .get("title")
             .and_then(Value::as_str)
@@ -627,6 +742,43 @@ fn parse_pr_graphql_value(
     })
 }
 
+/// Checks of the head commit from `statusCheckRollup`, empty when it has
+/// none. `None` if the head commit is missing from the response.
+fn parse_status_check_rollup(pr_val: &Value) -> Option<Vec<PrCheck>> {
+    let commit = pr_val
+        .get("commits")?
+        .get("nodes")?
+        .as_array()?
+        .last()?
+        .get("commit")?;
+    let contexts = commit
+        .get("statusCheckRollup")
+        .and_then(|rollup| rollup.get("contexts"))
+        .and_then(|contexts| contexts.get("nodes"))
+        .and_then(Value::as_array);
+    let text = |node: &Value, key: &str| node.get(key).and_then(Value::as_str).map(str::to_string);
+    Some(
+        contexts
+            .into_iter()
+            .flatten()
+            .filter_map(|node| match node.get("__typename")?.as_str()? {
+                "CheckRun" => Some(PrCheck {
+                    name: text(node, "name")?,
+                    // The conclusion is null until the run completes.
+                    conclusion: text(node, "conclusion").or_else(|| text(node, "status"))?,
+                    url: text(node, "detailsUrl"),
+                }),
+                "StatusContext" => Some(PrCheck {
+                    name: text(node, "context")?,
+                    conclusion: text(node, "state")?,
+                    url: text(node, "targetUrl"),
+                }),
+                _ => None,
+            })
+            .collect(),
+    )
+}
+
 fn parse_merge_queue_state(pr_val: &Value) -> Option<String> {
     if pr_val
         .get("isInMergeQueue")
@@ -648,410 +800,408 @@ fn parse_merge_queue_state(pr_val: &Value) -> Option<String> {
 /// GitHub's query complexity limits.
 const GRAPHQL_BATCH_CHUNK_SIZE: usize = 25;
 
-/// Fetch details for multiple PRs and issue states using batched GraphQL calls.
-/// `pr_urls` are HTML PR URLs like `https://github.com/owner/repo/pull/123`.
-/// `issue_api_urls` are REST API URLs like `https://api.github.com/repos/o/r/issues/42`.
-pub async fn fetch_batch(
-    pr_urls: &[String],
-    issue_api_urls: &[String],
-    discussion_api_urls: &[String],
-) -> anyhow::Result<BatchFetchResult> {
-    if pr_urls.is_empty() && issue_api_urls.is_empty() && discussion_api_urls.is_empty() {
-        return Ok(BatchFetchResult::default());
-    }
+impl GitHubClient {
+    /// See [`fetch_batch`].
+    pub async fn fetch_batch(
+        &self,
+        pr_urls: &[String],
+        issue_api_urls: &[String],
+        discussion_api_urls: &[String],
+    ) -> anyhow::Result<BatchFetchResult> {
+        if pr_urls.is_empty() && issue_api_urls.is_empty() && discussion_api_urls.is_empty() {
+            return Ok(BatchFetchResult::default());
+        }
 
-    // Deduplicate PRs by (owner, repo, number)
-    let mut pr_refs: Vec<(String, String, u64, String)> = Vec::new();
-    let mut seen_prs = std::collections::HashSet::new();
-    for url in pr_urls {
-        if let Ok(parsed) = parse_github_pr_url(url) {
-            let key = format!("{}/{}/{}", parsed.owner, parsed.repo, parsed.number);
-            if seen_prs.insert(key) {
-                pr_refs.push((parsed.owner, parsed.repo, parsed.number, url.clone()));
+        // Deduplicate PRs by (owner, repo, number)
+        let mut pr_refs: Vec<(String, String, u64, String)> = Vec::new();
+        let mut seen_prs = std::collections::HashSet::new();
+        for url in pr_urls {
+            if let Ok(parsed) = parse_github_pr_url(url) {
+                let key = format!("{}/{}/{}", parsed.owner, parsed.repo, parsed.number);
+                if seen_prs.insert(key) {
+                    pr_refs.push((parsed.owner, parsed.repo, parsed.number, url.clone()));
+                }
             }
         }
-    }
 
-    // Deduplicate issues
-    let mut issue_refs: Vec<(IssueRef, String)> = Vec::new();
-    let mut seen_issues = std::collections::HashSet::new();
-    for url in issue_api_urls {
-        if let Some(issue) = parse_issue_api_url(url) {
-            let key = format!("{}/{}/{}", issue.owner, issue.repo, issue.number);
-            if seen_issues.insert(key) {
-                issue_refs.push((issue, url.clone()));
+        // Deduplicate issues
+        let mut issue_refs: Vec<(IssueRef, String)> = Vec::new();
+        let mut seen_issues = std::collections::HashSet::new();
+        for url in issue_api_urls {
+            if let Some(issue) = parse_issue_api_url(url) {
+                let key = format!("{}/{}/{}", issue.owner, issue.repo, issue.number);
+                if seen_issues.insert(key) {
+                    issue_refs.push((issue, url.clone()));
+                }
             }
         }
-    }
 
-    let mut discussion_refs: Vec<(DiscussionRef, String)> = Vec::new();
-    let mut seen_discussions = std::collections::HashSet::new();
-    for url in discussion_api_urls {
-        if let Some(discussion) = parse_discussion_api_url(url) {
-            let key = format!(
-                "{}/{}/{}",
-                discussion.owner, discussion.repo, discussion.number
-            );
-            if seen_discussions.insert(key) {
-                discussion_refs.push((discussion, url.clone()));
+        let mut discussion_refs: Vec<(DiscussionRef, String)> = Vec::new();
+        let mut seen_discussions = std::collections::HashSet::new();
+        for url in discussion_api_urls {
+            if let Some(discussion) = parse_discussion_api_url(url) {
+                let key = format!(
+                    "{}/{}/{}",
+                    discussion.owner, discussion.repo, discussion.number
+                );
+                if seen_discussions.insert(key) {
+                    discussion_refs.push((discussion, url.clone()));
+                }
             }
         }
-    }
 
-    let mut result = BatchFetchResult::default();
+        let mut result = BatchFetchResult::default();
+
+        // Process in chunks to avoid GitHub GraphQL resource limits.
+        let pr_chunks: Vec<&[(String, String, u64, String)]> =
+            pr_refs.chunks(GRAPHQL_BATCH_CHUNK_SIZE).collect();
+        let issue_chunks: Vec<&[(IssueRef, String)]> =
+            issue_refs.chunks(GRAPHQL_BATCH_CHUNK_SIZE).collect();
+        let discussion_chunks: Vec<&[(DiscussionRef, String)]> =
+            discussion_refs.chunks(GRAPHQL_BATCH_CHUNK_SIZE).collect();
+        let total_chunks = pr_chunks
+            .len()
+            .max(issue_chunks.len())
+            .max(discussion_chunks.len());
+
+        for chunk_idx in 0..total_chunks {
+            let pr_chunk = pr_chunks.get(chunk_idx).copied().unwrap_or(&[]);
+            let issue_chunk = issue_chunks.get(chunk_idx).copied().unwrap_or(&[]);
+            let discussion_chunk = discussion_chunks.get(chunk_idx).copied().unwrap_or(&[]);
+
+            if pr_chunk.is_empty() && issue_chunk.is_empty() && discussion_chunk.is_empty() {
+                continue;
+            }
 
-    // Process in chunks to avoid GitHub GraphQL resource limits.
-    let pr_chunks: Vec<&[(String, String, u64, String)]> =
-        pr_refs.chunks(GRAPHQL_BATCH_CHUNK_SIZE).collect();
-    let issue_chunks: Vec<&[(IssueRef, String)]> =
-        issue_refs.chunks(GRAPHQL_BATCH_CHUNK_SIZE).collect();
-    let discussion_chunks: Vec<&[(DiscussionRef, String)]> =
-        discussion_refs.chunks(GRAPHQL_BATCH_CHUNK_SIZE).collect();
-    let total_chunks = pr_chunks
-        .len()
-        .max(issue_chunks.len())
-        .max(discussion_chunks.len());
-
-    for chunk_idx in 0..total_chunks {
-        let pr_chunk = pr_chunks.get(chunk_idx).copied().unwrap_or(&[]);
-        let issue_chunk = issue_chunks.get(chunk_idx).copied().unwrap_or(&[]);
-        let discussion_chunk = discussion_chunks.get(chunk_idx).copied().unwrap_or(&[]);
-
-        if pr_chunk.is_empty() && issue_chunk.is_empty() && discussion_chunk.is_empty() {
-            continue;
+            let chunk_result = self
+                .fetch_batch_chunk(pr_chunk, issue_chunk, discussion_chunk)
+                .await?;
+            result.extend(chunk_result);
         }
 
-        let chunk_result = fetch_batch_chunk(pr_chunk, issue_chunk, discussion_chunk).await?;
-
-        result.pr_details.extend(chunk_result.pr_details);
-        result.issue_states.extend(chunk_result.issue_states);
-        result
-            .discussion_states
-            .extend(chunk_result.discussion_states);
-        result
-            .discussion_answers
-            .extend(chunk_result.discussion_answers);
-        result.participants.extend(chunk_result.participants);
+        Ok(result)
     }
 
-    Ok(result)
-}
-
-/// Execute a single GraphQL batch request for a chunk of PRs and issues.
-async fn fetch_batch_chunk(
-    pr_chunk: &[(String, String, u64, String)],
-    issue_chunk: &[(IssueRef, String)],
-    discussion_chunk: &[(DiscussionRef, String)],
-) -> anyhow::Result<BatchFetchResult> {
-    let request = build_graphql_batch_request(pr_chunk, issue_chunk, discussion_chunk)?;
-    let output = Cmd::new("gh", request.into_gh_args()).run().await?;
-    output.ensure_success("❌ Failed to run batch GraphQL query")?;
+    /// Execute a single GraphQL batch request for a chunk of PRs and issues.
+    async fn fetch_batch_chunk(
+        &self,
+        pr_chunk: &[(String, String, u64, String)],
+        issue_chunk: &[(IssueRef, String)],
+        discussion_chunk: &[(DiscussionRef, String)],
+    ) -> anyhow::Result<BatchFetchResult> {
+        let request = build_graphql_batch_request(pr_chunk, issue_chunk, discussion_chunk)?;
+        let data = self
+            .graphql(&request.query, request.variables())
+            .await
+            .context("❌ Failed to run batch GraphQL query")?;
+        let data = &data;
+
+        let mut result = BatchFetchResult::default();
+
+        for (index, (owner, repo, _, pr_url)) in pr_chunk.iter().enumerate() {
+            let alias = format!("pr{index}");
+            let Some(repo_val) = data.get(&alias) else {
+                eprintln!("⚠️ Missing GraphQL alias {alias} for {pr_url}");
+                continue;
+            };
+            if repo_val.is_null() {
+                eprintln!("⚠️ GraphQL returned null for {alias} ({pr_url})");
+                continue;
+            }
+            let is_archived = repo_val
+                .get("isArchived")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            let Some(pr_val) = repo_val.get("pullRequest") else {
+                eprintln!("⚠️ No pullRequest in {alias} for {pr_url}");
+                continue;
+            };
+            if pr_val.is_null() {
+                eprintln!("⚠️ pullRequest is null for {pr_url}");
+                continue;
+            }
+            match parse_pr_graphql_value(pr_val, self.host(), owner, repo, is_archived) {
+                Ok(details) => {
+                    result.pr_details.insert(pr_url.clone(), details);
+                }
+                Err(err) => {
+                    eprintln!("⚠️ Failed to parse PR details for {pr_url}: {err}");
+                }
+            }
 
-    let response: Value =
-        serde_json::from_str(output.stdout()).context("Invalid batch GraphQL JSON")?;
-    let data = response.get("data").unwrap_or(&Value::Null);
+            if let Some(checks) = parse_status_check_rollup(pr_val) {
+                result.checks.insert(pr_url.clone(), checks);
+            }
 
-    let mut result = BatchFetchResult::default();
+            let mut participants = pr_val
+                .get("participants")
+                .and_then(|value| value.get("nodes"))
+                .and_then(Value::as_array)
+                .map(|nodes| {
+                    nodes
+                        .iter()
+                        .filter_map(|node| {
+                            let login = node.get("login")?.as_str()?.to_string();
+                            let avatar_url = node.get("avatarUrl")?.as_str()?.to_string();
+                            Some(Participant {
+                                login,
+                                avatar_url,
+                                last_activity_at: None,
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
 
-    for (index, (owner, repo, _, pr_url)) in pr_chunk.iter().enumerate() {
-        let alias = format!("pr{index}");
-        let Some(repo_val) = data.get(&alias) else {
-            eprintln!("⚠️ Missing GraphQL alias {alias} for {pr_url}");
-            continue;
-        };
-        if repo_val.is_null() {
-            eprintln!("⚠️ GraphQL returned null for {alias} ({pr_url})");
-            continue;
-        }
-        let is_archived = repo_val
-            .get("isArchived")
-            .and_then(Value::as_bool)
-            .unwrap_or(false);
-        let Some(pr_val) = repo_val.get("pullRequest") else {
-            eprintln!("⚠️ No pullRequest in {alias} for {pr_url}");
-            continue;
-        };
-        if pr_val.is_null() {
-            eprintln!("⚠️ pullRequest is null for {pr_url}");
-            continue;
-        }
-        match parse_pr_graphql_value(pr_val, owner, repo, is_archived) {
-            Ok(details) => {
-                result.pr_details.insert(pr_url.clone(), details);
-            }
-            Err(err) => {
-                eprintln!("⚠️ Failed to parse PR details for {pr_url}: {err}");
+            // Extract per-user last activity timestamps (and avatar URLs) from timelineItems.
+            let activity_map = extract_participant_activity(pr_val);
+            for participant in &mut participants {
+                if let Some((ts, _)) = activity_map.get(&participant.login) {
+                    participant.last_activity_at = Some(ts.clone());
+                }
             }
-        }
 
-        let mut participants = pr_val
-            .get("participants")
-            .and_then(|value| value.get("nodes"))
-            .and_then(Value::as_array)
-            .map(|nodes| {
-                nodes
+            // Include users from timeline items who aren't in the participants list
+            // (e.g. bots like github-actions[bot] which GitHub's participants field omits).
+            for (login, (ts, avatar_url)) in &activity_map {
+                if !participants
                     .iter()
-                    .filter_map(|node| {
-                        let login = node.get("login")?.as_str()?.to_string();
-                        let avatar_url = node.get("avatarUrl")?.as_str()?.to_string();
-                        Some(Participant {
-                            login,
-                            avatar_url,
-                            last_activity_at: None,
-                        })
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .unwrap_or_default();
-
-        // Extract per-user last activity timestamps (and avatar URLs) from timelineItems.
-        let activity_map = extract_participant_activity(pr_val);
-        for participant in &mut participants {
-            if let Some((ts, _)) = activity_map.get(&participant.login) {
-                participant.last_activity_at = Some(ts.clone());
+                    .any(|participant| participant.login == *login)
+                    && let Some(avatar_url) = avatar_url
+                {
+                    participants.push(Participant {
+                        login: login.clone(),
+                        avatar_url: avatar_url.clone(),
+                        last_activity_at: Some(ts.clone()),
+                    });
+                }
             }
-        }
 
-        // Include users from timeline items who aren't in the participants list
-        // (e.g. bots like github-actions[bot] which GitHub's participants field omits).
-        for (login, (ts, avatar_url)) in &activity_map {
-            if !participants
-                .iter()
-                .any(|participant| participant.login == *login)
-                && let Some(avatar_url) = avatar_url
+            // Include the PR author if not already in the participants list.
+            if let Some(author) = pr_val.get("author")
+                && let (Some(login), Some(avatar_url)) = (
+                    author.get("login").and_then(Value::as_str),
+                    author.get("avatarUrl").and_then(Value::as_str),
+                )
+                && !participants
+                    .iter()
+                    .any(|participant| participant.login == login)
             {
-                participants.push(Participant {
-                    login: login.clone(),
-                    avatar_url: avatar_url.clone(),
-                    last_activity_at: Some(ts.clone()),
-                });
+                let last_activity_at = activity_map.get(login).map(|(ts, _)| ts.clone());
+                participants.insert(
+                    0,
+                    Participant {
+                        login: login.to_string(),
+                        avatar_url: avatar_url.to_string(),
+                        last_activity_at,
+                    },
+                );
             }
-        }
-
-        // Include the PR author if not already in the participants list.
-        if let Some(author) = pr_val.get("author")
-            && let (Some(login), Some(avatar_url)) = (
-                author.get("login").and_then(Value::as_str),
-                author.get("avatarUrl").and_then(Value::as_str),
-            )
-            && !participants
-                .iter()
-                .any(|participant| participant.login == login)
-        {
-            let last_activity_at = activity_map.get(login).map(|(ts, _)| ts.clone());
-            participants.insert(
-                0,
-                Participant {
-                    login: login.to_string(),
-                    avatar_url: avatar_url.to_string(),
-                    last_activity_at,
-                },
-            );
-        }
-
-        // Sort participants by last activity (most recent first).
-        // Participants without activity data sink to the end.
-        participants.sort_by(|left, right| {
-            right
-                .last_activity_at
-                .as_deref()
-                .cmp(&left.last_activity_at.as_deref())
-        });
 
-        if !participants.is_empty() {
-            result.participants.insert(pr_url.clone(), participants);
-        }
-    }
+            // Sort participants by last activity (most recent first).
+            // Participants without activity data sink to the end.
+            participants.sort_by(|left, right| {
+                right
+                    .last_activity_at
+                    .as_deref()
+                    .cmp(&left.last_activity_at.as_deref())
+            });
 
-    for (index, (issue, api_url)) in issue_chunk.iter().enumerate() {
-        let alias = format!("issue{index}");
-        let Some(repo_val) = data.get(&alias) else {
-            continue;
-        };
-        let Some(issue_val) = repo_val.get("issue") else {
-            continue;
-        };
-        if issue_val.is_null() {
-            continue;
+            if !participants.is_empty() {
+                result.participants.insert(pr_url.clone(), participants);
+            }
         }
 
-        if let Some(state) = issue_val.get("state").and_then(Value::as_str) {
-            result
-                .issue_states
-                .insert(api_url.clone(), state.to_ascii_uppercase());
-        }
+        for (index, (issue, api_url)) in issue_chunk.iter().enumerate() {
+            let alias = format!("issue{index}");
+            let Some(repo_val) = data.get(&alias) else {
+                continue;
+            };
+            let Some(issue_val) = repo_val.get("issue") else {
+                continue;
+            };
+            if issue_val.is_null() {
+                continue;
+            }
 
-        // Extract participants for the issue, mirroring the PR participant logic.
-        let html_url = format!(
-            "https://github.com/{}/{}/issues/{}",
-            issue.owner, issue.repo, issue.number
-        );
+            if let Some(state) = issue_val.get("state").and_then(Value::as_str) {
+                result
+                    .issue_states
+                    .insert(api_url.clone(), state.to_ascii_uppercase());
+            }
 
-        let mut participants = issue_val
-            .get("participants")
-            .and_then(|value| value.get("nodes"))
-            .and_then(Value::as_array)
-            .map(|nodes| {
-                nodes
-                    .iter()
-                    .filter_map(|node| {
-                        let login = node.get("login")?.as_str()?.to_string();
-                        let avatar_url = node.get("avatarUrl")?.as_str()?.to_string();
-                        Some(Participant {
-                            login,
-                            avatar_url,
-                            last_activity_at: None,
+            // Extract participants for the issue, mirroring the PR participant logic.
+            let html_url = self.host().web_url(&format!(
+                "{}/{}/issues/{}",
+                issue.owner, issue.repo, issue.number
+            ));
+
+            let mut participants = issue_val
+                .get("participants")
+                .and_then(|value| value.get("nodes"))
+                .and_then(Value::as_array)
+                .map(|nodes| {
+                    nodes
+                        .iter()
+                        .filter_map(|node| {
+                            let login = node.get("login")?.as_str()?.to_string();
+                            let avatar_url = node.get("avatarUrl")?.as_str()?.to_string();
+                            Some(Participant {
+                                login,
+                                avatar_url,
+                                last_activity_at: None,
+                            })
                         })
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .unwrap_or_default();
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
 
-        let activity_map = extract_participant_activity(issue_val);
-        for participant in &mut participants {
-            if let Some((ts, _)) = activity_map.get(&participant.login) {
-                participant.last_activity_at = Some(ts.clone());
+            let activity_map = extract_participant_activity(issue_val);
+            for participant in &mut participants {
+                if let Some((ts, _)) = activity_map.get(&participant.login) {
+                    participant.last_activity_at = Some(ts.clone());
+                }
             }
-        }
 
-        for (login, (ts, avatar_url)) in &activity_map {
-            if !participants
-                .iter()
-                .any(|participant| participant.login == *login)
-                && let Some(avatar_url) = avatar_url
-            {
-                participants.push(Participant {
-                    login: login.clone(),
-                    avatar_url: avatar_url.clone(),
-                    last_activity_at: Some(ts.clone()),
-                });
+            for (login, (ts, avatar_url)) in &activity_map {
+                if !participants
+                    .iter()
+                    .any(|participant| participant.login == *login)
+                    && let Some(avatar_url) = avatar_url
+                {
+                    participants.push(Participant {
+                        login: login.clone(),
+                        avatar_url: avatar_url.clone(),
+                        last_activity_at: Some(ts.clone()),
+                    });
+                }
             }
-        }
 
-        // Include the issue author if not already present.
-        if let Some(author) = issue_val.get("author")
-            && let (Some(login), Some(avatar_url)) = (
-                author.get("login").and_then(Value::as_str),
-                author.get("avatarUrl").and_then(Value::as_str),
-            )
-            && !participants
-                .iter()
-                .any(|participant| participant.login == login)
-        {
-            let last_activity_at = activity_map.get(login).map(|(ts, _)| ts.clone());
-            participants.insert(
-                0,
-                Participant {
-                    login: login.to_string(),
-                    avatar_url: avatar_url.to_string(),
-                    last_activity_at,
-                },
-            );
-        }
+            // Include the issue author if not already present.
+            if let Some(author) = issue_val.get("author")
+                && let (Some(login), Some(avatar_url)) = (
+                    author.get("login").and_then(Value::as_str),
+                    author.get("avatarUrl").and_then(Value::as_str),
+                )
+                && !participants
+                    .iter()
+                    .any(|participant| participant.login == login)
+            {
+                let last_activity_at = activity_map.get(login).map(|(ts, _)| ts.clone());
+                participants.insert(
+                    0,
+                    Participant {
+                        login: login.to_string(),
+                        avatar_url: avatar_url.to_string(),
+                        last_activity_at,
+                    },
+                );
+            }
 
-        participants.sort_by(|left, right| {
-            right
-                .last_activity_at
-                .as_deref()
-                .cmp(&left.last_activity_at.as_deref())
-        });
+            participants.sort_by(|left, right| {
+                right
+                    .last_activity_at
+                    .as_deref()
+                    .cmp(&left.last_activity_at.as_deref())
+            });
 
-        if !participants.is_empty() {
-            result.participants.insert(html_url, participants);
+            if !participants.is_empty() {
+                result.participants.insert(html_url, participants);
+            }
         }
-    }
 
-    for (index, (discussion, api_url)) in discussion_chunk.iter().enumerate() {
-        let alias = format!("discussion{index}");
-        let Some(repo_val) = data.get(&alias) else {
-            continue;
-        };
-        let Some(discussion_val) = repo_val.get("discussion") else {
-            continue;
-        };
-        if discussion_val.is_null() {
-            continue;
-        }
+        for (index, (discussion, api_url)) in discussion_chunk.iter().enumerate() {
+            let alias = format!("discussion{index}");
+            let Some(repo_val) = data.get(&alias) else {
+                continue;
+            };
+            let Some(discussion_val) = repo_val.get("discussion") else {
+                continue;
+            };
+            if discussion_val.is_null() {
+                continue;
+            }
 
-        let discussion_state = discussion_val
-            .get("closed")
-            .and_then(Value::as_bool)
-            .map(|closed| if closed { "CLOSED" } else { "OPEN" }.to_string());
-        if let Some(state) = discussion_state {
-            result.discussion_states.insert(api_url.clone(), state);
-        }
+            let discussion_state = discussion_val
+                .get("closed")
+                .and_then(Value::as_bool)
+                .map(|closed| if closed { "CLOSED" } else { "OPEN" }.to_string());
+            if let Some(state) = discussion_state {
+                result.discussion_states.insert(api_url.clone(), state);
+            }
 
-        if let Some(answered) = discussion_val.get("isAnswered").and_then(Value::as_bool) {
-            result.discussion_answers.insert(api_url.clone(), answered);
-        }
+            if let Some(answered) = discussion_val.get("isAnswered").and_then(Value::as_bool) {
+                result.discussion_answers.insert(api_url.clone(), answered);
+            }
 
-        let html_url = format!(
-            "https://github.com/{}/{}/discussions/{}",
-            discussion.owner, discussion.repo, discussion.number
-        );
+            let html_url = self.host().web_url(&format!(
+                "{}/{}/discussions/{}",
+                discussion.owner, discussion.repo, discussion.number
+            ));
 
-        let activity_map = extract_discussion_participant_activity(discussion_val);
-        let mut participants_by_login = HashMap::<String, Participant>::new();
+            let activity_map = extract_discussion_participant_activity(discussion_val);
+            let mut participants_by_login = HashMap::<String, Participant>::new();
 
-        if let Some(author) = discussion_val.get("author")
-            && let (Some(login), Some(avatar_url)) = (
-                author.get("login").and_then(Value::as_str),
-                author.get("avatarUrl").and_then(Value::as_str),
-            )
-        {
-            let last_activity_at =
-                activity_map
-                    .get(login)
-                    .map(|(ts, _)| ts.clone())
-                    .or_else(|| {
-                        discussion_val
-                            .get("answerChosenAt")
-                            .and_then(Value::as_str)
-                            .map(ToString::to_string)
-                    });
-            participants_by_login.insert(
-                login.to_string(),
-                Participant {
-                    login: login.to_string(),
-                    avatar_url: avatar_url.to_string(),
-                    last_activity_at,
-                },
-            );
-        }
+            if let Some(author) = discussion_val.get("author")
+                && let (Some(login), Some(avatar_url)) = (
+                    author.get("login").and_then(Value::as_str),
+                    author.get("avatarUrl").and_then(Value::as_str),
+                )
+            {
+                let last_activity_at =
+                    activity_map
+                        .get(login)
+                        .map(|(ts, _)| ts.clone())
+                        .or_else(|| {
+                            discussion_val
+                                .get("answerChosenAt")
+                                .and_then(Value::as_str)
+                                .map(ToString::to_string)
+                        });
+                participants_by_login.insert(
+                    login.to_string(),
+                    Participant {
+                        login: login.to_string(),
+                        avatar_url: avatar_url.to_string(),
+                        last_activity_at,
+                    },
+                );
+            }
 
-        for (login, (ts, avatar_url)) in &activity_map {
-            let Some(avatar_url) = avatar_url else {
-                continue;
-            };
+            for (login, (ts, avatar_url)) in &activity_map {
+                let Some(avatar_url) = avatar_url else {
+                    continue;
+                };
 
-            participants_by_login
-                .entry(login.clone())
-                .and_modify(|participant| {
-                    participant.last_activity_at = Some(ts.clone());
-                })
-                .or_insert_with(|| Participant {
-                    login: login.clone(),
-                    avatar_url: avatar_url.clone(),
-                    last_activity_at: Some(ts.clone()),
-                });
-        }
+                participants_by_login
+                    .entry(login.clone())
+                    .and_modify(|participant| {
+                        participant.last_activity_at = Some(ts.clone());
+                    })
+                    .or_insert_with(|| Participant {
+                        login: login.clone(),
+                        avatar_url: avatar_url.clone(),
+                        last_activity_at: Some(ts.clone()),
+                    });
+            }
 
-        let mut participants: Vec<_> = participants_by_login.into_values().collect();
+            let mut participants: Vec<_> = participants_by_login.into_values().collect();
 
-        participants.sort_by(|left, right| {
-            right
-                .last_activity_at
-                .as_deref()
-                .cmp(&left.last_activity_at.as_deref())
-                .then_with(|| left.login.cmp(&right.login))
-        });
+            participants.sort_by(|left, right| {
+                right
+                    .last_activity_at
+                    .as_deref()
+                    .cmp(&left.last_activity_at.as_deref())
+                    .then_with(|| left.login.cmp(&right.login))
+            });
 
-        if !participants.is_empty() {
-            result.participants.insert(html_url, participants);
+            if !participants.is_empty() {
+                result.participants.insert(html_url, participants);
+            }
         }
-    }
 
-    Ok(result)
+        Ok(result)
+    }
 }
 
 fn build_graphql_batch_request(
@@ -1059,10 +1209,17 @@ fn build_graphql_batch_request(
     issue_chunk: &[(IssueRef, String)],
     discussion_chunk: &[(DiscussionRef, String)],
 ) -> anyhow::Result<GraphqlBatchRequest> {
-    const PR_FIELDS: &str = "number title state isDraft isInMergeQueue mergeQueueEntry { state } headRefName headRefOid baseRefName \
+    const PR_FIELDS: &str = "number title state isDraft isInMergeQueue mergeQueueEntry { state } autoMergeRequest { enabledAt } headRefName headRefOid baseRefName \
                              createdAt updatedAt author { login avatarUrl } \
                              headRepository { name } headRepositoryOwner { login } \
                              isCrossRepository \
+                             commits(last: 1) { nodes { commit { statusCheckRollup { \
+                               contexts(first: 50) { nodes { \
+                                 __typename \
+                                 ... on CheckRun { name status conclusion detailsUrl } \
+                                 ... on StatusContext { context state targetUrl } \
+                               } } \
+                             } } } } \
                              participants(first: 10) { nodes { login avatarUrl } } \
                              timelineItems(last: 30, itemTypes: [ISSUE_COMMENT, PULL_REQUEST_REVIEW, PULL_REQUEST_COMMIT, PULL_REQUEST_REVIEW_THREAD]) { \
                                nodes { \
@@ -1314,27 +1471,275 @@ fn record_discussion_author_activity(
     }
 }
 
-pub async fn mark_notification_read(thread_id: &str) -> anyhow::Result<()> {
-    let endpoint = format!("/notifications/threads/{thread_id}");
-    let output = Cmd::new("gh", ["api", "-X", "PATCH", &endpoint])
-        .run()
-        .await?;
-    output.ensure_success("❌ Failed to mark notification thread as read")?;
-    Ok(())
-}
-
-pub async fn mark_notification_done(thread_id: &str) -> anyhow::Result<()> {
-    let endpoint = format!("/notifications/threads/{thread_id}");
-    let output = Cmd::new("gh", ["api", "-X", "DELETE", &endpoint])
-        .run()
-        .await?;
-    output.ensure_success("❌ Failed to mark notification thread as done")?;
-    Ok(())
-}
-
 #[cfg(test)]
 mod tests {
+    use axum::{
+        Json, Router,
+        extract::{Query, State},
+        http::{HeaderMap, StatusCode},
+        response::IntoResponse,
+        routing::{get, patch, post},
+    };
+
     use super::*;
+    use crate::test_util::spawn_stub_with;
+
+    async fn stub_client(router: impl FnOnce(&str) -> Router) -> GitHubClient {
+        let host = GitHubHost {
+            api_url: spawn_stub_with(router).await,
+            ..crate::github::hosts().github_com().clone()
+        };
+        GitHubClient::new(host, "test-token".to_string()).unwrap()
+    }
+
+    fn notification(id: &str) -> Value {
+        serde_json::json!({
+            "id": id,
+            "unread": true,
+            "reason": "review_requested",
+            "updated_at": "2026-04-13T10:00:00Z",
+            "repository": { "full_name": "o/r" },
+            "subject": {
+                "type": "PullRequest",
+                "title": format!("PR {id}"),
+                "url": format!("https://api.github.com/repos/o/r/pulls/{id}")
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn client_follows_notification_pages_with_the_token() {
+        async fn notifications(
+            State(base_url): State<String>,
+            headers: HeaderMap,
+            Query(query): Query<HashMap<String, String>>,
+        ) -> impl IntoResponse {
+            assert_eq!(headers["authorization"], "Bearer test-token");
+            assert_eq!(query.get("all").map(String::as_str), Some("true"));
+            match query.get("page").map(String::as_str) {
+                Some("2") => (
+                    HeaderMap::new(),
+                    Json(serde_json::json!([notification("2")])),
+                ),
+                _ => {
+                    let mut headers = HeaderMap::new();
+                    headers.insert(
+                        "link",
+                        format!("<{base_url}/notifications?all=true&page=2>; rel=\"next\"")
+                            .parse()
+                            .unwrap(),
+                    );
+                    (headers, Json(serde_json::json!([notification("1")])))
+                }
+            }
+        }
+
+        let client = stub_client(|base_url| {
+            Router::new()
+                .route("/notifications", get(notifications))
+                .with_state(base_url.to_string())
+        })
+        .await;
+
+        let threads = client
+            .fetch_notifications(None, &mut ResponseCache::default())
+            .await
+            .unwrap();
+        let ids: Vec<&str> = threads
+            .iter()
+            .map(|thread| thread.thread_id.as_str())
+            .collect();
+        assert_eq!(ids, ["1", "2"]);
+        assert_eq!(
+            threads[1].pr_url.as_deref(),
+            Some("https://github.com/o/r/pull/2")
+        );
+    }
+
+    #[tokio::test]
+    async fn client_replays_unchanged_pages_and_tracks_the_quota() {
+        async fn notifications(headers: HeaderMap) -> impl IntoResponse {
+            let mut response_headers = HeaderMap::new();
+            for (name, value) in [
+                ("etag", "\"v1\""),
+                ("x-poll-interval", "60"),
+                ("x-ratelimit-resource", "core"),
+                ("x-ratelimit-limit", "5000"),
+                ("x-ratelimit-remaining", "4999"),
+                ("x-ratelimit-reset", "1760000000"),
+            ] {
+                response_headers.insert(name, value.parse().unwrap());
+            }
+            if headers
+                .get("if-none-match")
+                .is_some_and(|etag| etag == "\"v1\"")
+            {
+                return (StatusCode::NOT_MODIFIED, response_headers).into_response();
+            }
+            (
+                response_headers,
+                Json(serde_json::json!([notification("1")])),
+            )
+                .into_response()
+        }
+
+        let client =
+            stub_client(|_| Router::new().route("/notifications", get(notifications))).await;
+
+        let mut cache = ResponseCache::default();
+        let first = client.fetch_notifications(None, &mut cache).await.unwrap();
+        assert_eq!(cache.poll_interval, Some(60));
+        assert!(cache.must_wait(cache.fetched_at + 59));
+        assert!(!cache.must_wait(cache.fetched_at + 60));
+
+        let replayed = client.fetch_notifications(None, &mut cache).await.unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(replayed.len(), 1);
+        assert_eq!(replayed[0].thread_id, "1");
+        assert_eq!(
+            client.quota().get("core").map(|limit| limit.remaining),
+            Some(4999)
+        );
+    }
+
+    #[tokio::test]
+    async fn client_searches_prs_and_fetches_details_with_graphql() {
+        async fn search(Query(query): Query<HashMap<String, String>>) -> Json<Value> {
+            assert_eq!(query["q"], "is:pr assignee:@me state:open");
+            Json(serde_json::json!({
+                "incomplete_results": false,
+                "items": [{
+                    "html_url": "https://github.com/o/r/pull/7",
+                    "repository_url": "https://api.github.com/repos/o/r",
+                    "title": "Add cache",
+                    "updated_at": "2026-04-13T10:00:00Z",
+                    "state": "open",
+                    "draft": true
+                }]
+            }))
+        }
+
+        async fn graphql(Json(body): Json<Value>) -> Json<Value> {
+            assert_eq!(body["variables"]["prOwner0"], "o");
+            assert_eq!(body["variables"]["prNumber0"], 7);
+            Json(serde_json::json!({
+                "data": {
+                    "pr0": {
+                        "isArchived": false,
+                        "pullRequest": {
+                            "number": 7,
+                            "state": "OPEN",
+                            "title": "Add cache",
+                            "headRefName": "cache",
+                            "baseRefName": "main",
+                            "headRefOid": "abc123",
+                            "updatedAt": "2026-04-13T10:00:00Z"
+                        }
+                    }
+                }
+            }))
+        }
+
+        let client = stub_client(|_| {
+            Router::new()
+                .route("/search/issues", get(search))
+                .route("/graphql", post(graphql))
+        })
+        .await;
+
+        let prs = client
+            .fetch_assigned_prs(&mut ResponseCache::default())
+            .await
+            .unwrap();
+        assert_eq!(prs.len(), 1);
+        assert_eq!(prs[0].repository, "o/r");
+        assert!(prs[0].is_draft);
+
+        let batch = client
+            .fetch_batch(std::slice::from_ref(&prs[0].pr_url), &[], &[])
+            .await
+            .unwrap();
+        let details = &batch.pr_details["https://github.com/o/r/pull/7"];
+        assert_eq!(details.head_sha, "abc123");
+        assert_eq!(details.head_ref, "cache");
+    }
+
+    #[tokio::test]
+    async fn client_tells_team_review_requests_from_direct_ones() {
+        async fn search(Query(query): Query<HashMap<String, String>>) -> Json<Value> {
+            let item = |number: u32| {
+                serde_json::json!({
+                    "html_url": format!("https://github.com/o/r/pull/{number}"),
+                    "repository_url": "https://api.github.com/repos/o/r",
+                    "title": format!("PR {number}"),
+                    "updated_at": "2026-04-13T10:00:00Z",
+                    "state": "open"
+                })
+            };
+            let items = match query["q"].as_str() {
+                "is:pr review-requested:@me state:open" => vec![item(1), item(2)],
+                "is:pr user-review-requested:@me state:open" => vec![item(2)],
+                other => panic!("unexpected query: {other}"),
+            };
+            Json(serde_json::json!({ "incomplete_results": false, "items": items }))
+        }
+
+        let client = stub_client(|_| Router::new().route("/search/issues", get(search))).await;
+
+        let prs = client
+            .fetch_review_requested_prs(
+                &mut ResponseCache::default(),
+                &mut ResponseCache::default(),
+            )
+            .await
+            .unwrap();
+        let requests: Vec<_> = prs
+            .iter()
+            .map(|pr| (pr.pr_url.as_str(), pr.team_request))
+            .collect();
+        assert_eq!(
+            requests,
+            [
+                ("https://github.com/o/r/pull/1", true),
+                ("https://github.com/o/r/pull/2", false),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn client_reports_status_and_rate_limit() {
+        async fn rate_limited() -> impl IntoResponse {
+            (
+                StatusCode::FORBIDDEN,
+                [
+                    ("x-ratelimit-limit", "5000"),
+                    ("x-ratelimit-remaining", "0"),
+                    ("x-ratelimit-reset", "1760000000"),
+                ],
+                Json(serde_json::json!({ "message": "API rate limit exceeded" })),
+            )
+        }
+
+        let client = stub_client(|_| {
+            Router::new().route("/notifications/threads/{id}", patch(rate_limited))
+        })
+        .await;
+
+        let err = client
+            .request(Method::PATCH, &notification_thread_endpoint("42"))
+            .await
+            .unwrap_err();
+        assert!(err.is_rate_limited());
+        let GitHubError::Status {
+            status, rate_limit, ..
+        } = &err
+        else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(*status, StatusCode::FORBIDDEN);
+        assert_eq!(rate_limit.map(|limit| limit.remaining), Some(0));
+        assert!(err.to_string().contains("API rate limit exceeded"));
+    }
 
     #[test]
     fn notifications_endpoint_encodes_since_cursor() {
@@ -1424,4 +1829,77 @@ mod tests {
         );
         assert_eq!(request.int_variables, vec![("prNumber0".to_string(), 42)]);
     }
+
+    #[test]
+    fn graphql_batch_request_fetches_the_status_check_rollup() {
+        let pr_chunk = [(
+            "o".to_string(),
+            "r".to_string(),
+            1,
+            "https://github.com/o/r/pull/1".to_string(),
+        )];
+
+        let request = build_graphql_batch_request(&pr_chunk, &[], &[]).unwrap();
+
+        assert!(request.query.contains("statusCheckRollup"));
+        assert!(
+            request
+                .query
+                .contains("... on CheckRun { name status conclusion detailsUrl }")
+        );
+    }
+
+    #[test]
+    fn parses_check_runs_and_commit_statuses_of_the_head_commit() {
+        use crate::github::CheckOutcome;
+
+        let pr_val = serde_json::json!({
+            "commits": { "nodes": [{ "commit": { "statusCheckRollup": { "contexts": { "nodes": [
+                { "__typename": "CheckRun", "name": "test", "status": "COMPLETED",
+                  "conclusion": "FAILURE", "detailsUrl": "https://ci.example/1" },
+                { "__typename": "CheckRun", "name": "lint", "status": "IN_PROGRESS",
+                  "conclusion": null, "detailsUrl": null },
+                { "__typename": "StatusContext", "context": "deploy", "state": "SUCCESS",
+                  "targetUrl": "https://deploy.example" }
+            ] } } } }] }
+        });
+
+        let checks = parse_status_check_rollup(&pr_val).unwrap();
+
+        assert_eq!(
+            checks,
+            vec![
+                PrCheck {
+                    name: "test".to_string(),
+                    conclusion: "FAILURE".to_string(),
+                    url: Some("https://ci.example/1".to_string()),
+                },
+                PrCheck {
+                    name: "lint".to_string(),
+                    conclusion: "IN_PROGRESS".to_string(),
+                    url: None,
+                },
+                PrCheck {
+                    name: "deploy".to_string(),
+                    conclusion: "SUCCESS".to_string(),
+                    url: Some("https://deploy.example".to_string()),
+                },
+            ]
+        );
+        let outcomes: Vec<_> = checks.iter().map(PrCheck::outcome).collect();
+        assert_eq!(
+            outcomes,
+            [
+                CheckOutcome::Fail,
+                CheckOutcome::Pending,
+                CheckOutcome::Pass
+            ]
+        );
+        // A head commit without checks clears the stored ones.
+        let no_checks = serde_json::json!({
+            "commits": { "nodes": [{ "commit": { "statusCheckRollup": null } }] }
+        });
+        assert_eq!(parse_status_check_rollup(&no_checks), Some(Vec::new()));
+        assert_eq!(parse_status_check_rollup(&serde_json::json!({})), None);
+    }
 }
@@ -0,0 +1,552 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    sync::{Arc, LazyLock, Mutex},
+    time::Duration,
+};
+
+use reqwest::{Method, StatusCode, header::HeaderMap};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::cmd::Cmd;
+
+use super::hosts::GitHubHost;
+
+/// Env vars holding a github.com token, checked in order before falling
+/// back to `gh auth token`.
+const TOKEN_ENV_VARS: [&str; 3] = ["GIGI_GITHUB_TOKEN", "GH_TOKEN", "GITHUB_TOKEN"];
+
+/// Same as [`TOKEN_ENV_VARS`], for GitHub Enterprise Server hosts without
+/// `token_env`. These are the ones `gh` reads.
+const ENTERPRISE_TOKEN_ENV_VARS: [&str; 2] = ["GH_ENTERPRISE_TOKEN", "GITHUB_ENTERPRISE_TOKEN"];
+
+const USER_AGENT: &str = concat!("gigi/", env!("CARGO_PKG_VERSION"));
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Clients shared by the whole process, by web host.
+static CLIENTS: LazyLock<Mutex<HashMap<String, Arc<tokio::sync::OnceCell<GitHubClient>>>>> =
+    LazyLock::new(Mutex::default);
+
+/// Typed async client of the GitHub REST and GraphQL APIs of a host.
+#[derive(Debug, Clone)]
+pub struct GitHubClient {
+    http: reqwest::Client,
+    host: GitHubHost,
+    token: String,
+    /// Latest rate limit seen for each resource (`core`, `graphql`,
+    /// `search`, ...).
+    quota: Arc<Mutex<BTreeMap<String, RateLimit>>>,
+}
+
+/// Rate limit reported by the `x-ratelimit-*` headers of a response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RateLimit {
+    pub limit: u64,
+    pub remaining: u64,
+    /// Unix timestamp at which `remaining` goes back to `limit`.
+    pub reset: i64,
+}
+
+/// Validators and bodies of the pages of a paginated `GET`, sent back as
+/// `If-None-Match` / `If-Modified-Since` so that unchanged pages are
+/// answered with `304 Not Modified` and replayed from here. Conditional
+/// requests answered with `304` don't count against the rate limit.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResponseCache {
+    pages: Vec<CachedPage>,
+    /// `X-Poll-Interval` of the last response, in seconds.
+    pub poll_interval: Option<u64>,
+    /// Unix timestamp of the last request sent to GitHub.
+    pub fetched_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedPage {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    body: Value,
+    next: Option<String>,
+}
+
+impl ResponseCache {
+    /// `true` when the `X-Poll-Interval` of the last response has not
+    /// elapsed at `now`.
+    pub fn must_wait(&self, now: i64) -> bool {
+        self.poll_interval.is_some_and(|interval| {
+            now < self
+                .fetched_at
+                .saturating_add(i64::try_from(interval).unwrap_or(i64::MAX))
+        })
+    }
+}
+
+/// Response of [`GitHubClient::send`].
+#[derive(Debug)]
+struct Response {
+    status: StatusCode,
+    headers: HeaderMap,
+    text: String,
+}
+
+#[derive(Debug)]
+pub enum GitHubError {
+    /// The request could not be sent, or its response could not be read.
+    Transport(reqwest::Error),
+    /// GitHub answered with an error status.
+    Status {
+        method: Method,
+        url: String,
+        status: StatusCode,
+        /// `message` field of the error body, or the raw body.
+        message: String,
+        rate_limit: Option<RateLimit>,
+    },
+    /// A GraphQL query returned errors and no data.
+    GraphQl(Vec<String>),
+    /// The response body is not valid JSON.
+    Decode {
+        url: String,
+        source: serde_json::Error,
+    },
+}
+
+impl GitHubError {
+    /// `true` when the request was rejected because the rate limit is
+    /// exhausted.
+    pub fn is_rate_limited(&self) -> bool {
+        match self {
+            Self::Status {
+                status, rate_limit, ..
+            } => {
+                *status == StatusCode::TOO_MANY_REQUESTS
+                    || (*status == StatusCode::FORBIDDEN
+                        && rate_limit.is_some_and(|limit| limit.remaining == 0))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for GitHubError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(err) => write!(f, "GitHub request failed: {err}"),
+            Self::Status {
+                method,
+                url,
+                status,
+                message,
+                rate_limit,
+            } => {
+                write!(f, "{method} {url} returned {status}: {message}")?;
+                match rate_limit {
+                    Some(limit) if self.is_rate_limited() => {
+                        let reset = chrono::DateTime::from_timestamp(limit.reset, 0)
+                            .map_or_else(|| limit.reset.to_string(), |reset| reset.to_rfc3339());
+                        write!(f, " (rate limit exhausted until {reset})")
+                    }
+                    _ => Ok(()),
+                }
+            }
+            Self::GraphQl(errors) => write!(f, "GraphQL query failed: {}", errors.join("; ")),
+            Self::Decode { url, source } => write!(f, "Invalid JSON from {url}: {source}"),
+        }
+    }
+}
+
+impl std::error::Error for GitHubError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(err) => Some(err),
+            Self::Decode { source, .. } => Some(source),
+            Self::Status { .. } | Self::GraphQl(_) => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for GitHubError {
+    fn from(err: reqwest::Error) -> Self {
+        Self::Transport(err)
+    }
+}
+
+impl GitHubClient {
+    /// Client of the API of `host`.
+    pub fn new(host: GitHubHost, token: String) -> anyhow::Result<Self> {
+        let http = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| anyhow::anyhow!("❌ Failed to build the GitHub HTTP client: {e}"))?;
+        Ok(Self {
+            http,
+            host: GitHubHost {
+                api_url: host.api_url.trim_end_matches('/').to_string(),
+                ..host
+            },
+            token,
+            quota: Arc::default(),
+        })
+    }
+
+    /// Client of `host` shared by the whole process, created on first use.
+    /// See [`resolve_token`].
+    pub async fn for_host(host: &GitHubHost) -> anyhow::Result<Self> {
+        let cell = {
+            let mut clients = CLIENTS
+                .lock()
+                .map_err(|e| anyhow::anyhow!("❌ GitHub clients lock poisoned: {e}"))?;
+            Arc::clone(clients.entry(host.host.clone()).or_default())
+        };
+        let client = cell
+            .get_or_try_init(async || Self::new(host.clone(), resolve_token(host).await?))
+            .await?;
+        Ok(client.clone())
+    }
+
+    /// Rate limits of the process-wide clients, empty until they sent their
+    /// first request. Resources of GitHub Enterprise Server hosts are
+    /// prefixed by the host.
+    pub fn shared_quota() -> BTreeMap<String, RateLimit> {
+        let Ok(clients) = CLIENTS.lock() else {
+            return BTreeMap::new();
+        };
+        clients
+            .values()
+            .filter_map(|cell| cell.get())
+            .flat_map(|client| {
+                client.quota().into_iter().map(|(resource, limit)| {
+                    if client.host.is_github_com() {
+                        (resource, limit)
+                    } else {
+                        (format!("{} {resource}", client.host.host), limit)
+                    }
+                })
+            })
+            .collect()
+    }
+
+    pub fn host(&self) -> &GitHubHost {
+        &self.host
+    }
+
+    /// Latest rate limit reported by GitHub for each resource.
+    pub fn quota(&self) -> BTreeMap<String, RateLimit> {
+        self.quota
+            .lock()
+            .map(|quota| quota.clone())
+            .unwrap_or_default()
+    }
+
+    /// GraphQL endpoint next to the REST root: `/graphql` on github.com,
+    /// `/api/graphql` on GitHub Enterprise Server.
+    fn graphql_url(&self) -> String {
+        match self.host.api_url.strip_suffix("/api/v3") {
+            Some(host) => format!("{host}/api/graphql"),
+            None => format!("{}/graphql", self.host.api_url),
+        }
+    }
+
+    /// Absolute URL of `path`, relative to the REST root. Absolute URLs, like
+    /// the pagination links, are kept as they are.
+    fn url(&self, path: &str) -> String {
+        if path.starts_with("http://") || path.starts_with("https://") {
+            path.to_string()
+        } else {
+            format!("{}/{}", self.host.api_url, path.trim_start_matches('/'))
+        }
+    }
+
+    /// Send a request. `cached` adds the validators of an earlier response,
+    /// in which case `304 Not Modified` is not an error.
+    async fn send(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<&Value>,
+        cached: Option<&CachedPage>,
+    ) -> Result<Response, GitHubError> {
+        let mut request = self
+            .http
+            .request(method.clone(), url)
+            .bearer_auth(&self.token)
+            .header("accept", "application/vnd.github+json")
+            .header("x-github-api-version", "2022-11-28");
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        if let Some(cached) = cached {
+            if let Some(etag) = &cached.etag {
+                request = request.header("if-none-match", etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header("if-modified-since", last_modified);
+            }
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let text = response.text().await?;
+        let rate_limit = parse_rate_limit(&headers);
+        if let Some(limit) = rate_limit
+            && let Ok(mut quota) = self.quota.lock()
+        {
+            quota.insert(rate_limit_resource(&headers), limit);
+        }
+        let not_modified = status == StatusCode::NOT_MODIFIED && cached.is_some();
+        if !status.is_success() && !not_modified {
+            return Err(GitHubError::Status {
+                method,
+                url: url.to_string(),
+                status,
+                message: error_message(&text),
+                rate_limit,
+            });
+        }
+        Ok(Response {
+            status,
+            headers,
+            text,
+        })
+    }
+
+    /// `GET` a paginated list, following the `Link: rel="next"` headers for
+    /// at most `max_pages` pages (all of them when `None`). Returns one JSON
+    /// document per page.
+    ///
+    /// Requests are conditional: pages that did not change since they were
+    /// stored in `cache` come from there, and `cache` is updated with the new
+    /// validators.
+    pub async fn get_pages(
+        &self,
+        path: &str,
+        max_pages: Option<usize>,
+        cache: &mut ResponseCache,
+    ) -> Result<Vec<Value>, GitHubError> {
+        let mut fetched = Vec::new();
+        let mut next = Some(self.url(path));
+        let mut poll_interval = None;
+        while let Some(url) = next.take() {
+            if max_pages.is_some_and(|max| fetched.len() >= max) {
+                break;
+            }
+            let cached = cache
+                .pages
+                .get(fetched.len())
+                .filter(|page| page.url == url);
+            let response = self.send(Method::GET, &url, None, cached).await?;
+            poll_interval =
+                poll_interval.or_else(|| header_value(&response.headers, "x-poll-interval"));
+            let page = match cached {
+                Some(cached) if response.status == StatusCode::NOT_MODIFIED => cached.clone(),
+                _ => CachedPage {
+                    etag: header_value(&response.headers, "etag"),
+                    last_modified: header_value(&response.headers, "last-modified"),
+                    next: next_page_url(&response.headers),
+                    body: parse_json(&url, &response.text)?,
+                    url,
+                },
+            };
+            next.clone_from(&page.next);
+            fetched.push(page);
+        }
+
+        let pages = fetched.iter().map(|page| page.body.clone()).collect();
+        cache.pages = fetched;
+        cache.poll_interval = poll_interval;
+        cache.fetched_at = chrono::Utc::now().timestamp();
+        Ok(pages)
+    }
+
+    /// Send a request whose response body is not needed, e.g. `PATCH` or
+    /// `DELETE` of a notification thread.
+    pub async fn request(&self, method: Method, path: &str) -> Result<(), GitHubError> {
+        self.send(method, &self.url(path), None, None).await?;
+        Ok(())
+    }
+
+    /// Run a GraphQL query and return its `data`. Errors are reported when
+    /// no data comes back; partial errors (e.g. an alias on a deleted
+    /// repository resolving to `null`) are left to the caller.
+    pub async fn graphql(&self, query: &str, variables: Value) -> Result<Value, GitHubError> {
+        let url = self.graphql_url();
+        let body = serde_json::json!({ "query": query, "variables": variables });
+        let response = self.send(Method::POST, &url, Some(&body), None).await?;
+        let mut response = parse_json(&url, &response.text)?;
+        let data = response.get_mut("data").map(Value::take);
+        match data {
+            Some(data) if !data.is_null() => Ok(data),
+            _ => Err(GitHubError::GraphQl(graphql_errors(&response))),
+        }
+    }
+}
+
+/// Token of `host`: from its `token_env` when configured, otherwise from
+/// the first env var of [`TOKEN_ENV_VARS`] (or [`ENTERPRISE_TOKEN_ENV_VARS`])
+/// that is set, or from the GitHub CLI.
+pub async fn resolve_token(host: &GitHubHost) -> anyhow::Result<String> {
+    let env_token = |var: &str| {
+        std::env::var(var)
+            .ok()
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty())
+    };
+    if let Some(var) = &host.token_env {
+        return env_token(var).ok_or_else(|| {
+            anyhow::anyhow!("❌ No GitHub token for {}: {var} is not set", host.host)
+        });
+    }
+    let vars: &[&str] = if host.is_github_com() {
+        &TOKEN_ENV_VARS
+    } else {
+        &ENTERPRISE_TOKEN_ENV_VARS
+    };
+    if let Some(token) = vars.iter().find_map(|var| env_token(var)) {
+        return Ok(token);
+    }
+
+    let output = Cmd::new("gh", ["auth", "token", "--hostname", &host.host])
+        .hide_stdout()
+        .hide_stderr()
+        .run()
+        .await?;
+    output.ensure_success(format!(
+        "❌ No GitHub token for {}: set {} or run `gh auth login --hostname {}`",
+        host.host,
+        vars.join(", "),
+        host.host
+    ))?;
+    let token = output.stdout().trim().to_string();
+    anyhow::ensure!(
+        !token.is_empty(),
+        "❌ `gh auth token --hostname {}` returned an empty token",
+        host.host
+    );
+    Ok(token)
+}
+
+fn parse_json(url: &str, text: &str) -> Result<Value, GitHubError> {
+    if text.trim().is_empty() {
+        return Ok(Value::Null);
+    }
+    serde_json::from_str(text).map_err(|source| GitHubError::Decode {
+        url: url.to_string(),
+        source,
+    })
+}
+
+fn error_message(body: &str) -> String {
+    serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|value| value.get("message")?.as_str().map(ToString::to_string))
+        .unwrap_or_else(|| body.trim().to_string())
+}
+
+fn graphql_errors(response: &Value) -> Vec<String> {
+    let errors: Vec<String> = response
+        .get("errors")
+        .and_then(Value::as_array)
+        .map(|errors| {
+            errors
+                .iter()
+                .filter_map(|error| error.get("message")?.as_str().map(ToString::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    if errors.is_empty() {
+        vec!["response has no data".to_string()]
+    } else {
+        errors
+    }
+}
+
+pub(super) fn parse_rate_limit(headers: &HeaderMap) -> Option<RateLimit> {
+    Some(RateLimit {
+        limit: header_value(headers, "x-ratelimit-limit")?,
+        remaining: header_value(headers, "x-ratelimit-remaining")?,
+        reset: header_value(headers, "x-ratelimit-reset")?,
+    })
+}
+
+fn header_value<T: std::str::FromStr>(headers: &HeaderMap, name: &str) -> Option<T> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+/// Resource whose quota a response counts against. Missing on some
+/// GitHub Enterprise Server versions, which only have the `core` one.
+fn rate_limit_resource(headers: &HeaderMap) -> String {
+    header_value(headers, "x-ratelimit-resource").unwrap_or_else(|| "core".to_string())
+}
+
+/// URL of the `rel="next"` entry of the `Link` header.
+fn next_page_url(headers: &HeaderMap) -> Option<String> {
+    let link = headers.get("link")?.to_str().ok()?;
+    link.split(',').find_map(|entry| {
+        let (url, params) = entry.split_once(';')?;
+        params
+            .split(';')
+            .any(|param| param.trim() == "rel=\"next\"")
+            .then(|| {
+                url.trim()
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_string()
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn graphql_endpoint_follows_the_rest_root() {
+        let client = |api_url: &str| {
+            let host = GitHubHost {
+                api_url: api_url.to_string(),
+                ..crate::github::hosts().github_com().clone()
+            };
+            GitHubClient::new(host, String::new()).unwrap()
+        };
+        assert_eq!(
+            client("https://api.github.com").graphql_url(),
+            "https://api.github.com/graphql"
+        );
+        assert_eq!(
+            client("https://github.example.com/api/v3/").graphql_url(),
+            "https://github.example.com/api/graphql"
+        );
+    }
+
+    #[test]
+    fn reads_next_page_and_rate_limit_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "link",
+            HeaderValue::from_static(
+                "<https://api.github.com/notifications?page=1>; rel=\"prev\", \
+                 <https://api.github.com/notifications?page=3>; rel=\"next\"",
+            ),
+        );
+        headers.insert("x-ratelimit-limit", HeaderValue::from_static("5000"));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+        headers.insert("x-ratelimit-reset", HeaderValue::from_static("1760000000"));
+
+        assert_eq!(
+            next_page_url(&headers).as_deref(),
+            Some("https://api.github.com/notifications?page=3")
+        );
+        assert_eq!(
+            parse_rate_limit(&headers),
+            Some(RateLimit {
+                limit: 5000,
+                remaining: 0,
+                reset: 1_760_000_000,
+            })
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use std::sync::OnceLock;
+
+use crate::config::GitHubConfig;
+
+/// Web host of github.com.
+pub const GITHUB_COM: &str = "github.com";
+
+/// REST API root of github.com.
+const GITHUB_COM_API_URL: &str = "https://api.github.com";
+
+/// Env var overriding the REST API root of github.com, e.g. to test against
+/// a local server.
+const API_URL_ENV_VAR: &str = "GIGI_GITHUB_API_URL";
+
+static HOSTS: OnceLock<GitHubHosts> = OnceLock::new();
+
+/// A GitHub instance: github.com or a GitHub Enterprise Server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitHubHost {
+    /// Web host, e.g. `github.example.com`.
+    pub host: String,
+    /// REST API root, e.g. `https://github.example.com/api/v3`.
+    pub api_url: String,
+    /// Env var holding the token of this host.
+    pub token_env: Option<String>,
+    /// Watched by the `serve` poller.
+    pub watch: bool,
+    /// Teams, as `org/team`, whose mentions the poller follows.
+    pub team_mentions: Vec<String>,
+}
+
+/// GitHub instances known to gigi, github.com first.
+#[derive(Debug, Clone)]
+pub struct GitHubHosts(Vec<GitHubHost>);
+
+impl GitHubHost {
+    fn github_com() -> Self {
+        Self {
+            host: GITHUB_COM.to_string(),
+            api_url: std::env::var(API_URL_ENV_VAR)
+                .unwrap_or_else(|_| GITHUB_COM_API_URL.to_string()),
+            token_env: None,
+            watch: true,
+            team_mentions: Vec::new(),
+        }
+    }
+
+    pub fn is_github_com(&self) -> bool {
+        self.host == GITHUB_COM
+    }
+
+    /// Web URL of `path` on this host, e.g. `owner/repo/pull/1`.
+    pub fn web_url(&self, path: &str) -> String {
+        format!("https://{}/{}", self.host, path.trim_start_matches('/'))
+    }
+}
+
+impl GitHubHosts {
+    pub fn new(config: &GitHubConfig) -> Self {
+        let mut hosts = vec![GitHubHost::github_com()];
+        for entry in &config.hosts {
+            let host = normalize_host(&entry.host);
+            if host.is_empty() {
+                continue;
+            }
+            let api_url = entry.api_url.as_deref().map_or_else(
+                || default_api_url(&host),
+                |url| url.trim_end_matches('/').to_string(),
+            );
+            let configured = GitHubHost {
+                api_url,
+                token_env: entry.token_env.clone(),
+                watch: entry.watch,
+                team_mentions: entry.team_mentions.clone(),
+                host,
+            };
+            match hosts.iter_mut().find(|known| known.host == configured.host) {
+                Some(known) => *known = configured,
+                None => hosts.push(configured),
+            }
+        }
+        Self(hosts)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &GitHubHost> {
+        self.0.iter()
+    }
+
+    pub fn github_com(&self) -> &GitHubHost {
+        self.find(GITHUB_COM)
+            .expect("github.com is always a known host")
+    }
+
+    /// Host named `host`, ignoring case and a `www.` prefix.
+    pub fn find(&self, host: &str) -> Option<&GitHubHost> {
+        let host = normalize_host(host);
+        self.0.iter().find(|known| known.host == host)
+    }
+
+    /// Host of a web URL like `https://github.com/owner/repo`, with the path
+    /// after the host. The scheme is optional.
+    pub fn split_web_url<'a>(&self, url: &'a str) -> Option<(&GitHubHost, &'a str)> {
+        let url = url.trim();
+        let without_scheme = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))
+            .unwrap_or(url);
+        let (host, path) = without_scheme
+            .split_once('/')
+            .unwrap_or((without_scheme, ""));
+        Some((self.find(host)?, path))
+    }
+
+    /// Host of a REST API URL like `https://api.github.com/repos/o/r`, with
+    /// the path after the API root.
+    pub fn split_api_url<'a>(&self, url: &'a str) -> Option<(&GitHubHost, &'a str)> {
+        let url = url.trim();
+        self.0.iter().find_map(|host| {
+            let path = url.strip_prefix(host.api_url.as_str())?;
+            (path.is_empty() || path.starts_with('/')).then(|| (host, path.trim_start_matches('/')))
+        })
+    }
+}
+
+/// Register the hosts of `[github]`. Only the first call has an effect:
+/// it is done once, when gigi starts.
+pub fn configure_hosts(config: &GitHubConfig) {
+    drop(HOSTS.set(GitHubHosts::new(config)));
+}
+
+/// Known hosts; only github.com until [`configure_hosts`] is called.
+pub fn hosts() -> &'static GitHubHosts {
+    HOSTS.get_or_init(|| GitHubHosts::new(&unconfigured_hosts()))
+}
+
+#[cfg(not(test))]
+fn unconfigured_hosts() -> GitHubConfig {
+    GitHubConfig::default()
+}
+
+/// Tests know `github.example.com` too, to cover GitHub Enterprise Server
+/// URLs.
+#[cfg(test)]
+fn unconfigured_hosts() -> GitHubConfig {
+    GitHubConfig {
+        hosts: vec![crate::config::GitHubHostConfig {
+            host: "github.example.com".to_string(),
+            ..Default::default()
+        }],
+    }
+}
+
+fn normalize_host(host: &str) -> String {
+    let host = host.trim().trim_end_matches('/').to_ascii_lowercase();
+    host.strip_prefix("www.")
+        .map(ToString::to_string)
+        .unwrap_or(host)
+}
+
+fn default_api_url(host: &str) -> String {
+    if host == GITHUB_COM {
+        GITHUB_COM_API_URL.to_string()
+    } else {
+        format!("https://{host}/api/v3")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GitHubHostConfig;
+
+    fn enterprise_hosts() -> GitHubHosts {
+        GitHubHosts::new(&GitHubConfig {
+            hosts: vec![GitHubHostConfig {
+                host: "GitHub.Example.com".to_string(),
+                api_url: None,
+                token_env: Some("GHES_TOKEN".to_string()),
+                watch: true,
+                team_mentions: Vec::new(),
+            }],
+        })
+    }
+
+    #[test]
+    fn configured_hosts_are_found_by_web_and_api_url() {
+        let hosts = enterprise_hosts();
+
+        let (host, path) = hosts
+            .split_web_url("https://github.example.com/o/r/pull/1")
+            .unwrap();
+        assert_eq!(host.api_url, "https://github.example.com/api/v3");
+        assert_eq!(host.token_env.as_deref(), Some("GHES_TOKEN"));
+        assert_eq!(path, "o/r/pull/1");
+
+        let (host, path) = hosts
+            .split_api_url("https://github.example.com/api/v3/repos/o/r/issues/2")
+            .unwrap();
+        assert_eq!(host.host, "github.example.com");
+        assert_eq!(path, "repos/o/r/issues/2");
+
+        let (host, _) = hosts.split_web_url("www.github.com/o/r").unwrap();
+        assert!(host.is_github_com());
+        assert!(hosts.split_web_url("https://gitlab.com/o/r").is_none());
+    }
+
+    #[test]
+    fn github_com_can_be_reconfigured() {
+        let hosts = GitHubHosts::new(&GitHubConfig {
+            hosts: vec![GitHubHostConfig {
+                host: "github.com".to_string(),
+                api_url: None,
+                token_env: None,
+                watch: false,
+                team_mentions: vec!["org/team".to_string()],
+            }],
+        });
+        assert_eq!(hosts.iter().count(), 1);
+        assert!(!hosts.github_com().watch);
+        assert_eq!(hosts.github_com().team_mentions, ["org/team"]);
+    }
+}
@@ -2,30 +2,40 @@ use anyhow::Context as _;
 use camino::{Utf8Path, Utf8PathBuf};
 use tokio::fs;
 
-use crate::cmd::{Cmd, CmdOutput};
+use crate::{
+    checkout::parse_github_pr_url,
+    cmd::{Cmd, CmdOutput},
+};
 
 use super::{
     api::fetch_pr_details,
+    hosts::GITHUB_COM,
     parsing::{current_viewer_login, parse_github_name_with_owner},
     types::{CloneTarget, GitHubRepoRef, LocalPrRepo, PrDetails},
 };
 
-pub async fn ensure_local_repo(owner: &str, repo: &str) -> anyhow::Result<Utf8PathBuf> {
-    let repo_dir = local_repo_dir(owner, repo)?;
-    ensure_local_repo_at(owner, repo, &repo_dir).await?;
+pub async fn ensure_local_repo(host: &str, owner: &str, repo: &str) -> anyhow::Result<Utf8PathBuf> {
+    let repo_ref = GitHubRepoRef {
+        host: host.to_string(),
+        owner: owner.to_string(),
+        repo: repo.to_string(),
+    };
+    let repo_dir = local_repo_dir(host, owner, repo)?;
+    ensure_local_repo_at(&repo_ref, &repo_dir).await?;
     Ok(repo_dir)
 }
 
 pub async fn ensure_local_repo_for_pr(pr_url: &str) -> anyhow::Result<LocalPrRepo> {
+    let host = parse_github_pr_url(pr_url)?.host;
     let details = fetch_pr_details(pr_url).await?;
-    let clone_target = preferred_clone_target(&details, current_viewer_login().ok().as_deref());
-    let repo_dir = local_repo_dir(&clone_target.origin.owner, &clone_target.origin.repo)?;
-    ensure_local_repo_at(
+    let clone_target =
+        preferred_clone_target(&host, &details, current_viewer_login(&host).ok().as_deref());
+    let repo_dir = local_repo_dir(
+        &clone_target.origin.host,
         &clone_target.origin.owner,
         &clone_target.origin.repo,
-        &repo_dir,
-    )
-    .await?;
+    )?;
+    ensure_local_repo_at(&clone_target.origin, &repo_dir).await?;
 
     if let Some(upstream) = clone_target.upstream {
         ensure_remote_repo(&repo_dir, "upstream", &upstream).await?;
@@ -34,7 +44,7 @@ pub async fn ensure_local_repo_for_pr(pr_url: &str) -> anyhow::Result<LocalPrRep
     Ok(LocalPrRepo { repo_dir, details })
 }
 
-async fn ensure_local_repo_at(owner: &str, repo: &str, repo_dir: &Utf8Path) -> anyhow::Result<()> {
+async fn ensure_local_repo_at(repo_ref: &GitHubRepoRef, repo_dir: &Utf8Path) -> anyhow::Result<()> {
     if fs::try_exists(repo_dir).await? {
         anyhow::ensure!(
             repo_dir.join(".git").exists(),
@@ -50,17 +60,29 @@ async fn ensure_local_repo_at(owner: &str, repo: &str, repo_dir: &Utf8Path) -> a
         .await
         .with_context(|| format!("Failed to create {parent}"))?;
 
-    let repo_name = format!("{owner}/{repo}");
-    let output = Cmd::new("gh", ["repo", "clone", &repo_name, repo_dir.as_str()])
+    let repo_name = format!("{}/{}", repo_ref.owner, repo_ref.repo);
+    // `gh` picks the host from the `HOST/OWNER/REPO` form.
+    let clone_name = if repo_ref.host == GITHUB_COM {
+        repo_name.clone()
+    } else {
+        format!("{}/{repo_name}", repo_ref.host)
+    };
+    let output = Cmd::new("gh", ["repo", "clone", &clone_name, repo_dir.as_str()])
         .run()
         .await?;
-    output.ensure_success(format!("❌ Failed to clone repository {repo_name}"))?;
+    output.ensure_success(format!("❌ Failed to clone repository {clone_name}"))?;
     Ok(())
 }
 
-pub fn local_repo_dir(owner: &str, repo: &str) -> anyhow::Result<Utf8PathBuf> {
+/// Clone of a repository managed by gigi: `~/proj/{owner}/{repo}` for
+/// github.com, `~/proj/{host}/{owner}/{repo}` for the other hosts.
+pub fn local_repo_dir(host: &str, owner: &str, repo: &str) -> anyhow::Result<Utf8PathBuf> {
     let home = std::env::var("HOME").context("HOME env var is not set")?;
-    Ok(Utf8PathBuf::from(home).join("proj").join(owner).join(repo))
+    let mut dir = Utf8PathBuf::from(home).join("proj");
+    if host != GITHUB_COM {
+        dir.push(host);
+    }
+    Ok(dir.join(owner).join(repo))
 }
 
 pub async fn checkout_pr(repo_dir: &Utf8Path, pr_url: &str) -> anyhow::Result<()> {
@@ -170,8 +192,13 @@ fn is_diverged_local_branch_error_text(details: &str) -> bool {
         || details.contains("Not possible to fast-forward, aborting.")
 }
 
-fn preferred_clone_target(details: &PrDetails, viewer_login: Option<&str>) -> CloneTarget {
+fn preferred_clone_target(
+    host: &str,
+    details: &PrDetails,
+    viewer_login: Option<&str>,
+) -> CloneTarget {
     let base_repo = GitHubRepoRef {
+        host: host.to_string(),
         owner: details.owner.clone(),
         repo: details.repo.clone(),
     };
@@ -190,6 +217,7 @@ fn preferred_clone_target(details: &PrDetails, viewer_login: Option<&str>) -> Cl
 
     CloneTarget {
         origin: GitHubRepoRef {
+            host: host.to_string(),
             owner: details.head_repo_owner.clone().unwrap_or_default(),
             repo: details.head_repo_name.clone().unwrap_or_default(),
         },
@@ -215,8 +243,8 @@ async fn ensure_remote_repo(
     }
 
     let expected_url = format!(
-        "https://github.com/{}/{}.git",
-        expected_repo.owner, expected_repo.repo
+        "https://{}/{}/{}.git",
+        expected_repo.host, expected_repo.owner, expected_repo.repo
     );
     let command = if output.status().success() {
         ["remote", "set-url", remote_name, &expected_url]
@@ -257,6 +285,7 @@ mod tests {
     #[test]
     fn prefers_fork_for_cross_repo_pr_opened_by_viewer() {
         let target = preferred_clone_target(
+            "github.com",
             &PrDetails {
                 pr_url: "https://github.com/upstream/repo/pull/1".to_string(),
                 owner: "upstream".to_string(),
@@ -264,6 +293,7 @@ mod tests {
                 number: 1,
                 state: "OPEN".to_string(),
                 merge_queue_state: None,
+                auto_merge: false,
                 title: "t".to_string(),
                 head_ref: "feat".to_string(),
                 base_ref: "main".to_string(),
@@ -284,10 +314,12 @@ mod tests {
             target,
             CloneTarget {
                 origin: GitHubRepoRef {
+                    host: "github.com".to_string(),
                     owner: "me".to_string(),
                     repo: "repo".to_string(),
                 },
                 upstream: Some(GitHubRepoRef {
+                    host: "github.com".to_string(),
                     owner: "upstream".to_string(),
                     repo: "repo".to_string(),
                 }),
@@ -298,6 +330,7 @@ mod tests {
     #[test]
     fn keeps_base_repo_for_non_viewer_prs() {
         let target = preferred_clone_target(
+            "github.com",
             &PrDetails {
                 pr_url: "https://github.com/upstream/repo/pull/1".to_string(),
                 owner: "upstream".to_string(),
@@ -305,6 +338,7 @@ mod tests {
                 number: 1,
                 state: "OPEN".to_string(),
                 merge_queue_state: None,
+                auto_merge: false,
                 title: "t".to_string(),
                 head_ref: "feat".to_string(),
                 base_ref: "main".to_string(),
@@ -325,6 +359,7 @@ mod tests {
             target,
             CloneTarget {
                 origin: GitHubRepoRef {
+                    host: "github.com".to_string(),
                     owner: "upstream".to_string(),
                     repo: "repo".to_string(),
                 },
@@ -0,0 +1,189 @@
+use std::collections::HashSet;
+
+use anyhow::Context as _;
+use serde_json::Value;
+
+use super::{
+    client::GitHubClient, hosts::GitHubHost, parsing::parse_repo_from_pr_url, types::MentionSummary,
+};
+
+/// Searches return at most this many issues and PRs.
+const MENTIONS_SEARCH_LIMIT: u32 = 50;
+
+/// Comments of each issue or PR looked at for the mention, newest last.
+const MENTIONS_COMMENT_LIMIT: u32 = 30;
+
+const MENTIONS_QUERY: &str = r"
+query($query: String!, $first: Int!, $comments: Int!) {
+  viewer { login }
+  search(query: $query, type: ISSUE, first: $first) {
+    nodes {
+      __typename
+      ... on Issue {
+        url title updatedAt repository { nameWithOwner }
+        comments(last: $comments) { nodes { url body } }
+      }
+      ... on PullRequest {
+        url title updatedAt repository { nameWithOwner }
+        comments(last: $comments) { nodes { url body } }
+      }
+    }
+  }
+}";
+
+pub async fn fetch_mentions(host: &GitHubHost) -> anyhow::Result<Vec<MentionSummary>> {
+    GitHubClient::for_host(host).await?.fetch_mentions().await
+}
+
+impl GitHubClient {
+    /// Open issues and PRs mentioning you, then the ones mentioning the
+    /// `team_mentions` of the host. An issue mentioning both is listed once,
+    /// as a direct mention.
+    pub async fn fetch_mentions(&self) -> anyhow::Result<Vec<MentionSummary>> {
+        let mut mentions = self
+            .search_mentions("mentions:@me", None)
+            .await
+            .context("❌ Failed to fetch mentions")?;
+        for team in &self.host().team_mentions {
+            let team_mentions = self
+                .search_mentions(&format!("team-mentions:{team}"), Some(team))
+                .await
+                .with_context(|| format!("❌ Failed to fetch mentions of team {team}"))?;
+            mentions.extend(team_mentions);
+        }
+
+        let mut seen = HashSet::new();
+        mentions.retain(|mention| seen.insert(mention.thread_url.clone()));
+        Ok(mentions)
+    }
+
+    async fn search_mentions(
+        &self,
+        qualifier: &str,
+        team: Option<&str>,
+    ) -> anyhow::Result<Vec<MentionSummary>> {
+        let variables = serde_json::json!({
+            "query": format!("{qualifier} is:open sort:updated-desc"),
+            "first": MENTIONS_SEARCH_LIMIT,
+            "comments": MENTIONS_COMMENT_LIMIT,
+        });
+        let data = self.graphql(MENTIONS_QUERY, variables).await?;
+        Ok(parse_mentions(&data, team))
+    }
+}
+
+/// Map the search results. The thread links to the newest comment with the
+/// mention, or to the issue itself when the mention is in its description.
+fn parse_mentions(data: &Value, team: Option<&str>) -> Vec<MentionSummary> {
+    let handle = match team {
+        Some(team) => team.to_string(),
+        None => data["viewer"]["login"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+    };
+
+    let nodes = data["search"]["nodes"].as_array().into_iter().flatten();
+    nodes
+        .filter_map(|node| {
+            let thread_url = node["url"].as_str()?.to_string();
+            let repository = node["repository"]["nameWithOwner"]
+                .as_str()
+                .map(ToString::to_string)
+                .or_else(|| parse_repo_from_pr_url(&thread_url))?;
+            let comment_url = node["comments"]["nodes"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .rev()
+                .find(|comment| {
+                    comment["body"]
+                        .as_str()
+                        .is_some_and(|body| mentions_handle(body, &handle))
+                })
+                .and_then(|comment| comment["url"].as_str());
+            Some(MentionSummary {
+                subject_url: comment_url.unwrap_or(&thread_url).to_string(),
+                is_pull_request: node["__typename"] == "PullRequest",
+                repository,
+                title: node["title"].as_str().unwrap_or("(untitled)").to_string(),
+                updated_at: node["updatedAt"].as_str().unwrap_or_default().to_string(),
+                team: team.map(ToString::to_string),
+                thread_url,
+            })
+        })
+        .collect()
+}
+
+/// `true` when `body` mentions `@handle`, ignoring case, and not as the
+/// prefix of a longer handle.
+fn mentions_handle(body: &str, handle: &str) -> bool {
+    if handle.is_empty() {
+        return false;
+    }
+    let body = body.to_ascii_lowercase();
+    let mention = format!("@{}", handle.to_ascii_lowercase());
+    body.match_indices(&mention).any(|(index, _)| {
+        let after = body[index + mention.len()..].chars().next();
+        let before = body[..index].chars().next_back();
+        after.is_none_or(|c| !c.is_ascii_alphanumeric() && !matches!(c, '-' | '_' | '/'))
+            && before.is_none_or(|c| !c.is_ascii_alphanumeric())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mentions_link_to_the_newest_comment_with_the_mention() {
+        let data = serde_json::json!({
+            "viewer": { "login": "Octocat" },
+            "search": { "nodes": [
+                {
+                    "__typename": "Issue",
+                    "url": "https://github.com/o/r/issues/1",
+                    "title": "Crash on start",
+                    "updatedAt": "2026-04-13T10:00:00Z",
+                    "repository": { "nameWithOwner": "o/r" },
+                    "comments": { "nodes": [
+                        { "url": "https://github.com/o/r/issues/1#issuecomment-1", "body": "cc @octocat" },
+                        { "url": "https://github.com/o/r/issues/1#issuecomment-2", "body": "@octocat, thoughts?" },
+                        { "url": "https://github.com/o/r/issues/1#issuecomment-3", "body": "@octocat-bot ran" }
+                    ] }
+                },
+                {
+                    "__typename": "PullRequest",
+                    "url": "https://github.com/o/r/pull/2",
+                    "title": "Fix crash",
+                    "updatedAt": "2026-04-12T10:00:00Z",
+                    "repository": { "nameWithOwner": "o/r" },
+                    "comments": { "nodes": [] }
+                }
+            ] }
+        });
+
+        let mentions = parse_mentions(&data, None);
+        let links: Vec<_> = mentions
+            .iter()
+            .map(|mention| (mention.subject_url.as_str(), mention.is_pull_request))
+            .collect();
+        assert_eq!(
+            links,
+            [
+                ("https://github.com/o/r/issues/1#issuecomment-2", false),
+                ("https://github.com/o/r/pull/2", true),
+            ]
+        );
+        assert_eq!(mentions[0].thread_url, "https://github.com/o/r/issues/1");
+        assert_eq!(mentions[0].team, None);
+    }
+
+    #[test]
+    fn team_mentions_match_the_team_handle() {
+        assert!(mentions_handle("ping @org/reviewers", "org/reviewers"));
+        assert!(!mentions_handle("ping @org/reviewers-ext", "org/reviewers"));
+        assert!(!mentions_handle("mail me at a@octocat", "octocat"));
+        assert!(mentions_handle("(@Octocat)", "octocat"));
+    }
+}
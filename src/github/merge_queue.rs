@@ -0,0 +1,240 @@
+use anyhow::Context as _;
+
+use crate::checkout::parse_github_pr_url;
+
+use super::{client::GitHubClient, hosts::hosts};
+
+const PR_QUERY: &str = r"
+query($owner: String!, $repo: String!, $number: Int!) {
+  repository(owner: $owner, name: $repo) {
+    viewerDefaultMergeMethod
+    pullRequest(number: $number) {
+      id state baseRefName isInMergeQueue
+      mergeQueueEntry { position }
+      autoMergeRequest { mergeMethod }
+    }
+  }
+}";
+
+const MERGE_QUEUE_QUERY: &str = r"
+query($owner: String!, $repo: String!, $branch: String!) {
+  repository(owner: $owner, name: $repo) {
+    mergeQueue(branch: $branch) { id }
+  }
+}";
+
+const ENQUEUE_MUTATION: &str = r"
+mutation($id: ID!) {
+  enqueuePullRequest(input: { pullRequestId: $id }) {
+    mergeQueueEntry { position }
+  }
+}";
+
+const AUTO_MERGE_MUTATION: &str = r"
+mutation($id: ID!, $method: PullRequestMergeMethod!) {
+  enablePullRequestAutoMerge(input: { pullRequestId: $id, mergeMethod: $method }) {
+    pullRequest { autoMergeRequest { mergeMethod } }
+  }
+}";
+
+/// What `gigi queue` did to the PR.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueueOutcome {
+    /// Added to the merge queue of its base branch, at `position`.
+    Enqueued {
+        position: Option<i64>,
+    },
+    AlreadyQueued {
+        position: Option<i64>,
+    },
+    /// The base branch has no merge queue: the PR is merged with
+    /// `merge_method` once mergeable.
+    AutoMergeEnabled {
+        merge_method: String,
+    },
+    AutoMergeAlreadyEnabled {
+        merge_method: String,
+    },
+}
+
+/// Add the PR to the merge queue of its base branch, or enable auto-merge
+/// when the branch has no merge queue.
+pub async fn queue_pr(pr_url: &str) -> anyhow::Result<()> {
+    let pr = parse_github_pr_url(pr_url)?;
+    let host = hosts()
+        .find(&pr.host)
+        .with_context(|| format!("❌ Unknown GitHub host {}", pr.host))?;
+    let outcome = GitHubClient::for_host(host)
+        .await?
+        .queue_pr(&pr.owner, &pr.repo, pr.number)
+        .await?;
+    let position = |position: Option<i64>| {
+        position.map_or_else(String::new, |position| format!(" at position {position}"))
+    };
+    match outcome {
+        QueueOutcome::Enqueued { position: p } => {
+            println!("🚀 Added {pr_url} to the merge queue{}", position(p));
+        }
+        QueueOutcome::AlreadyQueued { position: p } => {
+            println!("✅ {pr_url} is already in the merge queue{}", position(p));
+        }
+        QueueOutcome::AutoMergeEnabled { merge_method } => println!(
+            "🚀 No merge queue on the base branch: enabled auto-merge ({}) for {pr_url}",
+            merge_method.to_lowercase()
+        ),
+        QueueOutcome::AutoMergeAlreadyEnabled { merge_method } => println!(
+            "✅ Auto-merge ({}) is already enabled for {pr_url}",
+            merge_method.to_lowercase()
+        ),
+    }
+    Ok(())
+}
+
+impl GitHubClient {
+    pub async fn queue_pr(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+    ) -> anyhow::Result<QueueOutcome> {
+        let data = self
+            .graphql(
+                PR_QUERY,
+                serde_json::json!({ "owner": owner, "repo": repo, "number": number }),
+            )
+            .await
+            .context("❌ Failed to fetch the PR to queue")?;
+        let repository = &data["repository"];
+        let pull_request = &repository["pullRequest"];
+        let Some(id) = pull_request["id"].as_str() else {
+            anyhow::bail!("❌ PR {owner}/{repo}#{number} not found");
+        };
+        let state = pull_request["state"].as_str().unwrap_or_default();
+        anyhow::ensure!(
+            state == "OPEN",
+            "❌ PR {owner}/{repo}#{number} is {}",
+            state.to_lowercase()
+        );
+
+        if pull_request["isInMergeQueue"].as_bool() == Some(true) {
+            return Ok(QueueOutcome::AlreadyQueued {
+                position: pull_request["mergeQueueEntry"]["position"].as_i64(),
+            });
+        }
+        if let Some(merge_method) = pull_request["autoMergeRequest"]["mergeMethod"].as_str() {
+            return Ok(QueueOutcome::AutoMergeAlreadyEnabled {
+                merge_method: merge_method.to_string(),
+            });
+        }
+
+        let branch = pull_request["baseRefName"].as_str().unwrap_or_default();
+        let queues = self
+            .graphql(
+                MERGE_QUEUE_QUERY,
+                serde_json::json!({ "owner": owner, "repo": repo, "branch": branch }),
+            )
+            .await
+            .context("❌ Failed to look up the merge queue")?;
+        if queues["repository"]["mergeQueue"].is_object() {
+            let enqueued = self
+                .graphql(ENQUEUE_MUTATION, serde_json::json!({ "id": id }))
+                .await
+                .context("❌ Failed to add the PR to the merge queue")?;
+            return Ok(QueueOutcome::Enqueued {
+                position: enqueued["enqueuePullRequest"]["mergeQueueEntry"]["position"].as_i64(),
+            });
+        }
+
+        let merge_method = repository["viewerDefaultMergeMethod"]
+            .as_str()
+            .unwrap_or("MERGE");
+        let enabled = self
+            .graphql(
+                AUTO_MERGE_MUTATION,
+                serde_json::json!({ "id": id, "method": merge_method }),
+            )
+            .await
+            .context("❌ Failed to enable auto-merge")?;
+        Ok(QueueOutcome::AutoMergeEnabled {
+            merge_method: enabled["enablePullRequestAutoMerge"]["pullRequest"]["autoMergeRequest"]
+                ["mergeMethod"]
+                .as_str()
+                .unwrap_or(merge_method)
+                .to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Json, Router, routing::post};
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{github::GitHubHost, test_util::spawn_stub};
+
+    async fn spawn_graphql_stub(has_merge_queue: bool) -> GitHubClient {
+        let router = Router::new().route(
+            "/graphql",
+            post(async move |Json(body): Json<Value>| {
+                let query = body["query"].as_str().unwrap_or_default();
+                let data = if query.contains("enqueuePullRequest") {
+                    assert_eq!(body["variables"]["id"], "PR_1");
+                    serde_json::json!({
+                        "enqueuePullRequest": { "mergeQueueEntry": { "position": 3 } }
+                    })
+                } else if query.contains("enablePullRequestAutoMerge") {
+                    assert_eq!(body["variables"]["method"], "SQUASH");
+                    serde_json::json!({
+                        "enablePullRequestAutoMerge": {
+                            "pullRequest": { "autoMergeRequest": { "mergeMethod": "SQUASH" } }
+                        }
+                    })
+                } else if query.contains("mergeQueue(") {
+                    assert_eq!(body["variables"]["branch"], "main");
+                    let queue = has_merge_queue.then(|| serde_json::json!({ "id": "MQ_1" }));
+                    serde_json::json!({ "repository": { "mergeQueue": queue } })
+                } else {
+                    serde_json::json!({
+                        "repository": {
+                            "viewerDefaultMergeMethod": "SQUASH",
+                            "pullRequest": {
+                                "id": "PR_1",
+                                "state": "OPEN",
+                                "baseRefName": "main",
+                                "isInMergeQueue": false,
+                                "mergeQueueEntry": null,
+                                "autoMergeRequest": null
+                            }
+                        }
+                    })
+                };
+                Json(serde_json::json!({ "data": data }))
+            }),
+        );
+        let host = GitHubHost {
+            api_url: spawn_stub(router).await,
+            ..hosts().github_com().clone()
+        };
+        GitHubClient::new(host, "test-token".to_string()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn prs_are_added_to_the_merge_queue_of_their_base_branch() {
+        let client = spawn_graphql_stub(true).await;
+        let outcome = client.queue_pr("o", "r", 1).await.unwrap();
+        assert_eq!(outcome, QueueOutcome::Enqueued { position: Some(3) });
+    }
+
+    #[tokio::test]
+    async fn auto_merge_is_enabled_without_a_merge_queue() {
+        let client = spawn_graphql_stub(false).await;
+        let outcome = client.queue_pr("o", "r", 1).await.unwrap();
+        assert_eq!(
+            outcome,
+            QueueOutcome::AutoMergeEnabled {
+                merge_method: "SQUASH".to_string()
+            }
+        );
+    }
+}
@@ -1,21 +1,29 @@
 mod api;
+mod client;
+mod hosts;
 mod local_repo;
+mod mentions;
+mod merge_queue;
 mod parsing;
 mod types;
 
 pub use api::{
     fetch_assigned_issues, fetch_assigned_prs, fetch_authored_prs, fetch_batch,
-    fetch_notifications, fetch_pr_details, mark_notification_done, mark_notification_read,
+    fetch_notifications, fetch_pr_details, fetch_review_requested_prs, mark_notification_done,
+    mark_notification_read,
 };
+pub use client::{GitHubClient, RateLimit, ResponseCache};
+pub use hosts::{GITHUB_COM, GitHubHost, configure_hosts, hosts};
 pub use local_repo::{
     checkout_branch, checkout_pr, checkout_pr_for_open_with_details, current_branch,
     default_branch, ensure_local_repo, ensure_local_repo_for_pr, is_clean_repo, local_repo_dir,
     prepare_repo_for_pr_checkout, pull_ff_only,
 };
-pub use parsing::parse_github_name_with_owner;
-#[cfg(test)]
-pub use types::AssignedIssueSummary;
+pub use mentions::fetch_mentions;
+pub use merge_queue::queue_pr;
+pub use parsing::{parse_github_name_with_owner, split_github_remote_url};
 pub use types::{
-    AssignedIssuesSearchResult, AssignedPrSummary, AuthoredPrSummary, NotificationThread,
-    Participant, PrDetails,
+    AssignedIssueSummary, AssignedIssuesSearchResult, AssignedPrSummary, AuthoredPrSummary,
+    CheckOutcome, MentionSummary, NotificationThread, Participant, PrCheck, PrDetails,
+    ReviewRequestedPrSummary,
 };
@@ -2,25 +2,35 @@ use anyhow::Context as _;
 
 use crate::checkout::parse_github_pr_url;
 
-pub(super) fn api_url_to_pr_url(api_url: &str, subject_type: Option<&str>) -> Option<String> {
-    let trimmed = api_url.trim();
+use super::hosts::{GitHubHost, hosts};
 
-    if let Some(path) = trimmed
-        .strip_prefix("https://api.github.com/")
-        .unwrap_or(trimmed)
-        .strip_prefix("repos/")
-    {
-        return github_subject_path_to_pr_url(path, subject_type);
+pub(super) fn api_url_to_pr_url(api_url: &str, subject_type: Option<&str>) -> Option<String> {
+    let hosts = hosts();
+    if let Some((host, path)) = split_api_url(api_url) {
+        let path = path.strip_prefix("repos/")?;
+        return github_subject_path_to_pr_url(host, path, subject_type);
     }
 
-    if let Some(path) = trimmed.strip_prefix("https://github.com/") {
-        return github_subject_path_to_pr_url(path, subject_type);
-    }
+    let (host, path) = hosts.split_web_url(api_url)?;
+    github_subject_path_to_pr_url(host, path, subject_type)
+}
 
-    None
+/// Host of a REST API URL and the path after the API root. URLs starting
+/// with `repos/` are relative to the github.com API.
+pub(super) fn split_api_url(api_url: &str) -> Option<(&'static GitHubHost, &str)> {
+    let hosts = hosts();
+    let api_url = api_url.trim();
+    if api_url.starts_with("repos/") {
+        return Some((hosts.github_com(), api_url));
+    }
+    hosts.split_api_url(api_url)
 }
 
-fn github_subject_path_to_pr_url(path: &str, subject_type: Option<&str>) -> Option<String> {
+fn github_subject_path_to_pr_url(
+    host: &GitHubHost,
+    path: &str,
+    subject_type: Option<&str>,
+) -> Option<String> {
     let parts: Vec<&str> = path.split('/').collect();
     if parts.len() < 4 {
         return None;
@@ -35,19 +45,13 @@ fn github_subject_path_to_pr_url(path: &str, subject_type: Option<&str>) -> Opti
         return None;
     }
 
-    Some(format!(
-        "https://github.com/{}/{}/pull/{}",
-        parts[0], parts[1], parts[3]
-    ))
+    Some(host.web_url(&format!("{}/{}/pull/{}", parts[0], parts[1], parts[3])))
 }
 
 pub(super) fn api_url_to_html_url(api_url: &str) -> Option<String> {
-    let path = api_url
-        .trim()
-        .strip_prefix("https://api.github.com/")
-        .unwrap_or(api_url);
-
-    if let Some(path) = path.strip_prefix("repos/") {
+    if let Some((host, path)) = split_api_url(api_url)
+        && let Some(path) = path.strip_prefix("repos/")
+    {
         let parts: Vec<&str> = path.split('/').collect();
         if parts.len() < 4 {
             return None;
@@ -66,7 +70,7 @@ pub(super) fn api_url_to_html_url(api_url: &str) -> Option<String> {
             _ => return None,
         };
 
-        return Some(format!("https://github.com/{owner}/{repo}/{route}"));
+        return Some(host.web_url(&format!("{owner}/{repo}/{route}")));
     }
 
     Some(api_url.to_string())
@@ -77,9 +81,9 @@ pub(super) fn parse_repo_from_pr_url(pr_url: &str) -> Option<String> {
     Some(format!("{}/{}", parsed.owner, parsed.repo))
 }
 
-pub(super) fn current_viewer_login() -> anyhow::Result<String> {
+pub(super) fn current_viewer_login(host: &str) -> anyhow::Result<String> {
     let output = std::process::Command::new("gh")
-        .args(["api", "user", "--jq", ".login"])
+        .args(["api", "--hostname", host, "user", "--jq", ".login"])
         .output()
         .context("❌ Failed to detect current GitHub user")?;
     anyhow::ensure!(
@@ -94,19 +98,19 @@ pub(super) fn current_viewer_login() -> anyhow::Result<String> {
     Ok(login.trim().to_string())
 }
 
+/// `owner/repo` of a git remote URL of github.com or of a configured host.
 pub fn parse_github_name_with_owner(url: &str) -> Option<String> {
+    split_github_remote_url(url).map(|(_, name_with_owner)| name_with_owner)
+}
+
+/// Known GitHub host and `owner/repo` of a git remote URL.
+pub fn split_github_remote_url(url: &str) -> Option<(&'static GitHubHost, String)> {
     let trimmed = url.trim().trim_end_matches('/');
-    let path = if let Some(path) = trimmed.strip_prefix("git@github.com:") {
-        path
-    } else if let Some(path) = trimmed.strip_prefix("ssh://git@github.com/") {
-        path
-    } else if let Some(path) = trimmed.strip_prefix("https://github.com/") {
-        path
-    } else if let Some(path) = trimmed.strip_prefix("http://github.com/") {
-        path
-    } else {
-        trimmed.strip_prefix("git://github.com/")?
-    };
+    let without_scheme = ["ssh://git@", "git@", "https://", "http://", "git://"]
+        .iter()
+        .find_map(|scheme| trimmed.strip_prefix(scheme))?;
+    let (host, path) = without_scheme.split_once(['/', ':'])?;
+    let host = hosts().find(host)?;
 
     let normalized = path.strip_suffix(".git").unwrap_or(path).trim_matches('/');
     let mut parts = normalized.split('/');
@@ -116,7 +120,7 @@ pub fn parse_github_name_with_owner(url: &str) -> Option<String> {
         return None;
     }
 
-    Some(format!("{owner}/{repo}"))
+    Some((host, format!("{owner}/{repo}")))
 }
 
 #[cfg(test)]
@@ -188,6 +192,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn converts_api_urls_of_a_configured_host() {
+        assert_eq!(
+            api_url_to_pr_url("https://github.example.com/api/v3/repos/o/r/pulls/7", None)
+                .as_deref(),
+            Some("https://github.example.com/o/r/pull/7")
+        );
+        assert_eq!(
+            api_url_to_html_url("https://github.example.com/api/v3/repos/o/r/issues/8").as_deref(),
+            Some("https://github.example.com/o/r/issues/8")
+        );
+        assert_eq!(
+            parse_github_name_with_owner("git@github.example.com:o/r.git"),
+            Some("o/r".to_string())
+        );
+        let (host, name_with_owner) =
+            split_github_remote_url("https://github.example.com/o/r.git").unwrap();
+        assert_eq!(
+            (host.host.as_str(), name_with_owner.as_str()),
+            ("github.example.com", "o/r")
+        );
+    }
+
     #[test]
     fn test_parse_github_name_with_owner_rejects_non_github() {
         assert_eq!(
@@ -27,6 +27,7 @@ pub struct LocalPrRepo {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(super) struct GitHubRepoRef {
+    pub host: String,
     pub owner: String,
     pub repo: String,
 }
@@ -66,6 +67,33 @@ pub struct AssignedPrSummary {
     pub is_draft: bool,
 }
 
+#[derive(Debug, Clone)]
+pub struct ReviewRequestedPrSummary {
+    pub pr_url: String,
+    pub repository: String,
+    pub title: String,
+    pub updated_at: String,
+    pub is_draft: bool,
+    /// The review is requested from one of your teams, not from you.
+    pub team_request: bool,
+}
+
+/// Open issue or PR mentioning you, or one of your teams.
+#[derive(Debug, Clone)]
+pub struct MentionSummary {
+    /// Issue or PR URL.
+    pub thread_url: String,
+    /// URL of the comment with the mention, or `thread_url` when the
+    /// mention is in the description.
+    pub subject_url: String,
+    pub repository: String,
+    pub title: String,
+    pub updated_at: String,
+    pub is_pull_request: bool,
+    /// Mentioned team, as `org/team`; `None` for mentions of you.
+    pub team: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct AssignedIssueSummary {
     pub issue_url: String,
@@ -89,6 +117,8 @@ pub struct PrDetails {
     pub number: i64,
     pub state: String,
     pub merge_queue_state: Option<String>,
+    /// Auto-merge is enabled: the PR is merged, or queued, once mergeable.
+    pub auto_merge: bool,
     pub title: String,
     pub head_ref: String,
     pub base_ref: String,
@@ -103,6 +133,45 @@ pub struct PrDetails {
     pub is_draft: bool,
 }
 
+/// A check run or a commit status of the head commit of a PR.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrCheck {
+    pub name: String,
+    /// Conclusion of a completed check run, otherwise its status, or the
+    /// state of a commit status, e.g. `SUCCESS`, `IN_PROGRESS` or `ERROR`.
+    pub conclusion: String,
+    pub url: Option<String>,
+}
+
+/// Whether a check, or the CI of a PR as a whole, passed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckOutcome {
+    Pass,
+    Fail,
+    Pending,
+}
+
+impl PrCheck {
+    pub fn outcome(&self) -> CheckOutcome {
+        match self.conclusion.as_str() {
+            "SUCCESS" | "NEUTRAL" | "SKIPPED" => CheckOutcome::Pass,
+            "FAILURE" | "ERROR" | "TIMED_OUT" | "CANCELLED" | "ACTION_REQUIRED"
+            | "STARTUP_FAILURE" | "STALE" => CheckOutcome::Fail,
+            _ => CheckOutcome::Pending,
+        }
+    }
+}
+
+impl CheckOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pass => "pass",
+            Self::Fail => "fail",
+            Self::Pending => "pending",
+        }
+    }
+}
+
 /// Result of a batch GraphQL fetch.
 #[derive(Debug, Default)]
 pub struct BatchFetchResult {
@@ -115,4 +184,18 @@ pub struct BatchFetchResult {
     pub discussion_answers: HashMap<String, bool>,
     /// Maps PR URL to the list of participants (for avatar display).
     pub participants: HashMap<String, Vec<Participant>>,
+    /// Maps PR URL to the checks of its head commit.
+    pub checks: HashMap<String, Vec<PrCheck>>,
+}
+
+impl BatchFetchResult {
+    /// Add the results of another batch.
+    pub fn extend(&mut self, other: Self) {
+        self.pr_details.extend(other.pr_details);
+        self.issue_states.extend(other.issue_states);
+        self.discussion_states.extend(other.discussion_states);
+        self.discussion_answers.extend(other.discussion_answers);
+        self.participants.extend(other.participants);
+        self.checks.extend(other.checks);
+    }
 }
@@ -35,3 +35,26 @@ pub async fn open_terminal(repo_dir: &Utf8Path) -> anyhow::Result<()> {
     open.ensure_success("❌ Failed to open Terminal")?;
     Ok(())
 }
+
+pub async fn open_url(url: &str) -> anyhow::Result<()> {
+    let open = Cmd::new("open", [url])
+        .with_title(format!("🌐 open {url}"))
+        .run()
+        .await;
+
+    if let Ok(open) = open
+        && open.status().success()
+    {
+        return Ok(());
+    }
+
+    let xdg_open = Cmd::new("xdg-open", [url])
+        .with_title(format!("🌐 xdg-open {url}"))
+        .run()
+        .await?;
+    xdg_open.ensure_success(format!(
+        "❌ Failed to open {url} (tried `open` and `xdg-open`)"
+    ))?;
+
+    Ok(())
+}
@@ -0,0 +1,162 @@
+/// Line of one side of a [`DiffRow`], numbered from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffLine<'a> {
+    pub number: usize,
+    pub text: &'a str,
+}
+
+/// Row of a side-by-side diff. Removed lines are paired with the lines
+/// added in their place; `None` pads the shorter side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffRow<'a> {
+    pub old: Option<DiffLine<'a>>,
+    pub new: Option<DiffLine<'a>>,
+    pub changed: bool,
+}
+
+/// Cells of the longest common subsequence table above which the changed
+/// lines are listed as removed and added instead of being matched.
+const MAX_LCS_CELLS: usize = 1_000_000;
+
+/// Side-by-side diff of the lines of `old` and `new`, following their
+/// longest common subsequence.
+pub fn side_by_side<'a>(old: &'a str, new: &'a str) -> Vec<DiffRow<'a>> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let (n, m) = (old_lines.len(), new_lines.len());
+
+    // Only the lines between the common prefix and suffix need matching.
+    let prefix = old_lines
+        .iter()
+        .zip(&new_lines)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let (old_end, new_end) = (n - suffix, m - suffix);
+
+    let line = |lines: &[&'a str], idx: usize| DiffLine {
+        number: idx + 1,
+        text: lines[idx],
+    };
+    let unchanged = |i: usize, j: usize| DiffRow {
+        old: Some(line(&old_lines, i)),
+        new: Some(line(&new_lines, j)),
+        changed: false,
+    };
+    let mut rows: Vec<DiffRow<'a>> = (0..prefix).map(|i| unchanged(i, i)).collect();
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    let (rows_left, cols_left) = (old_end - prefix, new_end - prefix);
+    if (rows_left + 1).saturating_mul(cols_left + 1) > MAX_LCS_CELLS {
+        removed.extend((prefix..old_end).map(|i| line(&old_lines, i)));
+        added.extend((prefix..new_end).map(|j| line(&new_lines, j)));
+    } else {
+        // common[i][j]: length of the common subsequence of the remaining
+        // old[prefix + i..] and new[prefix + j..].
+        let mut common = vec![vec![0_usize; cols_left + 1]; rows_left + 1];
+        for i in (0..rows_left).rev() {
+            for j in (0..cols_left).rev() {
+                common[i][j] = if old_lines[prefix + i] == new_lines[prefix + j] {
+                    common[i + 1][j + 1] + 1
+                } else {
+                    common[i + 1][j].max(common[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < rows_left || j < cols_left {
+            if i < rows_left && j < cols_left && old_lines[prefix + i] == new_lines[prefix + j] {
+                flush_changes(&mut rows, &mut removed, &mut added);
+                rows.push(unchanged(prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if j == cols_left || (i < rows_left && common[i + 1][j] >= common[i][j + 1]) {
+                removed.push(line(&old_lines, prefix + i));
+                i += 1;
+            } else {
+                added.push(line(&new_lines, prefix + j));
+                j += 1;
+            }
+        }
+    }
+    flush_changes(&mut rows, &mut removed, &mut added);
+    rows.extend((0..suffix).map(|k| unchanged(old_end + k, new_end + k)));
+    rows
+}
+
+fn flush_changes<'a>(
+    rows: &mut Vec<DiffRow<'a>>,
+    removed: &mut Vec<DiffLine<'a>>,
+    added: &mut Vec<DiffLine<'a>>,
+) {
+    let len = removed.len().max(added.len());
+    let mut removed_lines = removed.drain(..);
+    let mut added_lines = added.drain(..);
+    for _ in 0..len {
+        rows.push(DiffRow {
+            old: removed_lines.next(),
+            new: added_lines.next(),
+            changed: true,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(rows: &[DiffRow<'_>]) -> Vec<String> {
+        let side = |line: Option<DiffLine<'_>>| {
+            line.map_or_else(
+                || "-".to_string(),
+                |line| format!("{}:{}", line.number, line.text),
+            )
+        };
+        rows.iter()
+            .map(|row| {
+                let marker = if row.changed { "!" } else { " " };
+                format!("{marker}{} | {}", side(row.old), side(row.new))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pairs_replaced_lines_and_pads_the_shorter_side() {
+        let rows = side_by_side("a\nb\nc\nd", "a\nB\nB2\nc\ne");
+        assert_eq!(
+            render(&rows),
+            vec![
+                " 1:a | 1:a",
+                "!2:b | 2:B",
+                "!- | 3:B2",
+                " 3:c | 4:c",
+                "!4:d | 5:e",
+            ]
+        );
+    }
+
+    #[test]
+    fn identical_and_empty_texts() {
+        assert!(side_by_side("a\nb", "a\nb").iter().all(|row| !row.changed));
+        assert_eq!(render(&side_by_side("", "a")), vec!["!- | 1:a"]);
+        assert!(side_by_side("", "").is_empty());
+    }
+
+    #[test]
+    fn lists_large_changes_without_matching_them() {
+        let lines = |prefix: &str| -> String {
+            let changed: String = (0..2000).map(|i| format!("{prefix} {i}\n")).collect();
+            format!("a\n{changed}z")
+        };
+        let (old, new) = (lines("old"), lines("new"));
+        let rows = side_by_side(&old, &new);
+        assert_eq!(rows.len(), 2002);
+        assert_eq!(render(&rows[..2]), vec![" 1:a | 1:a", "!2:old 0 | 2:new 0"]);
+        assert_eq!(render(&rows[2001..]), vec![" 2002:z | 2002:z"]);
+    }
+}
@@ -1,3 +1,4 @@
+mod ai;
 mod args;
 mod authors;
 mod checkout;
@@ -6,21 +7,28 @@ mod commit;
 mod config;
 mod dashboard;
 mod db;
+mod forge;
 mod github;
 mod icons;
 mod init;
 mod launcher;
+mod line_diff;
+mod maintenance;
 mod review;
+mod search;
 mod serve;
 mod terminal;
+#[cfg(test)]
+mod test_util;
 mod web;
 mod workflows;
 
 use anyhow::Context;
 use args::CliArgs;
 use clap::Parser as _;
+use config::AppConfig;
 use git_cmd::Repo;
-use review::review_pr;
+use review::{PublishMode, review_pr, review_pr_interactive};
 
 use crate::{
     checkout::checkout_pr,
@@ -33,22 +41,83 @@ async fn main() -> anyhow::Result<()> {
     cmd::set_verbose(args.verbose);
 
     match args.command {
-        args::Command::CheckoutPr { pr } => checkout_pr(&pr).await,
+        args::Command::CheckoutPr { pr } => {
+            configure_forges(&load_config_or_default().await);
+            checkout_pr(&pr).await
+        }
 
         args::Command::OpenPr {
             message,
             agent,
             model,
         } => {
+            let user_config = config::load_user_config().await?;
+            configure_forges(&user_config);
             let repo_root = ensure_default_repo_and_root().await?;
-            open_pr(&repo_root, message, agent.as_ref(), model.as_deref()).await
+            let model = model.or_else(|| user_config.ai.model_for(agent?));
+            let agent = agent.map(|provider| ai::agent_for(provider, &user_config.ai));
+            open_pr(&repo_root, message, agent.as_deref(), model.as_deref()).await
         }
 
-        args::Command::Review { pr, agent, model } => {
+        args::Command::Review {
+            pr,
+            agent,
+            model,
+            min_severity,
+            post,
+            submit,
+            full_context,
+            incremental,
+            interactive,
+        } => {
+            let mut user_config = config::load_user_config().await?;
+            configure_forges(&user_config);
             let repo_root = ensure_default_repo_and_root().await?;
-            review_pr(&repo_root, &pr, agent.as_ref(), model.as_deref()).await
+            user_config.review.full_context |= full_context;
+            let provider = agent.unwrap_or(user_config.ai.provider);
+            let model = model.or_else(|| user_config.ai.model_for(provider));
+            let agent = ai::agent_for(provider, &user_config.ai);
+            if interactive {
+                return review_pr_interactive(&repo_root, &pr, agent.as_ref(), model.as_deref())
+                    .await;
+            }
+            review_pr(
+                &repo_root,
+                &pr,
+                agent.as_ref(),
+                model.as_deref(),
+                &user_config.review,
+                min_severity,
+                post.then_some(if submit {
+                    PublishMode::Submit
+                } else {
+                    PublishMode::Pending
+                }),
+                incremental,
+            )
+            .await
+        }
+
+        args::Command::Findings { pr, min_severity } => {
+            review::list_findings(pr.as_deref(), min_severity)
+        }
+
+        args::Command::Queue { pr } => {
+            configure_forges(&load_config_or_default().await);
+            github::queue_pr(&pr).await
         }
 
+        args::Command::Db { command } => match command {
+            args::DbCommand::Prune { dry_run } => maintenance::prune(dry_run).await,
+            args::DbCommand::Backup { destination } => maintenance::backup(destination),
+            args::DbCommand::Export { format, output } => {
+                maintenance::export(format, output.as_deref())
+            }
+            args::DbCommand::Import { input } => maintenance::import(&input),
+        },
+
+        args::Command::Search { query, limit } => search::run_search(&query.join(" "), limit),
+
         args::Command::Init => init::run_init().await,
 
         args::Command::Serve => serve::run_serve().await,
@@ -57,6 +126,7 @@ async fn main() -> anyhow::Result<()> {
             dry_run,
             add_co_author,
         } => {
+            configure_forges(&load_config_or_default().await);
             let repo_root = ensure_default_repo_and_root().await?;
             let repo = Repo::new(repo_root.clone())
                 .context("❌ Failed to open git repository for squash")?;
@@ -64,6 +134,7 @@ async fn main() -> anyhow::Result<()> {
         }
 
         args::Command::Sync => {
+            configure_forges(&load_config_or_default().await);
             let repo_root = ensure_default_repo_and_root().await?;
             sync_fork(&repo_root).await
         }
@@ -71,3 +142,22 @@ async fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Register the GitHub and GitLab hosts of `config`, so that their PR URLs
+/// and remotes are recognized.
+fn configure_forges(config: &AppConfig) {
+    github::configure_hosts(&config.github);
+    forge::configure_gitlab_hosts(&config.gitlab);
+}
+
+/// Config of the commands that only need it to recognize the forge hosts:
+/// they still work with the default hosts when it can't be loaded.
+async fn load_config_or_default() -> AppConfig {
+    match config::load_user_config().await {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("⚠️ Using the default config, failed to load it: {err:#}");
+            AppConfig::default()
+        }
+    }
+}
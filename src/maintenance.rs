@@ -0,0 +1,211 @@
+use std::{
+    io::Write as _,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
+
+use crate::{
+    config::{self, RetentionConfig},
+    db::{Db, DbExport, ExportRow, PruneStats},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// One JSON document with the rows of each table
+    #[default]
+    Json,
+    /// A `{"schema_version": N}` line, then one `{"table": ..., "row": ...}`
+    /// line per row
+    Ndjson,
+}
+
+/// `gigi db prune`: apply the `[retention]` of the config to the DB.
+pub async fn prune(dry_run: bool) -> anyhow::Result<()> {
+    let paths = config::resolve_paths()?;
+    let config = config::load_config(&paths.config_path).await?;
+    if !config.retention.is_enabled() {
+        println!("🗄️ Nothing to prune: set the limits of [retention] in the config");
+        return Ok(());
+    }
+    let db = Db::new(&paths.db_path)?;
+    prune_db(&db, &config.retention, dry_run)?;
+    Ok(())
+}
+
+/// The limits of `retention`, for logs.
+pub fn retention_summary(retention: &RetentionConfig) -> String {
+    format!(
+        "reviews_per_pr={} fix_runs_days={} sync_events_days={} closed_threads_days={}",
+        retention.reviews_per_pr,
+        retention.fix_runs_days,
+        retention.sync_events_days,
+        retention.closed_threads_days
+    )
+}
+
+/// Delete what `retention` no longer keeps, then `VACUUM` the DB to give
+/// the space back. `dry_run` only reports what would be deleted.
+pub fn prune_db(db: &Db, retention: &RetentionConfig, dry_run: bool) -> anyhow::Result<PruneStats> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs();
+    let stats = db.prune(retention, i64::try_from(now)?, dry_run)?;
+    let verb = if dry_run { "would delete" } else { "deleted" };
+    println!(
+        "🗄️ DB prune {verb}: reviews={} fix_runs={} sync_events={} threads={}",
+        stats.reviews, stats.fix_runs, stats.sync_events, stats.threads
+    );
+    if !dry_run && stats.total() > 0 {
+        db.vacuum()?;
+        println!("🗄️ DB vacuumed");
+    }
+    Ok(stats)
+}
+
+/// `gigi db backup`: copy the DB, even while `serve` writes to it. Defaults
+/// to a timestamped file next to the DB.
+pub fn backup(destination: Option<PathBuf>) -> anyhow::Result<()> {
+    let db_path = config::resolve_paths()?.db_path;
+    let destination = destination.unwrap_or_else(|| {
+        let ts = chrono::Local::now().format("%Y%m%d-%H%M%S");
+        db_path.with_file_name(format!("gigi-backup-{ts}.db"))
+    });
+    Db::new(&db_path)?.backup_to(&destination)?;
+    println!("🗄️ DB backed up to {}", destination.display());
+    Ok(())
+}
+
+/// `gigi db export`: write every table to `output`, or to stdout.
+pub fn export(format: ExportFormat, output: Option<&Path>) -> anyhow::Result<()> {
+    let export = Db::new(config::resolve_paths()?.db_path)?.export()?;
+    let mut out: Box<dyn std::io::Write> = match output {
+        Some(path) => Box::new(std::io::BufWriter::new(
+            std::fs::File::create(path)
+                .with_context(|| format!("❌ Failed to create {}", path.display()))?,
+        )),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    write_export(&export, format, &mut out)?;
+    out.flush()?;
+    if let Some(path) = output {
+        let rows: usize = export.tables.values().map(Vec::len).sum();
+        println!("🗄️ Exported {rows} rows to {}", path.display());
+    }
+    Ok(())
+}
+
+/// `gigi db import`: merge a JSON or NDJSON export into the DB.
+pub fn import(input: &Path) -> anyhow::Result<()> {
+    let file = std::fs::File::open(input)
+        .with_context(|| format!("❌ Failed to open {}", input.display()))?;
+    let export = read_export(std::io::BufReader::new(file))
+        .with_context(|| format!("❌ Failed to read the export {}", input.display()))?;
+    let stats = Db::new(config::resolve_paths()?.db_path)?.import(&export)?;
+    for (table, imported) in stats {
+        println!(
+            "🗄️ DB import {table}: merged={} skipped={}",
+            imported.merged, imported.skipped
+        );
+    }
+    Ok(())
+}
+
+fn write_export(
+    export: &DbExport,
+    format: ExportFormat,
+    out: &mut impl std::io::Write,
+) -> anyhow::Result<()> {
+    match format {
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, export)?;
+            writeln!(out)?;
+        }
+        ExportFormat::Ndjson => {
+            let header = serde_json::json!({ "schema_version": export.schema_version });
+            writeln!(out, "{header}")?;
+            for (table, rows) in &export.tables {
+                for row in rows {
+                    let line = serde_json::json!({ "table": table, "row": row });
+                    writeln!(out, "{line}")?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read either format: NDJSON starts with the `schema_version` line alone.
+fn read_export(input: impl std::io::BufRead) -> anyhow::Result<DbExport> {
+    let mut lines = input.lines();
+    let Some(first) = lines.next().transpose()? else {
+        anyhow::bail!("❌ Empty export");
+    };
+    let header: Option<serde_json::Value> = serde_json::from_str(&first).ok();
+    let schema_version = header
+        .as_ref()
+        .filter(|header| header.get("tables").is_none())
+        .and_then(|header| header["schema_version"].as_u64());
+    let Some(schema_version) = schema_version else {
+        let mut json = first;
+        for line in lines {
+            json.push('\n');
+            json.push_str(&line?);
+        }
+        return Ok(serde_json::from_str(&json)?);
+    };
+
+    let mut export = DbExport {
+        schema_version: usize::try_from(schema_version)?,
+        ..DbExport::default()
+    };
+    for (idx, line) in lines.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        #[derive(serde::Deserialize)]
+        struct Line {
+            table: String,
+            row: ExportRow,
+        }
+        let Line { table, row } = serde_json::from_str(&line)
+            .with_context(|| format!("❌ Invalid NDJSON line {}", idx + 2))?;
+        export.tables.entry(table).or_default().push(row);
+    }
+    Ok(export)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exports_read_back_in_both_formats() {
+        let mut row = ExportRow::new();
+        row.insert("pr_url".to_string(), "https://github.com/a/b/pull/1".into());
+        row.insert("number".to_string(), 1.into());
+        row.insert("merge_queue_state".to_string(), serde_json::Value::Null);
+        let export = DbExport {
+            schema_version: 1,
+            tables: [
+                ("prs".to_string(), vec![row.clone(), row]),
+                ("kv".to_string(), Vec::new()),
+            ]
+            .into_iter()
+            .collect(),
+        };
+
+        let mut json = Vec::new();
+        write_export(&export, ExportFormat::Json, &mut json).unwrap();
+        assert_eq!(read_export(json.as_slice()).unwrap(), export);
+
+        let mut ndjson = Vec::new();
+        write_export(&export, ExportFormat::Ndjson, &mut ndjson).unwrap();
+        assert_eq!(String::from_utf8_lossy(&ndjson).lines().count(), 3);
+        let read = read_export(ndjson.as_slice()).unwrap();
+        // Empty tables have no line.
+        assert_eq!(read.tables.get("prs"), export.tables.get("prs"));
+        assert_eq!(read.tables.get("kv"), None);
+    }
+}
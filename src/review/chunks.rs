@@ -0,0 +1,225 @@
+/// Rough token count used to size chunks. Close enough for code, where
+/// tokens average about four bytes.
+pub(super) fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+/// Diff of a single file: the `diff --git` header lines and its hunks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileDiff {
+    path: String,
+    header: String,
+    hunks: Vec<String>,
+}
+
+impl FileDiff {
+    fn text(&self) -> String {
+        let mut text = self.header.clone();
+        for hunk in &self.hunks {
+            text.push_str(hunk);
+        }
+        text
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(super) struct DiffSplit {
+    /// Parts of the diff to review, each within the token budget unless a
+    /// single hunk is larger than the budget.
+    pub chunks: Vec<String>,
+    /// Files left out because they match an exclude glob.
+    pub excluded: Vec<String>,
+}
+
+/// Split `diff` into chunks of at most `max_tokens`, keeping whole files
+/// together when they fit and splitting larger files by hunk. Files matching
+/// one of `exclude` are dropped.
+pub(super) fn split_diff(diff: &str, exclude: &[String], max_tokens: usize) -> DiffSplit {
+    let mut split = DiffSplit::default();
+    let mut current = String::new();
+
+    for file in parse_file_diffs(diff) {
+        if exclude.iter().any(|glob| glob_matches(glob, &file.path)) {
+            split.excluded.push(file.path);
+            continue;
+        }
+
+        let text = file.text();
+        if estimate_tokens(&current) + estimate_tokens(&text) <= max_tokens {
+            current.push_str(&text);
+            continue;
+        }
+        push_chunk(&mut split.chunks, &mut current);
+        if estimate_tokens(&text) <= max_tokens {
+            current = text;
+            continue;
+        }
+
+        // The file alone is too large: repeat its header in every chunk.
+        current.push_str(&file.header);
+        for hunk in &file.hunks {
+            if current.len() > file.header.len()
+                && estimate_tokens(&current) + estimate_tokens(hunk) > max_tokens
+            {
+                push_chunk(&mut split.chunks, &mut current);
+                current.push_str(&file.header);
+            }
+            current.push_str(hunk);
+        }
+        push_chunk(&mut split.chunks, &mut current);
+    }
+    push_chunk(&mut split.chunks, &mut current);
+
+    split
+}
+
+fn push_chunk(chunks: &mut Vec<String>, current: &mut String) {
+    if !current.trim().is_empty() {
+        chunks.push(std::mem::take(current));
+    }
+    current.clear();
+}
+
+/// Paths of the files changed by `diff`, in the new version of the tree.
+pub(super) fn changed_paths(diff: &str) -> Vec<String> {
+    parse_file_diffs(diff)
+        .into_iter()
+        .map(|file| file.path)
+        .collect()
+}
+
+fn parse_file_diffs(diff: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    for line in diff.split_inclusive('\n') {
+        if line.starts_with("diff --git ") {
+            files.push(FileDiff {
+                path: path_from_diff_header(line),
+                header: String::new(),
+                hunks: Vec::new(),
+            });
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+
+        if line.starts_with("@@") {
+            file.hunks.push(String::new());
+        }
+        match file.hunks.last_mut() {
+            Some(hunk) => hunk.push_str(line),
+            None => {
+                if let Some(path) = line.strip_prefix("+++ b/") {
+                    file.path = path.trim_end().to_string();
+                }
+                file.header.push_str(line);
+            }
+        }
+    }
+    files
+}
+
+/// Path from `diff --git a/<path> b/<path>`, refined later by the `+++` line.
+fn path_from_diff_header(line: &str) -> String {
+    line.trim_end()
+        .rsplit_once(" b/")
+        .map(|(_, path)| path.to_string())
+        .unwrap_or_default()
+}
+
+/// Minimal glob matching: `*` and `?` do not cross `/`, `**` matches any
+/// number of directories. Patterns without `/` match the file name, like in
+/// `.gitignore`.
+fn glob_matches(pattern: &str, path: &str) -> bool {
+    if pattern.contains('/') {
+        matches(pattern.as_bytes(), path.as_bytes())
+    } else {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        matches(pattern.as_bytes(), name.as_bytes())
+    }
+}
+
+fn matches(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', b'/', rest @ ..] => {
+            matches(rest, text)
+                || text
+                    .iter()
+                    .enumerate()
+                    .any(|(index, byte)| *byte == b'/' && matches(rest, &text[index + 1..]))
+        }
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|index| matches(rest, &text[index..])),
+        [b'*', rest @ ..] => {
+            let segment_end = text
+                .iter()
+                .position(|byte| *byte == b'/')
+                .unwrap_or(text.len());
+            (0..=segment_end).any(|index| matches(rest, &text[index..]))
+        }
+        [b'?', rest @ ..] => {
+            text.first().is_some_and(|byte| *byte != b'/') && matches(rest, &text[1..])
+        }
+        [expected, rest @ ..] => text.first() == Some(expected) && matches(rest, &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_diff(path: &str, hunks: &[&str]) -> String {
+        let mut diff = format!(
+            "diff --git a/{path} b/{path}\nindex 1..2 100644\n--- a/{path}\n+++ b/{path}\n"
+        );
+        for hunk in hunks {
+            diff.push_str(&format!("@@ -1,1 +1,1 @@\n{hunk}\n"));
+        }
+        diff
+    }
+
+    #[test]
+    fn glob_matching() {
+        assert!(glob_matches("*.lock", "Cargo.lock"));
+        assert!(glob_matches("*.lock", "crates/a/Cargo.lock"));
+        assert!(glob_matches("package-lock.json", "web/package-lock.json"));
+        assert!(glob_matches("**/generated/**", "src/generated/api.rs"));
+        assert!(glob_matches("dist/*.js", "dist/app.js"));
+        assert!(!glob_matches("dist/*.js", "dist/nested/app.js"));
+        assert!(!glob_matches("*.lock", "src/lock.rs"));
+        assert!(glob_matches("file?.rs", "file1.rs"));
+    }
+
+    #[test]
+    fn small_diff_is_a_single_chunk_without_excluded_files() {
+        let diff = format!(
+            "{}{}",
+            file_diff("src/a.rs", &["+a"]),
+            file_diff("Cargo.lock", &["+lock"])
+        );
+        let split = split_diff(&diff, &["*.lock".to_string()], 1_000);
+        assert_eq!(split.chunks, vec![file_diff("src/a.rs", &["+a"])]);
+        assert_eq!(split.excluded, vec!["Cargo.lock".to_string()]);
+    }
+
+    #[test]
+    fn files_are_grouped_until_the_budget_is_reached() {
+        let a = file_diff("a.rs", &[&"+a".repeat(40)]);
+        let b = file_diff("b.rs", &[&"+b".repeat(40)]);
+        let c = file_diff("c.rs", &[&"+c".repeat(40)]);
+        let budget = estimate_tokens(&a) + estimate_tokens(&b);
+        let split = split_diff(&format!("{a}{b}{c}"), &[], budget);
+        assert_eq!(split.chunks, vec![format!("{a}{b}"), c]);
+    }
+
+    #[test]
+    fn large_files_are_split_by_hunk_with_repeated_header() {
+        let hunk = "+x".repeat(100);
+        let diff = file_diff("big.rs", &[&hunk, &hunk, &hunk]);
+        let one_hunk = file_diff("big.rs", &[&hunk]);
+        let split = split_diff(&diff, &[], estimate_tokens(&one_hunk) + 5);
+        assert_eq!(
+            split.chunks,
+            vec![one_hunk.clone(), one_hunk.clone(), one_hunk]
+        );
+    }
+}
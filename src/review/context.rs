@@ -0,0 +1,342 @@
+use std::{
+    collections::BTreeSet,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use tokio::fs;
+
+use crate::{cmd::Cmd, config, forge};
+
+use super::chunks::{changed_paths, estimate_tokens};
+
+/// Keywords introducing a definition in common languages.
+const DEFINITION_KEYWORDS: [&str; 9] = [
+    "fn",
+    "def",
+    "func",
+    "function",
+    "class",
+    "struct",
+    "enum",
+    "trait",
+    "interface",
+];
+const MAX_SYMBOLS: usize = 15;
+const MAX_CALLERS_PER_SYMBOL: usize = 10;
+const MAX_TEST_FILES: usize = 20;
+
+/// Detached worktree of the PR head under the data dir, so that reviews
+/// leave the branches and the working tree of the clone under `~/proj` alone.
+#[derive(Debug)]
+pub(super) struct PrWorktree {
+    /// Clone the worktree belongs to.
+    repo_dir: Utf8PathBuf,
+    path: Utf8PathBuf,
+}
+
+impl PrWorktree {
+    /// Clone the PR repository under `~/proj` if needed, fetch the PR head and
+    /// check it out in a new worktree.
+    pub async fn create(pr_url: &str) -> anyhow::Result<Self> {
+        let (repo_dir, head) = forge::forge_for_url(pr_url)?.fetch_pr_head(pr_url).await?;
+        let db_path = Utf8PathBuf::try_from(config::resolve_paths()?.db_path)?;
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+        let path = db_path
+            .with_file_name("worktrees")
+            .join(format!("review-{}-{nanos}", std::process::id()));
+        Cmd::new(
+            "git",
+            ["worktree", "add", "--detach", path.as_str(), head.as_str()],
+        )
+        .with_current_dir(&repo_dir)
+        .run()
+        .await?
+        .ensure_success(format!("❌ Failed to create a worktree of {repo_dir}"))?;
+        println!("📦 Reviewing with the PR head checked out in {path}");
+        Ok(Self { repo_dir, path })
+    }
+
+    pub fn path(&self) -> &Utf8Path {
+        &self.path
+    }
+
+    /// Delete the worktree. Failures are only reported: the review is done.
+    pub async fn remove(self) {
+        let removed = Cmd::new("git", ["worktree", "remove", "--force", self.path.as_str()])
+            .with_current_dir(&self.repo_dir)
+            .run()
+            .await
+            .and_then(|output| output.ensure_success("git worktree remove failed"));
+        if let Err(err) = removed {
+            eprintln!("⚠️ Failed to remove the worktree {}: {err:#}", self.path);
+        }
+    }
+}
+
+/// Code related to a diff, read from the checked out PR head.
+#[derive(Debug, Default)]
+pub(super) struct RepoContext {
+    /// Changed files with their full content.
+    touched: Vec<(String, String)>,
+    /// `git grep` hits of the changed definitions outside the changed files.
+    callers: Vec<String>,
+    /// Test files next to or named after the changed files.
+    tests: Vec<(String, String)>,
+}
+
+impl RepoContext {
+    pub async fn collect(repo_dir: &Utf8Path, diff: &str) -> anyhow::Result<Self> {
+        let paths = changed_paths(diff);
+        let mut context = Self::default();
+        for path in &paths {
+            if let Some(content) = read_file(repo_dir, path).await {
+                context.touched.push((path.clone(), content));
+            }
+        }
+        for symbol in changed_symbols(diff) {
+            let hits = git_grep(repo_dir, &symbol).await?;
+            context.callers.extend(
+                hits.into_iter()
+                    .filter(|hit| {
+                        !paths
+                            .iter()
+                            .any(|path| hit.starts_with(&format!("{path}:")))
+                    })
+                    .take(MAX_CALLERS_PER_SYMBOL),
+            );
+        }
+        for path in related_test_files(&git_ls_files(repo_dir).await?, &paths) {
+            if let Some(content) = read_file(repo_dir, &path).await {
+                context.tests.push((path, content));
+            }
+        }
+        Ok(context)
+    }
+
+    /// Render the context within `max_tokens`. Files that do not fit are
+    /// listed by path only, so the agent can still open them.
+    pub fn to_prompt(&self, max_tokens: usize) -> String {
+        let mut budget = max_tokens;
+        let mut out = String::new();
+        if !self.touched.is_empty() {
+            out.push_str("FILES CHANGED BY THE PR (full content at the PR head):\n");
+            push_files(&mut out, &self.touched, &mut budget);
+        }
+        if !self.callers.is_empty() {
+            out.push_str("\nUSAGES OF THE CHANGED DEFINITIONS (git grep):\n");
+            for hit in &self.callers {
+                if estimate_tokens(hit) > budget {
+                    out.push_str("[... more usages omitted]\n");
+                    break;
+                }
+                budget -= estimate_tokens(hit);
+                out.push_str(hit);
+                out.push('\n');
+            }
+        }
+        if !self.tests.is_empty() {
+            out.push_str("\nRELATED TEST FILES:\n");
+            push_files(&mut out, &self.tests, &mut budget);
+        }
+        out
+    }
+}
+
+fn push_files(out: &mut String, files: &[(String, String)], budget: &mut usize) {
+    for (path, content) in files {
+        let tokens = estimate_tokens(content);
+        if tokens > *budget {
+            out.push_str(&format!("--- {path} (omitted, too large)\n"));
+            continue;
+        }
+        *budget -= tokens;
+        out.push_str(&format!("--- {path}\n{}\n", content.trim_end()));
+    }
+}
+
+async fn read_file(repo_dir: &Utf8Path, path: &str) -> Option<String> {
+    // Deleted and binary files are skipped.
+    fs::read_to_string(repo_dir.join(path)).await.ok()
+}
+
+/// Names defined on the changed lines, or enclosing the hunks.
+fn changed_symbols(diff: &str) -> Vec<String> {
+    let mut symbols = BTreeSet::new();
+    for line in diff.lines() {
+        let code = if line.starts_with("+++") || line.starts_with("---") {
+            continue;
+        } else if let Some(code) = line.strip_prefix('+').or_else(|| line.strip_prefix('-')) {
+            code
+        } else if line.starts_with("@@") {
+            // Hunk headers end with the enclosing definition, when git finds one.
+            line.rsplit_once("@@").map_or("", |(_, code)| code)
+        } else {
+            continue;
+        };
+        if let Some(symbol) = defined_symbol(code) {
+            symbols.insert(symbol);
+        }
+    }
+    symbols.into_iter().take(MAX_SYMBOLS).collect()
+}
+
+fn defined_symbol(code: &str) -> Option<String> {
+    let is_separator = |c: char| c.is_whitespace() || c == '(' || c == '<' || c == ':';
+    let mut offset = 0;
+    for piece in code.split_inclusive(is_separator) {
+        let word_end = offset + piece.trim_end_matches(is_separator).len();
+        let word = &code[offset..word_end];
+        offset += piece.len();
+        if !DEFINITION_KEYWORDS.contains(&word) {
+            continue;
+        }
+        let rest = code[word_end..].trim_start();
+        // Go methods: `func (r *Receiver) Name(`.
+        let rest = match rest.strip_prefix('(') {
+            Some(receiver) => receiver.split_once(')')?.1.trim_start(),
+            None => rest,
+        };
+        let name: String = rest
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+        return (name.len() >= 3).then_some(name);
+    }
+    None
+}
+
+async fn git_grep(repo_dir: &Utf8Path, symbol: &str) -> anyhow::Result<Vec<String>> {
+    let output = Cmd::new("git", ["grep", "-n", "-w", "-F", "-e", symbol])
+        .with_current_dir(repo_dir)
+        .hide_stdout()
+        .run()
+        .await?;
+    // `git grep` exits with 1 when nothing matches.
+    if !output.status().success() {
+        return Ok(Vec::new());
+    }
+    Ok(output.stdout().lines().map(str::to_string).collect())
+}
+
+async fn git_ls_files(repo_dir: &Utf8Path) -> anyhow::Result<Vec<String>> {
+    let output = Cmd::new("git", ["ls-files"])
+        .with_current_dir(repo_dir)
+        .hide_stdout()
+        .run()
+        .await?;
+    output.ensure_success("❌ Failed to list repository files")?;
+    Ok(output.stdout().lines().map(str::to_string).collect())
+}
+
+/// Test files named after a changed file, or `tests.*` modules next to it.
+/// Changed test files are already part of the context.
+fn related_test_files(files: &[String], changed: &[String]) -> Vec<String> {
+    let targets: Vec<(&str, &str)> = changed
+        .iter()
+        .filter(|path| !is_test_path(path))
+        .map(|path| {
+            let (dir, name) = path.rsplit_once('/').unwrap_or(("", path));
+            let stem = name.split('.').next().unwrap_or(name);
+            (dir, stem)
+        })
+        .filter(|(_, stem)| stem.len() >= 3 && *stem != "mod" && *stem != "index")
+        .collect();
+
+    files
+        .iter()
+        .filter(|file| is_test_path(file) && !changed.contains(file))
+        .filter(|file| {
+            let (dir, name) = file.rsplit_once('/').unwrap_or(("", file));
+            targets.iter().any(|(target_dir, stem)| {
+                let module_dir = if target_dir.is_empty() {
+                    (*stem).to_string()
+                } else {
+                    format!("{target_dir}/{stem}")
+                };
+                name.contains(stem)
+                    || (name.split('.').next() == Some("tests")
+                        && (dir == *target_dir || dir == module_dir))
+            })
+        })
+        .take(MAX_TEST_FILES)
+        .cloned()
+        .collect()
+}
+
+fn is_test_path(path: &str) -> bool {
+    path.split('/').any(|segment| {
+        let segment = segment.to_lowercase();
+        segment == "tests"
+            || segment == "test"
+            || segment == "__tests__"
+            || segment.contains("_test.")
+            || segment.contains(".test.")
+            || segment.contains(".spec.")
+            || segment.starts_with("test_")
+            || segment == "tests.rs"
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_symbols_defined_or_enclosing_the_changes() {
+        let diff = "\
+diff --git a/src/cache.rs b/src/cache.rs
+--- a/src/cache.rs
++++ b/src/cache.rs
+@@ -10,3 +10,4 @@ impl Cache {
++    pub fn invalidate(&mut self, key: &str) {
+@@ -40,2 +41,2 @@ fn evict_oldest(&mut self) {
+-    let x = 1;
++func (c *Cache) Lookup(key string) {}
+";
+        assert_eq!(
+            changed_symbols(diff),
+            ["Lookup", "evict_oldest", "invalidate"]
+        );
+    }
+
+    #[test]
+    fn selects_tests_related_to_changed_files() {
+        let files: Vec<String> = [
+            "src/cache.rs",
+            "src/cache/tests.rs",
+            "tests/cache_test.rs",
+            "tests/other_test.rs",
+            "web/cache.spec.ts",
+            "src/db/tests.rs",
+        ]
+        .into_iter()
+        .map(ToString::to_string)
+        .collect();
+        let changed = vec!["src/cache.rs".to_string()];
+        assert_eq!(
+            related_test_files(&files, &changed),
+            [
+                "src/cache/tests.rs",
+                "tests/cache_test.rs",
+                "web/cache.spec.ts"
+            ]
+        );
+    }
+
+    #[test]
+    fn prompt_lists_files_over_budget_by_path() {
+        let context = RepoContext {
+            touched: vec![
+                ("small.rs".to_string(), "fn a() {}".to_string()),
+                ("big.rs".to_string(), "x".repeat(400)),
+            ],
+            callers: vec!["src/main.rs:3:    a();".to_string()],
+            tests: Vec::new(),
+        };
+        let prompt = context.to_prompt(50);
+        assert!(prompt.contains("--- small.rs\nfn a() {}\n"));
+        assert!(prompt.contains("--- big.rs (omitted, too large)"));
+        assert!(prompt.contains("src/main.rs:3:    a();"));
+    }
+}
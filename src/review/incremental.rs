@@ -0,0 +1,172 @@
+use std::collections::BTreeSet;
+
+use camino::Utf8Path;
+
+use crate::{db::Db, forge};
+
+use super::{FindingStatus, ReviewFinding, structured::StructuredReview};
+
+/// Earlier review of a PR that a re-review builds on.
+#[derive(Debug, Clone)]
+pub struct PreviousReview {
+    pub id: i64,
+    /// Head SHA the previous review was computed against, if known.
+    pub head_sha: Option<String>,
+    pub summary: String,
+    /// Findings not resolved by the previous review, numbered from 1 in the
+    /// prompt.
+    pub findings: Vec<ReviewFinding>,
+}
+
+impl PreviousReview {
+    /// Latest stored review of `pr_url`. `fallback_sha` is used for reviews
+    /// stored before their head SHA was recorded.
+    pub fn load(db: &Db, pr_url: &str, fallback_sha: Option<&str>) -> anyhow::Result<Option<Self>> {
+        let Some(review) = db.latest_review_by_url(pr_url)? else {
+            return Ok(None);
+        };
+        let findings = db
+            .review_findings(review.id)?
+            .into_iter()
+            .map(|item| item.finding)
+            .filter(|finding| !finding.is_resolved())
+            .collect();
+        Ok(Some(Self {
+            id: review.id,
+            head_sha: review
+                .head_sha
+                .or_else(|| fallback_sha.map(ToString::to_string)),
+            summary: review.summary.unwrap_or(review.content_md),
+            findings,
+        }))
+    }
+
+    pub(super) fn to_prompt(&self) -> String {
+        let mut out = format!("Summary: {}\n\nFindings:\n", self.summary.trim());
+        if self.findings.is_empty() {
+            out.push_str("(none)\n");
+        }
+        for (index, finding) in self.findings.iter().enumerate() {
+            out.push_str(&format!(
+                "{}. [{}] {} ({}): {}\n",
+                index + 1,
+                finding.severity,
+                super::finding_location(finding),
+                finding.category,
+                finding.suggestion.trim()
+            ));
+        }
+        out
+    }
+}
+
+/// Diff of the commits pushed between `base_sha` and `head_sha`. Returns
+/// `None` when `head_sha` does not descend from `base_sha` anymore, e.g.
+/// after a force-push, so that the whole PR is reviewed again.
+pub(super) async fn fetch_incremental_diff(
+    repo_root: &Utf8Path,
+    pr_url: &str,
+    base_sha: &str,
+    head_sha: &str,
+) -> anyhow::Result<Option<String>> {
+    forge::forge_for_url(pr_url)?
+        .incremental_diff(repo_root, pr_url, base_sha, head_sha)
+        .await
+}
+
+/// Turn the answer of a re-review into the full list of findings: the new
+/// ones, the previous ones still open, and the previous ones resolved.
+pub(super) fn classify_findings(previous: &PreviousReview, review: &mut StructuredReview) {
+    let resolved: BTreeSet<usize> = review
+        .resolved
+        .iter()
+        .copied()
+        .filter(|number| (1..=previous.findings.len()).contains(number))
+        .collect();
+
+    let mut findings = std::mem::take(&mut review.findings);
+    for finding in &mut findings {
+        finding.status = Some(FindingStatus::New);
+    }
+    for (status, resolved_filter) in [
+        (FindingStatus::Open, false),
+        (FindingStatus::Resolved, true),
+    ] {
+        findings.extend(
+            previous
+                .findings
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| resolved.contains(&(index + 1)) == resolved_filter)
+                .map(|(_, finding)| ReviewFinding {
+                    status: Some(status),
+                    ..finding.clone()
+                }),
+        );
+    }
+    review.findings = findings;
+    review.resolved = resolved.into_iter().collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::review::{Severity, Verdict};
+
+    fn finding(file: &str) -> ReviewFinding {
+        ReviewFinding {
+            file: file.to_string(),
+            start_line: 1,
+            end_line: 1,
+            severity: Severity::Minor,
+            category: "bug".to_string(),
+            suggestion: "Fix it".to_string(),
+            status: None,
+        }
+    }
+
+    #[test]
+    fn findings_are_classified_against_the_previous_review() {
+        let previous = PreviousReview {
+            id: 1,
+            head_sha: Some("sha1".to_string()),
+            summary: "Old".to_string(),
+            findings: vec![finding("a.rs"), finding("b.rs")],
+        };
+        let mut review = StructuredReview {
+            summary: "New".to_string(),
+            verdict: Verdict::Comment,
+            findings: vec![finding("c.rs")],
+            resolved: vec![2, 7],
+        };
+
+        classify_findings(&previous, &mut review);
+
+        let classified: Vec<_> = review
+            .findings
+            .iter()
+            .map(|finding| (finding.file.as_str(), finding.status))
+            .collect();
+        assert_eq!(
+            classified,
+            [
+                ("c.rs", Some(FindingStatus::New)),
+                ("a.rs", Some(FindingStatus::Open)),
+                ("b.rs", Some(FindingStatus::Resolved)),
+            ]
+        );
+        assert_eq!(review.resolved, [2]);
+    }
+
+    #[test]
+    fn previous_findings_are_numbered_in_the_prompt() {
+        let previous = PreviousReview {
+            id: 1,
+            head_sha: None,
+            summary: "Old".to_string(),
+            findings: vec![finding("a.rs"), finding("b.rs")],
+        };
+        let prompt = previous.to_prompt();
+        assert!(prompt.contains("1. [minor] a.rs:1 (bug): Fix it\n2. [minor] b.rs:1"));
+    }
+}
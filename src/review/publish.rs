@@ -0,0 +1,430 @@
+use std::collections::HashMap;
+
+use camino::Utf8Path;
+use inquire::MultiSelect;
+use serde::Serialize;
+
+use crate::forge::{self, ReviewToPublish};
+
+use super::{ReviewFinding, Verdict, finding_location};
+
+/// Maps a line of a file to another number, per line of the new version.
+type LineMap = HashMap<u32, u32>;
+
+/// Maps lines of the new version of each file to their `position` in the PR
+/// diff, as expected by the GitHub review comments API.
+#[derive(Debug, Default)]
+pub struct DiffPositions {
+    files: HashMap<String, LineMap>,
+    /// Line in the old version of each unchanged line of the new version,
+    /// needed to anchor GitLab comments on context lines.
+    unchanged: HashMap<String, LineMap>,
+}
+
+/// Line of the diff a finding is anchored on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffLine {
+    pub new_line: u32,
+    /// Set for unchanged lines.
+    pub old_line: Option<u32>,
+}
+
+impl DiffPositions {
+    /// Parse the unified diff printed by `gh pr diff`. Positions count every
+    /// line after the first hunk header of a file, including later hunk
+    /// headers and removed lines.
+    pub fn parse(diff: &str) -> Self {
+        let mut files = HashMap::new();
+        let mut unchanged = HashMap::new();
+        let mut current: Option<(String, LineMap, LineMap)> = None;
+        let mut in_header = false;
+        let mut position = 0;
+        let mut new_line = 0;
+        let mut old_line = 0;
+
+        for line in diff.lines() {
+            if line.starts_with("diff --git ") {
+                if let Some((file, lines, old_lines)) = current.take() {
+                    unchanged.insert(file.clone(), old_lines);
+                    files.insert(file, lines);
+                }
+                in_header = true;
+                position = 0;
+                continue;
+            }
+            if in_header {
+                if let Some(path) = line.strip_prefix("+++ ") {
+                    // Deleted files have no new version to comment on.
+                    current = path
+                        .strip_prefix("b/")
+                        .map(|path| (path.to_string(), HashMap::new(), HashMap::new()));
+                }
+                if !line.starts_with("@@") {
+                    continue;
+                }
+                in_header = false;
+            }
+            let Some((_, lines, old_lines)) = current.as_mut() else {
+                continue;
+            };
+
+            if line.starts_with("@@") {
+                // The first hunk header is position 0, later ones take a position.
+                if position > 0 {
+                    position += 1;
+                }
+                new_line = parse_hunk_start(line, '+').unwrap_or_default();
+                old_line = parse_hunk_start(line, '-').unwrap_or_default();
+                continue;
+            }
+
+            position += 1;
+            if line.starts_with(' ') {
+                old_lines.insert(new_line, old_line);
+            }
+            if line.starts_with('+') || line.starts_with(' ') {
+                lines.insert(new_line, position);
+                new_line += 1;
+            }
+            if line.starts_with('-') || line.starts_with(' ') {
+                old_line += 1;
+            }
+        }
+        if let Some((file, lines, old_lines)) = current {
+            unchanged.insert(file.clone(), old_lines);
+            files.insert(file, lines);
+        }
+
+        Self { files, unchanged }
+    }
+
+    /// Position to anchor a finding on: its last line when it is part of the
+    /// diff, otherwise the closest earlier line of its range that is.
+    pub fn position_for(&self, finding: &ReviewFinding) -> Option<u32> {
+        let lines = self.files.get(&finding.file)?;
+        self.line_for(finding)
+            .and_then(|line| lines.get(&line.new_line).copied())
+    }
+
+    /// Line to anchor a finding on, picked like [`Self::position_for`].
+    pub fn line_for(&self, finding: &ReviewFinding) -> Option<DiffLine> {
+        let lines = self.files.get(&finding.file)?;
+        let new_line = (finding.start_line..=finding.end_line)
+            .rev()
+            .find(|line| lines.contains_key(line))?;
+        Some(DiffLine {
+            new_line,
+            old_line: self
+                .unchanged
+                .get(&finding.file)
+                .and_then(|old_lines| old_lines.get(&new_line))
+                .copied(),
+        })
+    }
+}
+
+/// Start of the new (`+`) or old (`-`) range of a hunk header.
+fn parse_hunk_start(header: &str, side: char) -> Option<u32> {
+    let range = header
+        .split_whitespace()
+        .find_map(|part| part.strip_prefix(side))?;
+    range.split(',').next()?.parse().ok()
+}
+
+/// Whether a published review is left pending for the user to submit from
+/// GitHub, or submitted right away with the event matching the verdict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PublishMode {
+    Pending,
+    Submit,
+}
+
+impl std::fmt::Display for PublishMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Pending => "pending",
+            Self::Submit => "submitted",
+        })
+    }
+}
+
+/// Payload of `POST /repos/{owner}/{repo}/pulls/{number}/reviews`.
+#[derive(Debug, Serialize)]
+pub struct ReviewPayload {
+    pub body: String,
+    /// `None` leaves the review pending.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<&'static str>,
+    pub comments: Vec<ReviewComment>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReviewComment {
+    pub path: String,
+    pub position: u32,
+    pub body: String,
+}
+
+fn review_event(verdict: Verdict) -> &'static str {
+    match verdict {
+        Verdict::Approve => "APPROVE",
+        Verdict::Comment => "COMMENT",
+        Verdict::RequestChanges => "REQUEST_CHANGES",
+    }
+}
+
+/// Findings that cannot be anchored on the diff are listed in the review body.
+pub fn build_review_payload(
+    summary: &str,
+    verdict: Verdict,
+    findings: &[ReviewFinding],
+    positions: &DiffPositions,
+    mode: PublishMode,
+) -> ReviewPayload {
+    let mut comments = Vec::new();
+    let mut unanchored = Vec::new();
+    for finding in findings {
+        match positions.position_for(finding) {
+            Some(position) => comments.push(ReviewComment {
+                path: finding.file.clone(),
+                position,
+                body: comment_body(finding),
+            }),
+            None => unanchored.push(finding),
+        }
+    }
+
+    ReviewPayload {
+        body: review_body(summary, &unanchored),
+        event: (mode == PublishMode::Submit).then(|| review_event(verdict)),
+        comments,
+    }
+}
+
+/// Summary of a published review, followed by the findings that cannot be
+/// anchored on the diff.
+pub fn review_body(summary: &str, unanchored: &[&ReviewFinding]) -> String {
+    let mut body = summary.trim().to_string();
+    if !unanchored.is_empty() {
+        body.push_str("\n\n**Findings outside the diff:**\n");
+        for finding in unanchored {
+            body.push_str(&format!(
+                "\n- **{}** `{}` ({}): {}",
+                finding.severity,
+                finding_location(finding),
+                finding.category,
+                finding.suggestion.trim()
+            ));
+        }
+    }
+    body
+}
+
+/// Inline comment of a finding anchored on the diff.
+pub fn comment_body(finding: &ReviewFinding) -> String {
+    let lines = if finding.start_line == finding.end_line {
+        String::new()
+    } else {
+        format!(" (lines {}-{})", finding.start_line, finding.end_line)
+    };
+    format!(
+        "**{}** · {}{lines}\n\n{}",
+        finding.severity,
+        finding.category,
+        finding.suggestion.trim()
+    )
+}
+
+/// Publish a review of `pr_url` on its forge. Inline comments are anchored
+/// on the current PR diff. Returns the URL of the created review.
+pub async fn publish_review(
+    repo_root: &Utf8Path,
+    pr_url: &str,
+    summary: &str,
+    verdict: Verdict,
+    findings: &[ReviewFinding],
+    mode: PublishMode,
+) -> anyhow::Result<String> {
+    let review = ReviewToPublish {
+        summary,
+        verdict,
+        findings,
+        mode,
+    };
+    forge::forge_for_url(pr_url)?
+        .publish_review(repo_root, pr_url, review)
+        .await
+}
+
+/// Let the user pick the findings to publish. All are selected by default.
+pub fn select_findings(findings: &[ReviewFinding]) -> anyhow::Result<Vec<ReviewFinding>> {
+    if findings.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let options: Vec<FindingOption> = findings.iter().cloned().map(FindingOption).collect();
+    let selected = MultiSelect::new("Select the findings to publish:", options)
+        .with_all_selected_by_default()
+        .prompt()?;
+    Ok(selected.into_iter().map(|option| option.0).collect())
+}
+
+#[derive(Debug)]
+struct FindingOption(ReviewFinding);
+
+impl std::fmt::Display for FindingOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] {} ({}): {}",
+            self.0.severity,
+            finding_location(&self.0),
+            self.0.category,
+            self.0.suggestion
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::review::Severity;
+
+    const DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,4 @@
+ fn a() {}
+-fn b() {}
++fn b() -> u8 { 1 }
++fn c() {}
+ fn d() {}
+@@ -20,2 +21,3 @@ fn e() {
+ fn f() {}
++fn g() {}
+ fn h() {}
+diff --git a/old.rs b/old.rs
+deleted file mode 100644
+--- a/old.rs
++++ /dev/null
+@@ -1 +0,0 @@
+-fn gone() {}
+";
+
+    fn finding(file: &str, start_line: u32, end_line: u32) -> ReviewFinding {
+        ReviewFinding {
+            file: file.to_string(),
+            start_line,
+            end_line,
+            severity: Severity::Major,
+            category: "bug".to_string(),
+            suggestion: "Fix it".to_string(),
+            status: None,
+        }
+    }
+
+    #[test]
+    fn diff_positions_follow_github_counting() {
+        let positions = DiffPositions::parse(DIFF);
+        assert_eq!(
+            positions.position_for(&finding("src/lib.rs", 1, 1)),
+            Some(1)
+        );
+        // Removed line takes position 2.
+        assert_eq!(
+            positions.position_for(&finding("src/lib.rs", 2, 2)),
+            Some(3)
+        );
+        assert_eq!(
+            positions.position_for(&finding("src/lib.rs", 4, 4)),
+            Some(5)
+        );
+        // The second hunk header takes position 6.
+        assert_eq!(
+            positions.position_for(&finding("src/lib.rs", 21, 21)),
+            Some(7)
+        );
+        assert_eq!(
+            positions.position_for(&finding("src/lib.rs", 22, 22)),
+            Some(8)
+        );
+        assert_eq!(positions.position_for(&finding("src/lib.rs", 10, 12)), None);
+        assert_eq!(positions.position_for(&finding("old.rs", 1, 1)), None);
+    }
+
+    #[test]
+    fn position_falls_back_to_earlier_line_in_range() {
+        let positions = DiffPositions::parse(DIFF);
+        // Lines 5-9 are not in the diff, line 4 is.
+        assert_eq!(
+            positions.position_for(&finding("src/lib.rs", 3, 9)),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn diff_lines_keep_the_old_line_of_unchanged_lines() {
+        let positions = DiffPositions::parse(DIFF);
+        assert_eq!(
+            positions.line_for(&finding("src/lib.rs", 1, 1)),
+            Some(DiffLine {
+                new_line: 1,
+                old_line: Some(1)
+            })
+        );
+        assert_eq!(
+            positions.line_for(&finding("src/lib.rs", 3, 3)),
+            Some(DiffLine {
+                new_line: 3,
+                old_line: None
+            })
+        );
+        assert_eq!(
+            positions.line_for(&finding("src/lib.rs", 4, 4)),
+            Some(DiffLine {
+                new_line: 4,
+                old_line: Some(3)
+            })
+        );
+        assert_eq!(
+            positions.line_for(&finding("src/lib.rs", 23, 23)),
+            Some(DiffLine {
+                new_line: 23,
+                old_line: Some(21)
+            })
+        );
+    }
+
+    #[test]
+    fn payload_moves_unanchored_findings_to_body() {
+        let positions = DiffPositions::parse(DIFF);
+        let findings = [finding("src/lib.rs", 2, 3), finding("README.md", 1, 1)];
+
+        let pending = build_review_payload(
+            "Looks fine.",
+            Verdict::RequestChanges,
+            &findings,
+            &positions,
+            PublishMode::Pending,
+        );
+        assert_eq!(pending.event, None);
+        assert_eq!(pending.comments.len(), 1);
+        assert_eq!(pending.comments[0].path, "src/lib.rs");
+        assert_eq!(pending.comments[0].position, 4);
+        assert!(pending.comments[0].body.contains("lines 2-3"));
+        assert!(pending.body.contains("`README.md:1`"));
+
+        let submitted = build_review_payload(
+            "Looks fine.",
+            Verdict::RequestChanges,
+            &findings,
+            &positions,
+            PublishMode::Submit,
+        );
+        assert_eq!(submitted.event, Some("REQUEST_CHANGES"));
+        let json = serde_json::to_value(&pending).unwrap();
+        assert!(json.get("event").is_none());
+    }
+}
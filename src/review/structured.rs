@@ -0,0 +1,437 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::terminal::strip_control_sequences;
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Minor,
+    Major,
+    Critical,
+}
+
+impl Severity {
+    /// Most severe first.
+    pub const ALL: [Self; 4] = [Self::Critical, Self::Major, Self::Minor, Self::Info];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Minor => "minor",
+            Self::Major => "major",
+            Self::Critical => "critical",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|severity| severity.as_str() == value)
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Number of findings per severity.
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+pub struct SeverityCounts {
+    pub critical: i64,
+    pub major: i64,
+    pub minor: i64,
+    pub info: i64,
+}
+
+impl SeverityCounts {
+    /// Resolved findings are not counted.
+    pub fn from_findings(findings: &[ReviewFinding]) -> Self {
+        let mut counts = Self::default();
+        for finding in findings.iter().filter(|finding| !finding.is_resolved()) {
+            *counts.get_mut(finding.severity) += 1;
+        }
+        counts
+    }
+
+    pub fn get(&self, severity: Severity) -> i64 {
+        match severity {
+            Severity::Critical => self.critical,
+            Severity::Major => self.major,
+            Severity::Minor => self.minor,
+            Severity::Info => self.info,
+        }
+    }
+
+    pub fn get_mut(&mut self, severity: Severity) -> &mut i64 {
+        match severity {
+            Severity::Critical => &mut self.critical,
+            Severity::Major => &mut self.major,
+            Severity::Minor => &mut self.minor,
+            Severity::Info => &mut self.info,
+        }
+    }
+
+    /// Findings at or above `min`.
+    pub fn at_least(&self, min: Severity) -> i64 {
+        Severity::ALL
+            .into_iter()
+            .filter(|severity| *severity >= min)
+            .map(|severity| self.get(severity))
+            .sum()
+    }
+}
+
+impl fmt::Display for SeverityCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = Severity::ALL
+            .into_iter()
+            .map(|severity| format!("{} {severity}", self.get(severity)))
+            .collect();
+        f.write_str(&parts.join(", "))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Approve,
+    Comment,
+    RequestChanges,
+}
+
+impl Verdict {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Approve => "approve",
+            Self::Comment => "comment",
+            Self::RequestChanges => "request_changes",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        [Self::Approve, Self::Comment, Self::RequestChanges]
+            .into_iter()
+            .find(|verdict| verdict.as_str() == value)
+    }
+}
+
+/// How a finding of a re-review relates to the previous review of the PR.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingStatus {
+    /// Introduced by the commits reviewed this time.
+    New,
+    /// Reported by the previous review and not fixed yet.
+    Open,
+    /// Reported by the previous review and fixed since.
+    Resolved,
+}
+
+impl FindingStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::New => "new",
+            Self::Open => "open",
+            Self::Resolved => "resolved",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        [Self::New, Self::Open, Self::Resolved]
+            .into_iter()
+            .find(|status| status.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ReviewFinding {
+    /// Path relative to the repository root, as shown in the diff.
+    pub file: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub severity: Severity,
+    pub category: String,
+    pub suggestion: String,
+    /// Set by gigi on re-reviews, `None` for reviews of the whole PR. Agent
+    /// answers carrying it are rejected, so that they can't hide findings.
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub status: Option<FindingStatus>,
+}
+
+impl ReviewFinding {
+    pub fn is_resolved(&self) -> bool {
+        self.status == Some(FindingStatus::Resolved)
+    }
+}
+
+/// Review as requested from the agent by [`super::build_review_prompt`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct StructuredReview {
+    pub summary: String,
+    pub verdict: Verdict,
+    pub findings: Vec<ReviewFinding>,
+    /// Numbers of the previous findings fixed since the last review, only
+    /// requested on re-reviews.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resolved: Vec<usize>,
+}
+
+impl StructuredReview {
+    pub fn requires_code_changes(&self) -> bool {
+        self.verdict == Verdict::RequestChanges
+    }
+
+    /// Markdown stored in `reviews.content_md` and shown in the dashboard.
+    /// Keeps the `REQUIRES_CODE_CHANGES` header understood by older code.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "REQUIRES_CODE_CHANGES: {}\n\n## Summary\n\n{}\n\n**Verdict:** {}\n\n## Findings\n\n",
+            if self.requires_code_changes() {
+                "YES"
+            } else {
+                "NO"
+            },
+            self.summary.trim(),
+            self.verdict.as_str()
+        );
+        let (resolved, open): (Vec<_>, Vec<_>) = self
+            .findings
+            .iter()
+            .partition(|finding| finding.is_resolved());
+        if open.is_empty() {
+            out.push_str("No issues found.\n");
+        }
+        for finding in open {
+            push_finding_line(&mut out, finding);
+        }
+        if !resolved.is_empty() {
+            out.push_str("\n## Resolved since the previous review\n\n");
+            for finding in resolved {
+                push_finding_line(&mut out, finding);
+            }
+        }
+        out
+    }
+}
+
+fn push_finding_line(out: &mut String, finding: &ReviewFinding) {
+    let status = match finding.status {
+        Some(FindingStatus::New) => "[new] ",
+        Some(FindingStatus::Open) => "[still open] ",
+        Some(FindingStatus::Resolved) | None => "",
+    };
+    out.push_str(&format!(
+        "- {status}**{}** `{}` ({}): {}\n",
+        finding.severity,
+        finding_location(finding),
+        finding.category,
+        finding.suggestion.trim()
+    ));
+}
+
+pub fn finding_location(finding: &ReviewFinding) -> String {
+    if finding.start_line == finding.end_line {
+        format!("{}:{}", finding.file, finding.start_line)
+    } else {
+        format!(
+            "{}:{}-{}",
+            finding.file, finding.start_line, finding.end_line
+        )
+    }
+}
+
+pub(super) const REVIEW_JSON_SCHEMA: &str = r#"{
+  "summary": "<short overall assessment>",
+  "verdict": "approve" | "comment" | "request_changes",
+  "findings": [
+    {
+      "file": "<path as shown in the diff>",
+      "start_line": <first line in the new version of the file>,
+      "end_line": <last line in the new version of the file>,
+      "severity": "critical" | "major" | "minor" | "info",
+      "category": "<e.g. bug, security, performance, style, tests>",
+      "suggestion": "<what is wrong and how to fix it>"
+    }
+  ]
+}"#;
+
+/// Schema of re-reviews, which only report the findings introduced by the
+/// new commits.
+pub(super) const INCREMENTAL_REVIEW_JSON_SCHEMA: &str = r#"{
+  "summary": "<short assessment of the changes since the previous review>",
+  "verdict": "approve" | "comment" | "request_changes",
+  "findings": [
+    {
+      "file": "<path as shown in the diff>",
+      "start_line": <first line in the new version of the file>,
+      "end_line": <last line in the new version of the file>,
+      "severity": "critical" | "major" | "minor" | "info",
+      "category": "<e.g. bug, security, performance, style, tests>",
+      "suggestion": "<what is wrong and how to fix it>"
+    }
+  ],
+  "resolved": [<numbers of the previous findings fixed by the new changes>]
+}"#;
+
+/// Parse and validate the agent answer. Tolerates code fences and text
+/// around the JSON object.
+pub fn parse_structured_review(output: &str) -> anyhow::Result<StructuredReview> {
+    let cleaned = strip_control_sequences(output);
+    let json = extract_json_object(&cleaned)
+        .ok_or_else(|| anyhow::anyhow!("no JSON object found in the output"))?;
+    let review: StructuredReview = serde_json::from_str(json)?;
+    validate(&review)?;
+    Ok(review)
+}
+
+fn extract_json_object(text: &str) -> Option<&str> {
+    let trimmed = text.trim();
+    if let Some(fenced) = trimmed
+        .split_once("```json")
+        .and_then(|(_, rest)| rest.split_once("```"))
+        .map(|(inner, _)| inner.trim())
+    {
+        return Some(fenced);
+    }
+
+    let start = trimmed.find('{')?;
+    let end = trimmed.rfind('}')?;
+    (start < end).then(|| &trimmed[start..=end])
+}
+
+fn validate(review: &StructuredReview) -> anyhow::Result<()> {
+    anyhow::ensure!(!review.summary.trim().is_empty(), "`summary` is empty");
+    for (index, finding) in review.findings.iter().enumerate() {
+        anyhow::ensure!(
+            !finding.file.trim().is_empty(),
+            "finding {index}: `file` is empty"
+        );
+        anyhow::ensure!(
+            finding.start_line >= 1 && finding.start_line <= finding.end_line,
+            "finding {index}: invalid line range {}-{}",
+            finding.start_line,
+            finding.end_line
+        );
+        anyhow::ensure!(
+            !finding.suggestion.trim().is_empty(),
+            "finding {index}: `suggestion` is empty"
+        );
+    }
+    Ok(())
+}
+
+pub(super) fn build_repair_prompt(
+    previous_output: &str,
+    error: &anyhow::Error,
+    schema: &str,
+) -> String {
+    format!(
+        "Your previous answer could not be parsed: {error}.\n\
+Reply again with ONLY a JSON object matching this schema, without code fences or any other text:\n\
+{schema}\n\n\
+Previous answer:\n{previous_output}\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = r#"{
+        "summary": "Adds caching.",
+        "verdict": "request_changes",
+        "findings": [{
+            "file": "src/cache.rs",
+            "start_line": 10,
+            "end_line": 12,
+            "severity": "major",
+            "category": "bug",
+            "suggestion": "Invalidate the entry on write."
+        }]
+    }"#;
+
+    #[test]
+    fn parses_valid_review() {
+        let review = parse_structured_review(VALID).unwrap();
+        assert_eq!(review.verdict, Verdict::RequestChanges);
+        assert!(review.requires_code_changes());
+        assert_eq!(review.findings.len(), 1);
+        assert_eq!(review.findings[0].severity, Severity::Major);
+    }
+
+    #[test]
+    fn parses_review_inside_code_fence_and_prose() {
+        let output = format!("Here is the review:\n```json\n{VALID}\n```\nDone.");
+        assert!(parse_structured_review(&output).is_ok());
+
+        let output = format!("\u{1b}[0mSure! {VALID} Hope it helps.");
+        assert!(parse_structured_review(&output).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_reviews() {
+        assert!(parse_structured_review("REQUIRES_CODE_CHANGES: NO\nLooks good").is_err());
+        assert!(parse_structured_review(&VALID.replace("major", "blocker")).is_err());
+        assert!(
+            parse_structured_review(&VALID.replace("\"end_line\": 12", "\"end_line\": 9")).is_err()
+        );
+        assert!(
+            parse_structured_review(&VALID.replace("\"category\"", "\"extra\": 1, \"category\""))
+                .is_err()
+        );
+        assert!(
+            parse_structured_review(
+                &VALID.replace("\"category\"", "\"status\": \"resolved\", \"category\"")
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn markdown_keeps_requires_code_changes_header() {
+        let review = parse_structured_review(VALID).unwrap();
+        let markdown = review.to_markdown();
+        assert!(markdown.starts_with("REQUIRES_CODE_CHANGES: YES\n"));
+        assert!(markdown.contains("- **major** `src/cache.rs:10-12` (bug): Invalidate"));
+    }
+
+    #[test]
+    fn severity_counts_count_and_filter() {
+        let mut review = parse_structured_review(VALID).unwrap();
+        let mut info = review.findings[0].clone();
+        info.severity = Severity::Info;
+        review.findings.push(info);
+
+        let counts = SeverityCounts::from_findings(&review.findings);
+        assert_eq!(counts.at_least(Severity::Info), 2);
+        assert_eq!(counts.at_least(Severity::Minor), 1);
+        assert_eq!(counts.to_string(), "0 critical, 1 major, 0 minor, 1 info");
+    }
+
+    #[test]
+    fn severity_orders_by_importance() {
+        assert!(Severity::Critical > Severity::Major);
+        assert!(Severity::Minor > Severity::Info);
+        assert_eq!(Severity::parse("minor"), Some(Severity::Minor));
+        assert_eq!(Severity::parse("blocker"), None);
+    }
+}
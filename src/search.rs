@@ -0,0 +1,36 @@
+use crate::{
+    config,
+    db::{Db, SNIPPET_MATCH_END, SNIPPET_MATCH_START, SearchHit},
+};
+
+/// `gigi search`: print the best matches of `query` in the stored reviews,
+/// thread titles and fix run outputs.
+pub fn run_search(query: &str, limit: usize) -> anyhow::Result<()> {
+    let db = Db::new(config::resolve_paths()?.db_path)?;
+    let hits = db.search(query, limit)?;
+    if hits.is_empty() {
+        println!("🔎 No matches for \"{query}\"");
+        return Ok(());
+    }
+    for (idx, hit) in hits.iter().enumerate() {
+        print_hit(idx + 1, hit);
+    }
+    Ok(())
+}
+
+fn print_hit(position: usize, hit: &SearchHit) {
+    let date = hit.date.get(..10).unwrap_or(&hit.date);
+    let url = hit.pr_url.as_deref().or(hit.url.as_deref()).unwrap_or("-");
+    println!("{position}. [{}] {date} {url}", hit.kind.label());
+    println!("   {}", hit.title);
+    println!("   {}", plain_snippet(&hit.snippet));
+}
+
+/// One line, with the matches in `**`.
+fn plain_snippet(snippet: &str) -> String {
+    snippet
+        .replace([SNIPPET_MATCH_START, SNIPPET_MATCH_END], "**")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     sync::{
         Arc,
         atomic::{AtomicBool, Ordering},
@@ -9,12 +10,20 @@ use std::{
 use anyhow::Context as _;
 use camino::Utf8PathBuf;
 
-use crate::{config, github, launcher, review, web};
+use crate::{
+    ai, checkout::parse_github_pr_url, config, forge, github, launcher, maintenance, review, web,
+};
 
 use super::{
     AppState, DashboardUpdate, MarkDoneRequest, PollMode, PollStats,
     helpers::{dashboard_browser_url, describe_open_target, resolve_open_target_repo},
-    poll::{poll_once_async, print_poll_stats, run_review_for_details, upsert_pr_from_details},
+    jobs::spawn_job_workers,
+    poll::{
+        poll_delay_seconds, poll_once_async, print_poll_stats, run_review_for_details,
+        upsert_pr_from_details,
+    },
+    time::unix_ts,
+    webhooks::apply_github_event,
 };
 
 pub async fn run_serve() -> anyhow::Result<()> {
@@ -22,6 +31,8 @@ pub async fn run_serve() -> anyhow::Result<()> {
     config::ensure_parent_dirs(&paths).await?;
 
     let cfg = config::load_config(&paths.config_path).await?;
+    github::configure_hosts(&cfg.github);
+    forge::configure_gitlab_hosts(&cfg.gitlab);
     let db = crate::db::Db::new(&paths.db_path)?;
     let current_dir = std::env::current_dir().context("Failed to read current directory")?;
     let work_dir = Utf8PathBuf::from_path_buf(current_dir).map_err(|path| {
@@ -40,6 +51,8 @@ pub async fn run_serve() -> anyhow::Result<()> {
         poll_lock: Arc::new(tokio::sync::Mutex::new(())),
         dashboard_refresh_in_flight: Arc::new(AtomicBool::new(false)),
         dashboard_updates,
+        jobs_wakeup: Arc::new(tokio::sync::Notify::new()),
+        running_jobs: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
     });
 
     let browser_url = dashboard_browser_url(&cfg);
@@ -50,6 +63,8 @@ pub async fn run_serve() -> anyhow::Result<()> {
     println!("📄 Config: {}", paths.config_path.display());
     println!("🗄️  DB: {}", paths.db_path.display());
 
+    let worker_handles = spawn_job_workers(&state);
+
     let startup_state = Arc::clone(&state);
     let startup_handle = tokio::spawn(async move {
         println!("🔄 Starting initial poll cycle...");
@@ -61,28 +76,67 @@ pub async fn run_serve() -> anyhow::Result<()> {
 
     let poll_state = Arc::clone(&state);
     let poll_handle = tokio::spawn(async move {
-        let mut interval = tokio::time::interval(Duration::from_secs(
-            poll_state.config.watch_period_seconds.max(1),
-        ));
-        interval.tick().await;
-
+        let period = poll_state.config.watch_period_seconds.max(1);
         loop {
-            interval.tick().await;
+            let delay = poll_delay_seconds(
+                period,
+                github::GitHubClient::shared_quota().into_values(),
+                unix_ts(),
+            );
+            if delay > period {
+                println!("🐢 GitHub API quota running low, next poll in {delay}s");
+            }
+            tokio::time::sleep(Duration::from_secs(delay)).await;
             if let Err(err) = poll_state.poll_once_regular().await {
                 eprintln!("⚠️ Poll cycle failed: {err}");
             }
         }
     });
 
+    let prune_state = Arc::clone(&state);
+    let prune_handle = tokio::spawn(async move {
+        let retention = &prune_state.config.retention;
+        let hours = retention.prune_interval_hours;
+        if hours == 0 || !retention.is_enabled() {
+            return;
+        }
+        println!(
+            "🗄️ Pruning the DB every {hours}h, keeping {} (0 keeps everything)",
+            maintenance::retention_summary(retention)
+        );
+        // The first prune waits for a whole interval, not for startup.
+        let period = Duration::from_secs(hours.saturating_mul(3600));
+        let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        loop {
+            interval.tick().await;
+            // VACUUM and the FTS rebuild block for a while: keep them off the
+            // runtime workers.
+            let state = Arc::clone(&prune_state);
+            let pruned = tokio::task::spawn_blocking(move || {
+                maintenance::prune_db(&state.db, &state.config.retention, false)
+            })
+            .await;
+            match pruned {
+                Ok(Ok(_)) => {}
+                Ok(Err(err)) => eprintln!("⚠️ DB prune failed: {err}"),
+                Err(err) => eprintln!("⚠️ DB prune task failed: {err}"),
+            }
+        }
+    });
+
     tokio::select! {
         server_result = web::run_server(state, &cfg) => {
             poll_handle.abort();
+            prune_handle.abort();
             startup_handle.abort();
+            worker_handles.iter().for_each(tokio::task::JoinHandle::abort);
             server_result
         }
         signal_result = tokio::signal::ctrl_c() => {
             poll_handle.abort();
+            prune_handle.abort();
             startup_handle.abort();
+            worker_handles.iter().for_each(tokio::task::JoinHandle::abort);
             match signal_result {
                 Ok(()) => {
                     println!("\n🛑 Received Ctrl+C, shutting down gigi serve...");
@@ -146,13 +200,15 @@ impl AppState {
             Ok(stats) => {
                 print_poll_stats("✅ Dashboard refresh complete:", stats);
                 self.notify_dashboard(format!(
-                    "Refresh complete: notifications={}, my_prs={}, assigned_prs={}, assigned_issues={}, prs={}, reviews={}",
+                    "Refresh complete: notifications={}, my_prs={}, assigned_prs={}, review_requested={}, assigned_issues={}, mentions={}, prs={}, reviews={}",
                     stats.notifications_fetched,
                     stats.authored_prs_fetched,
                     stats.assigned_prs_fetched,
+                    stats.review_requested_prs_fetched,
                     stats.assigned_issues_fetched,
+                    stats.mentions_fetched,
                     stats.prs_seen,
-                    stats.reviews_run
+                    stats.reviews_queued
                 ));
             }
             Err(err) => {
@@ -167,13 +223,15 @@ impl AppState {
         let result = self.poll_once_with_mode(PollMode::Startup).await;
         match &result {
             Ok(stats) => self.notify_dashboard(format!(
-                "Initial poll complete: notifications={}, my_prs={}, assigned_prs={}, assigned_issues={}, prs={}, reviews={}",
+                "Initial poll complete: notifications={}, my_prs={}, assigned_prs={}, review_requested={}, assigned_issues={}, mentions={}, prs={}, reviews={}",
                 stats.notifications_fetched,
                 stats.authored_prs_fetched,
                 stats.assigned_prs_fetched,
+                stats.review_requested_prs_fetched,
                 stats.assigned_issues_fetched,
+                stats.mentions_fetched,
                 stats.prs_seen,
-                stats.reviews_run
+                stats.reviews_queued
             )),
             Err(err) => self.notify_dashboard(format!("Initial poll failed: {err}")),
         }
@@ -184,13 +242,15 @@ impl AppState {
         let result = self.poll_once_with_mode(PollMode::Regular).await;
         match &result {
             Ok(stats) => self.notify_dashboard(format!(
-                "Background poll complete: notifications={}, my_prs={}, assigned_prs={}, assigned_issues={}, prs={}, reviews={}",
+                "Background poll complete: notifications={}, my_prs={}, assigned_prs={}, review_requested={}, assigned_issues={}, mentions={}, prs={}, reviews={}",
                 stats.notifications_fetched,
                 stats.authored_prs_fetched,
                 stats.assigned_prs_fetched,
+                stats.review_requested_prs_fetched,
                 stats.assigned_issues_fetched,
+                stats.mentions_fetched,
                 stats.prs_seen,
-                stats.reviews_run
+                stats.reviews_queued
             )),
             Err(err) => self.notify_dashboard(format!("Background poll failed: {err}")),
         }
@@ -200,9 +260,12 @@ impl AppState {
     async fn poll_once_with_mode(&self, mode: PollMode) -> anyhow::Result<PollStats> {
         let _guard = self.poll_lock.lock().await;
 
-        let stats = poll_once_async(&self.db, &self.config, &self.work_dir, mode)
+        let stats = poll_once_async(&self.db, &self.config, mode)
             .await
             .context("polling cycle failed")?;
+        if stats.reviews_queued > 0 {
+            self.jobs_wakeup.notify_waiters();
+        }
 
         for (pr_url, participants) in &stats.participants {
             if let Err(err) = self.db.upsert_pr_participants(pr_url, participants) {
@@ -241,6 +304,25 @@ impl AppState {
             }
         }
 
+        if request.mark_review_requested {
+            let pr_url = request.pr_url.as_deref().ok_or_else(|| {
+                anyhow::anyhow!("Missing PR URL for review-requested PR done action")
+            })?;
+            if self.db.mark_review_requested_done_local(pr_url)? {
+                marked_any = true;
+            }
+        }
+
+        if request.mark_mention {
+            let subject_url = request
+                .subject_url
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("Missing comment URL for mention done action"))?;
+            if self.db.mark_mention_done_local(subject_url)? {
+                marked_any = true;
+            }
+        }
+
         if request.mark_assigned_issue {
             let subject_url = request.subject_url.as_deref().ok_or_else(|| {
                 anyhow::anyhow!("Missing issue URL for assigned issue done action")
@@ -255,6 +337,23 @@ impl AppState {
         Ok(())
     }
 
+    /// Apply a verified GitHub webhook event, then refresh the dashboard.
+    /// The polls go on, catching up on missed deliveries.
+    pub async fn handle_github_webhook(
+        &self,
+        event: &str,
+        payload: &serde_json::Value,
+    ) -> anyhow::Result<()> {
+        match apply_github_event(&self.db, event, payload).await? {
+            Some(message) => {
+                println!("📨 {message}");
+                self.notify_dashboard(message);
+            }
+            None => println!("📨 Webhook {event} ignored"),
+        }
+        Ok(())
+    }
+
     pub async fn mark_notification_read(&self, thread_id: &str) -> anyhow::Result<()> {
         github::mark_notification_read(thread_id).await?;
         self.db.mark_thread_read_local(thread_id)?;
@@ -262,29 +361,25 @@ impl AppState {
         Ok(())
     }
 
-    pub async fn run_fix(
-        &self,
-        owner: String,
-        repo: String,
-        number: i64,
-    ) -> anyhow::Result<String> {
-        let provider = self.config.ai.provider;
+    /// Run the agent on the PR checked out under `~/proj` to apply its latest
+    /// review. Runs as a job, see [`Self::enqueue_job`].
+    pub(super) async fn run_fix(&self, pr_url: &str) -> anyhow::Result<String> {
+        let agent = ai::agent_for(self.config.ai.provider, &self.config.ai);
         let model = self.config.ai.model.clone();
-        let pr_url = format!("https://github.com/{owner}/{repo}/pull/{number}");
+        let pr = parse_github_pr_url(pr_url)?;
         let latest_review = self
             .db
-            .latest_review_by_url(&pr_url)?
+            .latest_review_by_url(pr_url)?
             .ok_or_else(|| anyhow::anyhow!("No review found for {pr_url}"))?;
 
-        let repo_dir = github::ensure_local_repo(&owner, &repo).await?;
-        github::checkout_pr(&repo_dir, &pr_url).await?;
+        let repo_dir = github::ensure_local_repo(&pr.host, &pr.owner, &pr.repo).await?;
+        github::checkout_pr(&repo_dir, pr_url).await?;
 
-        let agent = provider.as_agent();
         let output = review::run_fix(
             &repo_dir,
-            &pr_url,
+            pr_url,
             &latest_review.content_md,
-            Some(&agent),
+            agent.as_ref(),
             model.as_deref(),
         )
         .await;
@@ -292,53 +387,95 @@ impl AppState {
         match output {
             Ok(text) => {
                 self.db
-                    .insert_fix_run(&pr_url, provider.as_str(), "success", &text)?;
-                self.notify_dashboard(format!("Fix run completed for {pr_url}"));
+                    .insert_fix_run(pr_url, agent.id(), "success", &text)?;
+                println!("✅ Fix run completed: {pr_url}");
                 Ok(text)
             }
             Err(err) => {
                 self.db
-                    .insert_fix_run(&pr_url, provider.as_str(), "error", &err.to_string())?;
-                self.notify_dashboard(format!("Fix run failed for {pr_url}: {err}"));
+                    .insert_fix_run(pr_url, agent.id(), "error", &err.to_string())?;
+                eprintln!("❌ Fix run failed: {pr_url}: {err}");
                 Err(err)
             }
         }
     }
 
-    pub async fn run_review(&self, owner: String, repo: String, number: i64) -> anyhow::Result<()> {
-        let _guard = self.poll_lock.lock().await;
-        let pr_url = format!("https://github.com/{owner}/{repo}/pull/{number}");
+    /// Review the PR with its current details. Runs as a job, see
+    /// [`Self::enqueue_job`].
+    pub(super) async fn run_review(&self, pr_url: &str) -> anyhow::Result<()> {
         println!("🔍 Review started: {pr_url}");
 
         let result = async {
-            let details = github::fetch_pr_details(&pr_url).await?;
+            let details = forge::forge_for_url(pr_url)?.pr_details(pr_url).await?;
             upsert_pr_from_details(&self.db, &details)?;
             run_review_for_details(&self.db, &self.config, &self.work_dir, &details).await
         }
         .await;
 
         match &result {
-            Ok(()) => {
-                println!("✅ Review finished: {pr_url}");
-                self.notify_dashboard(format!("Review finished for {pr_url}"));
-            }
-            Err(err) => {
-                eprintln!("❌ Review failed: {pr_url}: {err}");
-                self.notify_dashboard(format!("Review failed for {pr_url}: {err}"));
-            }
+            Ok(()) => println!("✅ Review finished: {pr_url}"),
+            Err(err) => eprintln!("❌ Review failed: {pr_url}: {err}"),
         }
 
         result
     }
 
+    /// Publish the latest review of a PR on GitHub with the findings picked
+    /// in the dashboard. `review_id` guards against publishing a review that
+    /// was replaced after the dashboard was rendered.
+    pub async fn publish_review(
+        &self,
+        pr_url: String,
+        review_id: i64,
+        finding_ids: &[i64],
+        mode: review::PublishMode,
+    ) -> anyhow::Result<String> {
+        let latest_review = self
+            .db
+            .latest_review_by_url(&pr_url)?
+            .ok_or_else(|| anyhow::anyhow!("No review found for {pr_url}"))?;
+        anyhow::ensure!(
+            latest_review.id == review_id,
+            "A newer review exists for {pr_url}, reload the dashboard"
+        );
+
+        let findings: Vec<review::ReviewFinding> = self
+            .db
+            .review_findings(review_id)?
+            .into_iter()
+            .filter(|item| finding_ids.contains(&item.id))
+            .map(|item| item.finding)
+            .collect();
+        let summary = latest_review
+            .summary
+            .unwrap_or_else(|| latest_review.content_md.clone());
+        let verdict = latest_review
+            .verdict
+            .unwrap_or(if latest_review.requires_code_changes {
+                review::Verdict::RequestChanges
+            } else {
+                review::Verdict::Comment
+            });
+
+        let review_url =
+            review::publish_review(&self.work_dir, &pr_url, &summary, verdict, &findings, mode)
+                .await?;
+        let message = format!("Published {mode} GitHub review: {review_url}");
+        self.db.insert_sync_event(&pr_url, "success", &message)?;
+        self.notify_dashboard(format!("{message} ({pr_url})"));
+        Ok(review_url)
+    }
+
     pub async fn open_in_vscode(
         &self,
         repository: String,
         pr_url: Option<String>,
+        host: Option<String>,
     ) -> anyhow::Result<()> {
         let target_label = describe_open_target(&repository, pr_url.as_deref());
         println!("🧑‍💻 VS Code open requested: {target_label}");
-        let repo_dir = resolve_open_target_repo(&repository, pr_url.as_deref()).await?;
+        let repo_dir =
+            resolve_open_target_repo(&repository, pr_url.as_deref(), host.as_deref()).await?;
         println!("📂 Opening VS Code in {repo_dir}");
         let result = launcher::open_vscode(&repo_dir).await;
 
@@ -360,10 +497,12 @@ impl AppState {
         &self,
         repository: String,
         pr_url: Option<String>,
+        host: Option<String>,
     ) -> anyhow::Result<()> {
         let target_label = describe_open_target(&repository, pr_url.as_deref());
         println!("🖥️ Terminal open requested: {target_label}");
-        let repo_dir = resolve_open_target_repo(&repository, pr_url.as_deref()).await?;
+        let repo_dir =
+            resolve_open_target_repo(&repository, pr_url.as_deref(), host.as_deref()).await?;
         println!("📂 Opening Terminal in {repo_dir}");
         let result = launcher::open_terminal(&repo_dir).await;
 
@@ -23,6 +23,7 @@ pub(crate) fn parse_repository_name(repository: &str) -> anyhow::Result<(String,
 pub(crate) async fn resolve_open_target_repo(
     repository: &str,
     pr_url: Option<&str>,
+    host: Option<&str>,
 ) -> anyhow::Result<Utf8PathBuf> {
     if let Some(pr_url) = pr_url {
         let local_pr = github::ensure_local_repo_for_pr(pr_url).await?;
@@ -35,7 +36,14 @@ pub(crate) async fn resolve_open_target_repo(
     }
 
     let (owner, repo) = parse_repository_name(repository)?;
-    github::ensure_local_repo(&owner, &repo).await
+    let hosts = github::hosts();
+    let host = match host {
+        Some(host) => hosts
+            .find(host)
+            .ok_or_else(|| anyhow::anyhow!("❌ Unknown GitHub host '{host}'"))?,
+        None => hosts.github_com(),
+    };
+    github::ensure_local_repo(&host.host, &owner, &repo).await
 }
 
 pub(crate) fn describe_open_target(repository: &str, pr_url: Option<&str>) -> String {
@@ -0,0 +1,179 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::task::JoinHandle;
+
+use crate::db::{JobKind, StoredJob};
+
+use super::{AppState, time::unix_ts};
+
+/// Idle workers look for retries that became due at least this often.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Re-queue the jobs interrupted by the previous shutdown and start
+/// `jobs.workers` workers.
+pub(super) fn spawn_job_workers(state: &Arc<AppState>) -> Vec<tokio::task::JoinHandle<()>> {
+    match state.db.requeue_interrupted_jobs() {
+        Ok(0) => {}
+        Ok(count) => println!("♻️ Re-queued {count} job(s) interrupted by the last shutdown"),
+        Err(err) => eprintln!("⚠️ Failed to re-queue interrupted jobs: {err}"),
+    }
+
+    (1..=state.config.jobs.workers.max(1))
+        .map(|worker| {
+            let state = Arc::clone(state);
+            tokio::spawn(async move { state.run_job_worker(worker).await })
+        })
+        .collect()
+}
+
+/// Seconds to wait before the next attempt of a job that failed `attempts`
+/// times: `base`, then doubled for each further failure.
+pub(super) fn retry_delay_seconds(base: u64, attempts: u32) -> u64 {
+    base.saturating_mul(1_u64 << attempts.saturating_sub(1).min(16))
+}
+
+impl AppState {
+    /// Queue a review or a fix run of `pr_url`. Returns the id of the job,
+    /// which is the already queued one when the same work is pending.
+    /// Fix runs are not retried automatically: a failed run may leave the
+    /// clone with half-applied changes.
+    pub fn enqueue_job(&self, kind: JobKind, pr_url: &str) -> anyhow::Result<i64> {
+        let max_attempts = match kind {
+            JobKind::Review => self.config.jobs.max_attempts,
+            JobKind::Fix => 1,
+        };
+        let id = self
+            .db
+            .enqueue_job(kind, pr_url, None, max_attempts)?
+            .ok_or_else(|| {
+                anyhow::anyhow!("❌ Failed to queue {} job for {pr_url}", kind.as_str())
+            })?;
+        self.jobs_wakeup.notify_waiters();
+        println!("📥 Job #{id} queued: {} {pr_url}", kind.as_str());
+        self.notify_dashboard(format!("Queued {} job #{id} for {pr_url}", kind.as_str()));
+        Ok(id)
+    }
+
+    /// Cancel a queued job, or abort a running one. A running job stays
+    /// running until its task and commands have stopped, see [`Self::finish_job`].
+    pub async fn cancel_job(&self, id: i64) -> anyhow::Result<()> {
+        // Held while claiming jobs too, so a running job always has its handle.
+        let running_jobs = self.running_jobs.lock().await;
+        if let Some(handle) = running_jobs.get(&id) {
+            handle.abort();
+            println!("🛑 Cancelling job #{id}");
+            self.notify_dashboard(format!("Cancelling job #{id}"));
+            return Ok(());
+        }
+        self.db
+            .cancel_job(id)?
+            .ok_or_else(|| anyhow::anyhow!("❌ Job #{id} already finished"))?;
+        println!("🛑 Job #{id} cancelled");
+        self.notify_dashboard(format!("Cancelled job #{id}"));
+        Ok(())
+    }
+
+    /// Queue a failed or cancelled job again.
+    pub fn retry_job(&self, id: i64) -> anyhow::Result<()> {
+        self.db.retry_job(id)?;
+        self.jobs_wakeup.notify_waiters();
+        println!("🔁 Job #{id} queued again");
+        self.notify_dashboard(format!("Retrying job #{id}"));
+        Ok(())
+    }
+
+    async fn run_job_worker(self: Arc<Self>, worker: usize) {
+        loop {
+            let Some((job, task)) = self.start_next_job(worker).await else {
+                tokio::select! {
+                    () = self.jobs_wakeup.notified() => {}
+                    () = tokio::time::sleep(IDLE_POLL_INTERVAL) => {}
+                }
+                continue;
+            };
+            self.finish_job(job, task).await;
+        }
+    }
+
+    /// Claim the next due job and run it in a separate task, so that
+    /// cancelling the job can abort it. The task is registered in
+    /// `running_jobs` before [`Self::cancel_job`] can see the job running.
+    async fn start_next_job(
+        self: &Arc<Self>,
+        worker: usize,
+    ) -> Option<(StoredJob, JoinHandle<anyhow::Result<()>>)> {
+        let mut running_jobs = self.running_jobs.lock().await;
+        let job = match self.db.claim_next_job(unix_ts()) {
+            Ok(job) => job?,
+            Err(err) => {
+                eprintln!("⚠️ Job worker {worker} failed to claim a job: {err}");
+                return None;
+            }
+        };
+        let label = job_label(&job);
+        println!(
+            "⚙️ Worker {worker} started {label} (attempt {}/{})",
+            job.attempts, job.max_attempts
+        );
+        self.notify_dashboard(format!("Started {label}"));
+
+        let state = Arc::clone(self);
+        let kind = job.kind;
+        let pr_url = job.pr_url.clone();
+        let task = tokio::spawn(async move {
+            match kind {
+                JobKind::Review => state.run_review(&pr_url).await,
+                JobKind::Fix => state.run_fix(&pr_url).await.map(drop),
+            }
+        });
+        running_jobs.insert(job.id, task.abort_handle());
+        Some((job, task))
+    }
+
+    /// Wait for the task of `job` and record its outcome. A cancelled job is
+    /// only marked so once its task is gone, which kills the commands it ran:
+    /// until then, other jobs of its repository keep waiting.
+    pub(super) async fn finish_job(&self, job: StoredJob, task: JoinHandle<anyhow::Result<()>>) {
+        let label = job_label(&job);
+        let result = task.await;
+        let mut running_jobs = self.running_jobs.lock().await;
+        running_jobs.remove(&job.id);
+
+        let (recorded, message) = match result {
+            Ok(Ok(())) => (self.db.complete_job(job.id), format!("Finished {label}")),
+            Ok(Err(err)) if job.attempts < job.max_attempts => {
+                let delay =
+                    retry_delay_seconds(self.config.jobs.retry_backoff_seconds, job.attempts);
+                let retry_at = unix_ts().saturating_add(i64::try_from(delay).unwrap_or(i64::MAX));
+                (
+                    self.db
+                        .fail_job(job.id, &format!("{err:#}"), Some(retry_at)),
+                    format!("Failed {label}, retrying in {delay}s: {err}"),
+                )
+            }
+            Ok(Err(err)) => (
+                self.db.fail_job(job.id, &format!("{err:#}"), None),
+                format!("Failed {label}: {err}"),
+            ),
+            Err(err) if err.is_cancelled() => (
+                self.db.cancel_job(job.id).map(drop),
+                format!("Cancelled {label}"),
+            ),
+            Err(err) => (
+                self.db
+                    .fail_job(job.id, &format!("Job panicked: {err}"), None),
+                format!("Failed {label}: {err}"),
+            ),
+        };
+        drop(running_jobs);
+        if let Err(err) = recorded {
+            eprintln!("⚠️ Failed to record the outcome of {label}: {err}");
+        }
+        println!("⚙️ {message}");
+        self.notify_dashboard(message);
+    }
+}
+
+fn job_label(job: &StoredJob) -> String {
+    format!("{} job #{} for {}", job.kind.as_str(), job.id, job.pr_url)
+}
@@ -1,7 +1,9 @@
 mod app;
 mod helpers;
+mod jobs;
 mod poll;
 mod time;
+mod webhooks;
 
 #[cfg(test)]
 mod tests;
@@ -17,6 +19,7 @@ use serde::Serialize;
 use crate::{config::AppConfig, db::Db, github};
 
 pub use app::run_serve;
+pub use webhooks::{verify_webhook_signature, webhook_secret};
 
 #[derive(Debug)]
 pub struct AppState {
@@ -26,6 +29,10 @@ pub struct AppState {
     pub poll_lock: Arc<tokio::sync::Mutex<()>>,
     pub dashboard_refresh_in_flight: Arc<AtomicBool>,
     pub dashboard_updates: tokio::sync::watch::Sender<DashboardUpdate>,
+    /// Wakes up idle job workers when a job is queued.
+    pub jobs_wakeup: Arc<tokio::sync::Notify>,
+    /// Running jobs, aborted when cancelled from the dashboard.
+    pub running_jobs: Arc<tokio::sync::Mutex<HashMap<i64, tokio::task::AbortHandle>>>,
 }
 
 #[derive(Debug, Clone)]
@@ -34,14 +41,16 @@ pub struct DashboardUpdate {
     pub message: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct PollStats {
     pub notifications_fetched: usize,
     pub authored_prs_fetched: usize,
     pub assigned_prs_fetched: usize,
+    pub review_requested_prs_fetched: usize,
     pub assigned_issues_fetched: usize,
+    pub mentions_fetched: usize,
     pub prs_seen: usize,
-    pub reviews_run: usize,
+    pub reviews_queued: usize,
     #[serde(skip_serializing)]
     pub participants: HashMap<String, Vec<github::Participant>>,
 }
@@ -73,4 +82,6 @@ pub struct MarkDoneRequest {
     pub mark_authored_pr: bool,
     pub mark_assigned_pr: bool,
     pub mark_assigned_issue: bool,
+    pub mark_review_requested: bool,
+    pub mark_mention: bool,
 }
@@ -4,8 +4,10 @@ use camino::Utf8Path;
 use chrono::SecondsFormat;
 
 use crate::{
+    ai,
     config::{AppConfig, RereviewMode},
     db::{self, Db},
+    forge::{self, ForgejoClient},
     github, review,
 };
 
@@ -18,73 +20,143 @@ use super::{
 // instead of being skipped forever by a strict "since last seen timestamp" cursor.
 const FETCH_CURSOR_OVERLAP_SECS: i64 = 300;
 
+/// Forge the threads of a sync come from. GitHub keeps the `source` values
+/// and thread keys it had before other forges were supported, the others
+/// are namespaced by forge, e.g. `forgejo:my_pr`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ThreadForge {
+    GitHub,
+    Forgejo,
+}
+
+impl ThreadForge {
+    fn namespaced(self, value: &str) -> String {
+        match self {
+            Self::GitHub => value.to_string(),
+            Self::Forgejo => format!("forgejo:{value}"),
+        }
+    }
+}
+
+/// Results of the fetches of a GitHub host.
+#[derive(Debug)]
+struct HostFetch {
+    notifications: Vec<github::NotificationThread>,
+    authored_prs: Vec<github::AuthoredPrSummary>,
+    assigned_prs: Vec<github::AssignedPrSummary>,
+    review_requested_prs: Vec<github::ReviewRequestedPrSummary>,
+    assigned_issues: github::AssignedIssuesSearchResult,
+    mentions: Vec<github::MentionSummary>,
+    /// `kv` keys and values of the fetch cursors, stored once the poll
+    /// succeeded.
+    cursors: Vec<(String, String)>,
+}
+
+/// Poll the watched GitHub hosts one at a time, then the Forgejo hosts. A
+/// host that can't be polled is skipped, keeping its threads as they were;
+/// the poll only fails when every watched GitHub host did.
 pub(super) async fn poll_once_async(
     db: &Db,
     config: &AppConfig,
-    work_dir: &Utf8Path,
     mode: PollMode,
 ) -> anyhow::Result<PollStats> {
-    let notification_cursor = db.get_kv("last_notifications_fetch")?;
-    let notification_fetch_since = notification_cursor.as_deref();
-    let notification_now = poll_cursor_now();
-    println!(
-        "🔎 Notification fetch: mode={mode:?} stored_since={} request_since={}",
-        notification_cursor.as_deref().unwrap_or("<none>"),
-        notification_fetch_since.unwrap_or("<none>")
-    );
-    let mut notifications = github::fetch_notifications(notification_fetch_since).await?;
-    print_fetched_notifications(&notifications);
-    let newest_notification_ts = newest_seen_timestamp(
-        notifications
-            .iter()
-            .map(|notification| notification.updated_at.as_str()),
-    );
-    let next_notification_cursor = next_incremental_cursor(
-        notification_cursor.as_deref(),
-        newest_notification_ts,
-        &notification_now,
-    );
-    println!(
-        "⏱️ Cursor advance [notifications]: previous={} newest_seen={} next={}",
-        notification_cursor.as_deref().unwrap_or("<none>"),
-        format_cursor_debug_value(newest_notification_ts),
-        next_notification_cursor
-    );
+    let mut stats = PollStats::default();
+    let mut review_candidates = Vec::new();
+    let mut cursors = Vec::new();
+    let mut polled_any = false;
+    let mut last_error = None;
+    for host in github::hosts().iter().filter(|host| host.watch) {
+        match poll_github_host(db, config, host, mode, &mut stats).await {
+            Ok(polled) => {
+                polled_any = true;
+                review_candidates.extend(polled.review_candidates);
+                cursors.extend(polled.cursors);
+            }
+            Err(err) => {
+                eprintln!("⚠️ Skipping GitHub host {}: {err:#}", host.host);
+                last_error = Some(err);
+            }
+        }
+    }
 
-    let authored_pr_cursor = db.get_kv("last_authored_prs_fetch")?;
-    let authored_pr_fetch_since = authored_pr_cursor.as_deref();
-    let authored_pr_now = poll_cursor_now();
-    println!(
-        "🔎 Authored PR fetch: mode={mode:?} stored_since={} request_since={}",
-        authored_pr_cursor.as_deref().unwrap_or("<none>"),
-        authored_pr_fetch_since.unwrap_or("<none>")
-    );
-    let authored_prs = github::fetch_authored_prs(authored_pr_fetch_since).await?;
-    print_fetched_authored_prs(&authored_prs);
-    let newest_authored_pr_ts =
-        newest_seen_timestamp(authored_prs.iter().map(|pr| pr.updated_at.as_str()));
-    let next_authored_pr_cursor = next_incremental_cursor(
-        authored_pr_cursor.as_deref(),
-        newest_authored_pr_ts,
-        &authored_pr_now,
-    );
-    println!(
-        "⏱️ Cursor advance [authored_prs]: previous={} newest_seen={} next={}",
-        authored_pr_cursor.as_deref().unwrap_or("<none>"),
-        format_cursor_debug_value(newest_authored_pr_ts),
-        next_authored_pr_cursor
-    );
-    sync_authored_pr_threads(db, &authored_prs)?;
+    let selection = if mode == PollMode::Startup {
+        let limits = StartupReviewLimits {
+            lookback_days: config.initial_review_lookback_days,
+            max_prs: config.initial_review_max_prs,
+        };
+        apply_startup_review_limits(review_candidates, limits, unix_ts())
+    } else {
+        StartupReviewSelection {
+            to_review: review_candidates,
+            to_mark_baseline: Vec::new(),
+        }
+    };
 
-    println!("🔎 Assigned PR fetch: mode={mode:?}");
-    let assigned_prs = github::fetch_assigned_prs().await?;
-    print_fetched_assigned_prs(&assigned_prs);
-    sync_assigned_pr_threads(db, &assigned_prs)?;
+    for details in selection.to_mark_baseline {
+        db.set_pr_review_marker(&details.pr_url, &details.head_sha, &details.updated_at)?;
+    }
 
-    println!("🔎 Assigned issue fetch: mode={mode:?}");
-    let assigned_issues = github::fetch_assigned_issues().await?;
-    print_fetched_assigned_issues(&assigned_issues);
-    sync_assigned_issue_threads(db, &assigned_issues)?;
+    for details in selection.to_review {
+        match db.enqueue_job(
+            db::JobKind::Review,
+            &details.pr_url,
+            Some(&details.head_sha),
+            config.jobs.max_attempts,
+        ) {
+            Ok(Some(id)) => {
+                println!("📥 Auto-review queued as job #{id}: {}", details.pr_url);
+                stats.reviews_queued += 1;
+            }
+            Ok(None) => println!(
+                "⏭️ Auto-review skipped, a job already ran for {} at {}",
+                details.pr_url, details.head_sha
+            ),
+            Err(err) => eprintln!("❌ Failed to queue auto-review: {}: {err}", details.pr_url),
+        }
+    }
+
+    for (key, cursor) in &cursors {
+        db.set_kv(key, cursor)?;
+    }
+
+    poll_forgejo_hosts(db, config, mode, &mut stats).await?;
+    match last_error {
+        Some(err) if !polled_any => Err(err),
+        _ => Ok(stats),
+    }
+}
+
+/// PRs of a GitHub host that may need a review, and the fetch cursors to
+/// store once they are queued.
+struct GitHubHostPoll {
+    review_candidates: Vec<github::PrDetails>,
+    cursors: Vec<(String, String)>,
+}
+
+/// Fetch the threads and PRs of `host` and sync them in the DB. Only the
+/// threads of `host` are deleted when they are gone from its results.
+async fn poll_github_host(
+    db: &Db,
+    config: &AppConfig,
+    host: &github::GitHubHost,
+    mode: PollMode,
+    stats: &mut PollStats,
+) -> anyhow::Result<GitHubHostPoll> {
+    let HostFetch {
+        mut notifications,
+        authored_prs,
+        assigned_prs,
+        review_requested_prs,
+        assigned_issues,
+        mentions,
+        cursors,
+    } = fetch_host(db, host, mode).await?;
+    let host_url = host.web_url("");
+    sync_authored_pr_threads(db, ThreadForge::GitHub, &authored_prs)?;
+    sync_assigned_pr_threads(db, &host_url, &assigned_prs)?;
+    sync_review_requested_pr_threads(db, &host_url, &review_requested_prs)?;
+    sync_assigned_issue_threads(db, ThreadForge::GitHub, &host_url, &assigned_issues)?;
+    sync_mention_threads(db, &host_url, &mentions)?;
 
     let mut pr_urls = HashSet::new();
     for notification in &notifications {
@@ -98,6 +170,9 @@ pub(super) async fn poll_once_async(
     for assigned in &assigned_prs {
         pr_urls.insert(assigned.pr_url.clone());
     }
+    for requested in &review_requested_prs {
+        pr_urls.insert(requested.pr_url.clone());
+    }
 
     let issue_api_urls: Vec<String> = notifications
         .iter()
@@ -121,37 +196,9 @@ pub(super) async fn poll_once_async(
         }
     }
 
-    for notification in &notifications {
-        let thread_key = format!("notif:{}", notification.thread_id);
-        let row = db::NewThread {
-            thread_key,
-            github_thread_id: Some(notification.thread_id.clone()),
-            source: "notification".to_string(),
-            repository: notification.repository.clone(),
-            subject_type: notification.subject_type.clone(),
-            subject_title: notification.subject_title.clone(),
-            subject_url: notification.subject_url.clone(),
-            issue_state: notification.issue_state.clone(),
-            discussion_answered: notification.discussion_answered,
-            reason: notification.reason.clone(),
-            pr_url: notification.pr_url.clone(),
-            unread: notification.unread,
-            done: false,
-            updated_at: notification.updated_at.clone(),
-            is_draft: false,
-        };
-        db.upsert_thread(&row)?;
-        print_thread_db_write(&row);
-    }
-
-    let startup_limits = (mode == PollMode::Startup).then_some(StartupReviewLimits {
-        lookback_days: config.initial_review_lookback_days,
-        max_prs: config.initial_review_max_prs,
-    });
+    upsert_notification_threads(db, ThreadForge::GitHub, &notifications)?;
 
-    let mut reviews_run = 0_usize;
     let mut review_candidates = Vec::new();
-
     for pr_url in &pr_urls {
         let details = match batch.pr_details.get(pr_url) {
             Some(details) => details.clone(),
@@ -165,6 +212,10 @@ pub(super) async fn poll_once_async(
         let stored = db.get_pr(&details.pr_url)?;
         upsert_pr_from_details(db, &details)?;
         print_pr_db_write(stored.as_ref(), &details);
+        if let Some(checks) = batch.checks.get(pr_url) {
+            db.replace_pr_checks(&details.pr_url, checks)?;
+        }
+        watch_merge_queue(db, stored.as_ref(), &details)?;
 
         if should_review_pr(config.rereview_mode, stored.as_ref(), &details) {
             review_candidates.push(details.clone());
@@ -181,46 +232,362 @@ pub(super) async fn poll_once_async(
         }
     }
 
-    let selection = if let Some(limits) = startup_limits {
-        apply_startup_review_limits(review_candidates, limits, unix_ts())
-    } else {
-        StartupReviewSelection {
-            to_review: review_candidates,
-            to_mark_baseline: Vec::new(),
-        }
-    };
+    stats.notifications_fetched += notifications.len();
+    stats.authored_prs_fetched += authored_prs.len();
+    stats.assigned_prs_fetched += assigned_prs.len();
+    stats.review_requested_prs_fetched += review_requested_prs.len();
+    stats.mentions_fetched += mentions.len();
+    stats.assigned_issues_fetched += assigned_issues.issues.len();
+    stats.prs_seen += pr_urls.len();
+    stats.participants.extend(batch.participants);
+    Ok(GitHubHostPoll {
+        review_candidates,
+        cursors,
+    })
+}
 
-    for details in selection.to_mark_baseline {
-        db.set_pr_review_marker(&details.pr_url, &details.head_sha, &details.updated_at)?;
-    }
+/// Results of the fetches of a Forgejo host.
+#[derive(Debug)]
+struct ForgejoFetch {
+    client: ForgejoClient,
+    notifications: Vec<github::NotificationThread>,
+    authored_prs: Vec<github::AuthoredPrSummary>,
+    assigned_issues: github::AssignedIssuesSearchResult,
+    cursors: Vec<(String, String)>,
+}
 
-    for details in selection.to_review {
-        println!("🔍 Auto-review started: {}", details.pr_url);
-        match run_review_for_details(db, config, work_dir, &details).await {
-            Ok(()) => {
-                println!("✅ Auto-review finished: {}", details.pr_url);
-                reviews_run += 1;
-            }
+/// Ingest the notifications, authored PRs and assigned issues of the
+/// Forgejo hosts into `threads` and `prs`. Their PRs are not reviewed: the
+/// review flow does not support Forgejo. A host that can't be fetched is
+/// skipped, keeping its threads as they were.
+async fn poll_forgejo_hosts(
+    db: &Db,
+    config: &AppConfig,
+    mode: PollMode,
+    stats: &mut PollStats,
+) -> anyhow::Result<()> {
+    let mut fetches = Vec::new();
+    let mut all_fetched = true;
+    for host in forge::forgejo_hosts(&config.forgejo) {
+        let fetched = match ForgejoClient::for_host(&host) {
+            Ok(client) => fetch_forgejo_host(db, client, mode).await,
+            Err(err) => Err(err),
+        };
+        match fetched {
+            Ok(fetched) => fetches.push(fetched),
             Err(err) => {
-                eprintln!("❌ Auto-review failed: {}: {err}", details.pr_url);
+                eprintln!("⚠️ Skipping Forgejo host {}: {err}", host.host);
+                all_fetched = false;
             }
         }
     }
+    if fetches.is_empty() {
+        return Ok(());
+    }
+
+    let notifications: Vec<_> = fetches
+        .iter()
+        .flat_map(|fetched| fetched.notifications.iter().cloned())
+        .collect();
+    let authored_prs: Vec<_> = fetches
+        .iter()
+        .flat_map(|fetched| fetched.authored_prs.iter().cloned())
+        .collect();
+    // Issues of the skipped hosts must not be dropped as no longer assigned.
+    let assigned_issues = github::AssignedIssuesSearchResult {
+        issues: fetches
+            .iter()
+            .flat_map(|fetched| fetched.assigned_issues.issues.iter().cloned())
+            .collect(),
+        is_complete: all_fetched
+            && fetches
+                .iter()
+                .all(|fetched| fetched.assigned_issues.is_complete),
+    };
+    upsert_notification_threads(db, ThreadForge::Forgejo, &notifications)?;
+    sync_authored_pr_threads(db, ThreadForge::Forgejo, &authored_prs)?;
+    // All the Forgejo hosts at once: a skipped host makes the results
+    // incomplete, so nothing is deleted.
+    sync_assigned_issue_threads(db, ThreadForge::Forgejo, "", &assigned_issues)?;
+
+    for fetched in &fetches {
+        let pr_urls: std::collections::BTreeSet<&str> = fetched
+            .notifications
+            .iter()
+            .filter_map(|notification| notification.pr_url.as_deref())
+            .chain(fetched.authored_prs.iter().map(|pr| pr.pr_url.as_str()))
+            .collect();
+        for pr_url in &pr_urls {
+            let details = match fetched.client.fetch_pr_details(pr_url).await {
+                Ok(details) => details,
+                Err(err) => {
+                    eprintln!("⚠️ No PR details for {pr_url}: {err}");
+                    continue;
+                }
+            };
+            print_pr_details("Fetched PR details", &details);
+            let stored = db.get_pr(&details.pr_url)?;
+            upsert_pr_from_details(db, &details)?;
+            print_pr_db_write(stored.as_ref(), &details);
+        }
+        for (key, cursor) in &fetched.cursors {
+            db.set_kv(key, cursor)?;
+        }
+        stats.prs_seen += pr_urls.len();
+    }
+
+    stats.notifications_fetched += notifications.len();
+    stats.authored_prs_fetched += authored_prs.len();
+    stats.assigned_issues_fetched += assigned_issues.issues.len();
+    Ok(())
+}
+
+async fn fetch_forgejo_host(
+    db: &Db,
+    client: ForgejoClient,
+    mode: PollMode,
+) -> anyhow::Result<ForgejoFetch> {
+    let name = client.host().host.clone();
+
+    let notification_cursor_key = format!("forgejo:last_notifications_fetch:{name}");
+    let notification_cursor = db.get_kv(&notification_cursor_key)?;
+    let notification_now = poll_cursor_now();
+    println!(
+        "🔎 Notification fetch [{name}]: mode={mode:?} stored_since={}",
+        notification_cursor.as_deref().unwrap_or("<none>")
+    );
+    let notifications = client
+        .fetch_notifications(notification_cursor.as_deref())
+        .await?;
+    print_fetched_notifications(&notifications);
+    let next_notification_cursor = next_incremental_cursor(
+        notification_cursor.as_deref(),
+        newest_seen_timestamp(
+            notifications
+                .iter()
+                .map(|notification| notification.updated_at.as_str()),
+        ),
+        &notification_now,
+    );
 
-    db.set_kv("last_notifications_fetch", &next_notification_cursor)?;
-    db.set_kv("last_authored_prs_fetch", &next_authored_pr_cursor)?;
+    let authored_pr_cursor_key = format!("forgejo:last_authored_prs_fetch:{name}");
+    let authored_pr_cursor = db.get_kv(&authored_pr_cursor_key)?;
+    let authored_pr_now = poll_cursor_now();
+    println!(
+        "🔎 Authored PR fetch [{name}]: mode={mode:?} stored_since={}",
+        authored_pr_cursor.as_deref().unwrap_or("<none>")
+    );
+    let authored_prs = client
+        .fetch_authored_prs(authored_pr_cursor.as_deref())
+        .await?;
+    print_fetched_authored_prs(&authored_prs);
+    let next_authored_pr_cursor = next_incremental_cursor(
+        authored_pr_cursor.as_deref(),
+        newest_seen_timestamp(authored_prs.iter().map(|pr| pr.updated_at.as_str())),
+        &authored_pr_now,
+    );
 
-    Ok(PollStats {
-        notifications_fetched: notifications.len(),
-        authored_prs_fetched: authored_prs.len(),
-        assigned_prs_fetched: assigned_prs.len(),
-        assigned_issues_fetched: assigned_issues.issues.len(),
-        prs_seen: pr_urls.len(),
-        reviews_run,
-        participants: batch.participants,
+    println!("🔎 Assigned issue fetch [{name}]: mode={mode:?}");
+    let assigned_issues = client.fetch_assigned_issues().await?;
+    print_fetched_assigned_issues(&assigned_issues);
+
+    Ok(ForgejoFetch {
+        client,
+        notifications,
+        authored_prs,
+        assigned_issues,
+        cursors: vec![
+            (notification_cursor_key, next_notification_cursor),
+            (authored_pr_cursor_key, next_authored_pr_cursor),
+        ],
     })
 }
 
+async fn fetch_host(
+    db: &Db,
+    host: &github::GitHubHost,
+    mode: PollMode,
+) -> anyhow::Result<HostFetch> {
+    let name = &host.host;
+    let notification_cursor_key = host_kv_key("last_notifications_fetch", host);
+    let notification_cursor = db.get_kv(&notification_cursor_key)?;
+    let notification_fetch_since = notification_cursor.as_deref();
+    let notification_now = poll_cursor_now();
+    println!(
+        "🔎 Notification fetch [{name}]: mode={mode:?} stored_since={} request_since={}",
+        notification_cursor.as_deref().unwrap_or("<none>"),
+        notification_fetch_since.unwrap_or("<none>")
+    );
+    let notification_cache_key = host_kv_key("notifications", host);
+    let mut notification_cache = load_response_cache(db, &notification_cache_key)?;
+    let notifications = if notification_cache.must_wait(unix_ts()) {
+        println!(
+            "⏳ Notification fetch [{name}] skipped: X-Poll-Interval={}s not elapsed",
+            notification_cache.poll_interval.unwrap_or_default()
+        );
+        Vec::new()
+    } else {
+        let notifications =
+            github::fetch_notifications(host, notification_fetch_since, &mut notification_cache)
+                .await?;
+        store_response_cache(db, &notification_cache_key, &notification_cache)?;
+        notifications
+    };
+    print_fetched_notifications(&notifications);
+    let newest_notification_ts = newest_seen_timestamp(
+        notifications
+            .iter()
+            .map(|notification| notification.updated_at.as_str()),
+    );
+    let next_notification_cursor = next_incremental_cursor(
+        notification_cursor.as_deref(),
+        newest_notification_ts,
+        &notification_now,
+    );
+    println!(
+        "⏱️ Cursor advance [notifications {name}]: previous={} newest_seen={} next={}",
+        notification_cursor.as_deref().unwrap_or("<none>"),
+        format_cursor_debug_value(newest_notification_ts),
+        next_notification_cursor
+    );
+
+    let authored_pr_cursor_key = host_kv_key("last_authored_prs_fetch", host);
+    let authored_pr_cursor = db.get_kv(&authored_pr_cursor_key)?;
+    let authored_pr_fetch_since = authored_pr_cursor.as_deref();
+    let authored_pr_now = poll_cursor_now();
+    println!(
+        "🔎 Authored PR fetch [{name}]: mode={mode:?} stored_since={} request_since={}",
+        authored_pr_cursor.as_deref().unwrap_or("<none>"),
+        authored_pr_fetch_since.unwrap_or("<none>")
+    );
+    let authored_pr_cache_key = host_kv_key("authored_prs", host);
+    let mut authored_pr_cache = load_response_cache(db, &authored_pr_cache_key)?;
+    let authored_prs =
+        github::fetch_authored_prs(host, authored_pr_fetch_since, &mut authored_pr_cache).await?;
+    store_response_cache(db, &authored_pr_cache_key, &authored_pr_cache)?;
+    print_fetched_authored_prs(&authored_prs);
+    let newest_authored_pr_ts =
+        newest_seen_timestamp(authored_prs.iter().map(|pr| pr.updated_at.as_str()));
+    let next_authored_pr_cursor = next_incremental_cursor(
+        authored_pr_cursor.as_deref(),
+        newest_authored_pr_ts,
+        &authored_pr_now,
+    );
+    println!(
+        "⏱️ Cursor advance [authored_prs {name}]: previous={} newest_seen={} next={}",
+        authored_pr_cursor.as_deref().unwrap_or("<none>"),
+        format_cursor_debug_value(newest_authored_pr_ts),
+        next_authored_pr_cursor
+    );
+
+    println!("🔎 Assigned PR fetch [{name}]: mode={mode:?}");
+    let assigned_pr_cache_key = host_kv_key("assigned_prs", host);
+    let mut assigned_pr_cache = load_response_cache(db, &assigned_pr_cache_key)?;
+    let assigned_prs = github::fetch_assigned_prs(host, &mut assigned_pr_cache).await?;
+    store_response_cache(db, &assigned_pr_cache_key, &assigned_pr_cache)?;
+    print_fetched_assigned_prs(&assigned_prs);
+
+    println!("🔎 Review-requested PR fetch [{name}]: mode={mode:?}");
+    let review_requested_cache_key = host_kv_key("review_requested_prs", host);
+    let direct_review_requested_cache_key = host_kv_key("direct_review_requested_prs", host);
+    let mut review_requested_cache = load_response_cache(db, &review_requested_cache_key)?;
+    let mut direct_review_requested_cache =
+        load_response_cache(db, &direct_review_requested_cache_key)?;
+    let review_requested_prs = github::fetch_review_requested_prs(
+        host,
+        &mut review_requested_cache,
+        &mut direct_review_requested_cache,
+    )
+    .await?;
+    store_response_cache(db, &review_requested_cache_key, &review_requested_cache)?;
+    store_response_cache(
+        db,
+        &direct_review_requested_cache_key,
+        &direct_review_requested_cache,
+    )?;
+    print_fetched_review_requested_prs(&review_requested_prs);
+
+    println!("🔎 Assigned issue fetch [{name}]: mode={mode:?}");
+    let assigned_issue_cache_key = host_kv_key("assigned_issues", host);
+    let mut assigned_issue_cache = load_response_cache(db, &assigned_issue_cache_key)?;
+    let assigned_issues = github::fetch_assigned_issues(host, &mut assigned_issue_cache).await?;
+    store_response_cache(db, &assigned_issue_cache_key, &assigned_issue_cache)?;
+    print_fetched_assigned_issues(&assigned_issues);
+
+    println!("🔎 Mention fetch [{name}]: mode={mode:?}");
+    let mentions = github::fetch_mentions(host).await?;
+    print_fetched_mentions(&mentions);
+
+    Ok(HostFetch {
+        notifications,
+        authored_prs,
+        assigned_prs,
+        review_requested_prs,
+        assigned_issues,
+        mentions,
+        cursors: vec![
+            (notification_cursor_key, next_notification_cursor),
+            (authored_pr_cursor_key, next_authored_pr_cursor),
+        ],
+    })
+}
+
+/// `kv` key of the state of a fetch from `host`. github.com keeps the keys
+/// it had before GitHub Enterprise Server hosts were supported.
+fn host_kv_key(key: &str, host: &github::GitHubHost) -> String {
+    if host.is_github_com() {
+        key.to_string()
+    } else {
+        format!("{key}:{}", host.host)
+    }
+}
+
+/// Validators of the previous responses of a poll `fetch`, stored in `kv`.
+/// A cache that can't be read is dropped, the next fetch being unconditional.
+fn load_response_cache(db: &Db, fetch: &str) -> anyhow::Result<github::ResponseCache> {
+    let Some(value) = db.get_kv(&format!("http_cache:{fetch}"))? else {
+        return Ok(github::ResponseCache::default());
+    };
+    Ok(serde_json::from_str(&value).unwrap_or_else(|err| {
+        eprintln!("⚠️ Ignoring invalid HTTP cache of {fetch}: {err}");
+        github::ResponseCache::default()
+    }))
+}
+
+fn store_response_cache(db: &Db, fetch: &str, cache: &github::ResponseCache) -> anyhow::Result<()> {
+    db.set_kv(
+        &format!("http_cache:{fetch}"),
+        &serde_json::to_string(cache)?,
+    )
+}
+
+/// Seconds to wait before the next background poll: `period`, stretched as
+/// the remaining quota of an API resource runs low, and up to its reset when
+/// it is exhausted.
+pub(super) fn poll_delay_seconds(
+    period: u64,
+    quota: impl IntoIterator<Item = github::RateLimit>,
+    now: i64,
+) -> u64 {
+    quota
+        .into_iter()
+        .map(|limit| {
+            let until_reset = u64::try_from(limit.reset.saturating_sub(now))
+                .unwrap_or_default()
+                .max(period);
+            if limit.remaining == 0 {
+                return until_reset;
+            }
+            let slowdown = match limit.remaining.saturating_mul(100) / limit.limit.max(1) {
+                0..10 => 4,
+                10..25 => 2,
+                _ => 1,
+            };
+            period.saturating_mul(slowdown).min(until_reset)
+        })
+        .fold(period, u64::max)
+}
+
 fn poll_cursor_now() -> String {
     chrono::Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
 }
@@ -275,6 +642,37 @@ fn format_cursor_debug_value(unix_seconds: Option<i64>) -> String {
         .unwrap_or_else(|| "<none>".to_string())
 }
 
+fn upsert_notification_threads(
+    db: &Db,
+    forge: ThreadForge,
+    notifications: &[github::NotificationThread],
+) -> anyhow::Result<()> {
+    for notification in notifications {
+        let row = db::NewThread {
+            thread_key: forge.namespaced(&format!("notif:{}", notification.thread_id)),
+            // Only GitHub notifications can be marked read or done upstream.
+            github_thread_id: (forge == ThreadForge::GitHub)
+                .then(|| notification.thread_id.clone()),
+            source: forge.namespaced("notification"),
+            repository: notification.repository.clone(),
+            subject_type: notification.subject_type.clone(),
+            subject_title: notification.subject_title.clone(),
+            subject_url: notification.subject_url.clone(),
+            issue_state: notification.issue_state.clone(),
+            discussion_answered: notification.discussion_answered,
+            reason: notification.reason.clone(),
+            pr_url: notification.pr_url.clone(),
+            unread: notification.unread,
+            done: false,
+            updated_at: notification.updated_at.clone(),
+            is_draft: false,
+        };
+        db.upsert_thread(&row)?;
+        print_thread_db_write(&row);
+    }
+    Ok(())
+}
+
 /// Keeps the `threads` table in sync with the user's authored PRs.
 ///
 /// The dashboard shows threads from two sources: GitHub notifications and the
@@ -289,8 +687,10 @@ fn format_cursor_debug_value(unix_seconds: Option<i64>) -> String {
 ///    lists all of the user's active PRs regardless of notification state.
 pub(crate) fn sync_authored_pr_threads(
     db: &Db,
+    forge: ThreadForge,
     authored_prs: &[github::AuthoredPrSummary],
 ) -> anyhow::Result<()> {
+    let source = forge.namespaced("my_pr");
     let closed_pr_urls: Vec<_> = authored_prs
         .iter()
         .filter(|pr| !pr.is_open)
@@ -298,17 +698,17 @@ pub(crate) fn sync_authored_pr_threads(
         .collect();
 
     println!(
-        "🗄️ DB delete threads: source=my_pr closed_pr_urls={}",
+        "🗄️ DB delete threads: source={source} closed_pr_urls={}",
         closed_pr_urls.len()
     );
-    db.delete_threads_by_source_and_pr_urls("my_pr", &closed_pr_urls)?;
+    db.delete_threads_by_source_and_pr_urls(&source, &closed_pr_urls)?;
 
     for authored in authored_prs.iter().filter(|pr| pr.is_open) {
-        let thread_key = format!("mypr:{}", authored.pr_url);
+        let thread_key = forge.namespaced(&format!("mypr:{}", authored.pr_url));
         let row = db::NewThread {
             thread_key,
             github_thread_id: None,
-            source: "my_pr".to_string(),
+            source: source.clone(),
             repository: authored.repository.clone(),
             subject_type: Some("PullRequest".to_string()),
             subject_title: authored.title.clone(),
@@ -329,10 +729,15 @@ pub(crate) fn sync_authored_pr_threads(
     Ok(())
 }
 
+/// Only the threads whose URL starts with `host_url` are deleted when they
+/// are gone from the results.
 pub(crate) fn sync_assigned_issue_threads(
     db: &Db,
+    forge: ThreadForge,
+    host_url: &str,
     assigned_issues: &github::AssignedIssuesSearchResult,
 ) -> anyhow::Result<()> {
+    let source = forge.namespaced("my_issue");
     let open_issue_urls: Vec<_> = assigned_issues
         .issues
         .iter()
@@ -342,13 +747,13 @@ pub(crate) fn sync_assigned_issue_threads(
 
     if assigned_issues.is_complete {
         println!(
-            "🗄️ DB delete threads: source=my_issue keep_open_issue_urls={}",
+            "🗄️ DB delete threads: source={source} keep_open_issue_urls={}",
             open_issue_urls.len()
         );
-        db.delete_threads_by_source_except_subject_urls("my_issue", &open_issue_urls)?;
+        db.delete_threads_by_source_except_subject_urls(&source, host_url, &open_issue_urls)?;
     } else {
         println!(
-            "🗄️ DB skip delete threads: source=my_issue reason=incomplete_search_results keep_open_issue_urls={}",
+            "🗄️ DB skip delete threads: source={source} reason=incomplete_search_results keep_open_issue_urls={}",
             open_issue_urls.len()
         );
     }
@@ -358,11 +763,11 @@ pub(crate) fn sync_assigned_issue_threads(
         .iter()
         .filter(|issue| issue.state == "OPEN")
     {
-        let thread_key = format!("myissue:{}", issue.issue_url);
+        let thread_key = forge.namespaced(&format!("myissue:{}", issue.issue_url));
         let row = db::NewThread {
             thread_key,
             github_thread_id: None,
-            source: "my_issue".to_string(),
+            source: source.clone(),
             repository: issue.repository.clone(),
             subject_type: Some("Issue".to_string()),
             subject_title: issue.title.clone(),
@@ -385,6 +790,7 @@ pub(crate) fn sync_assigned_issue_threads(
 
 pub(crate) fn sync_assigned_pr_threads(
     db: &Db,
+    host_url: &str,
     assigned_prs: &[github::AssignedPrSummary],
 ) -> anyhow::Result<()> {
     let open_pr_urls: Vec<_> = assigned_prs.iter().map(|pr| pr.pr_url.clone()).collect();
@@ -393,7 +799,7 @@ pub(crate) fn sync_assigned_pr_threads(
         "🗄️ DB delete threads: source=assigned_pr keep_open_pr_urls={}",
         open_pr_urls.len()
     );
-    db.delete_threads_by_source_except_pr_urls("assigned_pr", &open_pr_urls)?;
+    db.delete_threads_by_source_except_pr_urls("assigned_pr", host_url, &open_pr_urls)?;
 
     for assigned in assigned_prs {
         let thread_key = format!("assignedpr:{}", assigned.pr_url);
@@ -421,6 +827,114 @@ pub(crate) fn sync_assigned_pr_threads(
     Ok(())
 }
 
+/// Review requests sent to one of your teams get the `team_review_requested`
+/// source, the ones sent to you `review_requested`. A PR keeps its thread,
+/// and its done state, when a request moves from a team to you.
+pub(crate) fn sync_review_requested_pr_threads(
+    db: &Db,
+    host_url: &str,
+    review_requested_prs: &[github::ReviewRequestedPrSummary],
+) -> anyhow::Result<()> {
+    let open_pr_urls: Vec<_> = review_requested_prs
+        .iter()
+        .map(|pr| pr.pr_url.clone())
+        .collect();
+
+    for source in ["review_requested", "team_review_requested"] {
+        println!(
+            "🗄️ DB delete threads: source={source} keep_open_pr_urls={}",
+            open_pr_urls.len()
+        );
+        db.delete_threads_by_source_except_pr_urls(source, host_url, &open_pr_urls)?;
+    }
+
+    for requested in review_requested_prs {
+        let (source, reason) = if requested.team_request {
+            ("team_review_requested", "team_review_requested")
+        } else {
+            ("review_requested", "review_requested")
+        };
+        let row = db::NewThread {
+            thread_key: format!("reviewrequested:{}", requested.pr_url),
+            github_thread_id: None,
+            source: source.to_string(),
+            repository: requested.repository.clone(),
+            subject_type: Some("PullRequest".to_string()),
+            subject_title: requested.title.clone(),
+            subject_url: Some(requested.pr_url.clone()),
+            issue_state: None,
+            discussion_answered: None,
+            reason: Some(reason.to_string()),
+            pr_url: Some(requested.pr_url.clone()),
+            unread: false,
+            done: false,
+            updated_at: requested.updated_at.clone(),
+            is_draft: requested.is_draft,
+        };
+        db.upsert_thread(&row)?;
+        print_thread_db_write(&row);
+    }
+
+    Ok(())
+}
+
+/// Mentions of one of your teams get the `team_mention` source, mentions of
+/// you `mention`. The thread links to the comment with the mention.
+pub(crate) fn sync_mention_threads(
+    db: &Db,
+    host_url: &str,
+    mentions: &[github::MentionSummary],
+) -> anyhow::Result<()> {
+    let subject_urls: Vec<_> = mentions
+        .iter()
+        .map(|mention| mention.subject_url.clone())
+        .collect();
+
+    for source in ["mention", "team_mention"] {
+        println!(
+            "🗄️ DB delete threads: source={source} keep_subject_urls={}",
+            subject_urls.len()
+        );
+        db.delete_threads_by_source_except_subject_urls(source, host_url, &subject_urls)?;
+    }
+
+    for mention in mentions {
+        let source = if mention.team.is_some() {
+            "team_mention"
+        } else {
+            "mention"
+        };
+        let row = db::NewThread {
+            thread_key: format!("mention:{}", mention.thread_url),
+            github_thread_id: None,
+            source: source.to_string(),
+            repository: mention.repository.clone(),
+            subject_type: Some(
+                if mention.is_pull_request {
+                    "PullRequest"
+                } else {
+                    "Issue"
+                }
+                .to_string(),
+            ),
+            subject_title: mention.title.clone(),
+            subject_url: Some(mention.subject_url.clone()),
+            issue_state: None,
+            discussion_answered: None,
+            reason: Some(mention.team.clone().unwrap_or_else(|| source.to_string())),
+            pr_url: mention.is_pull_request.then(|| mention.thread_url.clone()),
+            unread: false,
+            done: false,
+            updated_at: mention.updated_at.clone(),
+            is_draft: false,
+        };
+        db.upsert_thread(&row)?;
+        print_thread_db_write(&row);
+    }
+
+    Ok(())
+}
+
 pub(super) fn upsert_pr_from_details(db: &Db, details: &github::PrDetails) -> anyhow::Result<()> {
     let row = db::NewPr {
         pr_url: details.pr_url.clone(),
@@ -429,6 +943,7 @@ pub(super) fn upsert_pr_from_details(db: &Db, details: &github::PrDetails) -> an
         number: details.number,
         state: details.state.clone(),
         merge_queue_state: details.merge_queue_state.clone(),
+        auto_merge: details.auto_merge,
         title: details.title.clone(),
         head_ref: details.head_ref.clone(),
         base_ref: details.base_ref.clone(),
@@ -499,6 +1014,38 @@ fn print_fetched_assigned_prs(assigned_prs: &[github::AssignedPrSummary]) {
     }
 }
 
+fn print_fetched_review_requested_prs(review_requested_prs: &[github::ReviewRequestedPrSummary]) {
+    println!(
+        "📥 Review-requested PRs fetched: {}",
+        review_requested_prs.len()
+    );
+    for requested in review_requested_prs {
+        println!(
+            "  • pr_url={} repo={} updated_at={} is_draft={} team_request={} title={}",
+            requested.pr_url,
+            requested.repository,
+            requested.updated_at,
+            requested.is_draft,
+            requested.team_request,
+            requested.title
+        );
+    }
+}
+
+fn print_fetched_mentions(mentions: &[github::MentionSummary]) {
+    println!("📥 Mentions fetched: {}", mentions.len());
+    for mention in mentions {
+        println!(
+            "  • subject_url={} repo={} team={} updated_at={} title={}",
+            mention.subject_url,
+            mention.repository,
+            mention.team.as_deref().unwrap_or("<none>"),
+            mention.updated_at,
+            mention.title
+        );
+    }
+}
+
 fn print_pr_details(prefix: &str, details: &github::PrDetails) {
     println!(
         "📥 {prefix}: pr_url={} state={} head_sha={} updated_at={} archived={} title={}",
@@ -511,7 +1058,7 @@ fn print_pr_details(prefix: &str, details: &github::PrDetails) {
     );
 }
 
-fn print_thread_db_write(row: &db::NewThread) {
+pub(super) fn print_thread_db_write(row: &db::NewThread) {
     println!(
         "🗄️ DB upsert thread [{}]: source={} repo={} pr_url={} unread={} done={} updated_at={} title={}",
         row.thread_key,
@@ -525,7 +1072,7 @@ fn print_thread_db_write(row: &db::NewThread) {
     );
 }
 
-fn print_pr_db_write(existing: Option<&db::StoredPr>, details: &github::PrDetails) {
+pub(super) fn print_pr_db_write(existing: Option<&db::StoredPr>, details: &github::PrDetails) {
     let action = match existing {
         None => "insert",
         Some(_) => "update",
@@ -547,22 +1094,29 @@ pub(super) async fn run_review_for_details(
     work_dir: &Utf8Path,
     details: &github::PrDetails,
 ) -> anyhow::Result<()> {
-    let agent = config.ai.provider.as_agent();
+    let agent = ai::agent_for(config.ai.provider, &config.ai);
+    let previous = if config.review.incremental {
+        let last_reviewed_sha = db
+            .get_pr(&details.pr_url)?
+            .and_then(|pr| pr.last_reviewed_sha);
+        review::PreviousReview::load(db, &details.pr_url, last_reviewed_sha.as_deref())?
+    } else {
+        None
+    };
     let review_result = review::generate_review(
         work_dir,
         &details.pr_url,
-        Some(&agent),
+        agent.as_ref(),
         config.ai.model.as_deref(),
+        &config.review,
+        previous.as_ref(),
     )
     .await?;
 
-    db.insert_review(&db::NewReview {
-        pr_url: details.pr_url.clone(),
-        provider: review_result.provider,
-        model: review_result.model,
-        requires_code_changes: review_result.requires_code_changes,
-        content_md: review_result.markdown,
-    })?;
+    // Nothing to review is still handled: the head is not reviewed again.
+    if let Some(review_result) = review_result {
+        db.insert_review(&review_result.to_new_review(&details.pr_url))?;
+    }
     db.set_pr_review_marker(&details.pr_url, &details.head_sha, &details.updated_at)?;
 
     Ok(())
@@ -641,8 +1195,61 @@ pub(crate) fn should_review_pr(
     }
 }
 
+/// Why one of your open PRs needs attention since `stored` was polled: it
+/// left the merge queue without being merged, or auto-merge was disabled.
+pub(crate) fn merge_queue_alert(
+    stored: Option<&db::StoredPr>,
+    details: &github::PrDetails,
+) -> Option<&'static str> {
+    let stored = stored?;
+    if details.state != "OPEN" || details.merge_queue_state.is_some() || details.auto_merge {
+        return None;
+    }
+    if stored.merge_queue_state.is_some() {
+        Some("Removed from the merge queue")
+    } else if stored.auto_merge {
+        Some("Auto-merge disabled")
+    } else {
+        None
+    }
+}
+
+/// Record the alerts of your PRs as `attention` sync events, and flag the
+/// PR for the dashboard until it is queued again, gets auto-merge back or
+/// is closed.
+pub(super) fn watch_merge_queue(
+    db: &Db,
+    stored: Option<&db::StoredPr>,
+    details: &github::PrDetails,
+) -> anyhow::Result<()> {
+    let settled =
+        details.state != "OPEN" || details.merge_queue_state.is_some() || details.auto_merge;
+    if settled {
+        if stored.is_some_and(|pr| pr.attention.is_some()) {
+            db.set_pr_attention(&details.pr_url, None)?;
+        }
+        return Ok(());
+    }
+    let Some(alert) = merge_queue_alert(stored, details) else {
+        return Ok(());
+    };
+    let is_mine = db
+        .threads_for_url(&details.pr_url)?
+        .iter()
+        .any(|thread| db::source_kind(&thread.source) == "my_pr");
+    if !is_mine {
+        return Ok(());
+    }
+
+    println!("🚨 {alert}: {}", details.pr_url);
+    db.insert_sync_event(&details.pr_url, "attention", alert)?;
+    db.set_pr_attention(&details.pr_url, Some(alert))?;
+    Ok(())
+}
+
 async fn handle_closed_pr_branch_sync(db: &Db, details: &github::PrDetails) -> anyhow::Result<()> {
-    let repo_dir = github::local_repo_dir(&details.owner, &details.repo)?;
+    let host = crate::checkout::parse_github_pr_url(&details.pr_url)?.host;
+    let repo_dir = github::local_repo_dir(&host, &details.owner, &details.repo)?;
     if !repo_dir.exists() || !repo_dir.join(".git").exists() {
         return Ok(());
     }
@@ -675,12 +1282,14 @@ async fn handle_closed_pr_branch_sync(db: &Db, details: &github::PrDetails) -> a
 
 pub(super) fn print_poll_stats(prefix: &str, stats: &PollStats) {
     println!(
-        "{prefix} notifications={}, my_prs={}, assigned_prs={}, assigned_issues={}, prs={}, reviews={}",
+        "{prefix} notifications={}, my_prs={}, assigned_prs={}, review_requested={}, assigned_issues={}, mentions={}, prs={}, reviews={}",
         stats.notifications_fetched,
         stats.authored_prs_fetched,
         stats.assigned_prs_fetched,
+        stats.review_requested_prs_fetched,
         stats.assigned_issues_fetched,
+        stats.mentions_fetched,
         stats.prs_seen,
-        stats.reviews_run
+        stats.reviews_queued
     );
 }
@@ -1,14 +1,20 @@
 use super::{
     helpers::{dashboard_browser_url, parse_repository_name},
+    jobs::retry_delay_seconds,
     poll::{
-        apply_startup_review_limits, next_incremental_cursor, should_review_pr,
-        sync_assigned_issue_threads, sync_assigned_pr_threads, sync_authored_pr_threads,
+        ThreadForge, apply_startup_review_limits, merge_queue_alert, next_incremental_cursor,
+        poll_delay_seconds, should_review_pr, sync_assigned_issue_threads,
+        sync_assigned_pr_threads, sync_authored_pr_threads, sync_review_requested_pr_threads,
+        upsert_pr_from_details,
     },
     time::parse_github_timestamp_to_unix_seconds,
+    webhooks::apply_github_event,
     *,
 };
 use crate::{config, db};
 
+const GITHUB_URL: &str = "https://github.com/";
+
 fn test_db() -> Db {
     let mut path = std::env::temp_dir();
     let ts = std::time::SystemTime::now()
@@ -28,6 +34,7 @@ fn rereview_on_update() {
         number: 1,
         state: "OPEN".to_string(),
         merge_queue_state: None,
+        auto_merge: false,
         title: "t".to_string(),
         head_ref: "feat".to_string(),
         base_ref: "main".to_string(),
@@ -48,6 +55,7 @@ fn rereview_on_update() {
         number: 1,
         state: "OPEN".to_string(),
         merge_queue_state: None,
+        auto_merge: false,
         title: "t".to_string(),
         head_ref: "feat".to_string(),
         base_ref: "main".to_string(),
@@ -56,6 +64,7 @@ fn rereview_on_update() {
         is_archived: false,
         last_reviewed_sha: Some("sha1".to_string()),
         last_reviewed_updated_at: Some("2026-01-01T00:00:00Z".to_string()),
+        attention: None,
     };
 
     assert!(should_review_pr(
@@ -74,6 +83,7 @@ fn manual_mode_skips_after_first_review() {
         number: 1,
         state: "OPEN".to_string(),
         merge_queue_state: None,
+        auto_merge: false,
         title: "t".to_string(),
         head_ref: "feat".to_string(),
         base_ref: "main".to_string(),
@@ -94,6 +104,7 @@ fn manual_mode_skips_after_first_review() {
         number: 1,
         state: "OPEN".to_string(),
         merge_queue_state: None,
+        auto_merge: false,
         title: "t".to_string(),
         head_ref: "feat".to_string(),
         base_ref: "main".to_string(),
@@ -102,6 +113,7 @@ fn manual_mode_skips_after_first_review() {
         is_archived: false,
         last_reviewed_sha: Some("sha1".to_string()),
         last_reviewed_updated_at: Some("2026-01-01T00:00:00Z".to_string()),
+        attention: None,
     };
 
     assert!(!should_review_pr(
@@ -186,6 +198,7 @@ fn startup_limits_filter_and_cap_reviews() {
             number: 1,
             state: "OPEN".to_string(),
             merge_queue_state: None,
+            auto_merge: false,
             title: "old".to_string(),
             head_ref: "feat1".to_string(),
             base_ref: "main".to_string(),
@@ -206,6 +219,7 @@ fn startup_limits_filter_and_cap_reviews() {
             number: 2,
             state: "OPEN".to_string(),
             merge_queue_state: None,
+            auto_merge: false,
             title: "recent".to_string(),
             head_ref: "feat2".to_string(),
             base_ref: "main".to_string(),
@@ -226,6 +240,7 @@ fn startup_limits_filter_and_cap_reviews() {
             number: 3,
             state: "OPEN".to_string(),
             merge_queue_state: None,
+            auto_merge: false,
             title: "recent newer".to_string(),
             head_ref: "feat3".to_string(),
             base_ref: "main".to_string(),
@@ -287,7 +302,7 @@ fn sync_authored_pr_threads_removes_stale_entries() {
         is_draft: false,
     };
 
-    sync_authored_pr_threads(&db, &[closed_pr, current_pr.clone()]).unwrap();
+    sync_authored_pr_threads(&db, ThreadForge::GitHub, &[closed_pr, current_pr.clone()]).unwrap();
 
     let threads = db.list_dashboard_threads().unwrap();
     assert_eq!(threads.len(), 1);
@@ -310,14 +325,52 @@ fn sync_authored_pr_threads_preserves_done_entries() {
         is_draft: false,
     };
 
-    sync_authored_pr_threads(&db, std::slice::from_ref(&current_pr)).unwrap();
+    sync_authored_pr_threads(&db, ThreadForge::GitHub, std::slice::from_ref(&current_pr)).unwrap();
     db.mark_authored_pr_done_local(&current_pr.pr_url).unwrap();
-    sync_authored_pr_threads(&db, std::slice::from_ref(&current_pr)).unwrap();
+    sync_authored_pr_threads(&db, ThreadForge::GitHub, std::slice::from_ref(&current_pr)).unwrap();
 
     let threads = db.list_dashboard_threads().unwrap();
     assert!(threads.is_empty());
 }
 
+#[test]
+fn sync_authored_pr_threads_namespaces_other_forges() {
+    let db = test_db();
+    let authored = |pr_url: &str| github::AuthoredPrSummary {
+        pr_url: pr_url.to_string(),
+        repository: "o/r".to_string(),
+        title: pr_url.to_string(),
+        updated_at: "2026-01-02T00:00:00Z".to_string(),
+        is_open: true,
+        is_draft: false,
+    };
+    let github_pr = authored("https://github.com/o/r/pull/2");
+    let forgejo_pr = authored("https://codeberg.org/o/r/pulls/2");
+
+    sync_authored_pr_threads(&db, ThreadForge::GitHub, std::slice::from_ref(&github_pr)).unwrap();
+    sync_authored_pr_threads(&db, ThreadForge::Forgejo, std::slice::from_ref(&forgejo_pr)).unwrap();
+    sync_authored_pr_threads(
+        &db,
+        ThreadForge::Forgejo,
+        &[github::AuthoredPrSummary {
+            is_open: false,
+            ..github_pr.clone()
+        }],
+    )
+    .unwrap();
+
+    let mut threads = db.list_dashboard_threads().unwrap();
+    threads.sort_by(|a, b| a.thread_key.cmp(&b.thread_key));
+    assert_eq!(threads.len(), 2);
+    assert_eq!(
+        threads[0].thread_key,
+        format!("forgejo:mypr:{}", forgejo_pr.pr_url)
+    );
+    assert_eq!(threads[0].sources, vec!["forgejo:my_pr"]);
+    assert_eq!(threads[1].thread_key, format!("mypr:{}", github_pr.pr_url));
+    assert_eq!(threads[1].sources, vec!["my_pr"]);
+}
+
 #[test]
 fn sync_assigned_pr_threads_removes_stale_entries() {
     let db = test_db();
@@ -349,7 +402,7 @@ fn sync_assigned_pr_threads_removes_stale_entries() {
         is_draft: false,
     };
 
-    sync_assigned_pr_threads(&db, std::slice::from_ref(&current_pr)).unwrap();
+    sync_assigned_pr_threads(&db, GITHUB_URL, std::slice::from_ref(&current_pr)).unwrap();
 
     let threads = db.list_dashboard_threads().unwrap();
     assert_eq!(threads.len(), 1);
@@ -360,6 +413,40 @@ fn sync_assigned_pr_threads_removes_stale_entries() {
     assert_eq!(threads[0].subject_title, "current");
 }
 
+#[test]
+fn sync_assigned_pr_threads_only_deletes_threads_of_the_synced_host() {
+    let db = test_db();
+    let assigned = |pr_url: &str| github::AssignedPrSummary {
+        pr_url: pr_url.to_string(),
+        repository: "o/r".to_string(),
+        title: pr_url.to_string(),
+        updated_at: "2026-01-02T00:00:00Z".to_string(),
+        is_draft: false,
+    };
+    let enterprise_pr = assigned("https://github.example.com/o/r/pull/1");
+    sync_assigned_pr_threads(
+        &db,
+        "https://github.example.com/",
+        std::slice::from_ref(&enterprise_pr),
+    )
+    .unwrap();
+    sync_assigned_pr_threads(
+        &db,
+        GITHUB_URL,
+        &[assigned("https://github.com/o/r/pull/2")],
+    )
+    .unwrap();
+    // github.com no longer assigns anything: the other host is untouched.
+    sync_assigned_pr_threads(&db, GITHUB_URL, &[]).unwrap();
+
+    let threads = db.list_dashboard_threads().unwrap();
+    let pr_urls: Vec<_> = threads
+        .iter()
+        .filter_map(|thread| thread.pr_url.as_deref())
+        .collect();
+    assert_eq!(pr_urls, [enterprise_pr.pr_url.as_str()]);
+}
+
 #[test]
 fn sync_assigned_pr_threads_preserves_done_entries() {
     let db = test_db();
@@ -371,14 +458,63 @@ fn sync_assigned_pr_threads_preserves_done_entries() {
         is_draft: false,
     };
 
-    sync_assigned_pr_threads(&db, std::slice::from_ref(&current_pr)).unwrap();
+    sync_assigned_pr_threads(&db, GITHUB_URL, std::slice::from_ref(&current_pr)).unwrap();
     assert!(db.mark_assigned_pr_done_local(&current_pr.pr_url).unwrap());
-    sync_assigned_pr_threads(&db, std::slice::from_ref(&current_pr)).unwrap();
+    sync_assigned_pr_threads(&db, GITHUB_URL, std::slice::from_ref(&current_pr)).unwrap();
 
     let threads = db.list_dashboard_threads().unwrap();
     assert!(threads.is_empty());
 }
 
+#[test]
+fn sync_review_requested_pr_threads_tells_team_requests_apart() {
+    let db = test_db();
+    let requested = |number: u32, team_request: bool| github::ReviewRequestedPrSummary {
+        pr_url: format!("https://github.com/o/r/pull/{number}"),
+        repository: "o/r".to_string(),
+        title: format!("pr {number}"),
+        updated_at: format!("2026-01-0{number}T00:00:00Z"),
+        is_draft: false,
+        team_request,
+    };
+
+    sync_review_requested_pr_threads(&db, GITHUB_URL, &[requested(1, true), requested(2, false)])
+        .unwrap();
+    let sources: Vec<_> = db
+        .list_dashboard_threads()
+        .unwrap()
+        .into_iter()
+        .map(|thread| (thread.subject_title, thread.sources))
+        .collect();
+    assert_eq!(
+        sources,
+        [
+            ("pr 2".to_string(), vec!["review_requested".to_string()]),
+            (
+                "pr 1".to_string(),
+                vec!["team_review_requested".to_string()]
+            ),
+        ]
+    );
+
+    // Marked done, the PR stays done when the request moves to you, and
+    // the PRs no longer waiting for a review leave the dashboard.
+    assert!(
+        db.mark_review_requested_done_local("https://github.com/o/r/pull/1")
+            .unwrap()
+    );
+    sync_review_requested_pr_threads(&db, GITHUB_URL, &[requested(1, false)]).unwrap();
+    assert!(db.list_dashboard_threads().unwrap().is_empty());
+    let filters = db::DashboardThreadFilters {
+        show_done: true,
+        ..db::DashboardThreadFilters::default()
+    };
+    let threads = db.list_dashboard_threads_with_filters(&filters).unwrap();
+    assert_eq!(threads.len(), 1);
+    assert_eq!(threads[0].sources, ["review_requested"]);
+    assert!(threads[0].done);
+}
+
 #[test]
 fn sync_assigned_issue_threads_removes_stale_entries() {
     let db = test_db();
@@ -413,7 +549,7 @@ fn sync_assigned_issue_threads_removes_stale_entries() {
         is_complete: true,
     };
 
-    sync_assigned_issue_threads(&db, &current_issue).unwrap();
+    sync_assigned_issue_threads(&db, ThreadForge::GitHub, GITHUB_URL, &current_issue).unwrap();
 
     let threads = db.list_dashboard_threads().unwrap();
     assert_eq!(threads.len(), 1);
@@ -438,33 +574,38 @@ fn sync_assigned_issue_threads_preserves_done_entries() {
         is_complete: true,
     };
 
-    sync_assigned_issue_threads(&db, &current_issue).unwrap();
+    sync_assigned_issue_threads(&db, ThreadForge::GitHub, GITHUB_URL, &current_issue).unwrap();
     assert!(
         db.mark_assigned_issue_done_local(&current_issue.issues[0].issue_url)
             .unwrap()
     );
-    sync_assigned_issue_threads(&db, &current_issue).unwrap();
+    sync_assigned_issue_threads(&db, ThreadForge::GitHub, GITHUB_URL, &current_issue).unwrap();
 
     let threads = db.list_dashboard_threads().unwrap();
     assert!(threads.is_empty());
 }
 
-#[test]
-fn mark_done_rejects_missing_assigned_issue_row() {
-    let db = test_db();
+fn test_state() -> AppState {
     let work_dir = camino::Utf8PathBuf::from_path_buf(std::env::temp_dir()).unwrap();
     let (dashboard_updates, _) = tokio::sync::watch::channel(DashboardUpdate {
         version: 0,
         message: String::new(),
     });
-    let state = AppState {
-        db,
+    AppState {
+        db: test_db(),
         config: config::AppConfig::default(),
         work_dir,
         poll_lock: std::sync::Arc::new(tokio::sync::Mutex::new(())),
         dashboard_refresh_in_flight: std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
         dashboard_updates,
-    };
+        jobs_wakeup: std::sync::Arc::new(tokio::sync::Notify::new()),
+        running_jobs: std::sync::Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+    }
+}
+
+#[test]
+fn mark_done_rejects_missing_assigned_issue_row() {
+    let state = test_state();
 
     let err = tokio::runtime::Runtime::new()
         .unwrap()
@@ -475,6 +616,8 @@ fn mark_done_rejects_missing_assigned_issue_row() {
             mark_authored_pr: false,
             mark_assigned_pr: false,
             mark_assigned_issue: true,
+            mark_review_requested: false,
+            mark_mention: false,
         }))
         .unwrap_err();
 
@@ -515,7 +658,7 @@ fn sync_assigned_issue_threads_skips_stale_deletes_for_incomplete_results() {
         is_complete: false,
     };
 
-    sync_assigned_issue_threads(&db, &current_issue).unwrap();
+    sync_assigned_issue_threads(&db, ThreadForge::GitHub, GITHUB_URL, &current_issue).unwrap();
 
     let threads = db.list_dashboard_threads().unwrap();
     assert_eq!(threads.len(), 2);
@@ -562,3 +705,285 @@ fn next_incremental_cursor_bootstraps_from_now_when_cursor_is_missing() {
 
     assert_eq!(next, "2026-01-10T10:04:00Z");
 }
+
+#[test]
+fn retry_delay_doubles_after_each_failed_attempt() {
+    assert_eq!(retry_delay_seconds(60, 1), 60);
+    assert_eq!(retry_delay_seconds(60, 2), 120);
+    assert_eq!(retry_delay_seconds(60, 3), 240);
+    assert_eq!(retry_delay_seconds(u64::MAX, 5), u64::MAX);
+}
+
+#[tokio::test]
+async fn cancelled_jobs_keep_their_repository_busy_until_they_stop() {
+    let state = test_state();
+    let fix = state
+        .enqueue_job(db::JobKind::Fix, "https://github.com/o/r/pull/1")
+        .unwrap();
+    let review = state
+        .enqueue_job(db::JobKind::Review, "https://github.com/o/r/pull/2")
+        .unwrap();
+
+    let job = state.db.claim_next_job(time::unix_ts()).unwrap().unwrap();
+    assert_eq!((job.id, job.max_attempts), (fix, 1));
+    let task = tokio::spawn(std::future::pending::<anyhow::Result<()>>());
+    state
+        .running_jobs
+        .lock()
+        .await
+        .insert(job.id, task.abort_handle());
+
+    state.cancel_job(fix).await.unwrap();
+    assert!(state.db.claim_next_job(time::unix_ts()).unwrap().is_none());
+
+    state.finish_job(job, task).await;
+    let next = state.db.claim_next_job(time::unix_ts()).unwrap().unwrap();
+    assert_eq!(next.id, review);
+    let fix_status = state
+        .db
+        .list_jobs(10)
+        .unwrap()
+        .into_iter()
+        .find(|job| job.id == fix)
+        .unwrap()
+        .status;
+    assert_eq!(fix_status, db::JobStatus::Cancelled);
+}
+
+#[test]
+fn poll_delay_stretches_as_the_quota_runs_low() {
+    let limit = |remaining, reset| github::RateLimit {
+        limit: 5000,
+        remaining,
+        reset,
+    };
+    let now = 1_000;
+    assert_eq!(poll_delay_seconds(60, [], now), 60);
+    assert_eq!(poll_delay_seconds(60, [limit(4000, now + 3600)], now), 60);
+    assert_eq!(poll_delay_seconds(60, [limit(1000, now + 3600)], now), 120);
+    assert_eq!(
+        poll_delay_seconds(60, [limit(4000, now + 3600), limit(100, now + 3600)], now),
+        240
+    );
+    // Never later than the reset, nor sooner than the configured period.
+    assert_eq!(poll_delay_seconds(60, [limit(100, now + 90)], now), 90);
+    assert_eq!(poll_delay_seconds(60, [limit(0, now + 1800)], now), 1800);
+    assert_eq!(poll_delay_seconds(60, [limit(0, now - 5)], now), 60);
+}
+
+#[test]
+fn webhook_signatures_are_checked_against_the_secret() {
+    let body = br#"{"zen":"Keep it logically awesome."}"#;
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, b"s3cret");
+    let tag: String = ring::hmac::sign(&key, body)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    let signature = format!("sha256={tag}");
+
+    assert!(verify_webhook_signature("s3cret", body, Some(&signature)));
+    assert!(!verify_webhook_signature("other", body, Some(&signature)));
+    assert!(!verify_webhook_signature("s3cret", b"{}", Some(&signature)));
+    assert!(!verify_webhook_signature("s3cret", body, Some(&tag)));
+    assert!(!verify_webhook_signature("s3cret", body, Some("sha256=zz")));
+    assert!(!verify_webhook_signature("s3cret", body, None));
+}
+
+fn pr_thread(thread_key: &str, source: &str, pr_url: &str) -> db::NewThread {
+    db::NewThread {
+        thread_key: thread_key.to_string(),
+        github_thread_id: None,
+        source: source.to_string(),
+        repository: "o/r".to_string(),
+        subject_type: Some("PullRequest".to_string()),
+        subject_title: "old title".to_string(),
+        subject_url: Some(pr_url.to_string()),
+        issue_state: None,
+        discussion_answered: None,
+        reason: None,
+        pr_url: Some(pr_url.to_string()),
+        unread: false,
+        done: false,
+        updated_at: "2026-01-01T00:00:00Z".to_string(),
+        is_draft: true,
+    }
+}
+
+#[tokio::test]
+async fn pull_request_webhooks_update_the_pr_and_its_threads() {
+    let db = test_db();
+    let pr_url = "https://github.com/o/r/pull/7";
+    db.upsert_thread(&pr_thread("notif:1", "notification", pr_url))
+        .unwrap();
+    db.upsert_thread(&pr_thread(&format!("mypr:{pr_url}"), "my_pr", pr_url))
+        .unwrap();
+    let payload = serde_json::json!({
+        "action": "closed",
+        "repository": { "full_name": "o/r", "archived": false },
+        "pull_request": {
+            "html_url": pr_url,
+            "number": 7,
+            "state": "closed",
+            "merged": true,
+            "merged_at": "2026-01-02T00:00:00Z",
+            "draft": false,
+            "title": "new title",
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-02T00:00:00Z",
+            "user": { "login": "me" },
+            "head": {
+                "ref": "feature",
+                "sha": "abc123",
+                "repo": { "name": "r", "full_name": "me/r", "owner": { "login": "me" } }
+            },
+            "base": { "ref": "main", "repo": { "full_name": "o/r" } }
+        }
+    });
+
+    let message = apply_github_event(&db, "pull_request", &payload)
+        .await
+        .unwrap();
+
+    assert_eq!(message.as_deref(), Some("Webhook pull_request: o/r#7"));
+    let pr = db.get_pr(pr_url).unwrap().unwrap();
+    assert_eq!(
+        (pr.state.as_str(), pr.head_sha.as_str(), pr.title.as_str()),
+        ("MERGED", "abc123", "new title")
+    );
+    // The merged PR left "My PRs"; the notification shows the new title.
+    let threads = db.threads_for_url(pr_url).unwrap();
+    assert_eq!(threads.len(), 1);
+    assert_eq!(threads[0].thread_key, "notif:1");
+    assert_eq!(threads[0].subject_title, "new title");
+    assert_eq!(threads[0].updated_at, "2026-01-02T00:00:00Z");
+    assert!(!threads[0].is_draft);
+}
+
+#[tokio::test]
+async fn issue_webhooks_update_the_issue_threads() {
+    let db = test_db();
+    let issue_url = "https://github.com/o/r/issues/3";
+    db.upsert_thread(&db::NewThread {
+        subject_type: Some("Issue".to_string()),
+        issue_state: Some("OPEN".to_string()),
+        pr_url: None,
+        is_draft: false,
+        ..pr_thread(&format!("myissue:{issue_url}"), "my_issue", issue_url)
+    })
+    .unwrap();
+    let payload = serde_json::json!({
+        "action": "closed",
+        "repository": { "full_name": "o/r" },
+        "issue": {
+            "html_url": issue_url,
+            "number": 3,
+            "title": "fixed",
+            "state": "closed",
+            "updated_at": "2026-01-03T00:00:00Z"
+        }
+    });
+
+    let message = apply_github_event(&db, "issues", &payload).await.unwrap();
+
+    assert_eq!(message.as_deref(), Some("Webhook issues: o/r#3"));
+    let threads = db.threads_for_url(issue_url).unwrap();
+    assert_eq!(threads[0].issue_state.as_deref(), Some("CLOSED"));
+    assert_eq!(threads[0].subject_title, "fixed");
+    assert_eq!(
+        apply_github_event(&db, "star", &payload).await.unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn dequeued_prs_need_attention_until_queued_again() {
+    let db = test_db();
+    let pr_url = "https://github.com/o/r/pull/8";
+    db.upsert_thread(&pr_thread(&format!("mypr:{pr_url}"), "my_pr", pr_url))
+        .unwrap();
+    let event = |action: &str| {
+        serde_json::json!({
+            "action": action,
+            "repository": { "full_name": "o/r", "archived": false },
+            "pull_request": {
+                "html_url": pr_url,
+                "number": 8,
+                "state": "open",
+                "draft": false,
+                "title": "Ship it",
+                "created_at": "2026-01-01T00:00:00Z",
+                "updated_at": "2026-01-02T00:00:00Z",
+                "user": { "login": "me" },
+                "auto_merge": null,
+                "head": {
+                    "ref": "feature",
+                    "sha": "abc123",
+                    "repo": { "name": "r", "full_name": "o/r", "owner": { "login": "o" } }
+                },
+                "base": { "ref": "main", "repo": { "full_name": "o/r" } }
+            }
+        })
+    };
+    let attention = || db.get_pr(pr_url).unwrap().unwrap().attention;
+
+    apply_github_event(&db, "pull_request", &event("enqueued"))
+        .await
+        .unwrap();
+    assert_eq!(attention(), None);
+
+    apply_github_event(&db, "pull_request", &event("dequeued"))
+        .await
+        .unwrap();
+    assert_eq!(attention().as_deref(), Some("Removed from the merge queue"));
+
+    // Other events keep the alert until the PR is queued again.
+    apply_github_event(&db, "pull_request", &event("edited"))
+        .await
+        .unwrap();
+    assert_eq!(attention().as_deref(), Some("Removed from the merge queue"));
+    apply_github_event(&db, "pull_request", &event("enqueued"))
+        .await
+        .unwrap();
+    assert_eq!(attention(), None);
+}
+
+#[test]
+fn disabling_auto_merge_raises_an_alert() {
+    let details = |auto_merge| github::PrDetails {
+        pr_url: "u".to_string(),
+        owner: "o".to_string(),
+        repo: "r".to_string(),
+        number: 1,
+        state: "OPEN".to_string(),
+        merge_queue_state: None,
+        auto_merge,
+        title: "t".to_string(),
+        head_ref: "feat".to_string(),
+        base_ref: "main".to_string(),
+        head_sha: "sha".to_string(),
+        created_at: "2026-01-01T00:00:00Z".to_string(),
+        updated_at: "2026-01-01T00:00:00Z".to_string(),
+        is_archived: false,
+        author_login: None,
+        head_repo_owner: None,
+        head_repo_name: None,
+        is_cross_repository: false,
+        is_draft: false,
+    };
+    let db = test_db();
+    upsert_pr_from_details(&db, &details(true)).unwrap();
+    let stored = db.get_pr("u").unwrap();
+
+    assert_eq!(
+        merge_queue_alert(stored.as_ref(), &details(false)),
+        Some("Auto-merge disabled")
+    );
+    assert_eq!(merge_queue_alert(stored.as_ref(), &details(true)), None);
+    assert_eq!(merge_queue_alert(None, &details(false)), None);
+    let closed = github::PrDetails {
+        state: "CLOSED".to_string(),
+        ..details(false)
+    };
+    assert_eq!(merge_queue_alert(stored.as_ref(), &closed), None);
+}
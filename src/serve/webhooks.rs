@@ -0,0 +1,205 @@
+use ring::hmac;
+use serde_json::Value;
+
+use crate::{checkout::parse_github_pr_url, config::WebhooksConfig, db::Db, github};
+
+use super::poll::{
+    print_pr_db_write, print_thread_db_write, upsert_pr_from_details, watch_merge_queue,
+};
+
+/// Env var holding the webhook secret when `webhooks.secret_env` is unset.
+const DEFAULT_SECRET_ENV: &str = "GIGI_WEBHOOK_SECRET";
+
+/// Secret the webhooks are signed with, `None` while it isn't set.
+pub fn webhook_secret(config: &WebhooksConfig) -> Option<String> {
+    let env = config.secret_env.as_deref().unwrap_or(DEFAULT_SECRET_ENV);
+    std::env::var(env)
+        .ok()
+        .filter(|secret| !secret.trim().is_empty())
+}
+
+/// Check `X-Hub-Signature-256`, the `sha256=` HMAC of the body.
+pub fn verify_webhook_signature(secret: &str, body: &[u8], signature: Option<&str>) -> bool {
+    let Some(tag) = signature
+        .and_then(|signature| signature.strip_prefix("sha256="))
+        .and_then(decode_hex)
+    else {
+        return false;
+    };
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hmac::verify(&key, body, &tag).is_ok()
+}
+
+/// Apply a GitHub event to the DB. Returns what changed, for the dashboard,
+/// or `None` for the events gigi doesn't follow.
+pub(super) async fn apply_github_event(
+    db: &Db,
+    event: &str,
+    payload: &Value,
+) -> anyhow::Result<Option<String>> {
+    let repository = payload["repository"]["full_name"]
+        .as_str()
+        .unwrap_or("<unknown>");
+    match event {
+        "ping" => Ok(Some("Webhook connected".to_string())),
+        "pull_request" | "pull_request_review" => {
+            let pull_request = &payload["pull_request"];
+            let mut details = pr_details_from_payload(db, pull_request, &payload["repository"])?;
+            match payload["action"].as_str() {
+                Some("enqueued") if details.state == "OPEN" => {
+                    details.merge_queue_state = Some("QUEUED".to_string());
+                }
+                Some("dequeued") => details.merge_queue_state = None,
+                _ => {}
+            }
+            apply_pr_details(db, &details)?;
+            Ok(Some(format!(
+                "Webhook {event}: {repository}#{}",
+                details.number
+            )))
+        }
+        "issue_comment" | "issues" => {
+            let issue = &payload["issue"];
+            let number = issue["number"].as_i64().unwrap_or_default();
+            if let Some(pr_url) = issue["pull_request"]["html_url"].as_str() {
+                fetch_and_apply_pr(db, pr_url).await?;
+            } else {
+                apply_issue(db, issue)?;
+            }
+            Ok(Some(format!("Webhook {event}: {repository}#{number}")))
+        }
+        "check_suite" => {
+            let repository_url = payload["repository"]["html_url"].as_str().unwrap_or("");
+            let numbers: Vec<_> = payload["check_suite"]["pull_requests"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|pr| pr["number"].as_i64())
+                .collect();
+            for number in &numbers {
+                fetch_and_apply_pr(db, &format!("{repository_url}/pull/{number}")).await?;
+            }
+            Ok((!numbers.is_empty()).then(|| format!("Webhook {event}: {repository}")))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// For the events without the PR, or without its checks.
+async fn fetch_and_apply_pr(db: &Db, pr_url: &str) -> anyhow::Result<()> {
+    let pr_url = parse_github_pr_url(pr_url)?.url();
+    let batch = github::fetch_batch(std::slice::from_ref(&pr_url), &[], &[]).await?;
+    let Some(details) = batch.pr_details.get(&pr_url) else {
+        anyhow::bail!("❌ Failed to fetch PR details for {pr_url}");
+    };
+    apply_pr_details(db, details)?;
+    if let Some(checks) = batch.checks.get(&pr_url) {
+        db.replace_pr_checks(&pr_url, checks)?;
+    }
+    Ok(())
+}
+
+/// Store the PR and refresh its threads. Closed PRs leave "My PRs", as in
+/// the polls.
+fn apply_pr_details(db: &Db, details: &github::PrDetails) -> anyhow::Result<()> {
+    let stored = db.get_pr(&details.pr_url)?;
+    upsert_pr_from_details(db, details)?;
+    print_pr_db_write(stored.as_ref(), details);
+    watch_merge_queue(db, stored.as_ref(), details)?;
+
+    if details.state != "OPEN" {
+        println!(
+            "🗄️ DB delete threads: source=my_pr closed_pr_urls={}",
+            details.pr_url
+        );
+        db.delete_threads_by_source_and_pr_urls("my_pr", std::slice::from_ref(&details.pr_url))?;
+    }
+    for mut row in db.threads_for_url(&details.pr_url)? {
+        row.subject_title.clone_from(&details.title);
+        row.updated_at.clone_from(&details.updated_at);
+        row.is_draft = details.is_draft;
+        db.upsert_thread(&row)?;
+        print_thread_db_write(&row);
+    }
+    Ok(())
+}
+
+fn apply_issue(db: &Db, issue: &Value) -> anyhow::Result<()> {
+    let Some(issue_url) = issue["html_url"].as_str() else {
+        anyhow::bail!("❌ Webhook issue without html_url");
+    };
+    for mut row in db.threads_for_url(issue_url)? {
+        if let Some(title) = issue["title"].as_str() {
+            row.subject_title = title.to_string();
+        }
+        if let Some(updated_at) = issue["updated_at"].as_str() {
+            row.updated_at = updated_at.to_string();
+        }
+        row.issue_state = issue["state"].as_str().map(str::to_ascii_uppercase);
+        db.upsert_thread(&row)?;
+        print_thread_db_write(&row);
+    }
+    Ok(())
+}
+
+/// Map the `pull_request` of a webhook payload. The payload has no merge
+/// queue state: the stored one is kept while the PR is open.
+fn pr_details_from_payload(
+    db: &Db,
+    pull_request: &Value,
+    repository: &Value,
+) -> anyhow::Result<github::PrDetails> {
+    let text = |value: &Value| value.as_str().unwrap_or_default().to_string();
+    let Some(pr_url) = pull_request["html_url"].as_str() else {
+        anyhow::bail!("❌ Webhook pull_request without html_url");
+    };
+    let parsed = parse_github_pr_url(pr_url)?;
+    let state = if pull_request["merged"].as_bool() == Some(true)
+        || pull_request["merged_at"].is_string()
+    {
+        "MERGED"
+    } else if pull_request["state"] == "closed" {
+        "CLOSED"
+    } else {
+        "OPEN"
+    };
+    let merge_queue_state = if state == "OPEN" {
+        db.get_pr(pr_url)?.and_then(|pr| pr.merge_queue_state)
+    } else {
+        None
+    };
+    let head_repo = &pull_request["head"]["repo"];
+
+    Ok(github::PrDetails {
+        pr_url: parsed.url(),
+        owner: parsed.owner,
+        repo: parsed.repo,
+        number: i64::try_from(parsed.number)?,
+        state: state.to_string(),
+        merge_queue_state,
+        auto_merge: pull_request["auto_merge"].is_object(),
+        title: text(&pull_request["title"]),
+        head_ref: text(&pull_request["head"]["ref"]),
+        base_ref: text(&pull_request["base"]["ref"]),
+        head_sha: text(&pull_request["head"]["sha"]),
+        created_at: text(&pull_request["created_at"]),
+        updated_at: text(&pull_request["updated_at"]),
+        is_archived: repository["archived"].as_bool().unwrap_or(false),
+        author_login: pull_request["user"]["login"].as_str().map(str::to_string),
+        head_repo_owner: head_repo["owner"]["login"].as_str().map(str::to_string),
+        head_repo_name: head_repo["name"].as_str().map(str::to_string),
+        // The head repository is null once a fork is deleted.
+        is_cross_repository: head_repo["full_name"] != pull_request["base"]["repo"]["full_name"],
+        is_draft: pull_request["draft"].as_bool().unwrap_or(false),
+    })
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(hex.get(idx..idx + 2)?, 16).ok())
+        .collect()
+}
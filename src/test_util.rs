@@ -0,0 +1,17 @@
+use axum::Router;
+
+/// Serve `router` on a free local port and return its base URL.
+pub async fn spawn_stub(router: Router) -> String {
+    spawn_stub_with(|_| router).await
+}
+
+/// Like [`spawn_stub`], for routers whose answers link back to the stub.
+pub async fn spawn_stub_with(router: impl FnOnce(&str) -> Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let router = router(&base_url);
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    base_url
+}
@@ -2,7 +2,7 @@ use std::{collections::HashMap, convert::Infallible};
 
 use axum::{
     Form, Router,
-    extract::{Path as AxumPath, State},
+    extract::{Path as AxumPath, Query, State},
     http::{HeaderMap, HeaderValue, StatusCode, header},
     response::{Html, IntoResponse, Response, Sse, sse::Event, sse::KeepAlive},
     routing::{get, post},
@@ -13,14 +13,18 @@ use tokio_stream::{StreamExt as _, wrappers::WatchStream};
 use crate::{
     config::AppConfig,
     dashboard::{self, DashboardSnapshot},
-    db::DashboardThreadFilters,
-    serve::AppState,
+    db::{DashboardThreadFilters, JobKind},
+    github::{self, CheckOutcome},
+    review::{PublishMode, Severity},
+    serve::{self, AppState},
 };
 
 pub async fn run_server(state: std::sync::Arc<AppState>, config: &AppConfig) -> anyhow::Result<()> {
     let app = Router::new()
         .route("/", get(dashboard_page))
         .route("/dashboard/fragment", get(dashboard_fragment))
+        .route("/search", get(search_page))
+        .route("/prs/{owner}/{repo}/{number}", get(pr_page))
         .route("/dashboard/events", get(dashboard_events))
         .route("/dashboard/actions/filters", post(update_dashboard_filters))
         .route("/dashboard/actions/repo-filter", post(update_repo_filter))
@@ -41,6 +45,13 @@ pub async fn run_server(state: std::sync::Arc<AppState>, config: &AppConfig) ->
             "/dashboard/actions/prs/{owner}/{repo}/{number}/fix",
             post(run_fix),
         )
+        .route(
+            "/dashboard/actions/prs/{owner}/{repo}/{number}/publish",
+            post(publish_review),
+        )
+        .route("/dashboard/actions/jobs/{id}/cancel", post(cancel_job))
+        .route("/dashboard/actions/jobs/{id}/retry", post(retry_job))
+        .route("/webhooks/github", post(github_webhook))
         .route("/styles.css", get(stylesheet))
         .route("/app.js", get(script))
         .with_state(state);
@@ -75,6 +86,82 @@ async fn dashboard_fragment(
     Ok(Html(dashboard::render_fragment(snapshot)))
 }
 
+/// Hits listed by the search page.
+const SEARCH_LIMIT: usize = 50;
+
+#[derive(Debug, Deserialize)]
+struct SearchQuery {
+    #[serde(default)]
+    q: String,
+}
+
+async fn search_page(
+    State(state): State<std::sync::Arc<AppState>>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Html<String>, ApiErrorResponse> {
+    let hits = state
+        .db
+        .search(&query.q, SEARCH_LIMIT)
+        .map_err(|err| ApiErrorResponse::internal(&err))?;
+    Ok(Html(dashboard::render_search_page(query.q.trim(), hits)))
+}
+
+/// `?from=&to=` of the PR page: the ids of the reviews to diff.
+#[derive(Debug, Deserialize)]
+struct CompareQuery {
+    from: Option<i64>,
+    to: Option<i64>,
+}
+
+async fn pr_page(
+    State(state): State<std::sync::Arc<AppState>>,
+    AxumPath((owner, repo, number)): AxumPath<(String, String, i64)>,
+    Query(host_query): Query<HostQuery>,
+    Query(compare): Query<CompareQuery>,
+) -> Result<Html<String>, ApiErrorResponse> {
+    let pr_url = host_query.pr_url(&owner, &repo, number)?;
+    let title = state
+        .db
+        .get_pr(&pr_url)
+        .map_err(|err| ApiErrorResponse::internal(&err))?
+        .map(|pr| pr.title);
+    let history = state
+        .db
+        .pr_history(&pr_url)
+        .map_err(|err| ApiErrorResponse::internal(&err))?;
+    let compare = match (compare.from, compare.to) {
+        (Some(from), Some(to)) => {
+            if let Some(id) = [from, to]
+                .into_iter()
+                .find(|id| !history.reviews.iter().any(|review| review.id == *id))
+            {
+                return Err(ApiErrorResponse(
+                    StatusCode::BAD_REQUEST,
+                    format!("Review {id} is not a review of {pr_url}"),
+                ));
+            }
+            Some((from, to))
+        }
+        (None, None) => match history.reviews.as_slice() {
+            [.., previous, latest] => Some((previous.id, latest.id)),
+            _ => None,
+        },
+        _ => {
+            return Err(ApiErrorResponse(
+                StatusCode::BAD_REQUEST,
+                "Select two reviews to compare".to_string(),
+            ));
+        }
+    };
+    Ok(Html(dashboard::render_pr_page(dashboard::PrPage {
+        pr_url,
+        title,
+        host: host_query.host,
+        history,
+        compare,
+    })))
+}
+
 async fn dashboard_events(
     State(state): State<std::sync::Arc<AppState>>,
 ) -> Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>>> {
@@ -168,6 +255,8 @@ async fn mark_done(
             mark_authored_pr: form.mark_authored_pr,
             mark_assigned_pr: form.mark_assigned_pr,
             mark_assigned_issue: form.mark_assigned_issue,
+            mark_review_requested: form.mark_review_requested,
+            mark_mention: form.mark_mention,
         })
         .await
         .map_err(|err| ApiErrorResponse::internal(&err))?;
@@ -188,20 +277,60 @@ async fn mark_read(
 async fn run_fix(
     State(state): State<std::sync::Arc<AppState>>,
     AxumPath((owner, repo, number)): AxumPath<(String, String, i64)>,
+    Query(query): Query<HostQuery>,
 ) -> Result<StatusCode, ApiErrorResponse> {
+    let pr_url = query.pr_url(&owner, &repo, number)?;
     state
-        .run_fix(owner, repo, number)
+        .enqueue_job(JobKind::Fix, &pr_url)
+        .map_err(|err| ApiErrorResponse::internal(&err))?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+async fn run_review(
+    State(state): State<std::sync::Arc<AppState>>,
+    AxumPath((owner, repo, number)): AxumPath<(String, String, i64)>,
+    Query(query): Query<HostQuery>,
+) -> Result<StatusCode, ApiErrorResponse> {
+    let pr_url = query.pr_url(&owner, &repo, number)?;
+    state
+        .enqueue_job(JobKind::Review, &pr_url)
+        .map_err(|err| ApiErrorResponse::internal(&err))?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+async fn cancel_job(
+    State(state): State<std::sync::Arc<AppState>>,
+    AxumPath(id): AxumPath<i64>,
+) -> Result<StatusCode, ApiErrorResponse> {
+    state
+        .cancel_job(id)
         .await
         .map_err(|err| ApiErrorResponse::internal(&err))?;
     Ok(StatusCode::OK)
 }
 
-async fn run_review(
+async fn retry_job(
+    State(state): State<std::sync::Arc<AppState>>,
+    AxumPath(id): AxumPath<i64>,
+) -> Result<StatusCode, ApiErrorResponse> {
+    state
+        .retry_job(id)
+        .map_err(|err| ApiErrorResponse::internal(&err))?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+async fn publish_review(
     State(state): State<std::sync::Arc<AppState>>,
     AxumPath((owner, repo, number)): AxumPath<(String, String, i64)>,
+    Query(query): Query<HostQuery>,
+    Form(form): Form<PublishReviewForm>,
 ) -> Result<StatusCode, ApiErrorResponse> {
+    let pr_url = query.pr_url(&owner, &repo, number)?;
+    let review_id = form.review_id.parse().map_err(|err| {
+        ApiErrorResponse(StatusCode::BAD_REQUEST, format!("Invalid review id: {err}"))
+    })?;
     state
-        .run_review(owner, repo, number)
+        .publish_review(pr_url, review_id, &form.finding_ids(), form.mode())
         .await
         .map_err(|err| ApiErrorResponse::internal(&err))?;
     Ok(StatusCode::OK)
@@ -219,7 +348,7 @@ async fn open_vscode(
     Form(request): Form<OpenProjectRequest>,
 ) -> Result<StatusCode, ApiErrorResponse> {
     state
-        .open_in_vscode(request.repository, request.pr_url)
+        .open_in_vscode(request.repository, request.pr_url, request.host)
         .await
         .map_err(|err| ApiErrorResponse::internal(&err))?;
     Ok(StatusCode::OK)
@@ -230,12 +359,49 @@ async fn open_terminal(
     Form(request): Form<OpenProjectRequest>,
 ) -> Result<StatusCode, ApiErrorResponse> {
     state
-        .open_in_terminal(request.repository, request.pr_url)
+        .open_in_terminal(request.repository, request.pr_url, request.host)
         .await
         .map_err(|err| ApiErrorResponse::internal(&err))?;
     Ok(StatusCode::OK)
 }
 
+async fn github_webhook(
+    State(state): State<std::sync::Arc<AppState>>,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+) -> Result<StatusCode, ApiErrorResponse> {
+    let Some(secret) = serve::webhook_secret(&state.config.webhooks) else {
+        return Err(ApiErrorResponse(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Webhook secret is not set".to_string(),
+        ));
+    };
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    if !serve::verify_webhook_signature(&secret, &body, header("x-hub-signature-256")) {
+        return Err(ApiErrorResponse(
+            StatusCode::UNAUTHORIZED,
+            "Invalid webhook signature".to_string(),
+        ));
+    }
+    let Some(event) = header("x-github-event") else {
+        return Err(ApiErrorResponse(
+            StatusCode::BAD_REQUEST,
+            "Missing X-GitHub-Event header".to_string(),
+        ));
+    };
+    let payload: serde_json::Value = serde_json::from_slice(&body).map_err(|err| {
+        ApiErrorResponse(
+            StatusCode::BAD_REQUEST,
+            format!("Invalid webhook payload: {err}"),
+        )
+    })?;
+    state
+        .handle_github_webhook(event, &payload)
+        .await
+        .map_err(|err| ApiErrorResponse::internal(&err))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 async fn stylesheet() -> impl IntoResponse {
     let headers = static_asset_headers("text/css; charset=utf-8");
     (headers, include_str!("../assets/dashboard/styles.css"))
@@ -256,6 +422,9 @@ fn static_asset_headers(content_type: &'static str) -> HeaderMap {
     headers
 }
 
+/// Jobs listed in the dashboard, active ones first.
+const DASHBOARD_JOBS_LIMIT: usize = 20;
+
 fn load_snapshot(state: &AppState) -> anyhow::Result<DashboardSnapshot> {
     let filters = state.db.dashboard_thread_filters()?;
     let available_repositories = state.db.list_all_repositories()?;
@@ -265,12 +434,22 @@ fn load_snapshot(state: &AppState) -> anyhow::Result<DashboardSnapshot> {
         if let Some(key) = participant_key {
             thread.participants = state.db.get_pr_participants(key).unwrap_or_default();
         }
+        if let Some(review_id) = thread.latest_review_id {
+            thread.findings = state.db.review_findings(review_id).unwrap_or_default();
+        }
+        if thread.ci_status == Some(CheckOutcome::Fail)
+            && let Some(pr_url) = thread.pr_url.as_deref()
+        {
+            thread.failing_checks = state.db.failing_pr_checks(pr_url).unwrap_or_default();
+        }
     }
     Ok(DashboardSnapshot {
         filters,
         threads,
+        jobs: state.db.list_jobs(DASHBOARD_JOBS_LIMIT)?,
         available_repositories,
         status_message: state.dashboard_status_message(),
+        api_quota: github::GitHubClient::shared_quota(),
     })
 }
 
@@ -278,6 +457,30 @@ fn load_snapshot(state: &AppState) -> anyhow::Result<DashboardSnapshot> {
 struct OpenProjectRequest {
     repository: String,
     pr_url: Option<String>,
+    /// GitHub Enterprise Server host of the repository, github.com if unset.
+    host: Option<String>,
+}
+
+/// `?host=` of the PR actions, set for PRs of GitHub Enterprise Server hosts.
+#[derive(Debug, Deserialize)]
+struct HostQuery {
+    host: Option<String>,
+}
+
+impl HostQuery {
+    fn pr_url(&self, owner: &str, repo: &str, number: i64) -> Result<String, ApiErrorResponse> {
+        let hosts = github::hosts();
+        let host = match self.host.as_deref() {
+            Some(host) => hosts.find(host).ok_or_else(|| {
+                ApiErrorResponse(
+                    StatusCode::BAD_REQUEST,
+                    format!("Unknown GitHub host '{host}'"),
+                )
+            })?,
+            None => hosts.github_com(),
+        };
+        Ok(host.web_url(&format!("{owner}/{repo}/pull/{number}")))
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -291,6 +494,10 @@ struct MarkDoneForm {
     mark_assigned_pr: bool,
     #[serde(default)]
     mark_assigned_issue: bool,
+    #[serde(default)]
+    mark_review_requested: bool,
+    #[serde(default)]
+    mark_mention: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -304,9 +511,13 @@ struct DashboardFiltersForm {
     show_my_prs: Option<String>,
     show_assigned_prs: Option<String>,
     show_assigned_issues: Option<String>,
+    show_review_requested: Option<String>,
+    show_mentions: Option<String>,
     show_done: Option<String>,
     show_not_done: Option<String>,
     group_by_repository: Option<String>,
+    min_finding_severity: Option<String>,
+    failing_ci_only: Option<String>,
 }
 
 impl DashboardFiltersForm {
@@ -316,10 +527,17 @@ impl DashboardFiltersForm {
             show_my_prs: self.show_my_prs.is_some(),
             show_assigned_prs: self.show_assigned_prs.is_some(),
             show_assigned_issues: self.show_assigned_issues.is_some(),
+            show_review_requested: self.show_review_requested.is_some(),
+            show_mentions: self.show_mentions.is_some(),
             show_done: self.show_done.is_some(),
             show_not_done: self.show_not_done.is_some(),
             group_by_repository: self.group_by_repository.is_some(),
             hidden_repositories: Vec::new(),
+            min_finding_severity: self
+                .min_finding_severity
+                .as_deref()
+                .and_then(Severity::parse),
+            failing_ci_only: self.failing_ci_only.is_some(),
         }
     }
 }
@@ -340,6 +558,32 @@ impl RepoFilterForm {
     }
 }
 
+/// Findings are sent as `finding:<id>` checkboxes.
+#[derive(Debug, Deserialize)]
+struct PublishReviewForm {
+    review_id: String,
+    submit: Option<String>,
+    #[serde(flatten)]
+    fields: HashMap<String, String>,
+}
+
+impl PublishReviewForm {
+    fn finding_ids(&self) -> Vec<i64> {
+        self.fields
+            .keys()
+            .filter_map(|key| key.strip_prefix("finding:")?.parse().ok())
+            .collect()
+    }
+
+    fn mode(&self) -> PublishMode {
+        if self.submit.is_some() {
+            PublishMode::Submit
+        } else {
+            PublishMode::Pending
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct HideRepositoryForm {
     repository: String,
@@ -400,4 +644,20 @@ mod tests {
             vec!["e/f".to_string()]
         );
     }
+
+    #[test]
+    fn publish_review_form_reads_selected_findings() {
+        let form = PublishReviewForm {
+            review_id: "7".to_string(),
+            submit: None,
+            fields: HashMap::from([
+                ("finding:3".to_string(), "on".to_string()),
+                ("finding:x".to_string(), "on".to_string()),
+                ("other".to_string(), "1".to_string()),
+            ]),
+        };
+
+        assert_eq!(form.finding_ids(), vec![3]);
+        assert_eq!(form.mode(), PublishMode::Pending);
+    }
 }
@@ -2,17 +2,18 @@ use anyhow::Context;
 use camino::{Utf8Path, Utf8PathBuf};
 
 use crate::{
-    args,
+    ai::AiAgent,
     cmd::Cmd,
     commit::{check_commit_message, generate_commit_message, prompt_commit_message},
+    forge::{self, Forge},
 };
 
-use super::repo::{commit, default_branch, ensure_not_on_default_branch};
+use super::repo::{commit, ensure_not_on_default_branch};
 
 async fn resolve_commit_message(
     repo_root: &Utf8Path,
     message: Option<String>,
-    agent: Option<&args::Agent>,
+    agent: Option<&dyn AiAgent>,
     model: Option<&str>,
 ) -> anyhow::Result<String> {
     match message {
@@ -87,6 +88,7 @@ async fn stage_and_commit_changes(
 }
 
 async fn push_branch_and_open_pr(
+    forge: &dyn Forge,
     repo_root: &Utf8Path,
     branch_name: &str,
     commit_message: &str,
@@ -99,58 +101,32 @@ async fn push_branch_and_open_pr(
             "❌ Failed to push branch '{branch_name}' to origin"
         ))?;
 
-    let pr_exists = Cmd::new("gh", ["pr", "view", "--json", "number", "-q", ".number"])
-        .with_current_dir(repo_root)
-        .run()
-        .await?
-        .status()
-        .success();
-
-    if pr_exists {
-        Cmd::new("gh", ["pr", "view", "--web"])
-            .with_current_dir(repo_root)
-            .run()
-            .await?
-            .ensure_success("❌ Failed to open existing PR in browser")?;
-    } else {
-        Cmd::new(
-            "gh",
-            [
-                "pr",
-                "create",
-                "--title",
-                commit_message,
-                "--body",
-                "",
-                "--web",
-            ],
-        )
-        .with_current_dir(repo_root)
-        .run()
-        .await?
-        .ensure_success("❌ Failed to create PR in browser")?;
-    }
-
-    Ok(())
+    forge
+        .open_pr_in_browser(repo_root, branch_name, commit_message)
+        .await
 }
 
 pub async fn open_pr(
     repo_root: &Utf8Path,
     message: Option<String>,
-    agent: Option<&args::Agent>,
+    agent: Option<&dyn AiAgent>,
     model: Option<&str>,
 ) -> anyhow::Result<()> {
+    let forge = forge::forge_for_repo(repo_root).await?;
     let commit_message = resolve_commit_message(repo_root, message, agent, model).await?;
-    let default_branch_name = default_branch(repo_root).await?;
-    let branch_name =
-        branch_name_for_new_pr(repo_root, &branch_name_from_commit_message(&commit_message))
-            .await?;
+    let default_branch_name = forge.default_branch(repo_root).await?;
+    let branch_name = branch_name_for_new_pr(
+        forge.as_ref(),
+        repo_root,
+        &branch_name_from_commit_message(&commit_message),
+    )
+    .await?;
 
     ensure_branch_does_not_exist(repo_root, &branch_name).await?;
     create_feature_branch_from_default(repo_root, &default_branch_name, &branch_name).await?;
     stage_and_commit_changes(repo_root, &commit_message).await?;
     ensure_not_on_default_branch(repo_root, &default_branch_name).await?;
-    push_branch_and_open_pr(repo_root, &branch_name, &commit_message).await?;
+    push_branch_and_open_pr(forge.as_ref(), repo_root, &branch_name, &commit_message).await?;
 
     Ok(())
 }
@@ -166,31 +142,6 @@ async fn branch_exists_locally(repo_root: &Utf8Path, branch_name: &str) -> anyho
     Ok(!output.stdout().trim().is_empty())
 }
 
-async fn branch_has_associated_pr(repo_root: &Utf8Path, branch_name: &str) -> anyhow::Result<bool> {
-    let output = Cmd::new(
-        "gh",
-        [
-            "pr",
-            "list",
-            "--state",
-            "all",
-            "--head",
-            branch_name,
-            "--json",
-            "number",
-            "--limit",
-            "1",
-            "-q",
-            ".[0].number",
-        ],
-    )
-    .with_current_dir(repo_root)
-    .hide_stderr()
-    .run()
-    .await?;
-    Ok(output.status().success() && !output.stdout().trim().is_empty())
-}
-
 async fn current_utc_branch_timestamp() -> anyhow::Result<String> {
     let output = Cmd::new("date", ["-u", "+%Y-%m-%dT%H-%M-%SZ"])
         .run()
@@ -207,8 +158,12 @@ fn branch_name_with_timestamp(branch_name: &str, timestamp: &str) -> String {
     format!("{branch_name}-{timestamp}")
 }
 
-async fn branch_name_for_new_pr(repo_root: &Utf8Path, branch_name: &str) -> anyhow::Result<String> {
-    if !branch_has_associated_pr(repo_root, branch_name).await? {
+async fn branch_name_for_new_pr(
+    forge: &dyn Forge,
+    repo_root: &Utf8Path,
+    branch_name: &str,
+) -> anyhow::Result<String> {
+    if !forge.branch_has_pr(repo_root, branch_name).await? {
         return Ok(branch_name.to_string());
     }
 
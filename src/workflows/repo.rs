@@ -2,13 +2,19 @@ use camino::{Utf8Path, Utf8PathBuf};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::{cmd::Cmd, github};
+use crate::{
+    cmd::Cmd,
+    forge::{self, Forge as _},
+    github,
+};
 
 pub async fn ensure_default_repo_and_root() -> anyhow::Result<Utf8PathBuf> {
-    if !is_default_repo_set().await? {
-        set_default_repo().await?;
-    }
-    repo_root().await
+    let repo_root = repo_root().await?;
+    forge::forge_for_repo(&repo_root)
+        .await?
+        .prepare_repo(&repo_root)
+        .await?;
+    Ok(repo_root)
 }
 
 pub(crate) async fn ensure_clean_repo(repo_root: &Utf8Path) -> anyhow::Result<()> {
@@ -298,7 +304,9 @@ struct RepoInfo {
 }
 
 async fn fetch_repo_info(repo_root: &Utf8Path) -> anyhow::Result<RepoInfo> {
-    let origin_repo = origin_name_with_owner(repo_root).await?;
+    let (host, origin_repo) = origin_repo(repo_root).await?;
+    // `gh repo` defaults to github.com without the host.
+    let origin_repo = format!("{host}/{origin_repo}");
     let output = Cmd::new(
         "gh",
         [
@@ -342,7 +350,7 @@ async fn fetch_repo_info(repo_root: &Utf8Path) -> anyhow::Result<RepoInfo> {
         if branch.is_none()
             && let Some(parent_repo) = name.as_deref()
         {
-            branch = Some(fetch_default_branch(repo_root, parent_repo).await?);
+            branch = Some(fetch_default_branch(repo_root, &format!("{host}/{parent_repo}")).await?);
         }
         (name, branch)
     } else {
@@ -406,7 +414,8 @@ async fn fetch_default_branch(
         .to_string())
 }
 
-async fn origin_name_with_owner(repo_root: &Utf8Path) -> anyhow::Result<String> {
+/// GitHub host and `owner/repo` of the origin remote.
+async fn origin_repo(repo_root: &Utf8Path) -> anyhow::Result<(String, String)> {
     let output = Cmd::new("git", ["remote", "get-url", "origin"])
         .with_current_dir(repo_root)
         .run()
@@ -417,12 +426,14 @@ async fn origin_name_with_owner(repo_root: &Utf8Path) -> anyhow::Result<String>
         "❌ Failed to detect origin remote URL: command returned empty output"
     );
 
-    github::parse_github_name_with_owner(output.stdout()).ok_or_else(|| {
-        anyhow::anyhow!(
-            "❌ Failed to parse origin remote URL as GitHub repository: {}",
-            output.stdout()
-        )
-    })
+    let (host, name_with_owner) =
+        github::split_github_remote_url(output.stdout()).ok_or_else(|| {
+            anyhow::anyhow!(
+                "❌ Failed to parse origin remote URL as GitHub repository: {}",
+                output.stdout()
+            )
+        })?;
+    Ok((host.host.clone(), name_with_owner))
 }
 
 async fn ensure_upstream_remote(
@@ -437,7 +448,11 @@ async fn ensure_upstream_remote(
         return Ok(());
     }
 
-    let upstream_url = format!("https://github.com/{parent_name_with_owner}.git");
+    let (owner, repo) = parent_name_with_owner
+        .split_once('/')
+        .ok_or_else(|| anyhow::anyhow!("❌ Invalid parent repository {parent_name_with_owner}"))?;
+    // Forks live on the host of their parent.
+    let upstream_url = forge::GitHub.clone_url(repo_root, owner, repo).await?;
     let add_output = Cmd::new("git", ["remote", "add", "upstream", &upstream_url])
         .with_current_dir(repo_root)
         .run()
@@ -503,55 +518,6 @@ pub async fn sync_fork(repo_root: &Utf8Path) -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn is_default_repo_set() -> anyhow::Result<bool> {
-    let output = Cmd::new("gh", ["repo", "set-default", "--view"])
-        .hide_stdout()
-        .hide_stderr()
-        .run()
-        .await?;
-    Ok(!output.stdout().trim().is_empty())
-}
-
-async fn set_default_repo() -> anyhow::Result<()> {
-    let remotes_output = Cmd::new("git", ["remote"]).hide_stdout().run().await?;
-    let has_upstream = remotes_output
-        .stdout()
-        .lines()
-        .any(|line| line.trim() == "upstream");
-
-    let remote = if has_upstream { "upstream" } else { "origin" };
-    Cmd::new("gh", ["repo", "set-default", remote])
-        .run()
-        .await?
-        .ensure_success(format!(
-            "❌ Failed to set default GitHub repo to remote '{remote}'"
-        ))?;
-    Ok(())
-}
-
-pub(crate) async fn default_branch(repo_root: &Utf8Path) -> anyhow::Result<String> {
-    let output = Cmd::new(
-        "gh",
-        [
-            "repo",
-            "view",
-            "--json",
-            "defaultBranchRef",
-            "-q",
-            ".defaultBranchRef.name",
-        ],
-    )
-    .with_current_dir(repo_root)
-    .run()
-    .await?;
-    output.ensure_success("❌ Failed to detect default branch")?;
-    anyhow::ensure!(
-        !output.stdout().trim().is_empty(),
-        "❌ Failed to detect default branch: command returned empty output"
-    );
-    Ok(output.stdout().to_string())
-}
-
 pub(crate) async fn current_branch(repo_root: &Utf8Path) -> anyhow::Result<String> {
     let output = Cmd::new("git", ["branch", "--show-current"])
         .with_current_dir(repo_root)
@@ -590,15 +556,6 @@ pub(crate) async fn commit(repo_root: &Utf8Path, commit_message: &str) -> anyhow
     Ok(())
 }
 
-pub(crate) async fn view_pr_in_browser(repo_root: &Utf8Path) -> anyhow::Result<()> {
-    Cmd::new("gh", ["pr", "view", "--web"])
-        .with_current_dir(repo_root)
-        .run()
-        .await?
-        .ensure_success("❌ Failed to open PR in browser")?;
-    Ok(())
-}
-
 pub(crate) async fn repo_root() -> anyhow::Result<Utf8PathBuf> {
     let git_root = Cmd::new("git", ["rev-parse", "--show-toplevel"])
         .hide_stdout()
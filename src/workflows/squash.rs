@@ -1,51 +1,17 @@
 use camino::Utf8Path;
 use git_cmd::Repo;
-use serde::Deserialize;
 
-use crate::{authors, checkout::parse_github_pr_url, cmd::Cmd, github};
+use crate::{
+    authors,
+    cmd::Cmd,
+    forge::{self, BranchPr, Forge},
+};
 
 use super::repo::{
     PushLease, commit, current_branch, ensure_not_on_default_branch, fetch_branch_head,
-    remote_names, view_pr_in_browser,
+    remote_names,
 };
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct PullRequest {
-    number: u64,
-    title: String,
-    base_ref_name: String,
-    url: String,
-}
-
-async fn current_pull_request(
-    repo_root: &Utf8Path,
-    current_branch: &str,
-) -> anyhow::Result<PullRequest> {
-    let output = Cmd::new(
-        "gh",
-        [
-            "pr",
-            "list",
-            "--head",
-            current_branch,
-            "--json",
-            "number,title,baseRefName,url",
-            "--limit",
-            "1",
-        ],
-    )
-    .with_current_dir(repo_root)
-    .run()
-    .await?;
-    output.ensure_success("❌ Failed to get current PR")?;
-    let pull_requests: Vec<PullRequest> = serde_json::from_str(output.stdout())?;
-    pull_requests
-        .into_iter()
-        .next()
-        .ok_or_else(|| anyhow::anyhow!("❌ No open PR found for branch '{current_branch}'"))
-}
-
 async fn fetch_and_merge_base_from(
     repo_root: &Utf8Path,
     base_repo_url: &str,
@@ -62,21 +28,25 @@ async fn fetch_and_merge_base_from(
 }
 
 async fn fetch_and_merge_pull_request_base(
+    forge: &dyn Forge,
     repo_root: &Utf8Path,
-    pull_request: &PullRequest,
+    pull_request: &BranchPr,
 ) -> anyhow::Result<String> {
-    let base_repo = parse_github_pr_url(&pull_request.url)?;
-    let base_name_with_owner = format!("{}/{}", base_repo.owner, base_repo.repo);
-    let base_source =
-        if let Some(remote) = configured_remote_for_repo(repo_root, &base_name_with_owner).await? {
-            remote
-        } else {
-            github_clone_url(repo_root, &base_repo.owner, &base_repo.repo).await?
-        };
-    fetch_and_merge_base_from(repo_root, &base_source, &pull_request.base_ref_name).await
+    let base_name_with_owner = format!("{}/{}", pull_request.owner, pull_request.repo);
+    let base_source = if let Some(remote) =
+        configured_remote_for_repo(forge, repo_root, &base_name_with_owner).await?
+    {
+        remote
+    } else {
+        forge
+            .clone_url(repo_root, &pull_request.owner, &pull_request.repo)
+            .await?
+    };
+    fetch_and_merge_base_from(repo_root, &base_source, &pull_request.base_ref).await
 }
 
 async fn configured_remote_for_repo(
+    forge: &dyn Forge,
     repo_root: &Utf8Path,
     name_with_owner: &str,
 ) -> anyhow::Result<Option<String>> {
@@ -86,9 +56,7 @@ async fn configured_remote_for_repo(
             .run()
             .await?;
         url_output.ensure_success(format!("❌ Failed to resolve remote '{remote}'"))?;
-        if github::parse_github_name_with_owner(url_output.stdout()).as_deref()
-            == Some(name_with_owner)
-        {
+        if forge.remote_repo_name(url_output.stdout()).as_deref() == Some(name_with_owner) {
             return Ok(Some(remote));
         }
     }
@@ -96,31 +64,6 @@ async fn configured_remote_for_repo(
     Ok(None)
 }
 
-async fn github_clone_url(repo_root: &Utf8Path, owner: &str, repo: &str) -> anyhow::Result<String> {
-    let protocol_output = Cmd::new(
-        "gh",
-        ["config", "get", "git_protocol", "--host", "github.com"],
-    )
-    .with_current_dir(repo_root)
-    .run()
-    .await?;
-    protocol_output.ensure_success("❌ Failed to detect the configured GitHub git protocol")?;
-
-    github_clone_url_for_protocol(protocol_output.stdout(), owner, repo)
-}
-
-fn github_clone_url_for_protocol(
-    protocol: &str,
-    owner: &str,
-    repo: &str,
-) -> anyhow::Result<String> {
-    match protocol {
-        "ssh" => Ok(format!("git@github.com:{owner}/{repo}.git")),
-        "https" => Ok(format!("https://github.com/{owner}/{repo}.git")),
-        protocol => anyhow::bail!("❌ Unsupported GitHub git protocol '{protocol}'"),
-    }
-}
-
 fn print_dry_run_summary(
     commits_to_squash: &[authors::PullRequestCommit],
     pr_title: &str,
@@ -199,18 +142,22 @@ pub async fn squash(
     add_co_author: bool,
 ) -> anyhow::Result<()> {
     anyhow::ensure!(repo.is_clean().is_ok(), "❌ Repository is not clean");
+    let forge = forge::forge_for_repo(repo_root).await?;
     let feature_branch = current_branch(repo_root).await?;
-    let pull_request = current_pull_request(repo_root, &feature_branch).await?;
+    let pull_request = forge
+        .pr_for_branch(repo_root, &feature_branch)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("❌ No open PR found for branch '{feature_branch}'"))?;
     anyhow::ensure!(
-        feature_branch != pull_request.base_ref_name,
+        feature_branch != pull_request.base_ref,
         "❌ You are on the PR base branch. Switch to the PR feature branch to squash"
     );
 
     let push_lease = PushLease::prepare(repo_root, &feature_branch).await?;
-    let base_commit = fetch_and_merge_pull_request_base(repo_root, &pull_request).await?;
+    let base_commit =
+        fetch_and_merge_pull_request_base(forge.as_ref(), repo_root, &pull_request).await?;
 
-    let pull_request_commits =
-        authors::get_pull_request_commits(repo_root, pull_request.number).await?;
+    let pull_request_commits = forge.pr_commits(repo_root, &pull_request).await?;
     let detected_co_authors = authors::get_co_authors(repo_root, &pull_request_commits).await?;
     let additional_co_authors = if add_co_author {
         let selectable_co_authors =
@@ -244,11 +191,13 @@ pub async fn squash(
         repo_root,
         &base_commit,
         &commit_message,
-        &pull_request.base_ref_name,
+        &pull_request.base_ref,
         push_lease,
     )
     .await?;
-    view_pr_in_browser(repo_root).await?;
+    forge
+        .open_pr_in_browser(repo_root, &feature_branch, &pull_request.title)
+        .await?;
 
     Ok(())
 }
@@ -261,9 +210,9 @@ mod tests {
         TestDir, configure_test_user, git_success, init_bare_repo,
     };
 
-    use super::{
-        configured_remote_for_repo, fetch_and_merge_base_from, github_clone_url_for_protocol,
-    };
+    use crate::forge::GitHub;
+
+    use super::{configured_remote_for_repo, fetch_and_merge_base_from};
 
     #[tokio::test]
     async fn uses_matching_configured_remote_for_pr_base() {
@@ -290,25 +239,13 @@ mod tests {
         );
 
         assert_eq!(
-            configured_remote_for_repo(&repo, "organization/project")
+            configured_remote_for_repo(&GitHub, &repo, "organization/project")
                 .await
                 .unwrap(),
             Some("upstream".to_string())
         );
     }
 
-    #[test]
-    fn base_repo_fallback_uses_configured_git_protocol() {
-        assert_eq!(
-            github_clone_url_for_protocol("ssh", "organization", "project").unwrap(),
-            "git@github.com:organization/project.git"
-        );
-        assert_eq!(
-            github_clone_url_for_protocol("https", "organization", "project").unwrap(),
-            "https://github.com/organization/project.git"
-        );
-    }
-
     #[tokio::test]
     async fn uses_pr_base_repo_when_origin_default_branch_is_stale() {
         let fixture = TestDir::new("stale-origin");